@@ -0,0 +1,850 @@
+//! API Client Library
+//!
+//! Typed client for the Proxy Desktop Browser REST API:
+//! - One method per operation in the server's OpenAPI document, except the
+//!   WebSocket/SSE event streams
+//! - API key or bearer token authentication
+//! - Non-success responses surface as `ApiError`, reachable through
+//!   `anyhow::Error::downcast_ref`
+//!
+//! The tests run every method against an in-process server and check the
+//! exercised operations and the schemas of `types` against the spec.
+
+pub mod types;
+
+pub use types::*;
+
+use anyhow::{anyhow, Result};
+use reqwest::{Client, Method, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A non-success response from the API
+#[derive(Debug, thiserror::Error)]
+#[error("API returned {status}: {body}")]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+enum Credential {
+    ApiKey(String),
+    Bearer(String),
+}
+
+/// Client for one API server
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: Client,
+    base_url: Url,
+    credential: Option<Credential>,
+}
+
+impl ApiClient {
+    /// Creates a client for the server at `base_url` (e.g. `http://127.0.0.1:8080`).
+    pub fn new(base_url: &str) -> Result<Self> {
+        let base_url = Url::parse(base_url)?;
+        if base_url.cannot_be_a_base() {
+            return Err(anyhow!("Invalid API base URL: {}", base_url));
+        }
+        Ok(Self {
+            http: Client::new(),
+            base_url,
+            credential: None,
+        })
+    }
+
+    /// Authenticate with an API key (`pxb_...`)
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.credential = Some(Credential::ApiKey(key.into()));
+        self
+    }
+
+    /// Authenticate with a JWT from `login` or `issue_token`
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.credential = Some(Credential::Bearer(token.into()));
+        self
+    }
+
+    /// Send requests through `client`, e.g. one with custom TLS roots
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Build a request for an operation, filling the `{param}` segments of
+    /// `template` in order from `params`
+    fn request(&self, method: Method, template: &'static str, params: &[&str]) -> Result<RequestBuilder> {
+        #[cfg(test)]
+        tests::record(&method, template);
+
+        let mut url = self.base_url.clone();
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .map_err(|_| anyhow!("Invalid API base URL"))?;
+            segments.pop_if_empty();
+            let mut params = params.iter();
+            for segment in template.trim_start_matches('/').split('/') {
+                if segment.starts_with('{') {
+                    let value = params
+                        .next()
+                        .ok_or_else(|| anyhow!("Missing path parameter {} for {}", segment, template))?;
+                    segments.push(value);
+                } else {
+                    segments.push(segment);
+                }
+            }
+        }
+
+        let builder = self.http.request(method, url);
+        Ok(match &self.credential {
+            Some(Credential::ApiKey(key)) => builder.header("x-api-key", key),
+            Some(Credential::Bearer(token)) => builder.bearer_auth(token),
+            None => builder,
+        })
+    }
+
+    async fn send(&self, builder: RequestBuilder) -> Result<reqwest::Response> {
+        let response = builder.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(ApiError { status, body }.into())
+    }
+
+    async fn json<T: DeserializeOwned>(&self, builder: RequestBuilder) -> Result<T> {
+        Ok(self.send(builder).await?.json().await?)
+    }
+
+    async fn empty(&self, builder: RequestBuilder) -> Result<()> {
+        self.send(builder).await.map(drop)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, template: &'static str, params: &[&str]) -> Result<T> {
+        self.json(self.request(Method::GET, template, params)?).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        template: &'static str,
+        params: &[&str],
+        body: &B,
+    ) -> Result<T> {
+        self.json(self.request(Method::POST, template, params)?.json(body)).await
+    }
+
+    async fn delete(&self, template: &'static str, params: &[&str]) -> Result<()> {
+        self.empty(self.request(Method::DELETE, template, params)?).await
+    }
+
+    // ========= Tabs =========
+
+    /// Open a tab with a virtual IP from `country_code`
+    pub async fn create_tab(&self, country_code: &str) -> Result<TabResponse> {
+        let body = CreateTabRequest {
+            country_code: country_code.to_string(),
+        };
+        self.post("/api/tabs", &[], &body).await
+    }
+
+    /// List open tabs
+    pub async fn list_tabs(&self) -> Result<Vec<TabResponse>> {
+        self.get("/api/tabs", &[]).await
+    }
+
+    /// Get a tab
+    pub async fn get_tab(&self, id: &str) -> Result<TabResponse> {
+        self.get("/api/tabs/{id}", &[id]).await
+    }
+
+    /// Close a tab
+    pub async fn close_tab(&self, id: &str) -> Result<()> {
+        self.delete("/api/tabs/{id}", &[id]).await
+    }
+
+    /// Give a tab a new virtual IP, optionally from another country
+    pub async fn rotate_ip(&self, id: &str, new_country: Option<&str>) -> Result<VirtualIPResponse> {
+        let body = RotateIPRequest {
+            new_country: new_country.map(str::to_string),
+        };
+        self.post("/api/tabs/{id}/rotate-ip", &[id], &body).await
+    }
+
+    /// Run leak checks on a tab's virtual IP
+    pub async fn validate_tab_ip(&self, id: &str) -> Result<ValidationResponse> {
+        self.get("/api/tabs/{id}/validate", &[id]).await
+    }
+
+    /// Record a navigation in a tab
+    pub async fn navigate(&self, id: &str, url: &str) -> Result<()> {
+        let body = NavigateRequest { url: url.to_string() };
+        let builder = self.request(Method::POST, "/api/tabs/{id}/navigate", &[id])?;
+        self.empty(builder.json(&body)).await
+    }
+
+    /// List countries virtual IPs can be generated for
+    pub async fn list_countries(&self) -> Result<Vec<CountryResponse>> {
+        self.get("/api/countries", &[]).await
+    }
+
+    // ========= Headless browser =========
+
+    /// State of the server's Chromium engine
+    pub async fn browser_status(&self) -> Result<BrowserStatusResponse> {
+        self.get("/api/browser", &[]).await
+    }
+
+    /// Open a real browser tab
+    pub async fn create_browser_tab(&self, request: &CreateBrowserTabRequest) -> Result<BrowserTabResponse> {
+        self.post("/api/browser/tabs", &[], request).await
+    }
+
+    /// List real browser tabs
+    pub async fn list_browser_tabs(&self) -> Result<Vec<BrowserTabResponse>> {
+        self.get("/api/browser/tabs", &[]).await
+    }
+
+    /// Get a real browser tab
+    pub async fn get_browser_tab(&self, id: &str) -> Result<BrowserTabResponse> {
+        self.get("/api/browser/tabs/{id}", &[id]).await
+    }
+
+    /// Close a real browser tab
+    pub async fn close_browser_tab(&self, id: &str) -> Result<()> {
+        self.delete("/api/browser/tabs/{id}", &[id]).await
+    }
+
+    /// Load `url` in a browser tab
+    pub async fn navigate_browser_tab(&self, id: &str, url: &str) -> Result<BrowserTabResponse> {
+        let body = NavigateRequest { url: url.to_string() };
+        self.post("/api/browser/tabs/{id}/navigate", &[id], &body).await
+    }
+
+    /// Evaluate JavaScript in a browser tab
+    pub async fn execute_script(&self, id: &str, script: &str) -> Result<ScriptResponse> {
+        let body = ScriptRequest {
+            script: script.to_string(),
+        };
+        self.post("/api/browser/tabs/{id}/script", &[id], &body).await
+    }
+
+    /// HTML of a browser tab
+    pub async fn page_content(&self, id: &str) -> Result<PageContentResponse> {
+        self.get("/api/browser/tabs/{id}/content", &[id]).await
+    }
+
+    /// PNG screenshot of a browser tab
+    pub async fn screenshot(&self, id: &str) -> Result<Vec<u8>> {
+        let builder = self.request(Method::GET, "/api/browser/tabs/{id}/screenshot", &[id])?;
+        Ok(self.send(builder).await?.bytes().await?.to_vec())
+    }
+
+    // ========= Batch jobs =========
+
+    /// Start a batch fetch job
+    pub async fn submit_job(&self, spec: &BatchJobSpec) -> Result<BatchJobProgress> {
+        self.post("/api/jobs", &[], spec).await
+    }
+
+    /// List batch jobs, newest first
+    pub async fn list_jobs(&self) -> Result<Vec<BatchJobProgress>> {
+        self.get("/api/jobs", &[]).await
+    }
+
+    /// Progress of a batch job
+    pub async fn get_job(&self, id: &str) -> Result<BatchJobProgress> {
+        self.get("/api/jobs/{id}", &[id]).await
+    }
+
+    /// Stop a running batch job
+    pub async fn cancel_job(&self, id: &str) -> Result<BatchJobProgress> {
+        self.json(self.request(Method::POST, "/api/jobs/{id}/cancel", &[id])?).await
+    }
+
+    /// Results of a batch job as parsed JSONL lines
+    pub async fn job_results(&self, id: &str) -> Result<Vec<FetchResult>> {
+        self.job_results_text(id, "jsonl")
+            .await?
+            .lines()
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    /// Results of a batch job as raw `jsonl` or `csv`
+    pub async fn job_results_text(&self, id: &str, format: &str) -> Result<String> {
+        let builder = self
+            .request(Method::GET, "/api/jobs/{id}/results", &[id])?
+            .query(&[("format", format)]);
+        Ok(self.send(builder).await?.text().await?)
+    }
+
+    // ========= Proxy pool =========
+
+    /// List pooled proxies matching `query`
+    pub async fn list_proxies(&self, query: &ProxyQuery) -> Result<Page<ProxyResponse>> {
+        self.json(self.request(Method::GET, "/api/proxies", &[])?.query(query)).await
+    }
+
+    /// Add a proxy to the pool
+    pub async fn add_proxy(&self, proxy: &NewProxyRequest) -> Result<ProxyResponse> {
+        self.post("/api/proxies", &[], proxy).await
+    }
+
+    /// Import proxies in bulk
+    pub async fn import_proxies(&self, request: &ImportRequest) -> Result<ImportResponse> {
+        self.post("/api/proxies/import", &[], request).await
+    }
+
+    /// Get a pooled proxy by `ip:port`
+    pub async fn get_proxy(&self, id: &str) -> Result<ProxyResponse> {
+        self.get("/api/proxies/{id}", &[id]).await
+    }
+
+    /// Remove a proxy from the pool
+    pub async fn delete_proxy(&self, id: &str) -> Result<()> {
+        self.delete("/api/proxies/{id}", &[id]).await
+    }
+
+    /// The proxy used for new connections, if any
+    pub async fn active_proxy(&self) -> Result<Option<FreeProxy>> {
+        self.get("/api/proxies/active", &[]).await
+    }
+
+    /// Switch the active proxy; `None` connects directly
+    pub async fn set_active_proxy(&self, id: Option<&str>) -> Result<Option<FreeProxy>> {
+        let body = SetActiveProxyRequest {
+            id: id.map(str::to_string),
+        };
+        let builder = self.request(Method::PUT, "/api/proxies/active", &[])?;
+        self.json(builder.json(&body)).await
+    }
+
+    // ========= Validation, quarantine and health =========
+
+    /// Validate one pooled proxy
+    pub async fn validate_proxy(&self, id: &str) -> Result<ProxyValidationResponse> {
+        self.json(self.request(Method::POST, "/api/proxies/{id}/validate", &[id])?)
+            .await
+    }
+
+    /// Validate every pooled proxy matching `query`
+    pub async fn validate_proxies(&self, query: &ProxyQuery) -> Result<Vec<ProxyValidationResponse>> {
+        self.json(self.request(Method::POST, "/api/proxies/validate", &[])?.query(query))
+            .await
+    }
+
+    /// Quarantine a pooled proxy
+    pub async fn quarantine_proxy(&self, id: &str, reason: Option<&str>) -> Result<QuarantinedProxy> {
+        let body = QuarantineRequest {
+            reason: reason.map(str::to_string),
+        };
+        self.post("/api/proxies/{id}/quarantine", &[id], &body).await
+    }
+
+    /// Release a proxy from quarantine
+    pub async fn release_proxy(&self, id: &str) -> Result<()> {
+        self.delete("/api/proxies/{id}/quarantine", &[id]).await
+    }
+
+    /// List quarantined proxies
+    pub async fn list_quarantine(&self, page: &PageQuery) -> Result<QuarantineResponse> {
+        self.json(self.request(Method::GET, "/api/quarantine", &[])?.query(page))
+            .await
+    }
+
+    /// Health and bandwidth of one proxy
+    pub async fn proxy_health(&self, id: &str) -> Result<ProxyHealthResponse> {
+        self.get("/api/proxies/{id}/health", &[id]).await
+    }
+
+    /// List proxy health records, optionally only healthy or unhealthy ones
+    pub async fn list_health(&self, healthy: Option<bool>, page: &PageQuery) -> Result<Page<ProxyHealthStatus>> {
+        let mut builder = self.request(Method::GET, "/api/health/proxies", &[])?.query(page);
+        if let Some(healthy) = healthy {
+            builder = builder.query(&[("healthy", healthy)]);
+        }
+        self.json(builder).await
+    }
+
+    // ========= Rotation =========
+
+    /// The current rotation strategy
+    pub async fn rotation_strategy(&self) -> Result<StrategyRequest> {
+        self.get("/api/rotation/strategy", &[]).await
+    }
+
+    /// Switch the rotation strategy
+    pub async fn set_rotation_strategy(&self, strategy: &StrategyRequest) -> Result<StrategyRequest> {
+        let builder = self.request(Method::PUT, "/api/rotation/strategy", &[])?;
+        self.json(builder.json(strategy)).await
+    }
+
+    /// List per-tab proxy sessions
+    pub async fn list_sessions(&self, page: &PageQuery) -> Result<Page<ProxySessionStats>> {
+        self.json(self.request(Method::GET, "/api/rotation/sessions", &[])?.query(page))
+            .await
+    }
+
+    /// Get a tab's proxy session
+    pub async fn get_session(&self, tab_id: &str) -> Result<ProxySessionStats> {
+        self.get("/api/rotation/sessions/{tab_id}", &[tab_id]).await
+    }
+
+    /// End a tab's proxy session
+    pub async fn end_session(&self, tab_id: &str) -> Result<()> {
+        self.delete("/api/rotation/sessions/{tab_id}", &[tab_id]).await
+    }
+
+    /// Move a tab to its next proxy
+    pub async fn rotate_session(&self, tab_id: &str) -> Result<FreeProxy> {
+        self.json(self.request(Method::POST, "/api/rotation/sessions/{tab_id}/rotate", &[tab_id])?)
+            .await
+    }
+
+    // ========= Providers =========
+
+    /// List free proxy providers
+    pub async fn list_providers(&self) -> Result<Vec<ProviderResponse>> {
+        self.get("/api/providers", &[]).await
+    }
+
+    /// Fetch proxies from one provider, or from all when `provider` is `None`
+    pub async fn refresh_providers(&self, provider: Option<&str>) -> Result<RefreshResponse> {
+        let mut builder = self.request(Method::POST, "/api/providers/refresh", &[])?;
+        if let Some(provider) = provider {
+            builder = builder.query(&[("provider", provider)]);
+        }
+        self.json(builder).await
+    }
+
+    // ========= Auth =========
+
+    /// Log in with a username and password
+    pub async fn login(&self, username: &str, password: &str) -> Result<TokenResponse> {
+        let body = LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        self.post("/api/auth/login", &[], &body).await
+    }
+
+    /// Exchange a refresh token for a new access token
+    pub async fn refresh_token(&self, refresh_token: &str) -> Result<TokenResponse> {
+        let body = RefreshRequest {
+            refresh_token: refresh_token.to_string(),
+        };
+        self.post("/api/auth/refresh", &[], &body).await
+    }
+
+    /// The caller as the server sees it
+    pub async fn whoami(&self) -> Result<Principal> {
+        self.get("/api/auth/whoami", &[]).await
+    }
+
+    /// Issue a scoped service token
+    pub async fn issue_token(&self, request: &IssueTokenRequest) -> Result<TokenResponse> {
+        self.post("/api/auth/tokens", &[], request).await
+    }
+
+    /// List API keys
+    pub async fn list_keys(&self) -> Result<Vec<ApiKeyResponse>> {
+        self.get("/api/auth/keys", &[]).await
+    }
+
+    /// Create an API key; the returned token is not retrievable later
+    pub async fn create_key(&self, key: &NewApiKey) -> Result<CreatedKeyResponse> {
+        self.post("/api/auth/keys", &[], key).await
+    }
+
+    /// Revoke an API key
+    pub async fn revoke_key(&self, id: &str) -> Result<()> {
+        self.delete("/api/auth/keys/{id}", &[id]).await
+    }
+
+    /// The server's OpenAPI document
+    pub async fn openapi(&self) -> Result<serde_json::Value> {
+        self.get("/api/openapi.json", &[]).await
+    }
+
+    /// Metrics in the Prometheus text format
+    pub async fn metrics(&self) -> Result<String> {
+        Ok(self.send(self.request(Method::GET, "/metrics", &[])?).await?.text().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api_server::openapi::ApiDoc;
+    use api_server::{ApiAuth, ApiServer, BatchJobManager, ProxyServices};
+    use browser_core::{
+        ApiKeyStore, AuthManager, FreeIpProviderManager, ProxyHealthMonitor, ProxyManager,
+        ProxyQuarantineManager, ProxyRotationManager, ProxyRotationStrategy, ProxyValidator,
+        ProxyValidatorConfig, TabIPManager,
+    };
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+    use utoipa::OpenApi;
+
+    /// Operations the client has called, as `(method, path template)`
+    static CALLED: Mutex<BTreeSet<(String, String)>> = Mutex::new(BTreeSet::new());
+
+    pub(super) fn record(method: &Method, template: &str) {
+        CALLED
+            .lock()
+            .expect("Recorder lock should not be poisoned")
+            .insert((method.as_str().to_ascii_lowercase(), template.to_string()));
+    }
+
+    /// Streaming operations the client leaves to WebSocket/SSE libraries
+    const STREAMING: [&str; 2] = ["/api/events", "/api/events/sse"];
+
+    #[derive(OpenApi)]
+    #[openapi(components(schemas(
+        TabResponse,
+        VirtualIPResponse,
+        CountryResponse,
+        ValidationResponse,
+        CreateTabRequest,
+        NavigateRequest,
+        RotateIPRequest,
+        ProxyResponse,
+        Page<ProxyResponse>,
+        Page<ProxyHealthStatus>,
+        Page<ProxySessionStats>,
+        Page<QuarantinedProxy>,
+        NewProxyRequest,
+        ImportRequest,
+        ImportResponse,
+        SetActiveProxyRequest,
+        ProxyValidationResponse,
+        QuarantineRequest,
+        QuarantineResponse,
+        ProxyHealthResponse,
+        StrategyRequest,
+        ProviderResponse,
+        RefreshResponse,
+        FreeProxy,
+        ProxyType,
+        ValidationResult,
+        QuarantinedProxy,
+        QuarantineStats,
+        ProxyHealthStatus,
+        BandwidthStats,
+        ProxySessionStats,
+        LoginRequest,
+        RefreshRequest,
+        TokenResponse,
+        IssueTokenRequest,
+        Principal,
+        PrincipalKind,
+        ApiScope,
+        NewApiKey,
+        ApiKeyResponse,
+        CreatedKeyResponse,
+        BrowserStatusResponse,
+        BrowserTabResponse,
+        CreateBrowserTabRequest,
+        ScriptRequest,
+        ScriptResponse,
+        PageContentResponse,
+        BatchJobSpec,
+        BatchJobProgress,
+        JobState,
+        FetchResult,
+    )))]
+    struct ClientDoc;
+
+    /// Drop documentation so only the wire shape is compared
+    fn strip_descriptions(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("description");
+                map.values_mut().for_each(strip_descriptions);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip_descriptions),
+            _ => {}
+        }
+    }
+
+    fn schemas<T: OpenApi>() -> serde_json::Map<String, Value> {
+        let mut spec = serde_json::to_value(T::openapi()).expect("Spec should serialize");
+        strip_descriptions(&mut spec);
+        spec["components"]["schemas"]
+            .as_object()
+            .cloned()
+            .expect("Spec should have schemas")
+    }
+
+    #[test]
+    fn test_types_match_server_schemas() {
+        let server = schemas::<ApiDoc>();
+        let client = schemas::<ClientDoc>();
+
+        let server_names: BTreeSet<_> = server.keys().collect();
+        let client_names: BTreeSet<_> = client.keys().collect();
+        assert_eq!(client_names, server_names, "Client and server define different types");
+        for (name, schema) in &server {
+            assert_eq!(&client[name], schema, "Schema of {} differs from the server's", name);
+        }
+    }
+
+    fn status_of(result: Result<impl std::fmt::Debug>) -> StatusCode {
+        result
+            .expect_err("Call should fail")
+            .downcast_ref::<ApiError>()
+            .expect("Error should come from the API")
+            .status
+    }
+
+    async fn start_server(jobs_dir: &std::path::Path) -> (String, String) {
+        let providers = Arc::new(RwLock::new(
+            FreeIpProviderManager::new().expect("Provider manager should build"),
+        ));
+        let proxy_services = ProxyServices::new(
+            Arc::new(ProxyManager::new()),
+            providers.clone(),
+            Arc::new(RwLock::new(ProxyRotationManager::new(
+                providers,
+                ProxyRotationStrategy::RoundRobin,
+            ))),
+            Arc::new(ProxyValidator::new(ProxyValidatorConfig::default())),
+            Arc::new(ProxyQuarantineManager::new(
+                3,
+                Duration::from_secs(300),
+                Duration::from_secs(3600),
+            )),
+            Arc::new(ProxyHealthMonitor::new()),
+        );
+
+        let keys = Arc::new(ApiKeyStore::new());
+        let (_, admin) = keys
+            .create(browser_core::NewApiKey {
+                name: "admin".to_string(),
+                scopes: vec![browser_core::ApiScope::Admin],
+                rate_limit_per_minute: None,
+                expires_at: None,
+            })
+            .await
+            .expect("Create should succeed");
+
+        let generator = virtual_ip::demo_generator();
+        let server = ApiServer::new(
+            Arc::new(tokio::sync::Mutex::new(TabIPManager::new(generator.clone()))),
+            Arc::new(generator),
+        )
+        .with_batch_jobs(
+            BatchJobManager::open(jobs_dir, Some(proxy_services.clone()))
+                .await
+                .expect("Jobs should open"),
+        )
+        .with_proxy_services(proxy_services)
+        .with_auth(ApiAuth::new(Arc::new(AuthManager::new("test-secret".to_string())), keys));
+        let app = Arc::new(server).router().await;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Bind should succeed");
+        let addr = listener.local_addr().expect("Listener should have an address");
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}", addr), admin)
+    }
+
+    #[tokio::test]
+    async fn test_client_covers_every_operation() {
+        let jobs_dir = tempfile::TempDir::new().expect("Temp dir should be created");
+        let (base_url, admin) = start_server(jobs_dir.path()).await;
+        let anonymous = ApiClient::new(&base_url).expect("Client should build");
+        let client = anonymous.clone().with_api_key(admin);
+
+        // Meta and auth
+        assert_eq!(client.openapi().await.expect("Spec should load")["openapi"], "3.1.0");
+        assert!(client.metrics().await.expect("Metrics should load").contains("# TYPE proxy_browser_tabs gauge"));
+        assert_eq!(status_of(anonymous.list_tabs().await), StatusCode::UNAUTHORIZED);
+        assert_eq!(status_of(anonymous.login("nobody", "secret").await), StatusCode::UNAUTHORIZED);
+        assert_eq!(status_of(anonymous.refresh_token("bogus").await), StatusCode::UNAUTHORIZED);
+        assert_eq!(client.whoami().await.expect("Whoami should succeed").scopes, vec![ApiScope::Admin]);
+        let token = client
+            .issue_token(&IssueTokenRequest {
+                subject: "ci".to_string(),
+                scopes: vec![ApiScope::ReadOnly],
+                ttl_secs: 60,
+            })
+            .await
+            .expect("Issue should succeed");
+        let reader = anonymous.clone().with_bearer_token(token.access_token);
+        assert_eq!(reader.whoami().await.expect("Whoami should succeed").kind, PrincipalKind::Token);
+        let created = client
+            .create_key(&NewApiKey {
+                name: "reader".to_string(),
+                scopes: vec![ApiScope::ReadOnly],
+                ..NewApiKey::default()
+            })
+            .await
+            .expect("Create should succeed");
+        assert!(created.token.starts_with("pxb_"));
+        assert_eq!(client.list_keys().await.expect("List should succeed").len(), 2);
+        client.revoke_key(&created.key.id).await.expect("Revoke should succeed");
+
+        // Tabs
+        let countries = client.list_countries().await.expect("Countries should load");
+        let tab = client.create_tab(&countries[0].code).await.expect("Create should succeed");
+        assert_eq!(client.list_tabs().await.expect("List should succeed").len(), 1);
+        assert_eq!(client.get_tab(&tab.tab_id).await.expect("Get should succeed").ip, tab.ip);
+        client
+            .navigate(&tab.tab_id, "https://example.com")
+            .await
+            .expect("Navigate should succeed");
+        client.rotate_ip(&tab.tab_id, None).await.expect("Rotate should succeed");
+        assert_eq!(status_of(client.validate_tab_ip("missing").await), StatusCode::NOT_FOUND);
+        client.close_tab(&tab.tab_id).await.expect("Close should succeed");
+
+        // Headless browser (no Chromium engine in tests)
+        assert!(!client.browser_status().await.expect("Status should load").running);
+        let unavailable = StatusCode::SERVICE_UNAVAILABLE;
+        let open = CreateBrowserTabRequest {
+            url: Some("https://example.com".to_string()),
+            ..CreateBrowserTabRequest::default()
+        };
+        assert_eq!(status_of(client.create_browser_tab(&open).await), unavailable);
+        assert_eq!(status_of(client.list_browser_tabs().await), unavailable);
+        assert_eq!(status_of(client.get_browser_tab("b1").await), unavailable);
+        assert_eq!(status_of(client.navigate_browser_tab("b1", "https://example.com").await), unavailable);
+        assert_eq!(status_of(client.execute_script("b1", "1 + 1").await), unavailable);
+        assert_eq!(status_of(client.page_content("b1").await), unavailable);
+        assert_eq!(status_of(client.screenshot("b1").await), unavailable);
+        assert_eq!(status_of(client.close_browser_tab("b1").await), unavailable);
+
+        // Proxy pool
+        let added = client
+            .add_proxy(&NewProxyRequest {
+                ip: "10.0.0.1".to_string(),
+                port: 8080,
+                ..NewProxyRequest::default()
+            })
+            .await
+            .expect("Add should succeed");
+        let imported = client
+            .import_proxies(&ImportRequest {
+                text: Some("socks5://10.0.0.2:1080".to_string()),
+                ..ImportRequest::default()
+            })
+            .await
+            .expect("Import should succeed");
+        assert_eq!(imported.imported, 1);
+        let page = client
+            .list_proxies(&ProxyQuery {
+                protocol: Some("socks5".to_string()),
+                ..ProxyQuery::default()
+            })
+            .await
+            .expect("List should succeed");
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].protocol, ProxyType::Socks5);
+        assert_eq!(client.get_proxy(&added.id).await.expect("Get should succeed").port, 8080);
+        client.set_active_proxy(Some(&added.id)).await.expect("Set should succeed");
+        assert!(client.active_proxy().await.expect("Get should succeed").is_some());
+
+        // Validation, quarantine and health (no network: nothing matches)
+        let validated = client
+            .validate_proxies(&ProxyQuery {
+                country: Some("ZZ".to_string()),
+                ..ProxyQuery::default()
+            })
+            .await
+            .expect("Validate should succeed");
+        assert!(validated.is_empty());
+        assert_eq!(status_of(client.validate_proxy("10.9.9.9:1").await), StatusCode::NOT_FOUND);
+        let quarantined = client
+            .quarantine_proxy(&added.id, Some("test"))
+            .await
+            .expect("Quarantine should succeed");
+        assert_eq!(quarantined.failure_reasons, vec!["test".to_string()]);
+        let listing = client.list_quarantine(&PageQuery::default()).await.expect("List should succeed");
+        assert_eq!(listing.proxies.total, 1);
+        client.release_proxy(&added.id).await.expect("Release should succeed");
+        assert!(!client.proxy_health(&added.id).await.expect("Health should load").quarantined);
+        client
+            .list_health(Some(true), &PageQuery::default())
+            .await
+            .expect("List should succeed");
+        client.delete_proxy("10.0.0.2:1080").await.expect("Delete should succeed");
+
+        // Rotation and providers
+        assert_eq!(
+            client.rotation_strategy().await.expect("Get should succeed").strategy,
+            "round_robin"
+        );
+        let sticky = client
+            .set_rotation_strategy(&StrategyRequest {
+                strategy: "sticky".to_string(),
+                interval_secs: Some(60),
+                ..StrategyRequest::default()
+            })
+            .await
+            .expect("Set should succeed");
+        assert_eq!(sticky.interval_secs, Some(60));
+        assert_eq!(
+            client.list_sessions(&PageQuery::default()).await.expect("List should succeed").total,
+            0
+        );
+        assert_eq!(status_of(client.get_session("none").await), StatusCode::NOT_FOUND);
+        assert_eq!(status_of(client.end_session("none").await), StatusCode::NOT_FOUND);
+        assert_eq!(status_of(client.rotate_session("none").await), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!client.list_providers().await.expect("List should succeed").is_empty());
+        assert_eq!(status_of(client.refresh_providers(Some("nope")).await), StatusCode::NOT_FOUND);
+
+        // Batch jobs, fetching one of the server's own pages directly
+        let job = client
+            .submit_job(&BatchJobSpec {
+                urls: vec![format!("{}/api/countries", base_url)],
+                direct: true,
+                ..BatchJobSpec::default()
+            })
+            .await
+            .expect("Submit should succeed");
+        let mut progress = client.get_job(&job.id).await.expect("Get should succeed");
+        for _ in 0..200 {
+            if progress.state != JobState::Running {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+            progress = client.get_job(&job.id).await.expect("Get should succeed");
+        }
+        assert_eq!(progress.state, JobState::Completed);
+        assert_eq!(client.list_jobs().await.expect("List should succeed").len(), 1);
+        let results = client.job_results(&job.id).await.expect("Results should load");
+        // The job fetches without credentials
+        assert_eq!(results[0].status, Some(401));
+        let csv = client.job_results_text(&job.id, "csv").await.expect("CSV should load");
+        assert!(csv.starts_with("url,country,status,"));
+        assert_eq!(client.cancel_job(&job.id).await.expect("Cancel should succeed").state, JobState::Completed);
+        assert_eq!(status_of(client.submit_job(&BatchJobSpec::default()).await), StatusCode::BAD_REQUEST);
+
+        // Every documented operation is reachable through the client
+        let spec = serde_json::to_value(ApiDoc::openapi()).expect("Spec should serialize");
+        let mut documented = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().expect("Spec should have paths") {
+            if STREAMING.contains(&path.as_str()) {
+                continue;
+            }
+            for method in ["get", "post", "put", "delete", "patch"] {
+                if item.get(method).is_some() {
+                    documented.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        let called = CALLED.lock().expect("Recorder lock should not be poisoned").clone();
+        let missing: Vec<_> = documented.difference(&called).collect();
+        let unknown: Vec<_> = called.difference(&documented).collect();
+        assert!(missing.is_empty(), "Operations without a client method: {:?}", missing);
+        assert!(unknown.is_empty(), "Client calls operations not in the spec: {:?}", unknown);
+    }
+}
@@ -0,0 +1,848 @@
+//! API Authentication Module
+//!
+//! Guards the API server:
+//! - API keys (`X-API-Key` or `Authorization: Bearer pxb_...`) and JWTs
+//!   (`Authorization: Bearer ...`) from the shared `browser_core::auth`
+//! - An `access_token` query parameter on the `/api/events` WebSocket/SSE
+//!   routes, for browser clients which cannot set headers
+//! - Scope checks per route: reads need `read_only`, tab changes `tabs`,
+//!   proxy changes `proxies`, key management `admin`
+//! - Per-principal rate limits via `governor`, and per-client-IP limits on
+//!   the public login and refresh routes so they cannot be brute-forced
+//! - An append-only JSON-lines audit log of mutating calls
+//!
+//! `/api/auth/login`, `/api/auth/refresh` and `/api/openapi.json` stay
+//! public.
+
+use axum::{
+    extract::{ConnectInfo, Path, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Extension, Json, Router,
+};
+use browser_core::auth::{scopes_grant, API_KEY_PREFIX};
+use browser_core::{ApiKey, ApiKeyStore, ApiScope, AuthManager, NewApiKey};
+use chrono::{DateTime, Utc};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+/// Requests per minute for principals without their own limit
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 120;
+
+/// Requests per minute one client IP may make to the public auth routes
+pub const DEFAULT_PUBLIC_RATE_LIMIT_PER_MINUTE: u32 = 10;
+
+/// How often limiter state of principals and clients whose quota has fully
+/// refilled is dropped
+const LIMITER_EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Longest lifetime of a service token issued through the API
+const MAX_SERVICE_TOKEN_TTL_SECS: i64 = 30 * 24 * 3600;
+
+/// How a caller authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrincipalKind {
+    ApiKey,
+    Token,
+}
+
+/// The authenticated caller, available to handlers as a request extension
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Principal {
+    /// Key ID or token subject
+    pub id: String,
+    pub name: String,
+    pub kind: PrincipalKind,
+    pub scopes: Vec<ApiScope>,
+    #[serde(skip)]
+    rate_limit_per_minute: Option<u32>,
+}
+
+impl Principal {
+    fn limiter_key(&self) -> String {
+        match self.kind {
+            PrincipalKind::ApiKey => format!("key:{}", self.id),
+            PrincipalKind::Token => format!("token:{}", self.id),
+        }
+    }
+}
+
+/// One audit log line
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub principal: Option<String>,
+    pub kind: Option<PrincipalKind>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+}
+
+/// Authentication, authorization, rate limiting and auditing state
+pub struct ApiAuth {
+    auth: Arc<AuthManager>,
+    keys: Arc<ApiKeyStore>,
+    default_rate_limit: NonZeroU32,
+    public_rate_limit: NonZeroU32,
+    /// One keyed limiter per distinct requests-per-minute limit
+    limiters: Mutex<HashMap<NonZeroU32, Arc<DefaultKeyedRateLimiter<String>>>>,
+    last_eviction: Mutex<Instant>,
+    audit_log: Option<PathBuf>,
+    audit_lock: tokio::sync::Mutex<()>,
+}
+
+impl ApiAuth {
+    /// Creates a new instance from the shared token and key stores.
+    pub fn new(auth: Arc<AuthManager>, keys: Arc<ApiKeyStore>) -> Self {
+        Self {
+            auth,
+            keys,
+            default_rate_limit: NonZeroU32::new(DEFAULT_RATE_LIMIT_PER_MINUTE).expect("default limit is non-zero"),
+            public_rate_limit: NonZeroU32::new(DEFAULT_PUBLIC_RATE_LIMIT_PER_MINUTE).expect("public limit is non-zero"),
+            limiters: Mutex::new(HashMap::new()),
+            last_eviction: Mutex::new(Instant::now()),
+            audit_log: None,
+            audit_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Requests per minute for tokens and keys without their own limit
+    pub fn with_default_rate_limit(mut self, per_minute: u32) -> Self {
+        if let Some(limit) = NonZeroU32::new(per_minute) {
+            self.default_rate_limit = limit;
+        }
+        self
+    }
+
+    /// Requests per minute each client IP may make to login and refresh
+    pub fn with_public_rate_limit(mut self, per_minute: u32) -> Self {
+        if let Some(limit) = NonZeroU32::new(per_minute) {
+            self.public_rate_limit = limit;
+        }
+        self
+    }
+
+    /// Append an entry for every mutating call to `path`
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    /// The API key store
+    pub fn keys(&self) -> &Arc<ApiKeyStore> {
+        &self.keys
+    }
+
+    /// Routes for login and key management, ready to merge into the server
+    /// router (behind `authenticate`).
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/api/auth/login", post(login_handler))
+            .route("/api/auth/refresh", post(refresh_handler))
+            .route("/api/auth/whoami", get(whoami_handler))
+            .route("/api/auth/tokens", post(issue_token_handler))
+            .route("/api/auth/keys", get(list_keys_handler).post(create_key_handler))
+            .route("/api/auth/keys/:id", delete(revoke_key_handler))
+            .with_state(self)
+    }
+
+    async fn principal(&self, credential: &str) -> Option<Principal> {
+        if credential.starts_with(API_KEY_PREFIX) {
+            let key = self.keys.verify(credential).await?;
+            return Some(Principal {
+                id: key.id,
+                name: key.name,
+                kind: PrincipalKind::ApiKey,
+                scopes: key.scopes,
+                rate_limit_per_minute: key.rate_limit_per_minute,
+            });
+        }
+        let claims = self.auth.validate_token(credential).ok()?;
+        Some(Principal {
+            scopes: claims.effective_scopes(),
+            id: claims.sub,
+            name: claims.username,
+            kind: PrincipalKind::Token,
+            rate_limit_per_minute: None,
+        })
+    }
+
+    /// Take one request from the principal's quota, or return how many
+    /// seconds to wait
+    fn check_rate(&self, principal: &Principal) -> Result<(), u64> {
+        let limit = principal
+            .rate_limit_per_minute
+            .and_then(NonZeroU32::new)
+            .unwrap_or(self.default_rate_limit);
+        self.take(principal.limiter_key(), limit)
+    }
+
+    /// Take one request from a client IP's quota for the public routes.
+    /// Requests served without connection info share one bucket.
+    fn check_public_rate(&self, client: Option<IpAddr>) -> Result<(), u64> {
+        let key = client.map_or_else(|| "ip:unknown".to_string(), |ip| format!("ip:{}", ip));
+        self.take(key, self.public_rate_limit)
+    }
+
+    fn take(&self, key: String, limit: NonZeroU32) -> Result<(), u64> {
+        let limiter = {
+            let mut limiters = self.limiters.lock().unwrap_or_else(|e| e.into_inner());
+            limiters
+                .entry(limit)
+                .or_insert_with(|| Arc::new(RateLimiter::dashmap(Quota::per_minute(limit))))
+                .clone()
+        };
+        {
+            let mut last_eviction = self.last_eviction.lock().unwrap_or_else(|e| e.into_inner());
+            if last_eviction.elapsed() >= LIMITER_EVICTION_INTERVAL {
+                *last_eviction = Instant::now();
+                drop(last_eviction);
+                self.evict_idle_limiters();
+            }
+        }
+        limiter.check_key(&key).map_err(|not_until| {
+            not_until
+                .wait_time_from(DefaultClock::default().now())
+                .as_secs()
+                .max(1)
+        })
+    }
+
+    /// Forget keys whose quota has fully refilled; they behave exactly like
+    /// keys never seen before, so every client IP or principal does not
+    /// keep its state forever
+    fn evict_idle_limiters(&self) {
+        let limiters: Vec<_> = self
+            .limiters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        for limiter in limiters {
+            limiter.retain_recent();
+            limiter.shrink_to_fit();
+        }
+    }
+
+    async fn audit(&self, principal: Option<&Principal>, method: &Method, path: &str, status: StatusCode) {
+        info!(
+            "API {} {} by {} -> {}",
+            method,
+            path,
+            principal.map_or("anonymous", |p| p.id.as_str()),
+            status.as_u16()
+        );
+        let Some(log_path) = &self.audit_log else {
+            return;
+        };
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            principal: principal.map(|p| p.id.clone()),
+            kind: principal.map(|p| p.kind),
+            method: method.to_string(),
+            path: path.to_string(),
+            status: status.as_u16(),
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+
+        let _guard = self.audit_lock.lock().await;
+        let result = async {
+            if let Some(parent) = log_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(log_path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            // tokio files finish writes in the background unless flushed
+            file.flush().await
+        }
+        .await;
+        if let Err(e) = result {
+            error!("Failed to write audit log {:?}: {}", log_path, e);
+        }
+    }
+}
+
+/// Routes reachable without credentials
+fn is_public(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::POST => matches!(path, "/api/auth/login" | "/api/auth/refresh"),
+        Method::GET => path == "/api/openapi.json",
+        _ => false,
+    }
+}
+
+/// Scope a request needs
+fn required_scope(method: &Method, path: &str) -> ApiScope {
+    if path == "/api/auth/whoami" {
+        return ApiScope::ReadOnly;
+    }
+    if path.starts_with("/api/auth") {
+        return ApiScope::Admin;
+    }
+    if matches!(*method, Method::GET | Method::HEAD) {
+        return ApiScope::ReadOnly;
+    }
+    if path.starts_with("/api/tabs") || path.starts_with("/api/browser") {
+        ApiScope::Tabs
+    } else if ["/api/proxies", "/api/rotation", "/api/providers", "/api/quarantine", "/api/jobs"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        ApiScope::Proxies
+    } else {
+        ApiScope::Admin
+    }
+}
+
+/// Routes accepting the credential as an `access_token` query parameter.
+/// Kept to the event streams, since URLs end up in logs and history.
+fn accepts_query_token(path: &str) -> bool {
+    path == "/api/events" || path.starts_with("/api/events/")
+}
+
+fn credential(request: &Request) -> Option<String> {
+    let headers = request.headers();
+    if let Some(key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+        return Some(key.trim().to_string());
+    }
+    if let Some(bearer) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(bearer.trim().to_string());
+    }
+    if !accepts_query_token(request.uri().path()) {
+        return None;
+    }
+    url::form_urlencoded::parse(request.uri().query()?.as_bytes())
+        .find(|(name, value)| name == "access_token" && !value.is_empty())
+        .map(|(_, token)| token.into_owned())
+}
+
+fn too_many_requests(retry_after: u64) -> Response {
+    let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+fn is_mutating(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Middleware authenticating, authorizing, rate limiting and auditing every
+/// request. Install with `axum::middleware::from_fn_with_state`.
+pub async fn authenticate(State(auth): State<Arc<ApiAuth>>, mut request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    if is_public(&method, &path) {
+        if method == Method::POST {
+            let client = request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip());
+            if let Err(retry_after) = auth.check_public_rate(client) {
+                warn!("Rate limit exceeded on {} by {:?}", path, client);
+                return too_many_requests(retry_after);
+            }
+        }
+        return next.run(request).await;
+    }
+
+    let principal = match credential(&request) {
+        Some(credential) => auth.principal(&credential).await,
+        None => None,
+    };
+    let outcome = match &principal {
+        None => Err(StatusCode::UNAUTHORIZED.into_response()),
+        Some(p) if !scopes_grant(&p.scopes, required_scope(&method, &path)) => {
+            Err(StatusCode::FORBIDDEN.into_response())
+        }
+        Some(p) => match auth.check_rate(p) {
+            Ok(()) => Ok(p.clone()),
+            Err(retry_after) => {
+                warn!("Rate limit exceeded by {}", p.id);
+                Err(too_many_requests(retry_after))
+            }
+        },
+    };
+
+    let response = match outcome {
+        Ok(principal) => {
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        Err(response) => response,
+    };
+    if is_mutating(&method) {
+        auth.audit(principal.as_ref(), &method, &path, response.status()).await;
+    }
+    response
+}
+
+// ========= Handlers =========
+
+#[derive(Deserialize, ToSchema)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+/// Represents a TokenResponse.
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    security(()),
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Access and refresh tokens", body = TokenResponse),
+        (status = 401, description = "Invalid credentials"),
+    )
+)]
+async fn login_handler(
+    State(auth): State<Arc<ApiAuth>>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let (access_token, refresh_token) = auth
+        .auth
+        .login(payload.username, payload.password)
+        .await
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+    Ok(Json(TokenResponse {
+        access_token,
+        refresh_token: Some(refresh_token),
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    security(()),
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "A new access token", body = TokenResponse),
+        (status = 401, description = "Invalid or expired refresh token"),
+    )
+)]
+async fn refresh_handler(
+    State(auth): State<Arc<ApiAuth>>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let access_token = auth
+        .auth
+        .refresh_access_token(payload.refresh_token)
+        .await
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+    Ok(Json(TokenResponse {
+        access_token,
+        refresh_token: None,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/auth/whoami",
+    tag = "auth",
+    responses((status = 200, description = "The authenticated caller", body = Principal))
+)]
+async fn whoami_handler(Extension(principal): Extension<Principal>) -> Json<Principal> {
+    Json(principal)
+}
+
+#[derive(Deserialize, ToSchema)]
+struct IssueTokenRequest {
+    subject: String,
+    scopes: Vec<ApiScope>,
+    #[serde(default = "default_token_ttl")]
+    ttl_secs: i64,
+}
+
+fn default_token_ttl() -> i64 {
+    3600
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/tokens",
+    tag = "auth",
+    request_body = IssueTokenRequest,
+    responses(
+        (status = 200, description = "A scoped service token", body = TokenResponse),
+        (status = 400, description = "Invalid scopes or lifetime"),
+    )
+)]
+async fn issue_token_handler(
+    State(auth): State<Arc<ApiAuth>>,
+    Json(payload): Json<IssueTokenRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    if !(1..=MAX_SERVICE_TOKEN_TTL_SECS).contains(&payload.ttl_secs) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let access_token = auth
+        .auth
+        .issue_service_token(&payload.subject, payload.scopes, chrono::Duration::seconds(payload.ttl_secs))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    Ok(Json(TokenResponse {
+        access_token,
+        refresh_token: None,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/auth/keys",
+    tag = "auth",
+    responses((status = 200, description = "API keys, without their secrets", body = Vec<ApiKeyResponse>))
+)]
+async fn list_keys_handler(State(auth): State<Arc<ApiAuth>>) -> Json<Vec<ApiKeyResponse>> {
+    Json(auth.keys.list().await.into_iter().map(ApiKeyResponse::from).collect())
+}
+
+/// A new key with its secret token, which is only ever shown here
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreatedKeyResponse {
+    pub key: ApiKeyResponse,
+    pub token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/keys",
+    tag = "auth",
+    request_body = NewApiKey,
+    responses(
+        (status = 201, description = "The new key and its secret token", body = CreatedKeyResponse),
+        (status = 400, description = "Invalid key options"),
+    )
+)]
+async fn create_key_handler(
+    State(auth): State<Arc<ApiAuth>>,
+    Json(payload): Json<NewApiKey>,
+) -> Result<(StatusCode, Json<CreatedKeyResponse>), StatusCode> {
+    let (key, token) = auth.keys.create(payload).await.map_err(|e| {
+        error!("Failed to create API key: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedKeyResponse {
+            key: ApiKeyResponse::from(key),
+            token,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/auth/keys/{id}",
+    tag = "auth",
+    params(("id" = String, Path, description = "API key ID")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "Unknown key"),
+    )
+)]
+async fn revoke_key_handler(
+    State(auth): State<Arc<ApiAuth>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let revoked = auth.keys.revoke(&id).await.map_err(|e| {
+        error!("Failed to revoke API key '{}': {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if revoked {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+// ========= DTOs =========
+
+#[derive(Serialize, Deserialize, ToSchema)]
+/// Represents a ApiKeyResponse.
+pub struct ApiKeyResponse {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub rate_limit_per_minute: Option<u32>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            scopes: key.scopes,
+            rate_limit_per_minute: key.rate_limit_per_minute,
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::middleware;
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    async fn ok() -> StatusCode {
+        StatusCode::OK
+    }
+
+    fn app(auth: Arc<ApiAuth>) -> Router {
+        Router::new()
+            .route("/api/tabs", get(ok).post(ok))
+            .route("/api/proxies", post(ok))
+            .route("/api/events", get(ok))
+            .merge(auth.clone().router())
+            .layer(middleware::from_fn_with_state(auth, authenticate))
+    }
+
+    async fn key(auth: &ApiAuth, scopes: Vec<ApiScope>, rate_limit_per_minute: Option<u32>) -> String {
+        auth.keys()
+            .create(NewApiKey {
+                name: "test".to_string(),
+                scopes,
+                rate_limit_per_minute,
+                expires_at: None,
+            })
+            .await
+            .expect("Create should succeed")
+            .1
+    }
+
+    async fn call(auth: &Arc<ApiAuth>, method: Method, uri: &str, key: Option<&str>, body: Option<Value>) -> Response {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json");
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        app(auth.clone())
+            .oneshot(
+                request
+                    .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                    .expect("Request should build"),
+            )
+            .await
+            .expect("Request should succeed")
+    }
+
+    fn new_auth() -> ApiAuth {
+        ApiAuth::new(
+            Arc::new(AuthManager::new("test-secret".to_string())),
+            Arc::new(ApiKeyStore::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_scopes_are_enforced() {
+        let auth = Arc::new(new_auth());
+        let read_only = key(&auth, vec![ApiScope::ReadOnly], None).await;
+        let tabs = key(&auth, vec![ApiScope::Tabs], None).await;
+        let admin = key(&auth, vec![ApiScope::Admin], None).await;
+
+        assert_eq!(call(&auth, Method::GET, "/api/tabs", None, None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            call(&auth, Method::GET, "/api/tabs", Some("pxb_bogus_key"), None).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(call(&auth, Method::GET, "/api/tabs", Some(&read_only), None).await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&auth, Method::POST, "/api/tabs", Some(&read_only), None).await.status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(call(&auth, Method::POST, "/api/tabs", Some(&tabs), None).await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&auth, Method::POST, "/api/proxies", Some(&tabs), None).await.status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            call(&auth, Method::GET, "/api/auth/keys", Some(&tabs), None).await.status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let response = call(
+            &auth,
+            Method::POST,
+            "/api/auth/keys",
+            Some(&admin),
+            Some(json!({ "name": "ci", "scopes": ["proxies"] })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(auth.keys().list().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_jwt_and_query_token() {
+        let auth = Arc::new(new_auth());
+        let token = auth
+            .auth
+            .issue_service_token("ci", vec![ApiScope::Proxies], chrono::Duration::minutes(5))
+            .expect("Issue should succeed");
+
+        let request = |method: Method, uri: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .expect("Request should build")
+        };
+        let status = |response: Response| response.status();
+
+        assert_eq!(
+            status(app(auth.clone()).oneshot(request(Method::POST, "/api/proxies")).await.expect("ok")),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(app(auth.clone()).oneshot(request(Method::POST, "/api/tabs")).await.expect("ok")),
+            StatusCode::FORBIDDEN
+        );
+
+        // Query tokens are percent-decoded, and only accepted by the event streams
+        let encoded: String = token.bytes().map(|b| format!("%{:02X}", b)).collect();
+        let response = call(&auth, Method::GET, &format!("/api/events?access_token={}", encoded), None, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = call(&auth, Method::GET, "/api/events?access_token=%20", None, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = call(&auth, Method::GET, &format!("/api/tabs?access_token={}", token), None, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_per_key_rate_limit() {
+        let auth = Arc::new(new_auth().with_default_rate_limit(100));
+        let limited = key(&auth, vec![ApiScope::ReadOnly], Some(2)).await;
+        let unlimited = key(&auth, vec![ApiScope::ReadOnly], None).await;
+
+        for _ in 0..2 {
+            assert_eq!(call(&auth, Method::GET, "/api/tabs", Some(&limited), None).await.status(), StatusCode::OK);
+        }
+        let response = call(&auth, Method::GET, "/api/tabs", Some(&limited), None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        // Other keys have their own budget
+        assert_eq!(call(&auth, Method::GET, "/api/tabs", Some(&unlimited), None).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_public_routes_are_rate_limited_per_ip() {
+        let auth = Arc::new(new_auth().with_public_rate_limit(3));
+        let login = |ip: [u8; 4]| {
+            let mut request = Request::builder()
+                .method(Method::POST)
+                .uri("/api/auth/login")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "username": "admin", "password": "guess" }).to_string()))
+                .expect("Request should build");
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+            app(auth.clone()).oneshot(request)
+        };
+
+        for _ in 0..3 {
+            let response = login([10, 0, 0, 1]).await.expect("Request should succeed");
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        let response = login([10, 0, 0, 1]).await.expect("Request should succeed");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        // Other clients have their own budget
+        let response = login([10, 0, 0, 2]).await.expect("Request should succeed");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_idle_limiter_state_is_evicted() {
+        let auth = new_auth();
+        let fast = NonZeroU32::new(60_000).expect("limit is non-zero");
+        let slow = NonZeroU32::new(1).expect("limit is non-zero");
+        for i in 0..1000 {
+            assert!(auth.take(format!("ip:10.0.{}.{}", i / 256, i % 256), fast).is_ok());
+        }
+        assert!(auth.take("key:busy".to_string(), slow).is_ok());
+        let tracked = |auth: &ApiAuth| -> usize {
+            auth.limiters.lock().expect("lock").values().map(|limiter| limiter.len()).sum()
+        };
+        assert_eq!(tracked(&auth), 1001);
+
+        // A 60000/min quota refills within a millisecond, a 1/min one does not
+        std::thread::sleep(Duration::from_millis(20));
+        auth.evict_idle_limiters();
+        assert_eq!(tracked(&auth), 1);
+        assert!(auth.take("key:busy".to_string(), slow).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mutating_calls_are_audited() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let log = dir.path().join("audit.log");
+        let auth = Arc::new(new_auth().with_audit_log(&log));
+        let tabs = key(&auth, vec![ApiScope::Tabs], None).await;
+
+        call(&auth, Method::GET, "/api/tabs", Some(&tabs), None).await;
+        call(&auth, Method::POST, "/api/tabs", Some(&tabs), None).await;
+        call(&auth, Method::POST, "/api/proxies", Some(&tabs), None).await;
+        call(&auth, Method::POST, "/api/tabs", None, None).await;
+
+        let entries: Vec<AuditEntry> = std::fs::read_to_string(&log)
+            .expect("Audit log should exist")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("Audit line should parse"))
+            .collect();
+        let statuses: Vec<u16> = entries.iter().map(|e| e.status).collect();
+        assert_eq!(statuses, vec![200, 403, 401]);
+        assert_eq!(entries[0].kind, Some(PrincipalKind::ApiKey));
+        assert_eq!(entries[2].principal, None);
+    }
+}
@@ -0,0 +1,239 @@
+//! Bootstrap Module
+//!
+//! Wires a complete `ApiServer` from environment variables. Shared by the
+//! `api-server` binary and the CLI's `serve` command.
+//!
+//! Data and lookup tables:
+//! - `COUNTRIES_PATH` / `IP_RANGES_PATH`: country and IP range overrides
+//! - `JOBS_DIR`: batch job records and results (default `data/jobs`)
+//! - `PORT`: listen port (default 8080)
+//! - `CONFIG_PATH`: app config file; when set, proxy rotation follows it and
+//!   edits are applied without a restart
+//!
+//! Secrets:
+//! - `SECRETS_VAULT_PATH`: encrypted vault file (default `data/secrets.vault`)
+//! - `SECRETS_MASTER_PASSWORD`: unlocks the vault file; without it the OS
+//!   keyring is used when one is available
+//!
+//! Authentication:
+//! - `API_JWT_SECRET`: token signing secret (random per process if unset)
+//! - `API_KEYS_PATH`: API key store (default `data/api_keys.json`)
+//! - `API_AUDIT_LOG`: audit log of mutating calls (default `data/api_audit.log`)
+//! - `API_RATE_LIMIT`: default requests per minute per key or token
+//! - `API_TLS_CERT` / `API_TLS_KEY`: PEM files enabling HTTPS
+//! - `API_BIND_HOST`: listen address (default `127.0.0.1`)
+//! - `API_AUTH_DISABLED=1`: serve without authentication
+//!
+//! Headless daemon mode:
+//! - `BROWSER_DAEMON=1`: launch a headless Chromium engine at startup
+//! - `CHROMIUM_PATH`: browser executable (auto-detected if unset)
+//! - `CHROMIUM_NO_SANDBOX=1`: disable the sandbox, e.g. in containers
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use rand::distributions::{Alphanumeric, DistString};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use crate::{ApiAuth, ApiServer, BatchJobManager, ProxyServices};
+use browser_core::{
+    ApiKeyStore, ApiScope, AuthManager, BrowserEngineManager, BrowserEngineType, ChromiumEngineConfig,
+    ConfigHotReloader, ConfigManager, ErrorRecoveryManager, EventBus, FreeIpProviderManager, FreeProxy, LocalProxyManager, MetricsCollector, NewApiKey,
+    ProxyGateway, ProxyHealthMonitor, ProxyManager, ProxyQuarantineManager, ProxyRotationManager,
+    ProxyRotationStrategy, ProxyValidator, ProxyValidatorConfig, SecretsVault, TabIPManager, UpstreamFailover,
+};
+use virtual_ip::{
+    demo_generator, load_countries_from_file, load_ip_ranges, load_ip_ranges_from_file, CountryDatabase, IPGenerator,
+};
+
+/// Listen port from `PORT`, defaulting to 8080
+pub fn port_from_env() -> u16 {
+    env::var("PORT").ok().and_then(|s| s.parse().ok()).unwrap_or(8080)
+}
+
+/// Server built from the environment
+pub struct BootstrappedServer {
+    pub server: ApiServer,
+    /// Admin API key created because the key store was empty. Only its hash
+    /// is stored, so the caller has to show it to the operator once.
+    pub admin_key: Option<String>,
+}
+
+/// Build the server described by the environment, starting with
+/// `initial_pool` in the proxy pool
+pub async fn server_from_env(initial_pool: Vec<FreeProxy>) -> Result<BootstrappedServer> {
+    let ip_generator = load_ip_generator();
+    let secrets = load_secrets_vault();
+
+    // Shared by every component that publishes to /api/events
+    let events = Arc::new(EventBus::new());
+
+    // Create TabIPManager with in-memory storage (no database)
+    let tab_manager = Arc::new(Mutex::new(
+        TabIPManager::new(ip_generator.clone()).with_event_bus(events.clone()),
+    ));
+
+    // Request counters and latency histograms exported at /metrics
+    let metrics = Arc::new(MetricsCollector::new());
+
+    // Proxy pool shared by rotation and the pool endpoints
+    let mut pool = FreeIpProviderManager::new()?;
+    pool.set_proxy_pool(initial_pool);
+    let providers = Arc::new(RwLock::new(pool));
+    let proxy_manager = match &secrets {
+        Some(vault) => ProxyManager::new().with_secrets_vault(vault.clone()),
+        None => ProxyManager::new(),
+    };
+    let proxy_services = ProxyServices::new(
+        Arc::new(proxy_manager),
+        providers.clone(),
+        Arc::new(RwLock::new(
+            ProxyRotationManager::new(providers, ProxyRotationStrategy::RoundRobin).with_event_bus(events.clone()),
+        )),
+        Arc::new(ProxyValidator::new(ProxyValidatorConfig::default()).with_event_bus(events.clone())),
+        Arc::new(
+            ProxyQuarantineManager::new(3, Duration::from_secs(300), Duration::from_secs(3600))
+                .with_event_bus(events.clone()),
+        ),
+        Arc::new(ProxyHealthMonitor::new().with_metrics(metrics.clone())),
+    );
+
+    // Per-tab local proxies fail over through the same pool, skipping
+    // quarantined proxies
+    let gateway = ProxyGateway::new(proxy_services.rotation.clone(), proxy_services.health.clone())
+        .with_quarantine(proxy_services.quarantine.clone());
+    let local_proxies =
+        Arc::new(LocalProxyManager::new(9000..10000).with_failover(UpstreamFailover::new(Arc::new(gateway))));
+    let error_recovery = Arc::new(ErrorRecoveryManager::new().with_event_bus(events.clone()));
+
+    if let Ok(path) = env::var("CONFIG_PATH") {
+        watch_config(path, &proxy_services).await?;
+    }
+
+    let jobs_dir = env::var("JOBS_DIR").unwrap_or_else(|_| "data/jobs".to_string());
+    let jobs = BatchJobManager::open(jobs_dir, Some(proxy_services.clone())).await?;
+
+    let mut server = ApiServer::new(tab_manager, Arc::new(ip_generator))
+        .with_proxy_services(proxy_services)
+        .with_batch_jobs(jobs)
+        .with_local_proxies(local_proxies)
+        .with_error_recovery(error_recovery)
+        .with_event_bus(events)
+        .with_metrics(metrics);
+
+    if env::var("BROWSER_DAEMON").as_deref() == Ok("1") {
+        server = server.with_browser_engine(launch_browser(secrets).await?);
+    }
+    let mut admin_key = None;
+    if env::var("API_AUTH_DISABLED").as_deref() != Ok("1") {
+        let (auth, created_key) = load_auth().await?;
+        server = server.with_auth(auth);
+        admin_key = created_key;
+    }
+    if let (Ok(cert), Ok(key)) = (env::var("API_TLS_CERT"), env::var("API_TLS_KEY")) {
+        server = server.with_tls(cert, key);
+    }
+    if let Ok(host) = env::var("API_BIND_HOST") {
+        server = server.with_bind_host(host);
+    }
+    Ok(BootstrappedServer { server, admin_key })
+}
+
+/// Country and IP range data, with file-based overrides from the environment
+fn load_ip_generator() -> IPGenerator {
+    let country_path = env::var("COUNTRIES_PATH").ok();
+    let ip_ranges_path = env::var("IP_RANGES_PATH").ok();
+
+    let countries = country_path
+        .as_deref()
+        .map(std::path::Path::new)
+        .map(load_countries_from_file)
+        .unwrap_or_else(CountryDatabase::load_all_countries);
+
+    let ranges = ip_ranges_path
+        .as_deref()
+        .map(std::path::Path::new)
+        .map(load_ip_ranges_from_file)
+        .unwrap_or_else(load_ip_ranges);
+    if countries.is_empty() || ranges.is_empty() {
+        demo_generator()
+    } else {
+        IPGenerator::new(countries, ranges)
+    }
+}
+
+/// Apply the config file's proxy rotation now and on every edit
+async fn watch_config(path: String, services: &ProxyServices) -> Result<()> {
+    let manager = Arc::new(ConfigManager::with_path(path));
+    manager.load().await?;
+    let reloader = Arc::new(ConfigHotReloader::new(manager)?);
+    reloader.register(services.rotation.clone()).await?;
+    reloader.start();
+    Ok(())
+}
+
+/// Vault resolving `secret://` proxy passwords, if one can be opened
+fn load_secrets_vault() -> Option<Arc<SecretsVault>> {
+    let path = env::var("SECRETS_VAULT_PATH").unwrap_or_else(|_| "data/secrets.vault".to_string());
+    let master_password = env::var("SECRETS_MASTER_PASSWORD").ok();
+    match SecretsVault::detect("virtual-ip-browser", path, master_password.as_deref()) {
+        Ok(vault) => Some(Arc::new(vault)),
+        Err(e) => {
+            warn!("Secrets vault unavailable, proxy password references will not resolve: {}", e);
+            None
+        }
+    }
+}
+
+/// Launch the headless Chromium engine served at `/api/browser`
+async fn launch_browser(secrets: Option<Arc<SecretsVault>>) -> Result<Arc<BrowserEngineManager>> {
+    let config = ChromiumEngineConfig {
+        executable_path: env::var("CHROMIUM_PATH").ok().map(Into::into),
+        headless: true,
+        sandbox: env::var("CHROMIUM_NO_SANDBOX").as_deref() != Ok("1"),
+        ..ChromiumEngineConfig::default()
+    };
+    let browser = Arc::new(match secrets {
+        Some(vault) => BrowserEngineManager::new().with_secrets_vault(vault),
+        None => BrowserEngineManager::new(),
+    });
+    browser.update_chromium_config(config).await?;
+    browser.set_engine_type(BrowserEngineType::IntegratedChromium).await?;
+    info!("Headless Chromium engine running");
+    Ok(browser)
+}
+
+/// Build the authentication layer, creating an admin key on first start.
+/// Returns the new key's token if one was created.
+async fn load_auth() -> Result<(ApiAuth, Option<String>)> {
+    let secret = env::var("API_JWT_SECRET").unwrap_or_else(|_| {
+        warn!("API_JWT_SECRET not set; issued tokens will not survive a restart");
+        Alphanumeric.sample_string(&mut rand::thread_rng(), 48)
+    });
+    let keys_path = env::var("API_KEYS_PATH").unwrap_or_else(|_| "data/api_keys.json".to_string());
+    let keys = Arc::new(ApiKeyStore::open(&keys_path).await?);
+
+    let mut admin_key = None;
+    if keys.is_empty().await {
+        let (_, token) = keys
+            .create(NewApiKey {
+                name: "bootstrap-admin".to_string(),
+                scopes: vec![ApiScope::Admin],
+                rate_limit_per_minute: None,
+                expires_at: None,
+            })
+            .await?;
+        info!("Created bootstrap admin API key");
+        admin_key = Some(token);
+    }
+
+    let mut auth = ApiAuth::new(Arc::new(AuthManager::new(secret)), keys)
+        .with_audit_log(env::var("API_AUDIT_LOG").unwrap_or_else(|_| "data/api_audit.log".to_string()));
+    if let Some(limit) = env::var("API_RATE_LIMIT").ok().and_then(|s| s.parse().ok()) {
+        auth = auth.with_default_rate_limit(limit);
+    }
+    Ok((auth, admin_key))
+}
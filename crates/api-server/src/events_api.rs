@@ -0,0 +1,238 @@
+//! Events API Module
+//!
+//! Push endpoints over the browser `EventBus`:
+//! - `GET /api/events` upgrades to a WebSocket streaming JSON events
+//! - `GET /api/events/sse` streams the same events as Server-Sent Events
+//!
+//! Both accept `topics` (comma separated), `tab_id` and `since` query
+//! parameters. `since` resumes after a sequence number; the SSE endpoint
+//! also honours the `Last-Event-ID` header browsers send on reconnect.
+//! Events that are no longer buffered arrive as a single `gap` message.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    routing::get,
+    Router,
+};
+use browser_core::{EventBus, EventFilter, EventSubscription, EventTopic, StreamItem};
+use futures::Stream;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tracing::debug;
+use utoipa::IntoParams;
+
+/// Routes for the event stream, ready to merge into the server router.
+pub fn router(bus: Arc<EventBus>) -> Router {
+    Router::new()
+        .route("/api/events", get(events_ws_handler))
+        .route("/api/events/sse", get(events_sse_handler))
+        .with_state(bus)
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct EventQuery {
+    /// Comma-separated topic names; all topics when absent
+    topics: Option<String>,
+    tab_id: Option<String>,
+    /// Resume after this sequence number
+    since: Option<u64>,
+}
+
+impl EventQuery {
+    fn filter(&self) -> Result<EventFilter, StatusCode> {
+        let mut filter = EventFilter::all();
+        if let Some(topics) = &self.topics {
+            let topics = topics
+                .split(',')
+                .filter(|t| !t.trim().is_empty())
+                .map(|t| EventTopic::parse(t).ok_or(StatusCode::BAD_REQUEST))
+                .collect::<Result<Vec<_>, _>>()?;
+            filter = filter.with_topics(topics);
+        }
+        if let Some(tab_id) = &self.tab_id {
+            filter = filter.with_tab(tab_id.clone());
+        }
+        Ok(filter)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    params(EventQuery),
+    responses(
+        (status = 101, description = "WebSocket of JSON events and gap messages"),
+        (status = 400, description = "Unknown topic"),
+    )
+)]
+async fn events_ws_handler(
+    ws: WebSocketUpgrade,
+    State(bus): State<Arc<EventBus>>,
+    Query(query): Query<EventQuery>,
+) -> Result<Response, StatusCode> {
+    let subscription = bus.subscribe(query.filter()?, query.since);
+    Ok(ws.on_upgrade(move |socket| stream_events(socket, subscription)))
+}
+
+async fn stream_events(mut socket: WebSocket, mut subscription: EventSubscription) {
+    loop {
+        tokio::select! {
+            item = subscription.next() => {
+                let Some(item) = item else { break };
+                let Ok(text) = serde_json::to_string(&item) else { continue };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                // Pings are answered by axum; other client messages are ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+    debug!("Event stream client disconnected at seq {}", subscription.last_seq());
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/events/sse",
+    tag = "events",
+    params(
+        EventQuery,
+        ("Last-Event-ID" = Option<u64>, Header, description = "Resume after this sequence number"),
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events of JSON events and gap messages", content_type = "text/event-stream"),
+        (status = 400, description = "Unknown topic"),
+    )
+)]
+async fn events_sse_handler(
+    State(bus): State<Arc<EventBus>>,
+    Query(query): Query<EventQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let since = query.since.or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+    });
+    let subscription = bus.subscribe(query.filter()?, since);
+
+    let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+        let item = subscription.next().await?;
+        // The ID is what the client resumes from, so a gap carries the last
+        // sequence number it covers
+        let event = match &item {
+            StreamItem::Event(envelope) => Event::default()
+                .id(envelope.seq.to_string())
+                .event(envelope.topic.as_str()),
+            StreamItem::Gap(gap) => Event::default().id(gap.last_missed.to_string()).event("gap"),
+        };
+        let event = event.json_data(&item).ok()?;
+        Some((Ok(event), subscription))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use browser_core::BrowserEvent;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn closed(tab_id: &str) -> BrowserEvent {
+        BrowserEvent::TabClosed {
+            tab_id: tab_id.to_string(),
+        }
+    }
+
+    /// Read SSE frames until `count` events have arrived
+    async fn read_sse(bus: Arc<EventBus>, uri: &str, last_event_id: Option<&str>, count: usize) -> String {
+        let mut request = Request::builder().uri(uri);
+        if let Some(id) = last_event_id {
+            request = request.header("last-event-id", id);
+        }
+        let response = router(bus)
+            .oneshot(request.body(Body::empty()).expect("Request should build"))
+            .await
+            .expect("Request should succeed");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut body = response.into_body();
+        let mut text = String::new();
+        while text.matches("\n\n").count() < count {
+            let frame = body
+                .frame()
+                .await
+                .expect("Stream should stay open")
+                .expect("Frame should be readable");
+            if let Ok(data) = frame.into_data() {
+                text.push_str(&String::from_utf8_lossy(&data));
+            }
+        }
+        text
+    }
+
+    #[tokio::test]
+    async fn test_sse_resumes_with_filters() {
+        let bus = Arc::new(EventBus::new());
+        bus.publish(closed("a"));
+        bus.publish(BrowserEvent::ProxyReleased {
+            proxy_id: "10.0.0.1:8080".to_string(),
+        });
+        bus.publish(closed("b"));
+        bus.publish(closed("a"));
+
+        let text = read_sse(bus.clone(), "/api/events/sse?since=1&topics=tab&tab_id=a", None, 1).await;
+        assert!(text.contains("id: 4"), "{}", text);
+        assert!(text.contains("event: tab"), "{}", text);
+        assert!(text.contains("\"type\":\"tab_closed\""), "{}", text);
+
+        let text = read_sse(bus, "/api/events/sse", Some("2"), 2).await;
+        assert!(text.contains("id: 3") && text.contains("id: 4"), "{}", text);
+        assert!(!text.contains("id: 2"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_sse_reports_gap() {
+        let bus = Arc::new(EventBus::with_replay_capacity(1));
+        bus.publish(closed("a"));
+        bus.publish(closed("b"));
+
+        let text = read_sse(bus, "/api/events/sse?since=0", None, 2).await;
+        assert!(text.contains("event: gap"), "{}", text);
+        assert!(text.contains("\"first_missed\":1"), "{}", text);
+        assert!(text.contains("id: 2"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_topic_is_rejected() {
+        let response = router(Arc::new(EventBus::new()))
+            .oneshot(
+                Request::builder()
+                    .uri("/api/events/sse?topics=tab,nope")
+                    .body(Body::empty())
+                    .expect("Request should build"),
+            )
+            .await
+            .expect("Request should succeed");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
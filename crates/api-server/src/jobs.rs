@@ -0,0 +1,900 @@
+//! Batch Fetch Jobs
+//!
+//! Fetches one URL list from many countries, e.g. "these 500 URLs from 10
+//! countries", and records status, headers, a SHA-256 hash of the body and
+//! timing for every fetch:
+//! - every country of a job gets a sticky session in `ProxyRotationManager`;
+//!   a proxy that fails is excluded and the session rotates to another
+//!   proxy from the same country for the next attempt
+//! - outcomes feed `ProxyHealthMonitor` and `ProxyQuarantineManager`, and
+//!   quarantined proxies are skipped
+//! - fetches in flight are bounded per job and per target domain
+//! - each job is stored as `<id>.json` plus an append-only
+//!   `<id>.results.jsonl` in the jobs directory; `BatchJobManager::open`
+//!   reloads them and resumes running jobs with the fetches that have no
+//!   result yet
+
+use crate::proxy_api::{proxy_id, ProxyServices};
+use anyhow::{anyhow, Context, Result};
+use browser_core::{FreeProxy, RequestManager};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex, RwLock, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+fn default_concurrency() -> usize {
+    16
+}
+
+fn default_per_domain_concurrency() -> usize {
+    2
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// What a batch job fetches and how
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchJobSpec {
+    /// http(s) URLs to fetch; duplicates are dropped
+    pub urls: Vec<String>,
+    /// Country codes to fetch every URL from; when empty each URL is
+    /// fetched once through any pooled proxy
+    #[serde(default)]
+    pub countries: Vec<String>,
+    /// Fetch without a proxy; `countries` must be empty
+    #[serde(default)]
+    pub direct: bool,
+    /// Fetches in flight across the job
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Fetches in flight against one domain
+    #[serde(default = "default_per_domain_concurrency")]
+    pub per_domain_concurrency: usize,
+    /// Attempts per fetch; each retry goes through a different proxy
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Timeout of a single attempt in seconds
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// Lifecycle of a batch job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+/// Progress of a batch job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchJobProgress {
+    pub id: String,
+    pub state: JobState,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub urls: usize,
+    pub countries: Vec<String>,
+    /// Fetches in the job: URLs times countries
+    pub total: usize,
+    /// Fetches with a result
+    pub completed: usize,
+    /// Results with an HTTP response, whatever its status
+    pub fetched: usize,
+    /// Results where every attempt failed
+    pub failed: usize,
+}
+
+/// Outcome of fetching one URL from one country, a line of the results
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FetchResult {
+    pub url: String,
+    /// Country the URL was fetched from, absent for direct or any-country
+    /// jobs
+    pub country: Option<String>,
+    /// HTTP status, absent when every attempt failed
+    pub status: Option<u16>,
+    pub headers: BTreeMap<String, String>,
+    /// Hex SHA-256 of the decoded response body
+    pub body_sha256: Option<String>,
+    pub body_bytes: Option<usize>,
+    /// Duration of the final attempt
+    pub elapsed_ms: u64,
+    pub attempts: u32,
+    /// `ip:port` of the proxy used by the final attempt
+    pub proxy: Option<String>,
+    /// Error of the final attempt when it failed
+    pub error: Option<String>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Header row of the CSV results
+pub const CSV_HEADER: &str =
+    "url,country,status,body_sha256,body_bytes,elapsed_ms,attempts,proxy,error,finished_at,headers";
+
+impl FetchResult {
+    fn key(&self) -> (String, Option<String>) {
+        (self.url.clone(), self.country.clone())
+    }
+
+    /// The result as a CSV row matching `CSV_HEADER`; headers are a JSON
+    /// object
+    pub fn to_csv_row(&self) -> String {
+        let fields = [
+            self.url.clone(),
+            self.country.clone().unwrap_or_default(),
+            self.status.map(|s| s.to_string()).unwrap_or_default(),
+            self.body_sha256.clone().unwrap_or_default(),
+            self.body_bytes.map(|b| b.to_string()).unwrap_or_default(),
+            self.elapsed_ms.to_string(),
+            self.attempts.to_string(),
+            self.proxy.clone().unwrap_or_default(),
+            self.error.clone().unwrap_or_default(),
+            self.finished_at.to_rfc3339(),
+            serde_json::to_string(&self.headers).unwrap_or_default(),
+        ];
+        fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Stored form of a job, `<id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JobRecord {
+    id: String,
+    spec: BatchJobSpec,
+    state: JobState,
+    created_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+struct BatchJob {
+    record: RwLock<JobRecord>,
+    completed: AtomicUsize,
+    fetched: AtomicUsize,
+    failed: AtomicUsize,
+    cancelled: AtomicBool,
+    /// Serializes appends to the results file
+    results: Mutex<()>,
+}
+
+impl BatchJob {
+    fn new(record: JobRecord) -> Self {
+        Self {
+            cancelled: AtomicBool::new(record.state == JobState::Cancelled),
+            record: RwLock::new(record),
+            completed: AtomicUsize::new(0),
+            fetched: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            results: Mutex::new(()),
+        }
+    }
+
+    fn count(&self, result: &FetchResult) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        if result.status.is_some() {
+            self.fetched.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn progress(&self) -> BatchJobProgress {
+        let record = self.record.read().await;
+        BatchJobProgress {
+            id: record.id.clone(),
+            state: record.state,
+            created_at: record.created_at,
+            finished_at: record.finished_at,
+            urls: record.spec.urls.len(),
+            countries: record.spec.countries.clone(),
+            total: record.spec.urls.len() * record.spec.countries.len().max(1),
+            completed: self.completed.load(Ordering::Relaxed),
+            fetched: self.fetched.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Runs batch fetch jobs and keeps their results on disk
+pub struct BatchJobManager {
+    dir: PathBuf,
+    proxies: Option<ProxyServices>,
+    requests: RequestManager,
+    jobs: RwLock<HashMap<String, Arc<BatchJob>>>,
+}
+
+impl BatchJobManager {
+    /// Load the jobs stored in `dir` and resume the ones still running.
+    /// Without `proxies` only direct jobs can be submitted.
+    pub async fn open(dir: impl AsRef<Path>, proxies: Option<ProxyServices>) -> Result<Arc<Self>> {
+        let dir = dir.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create jobs directory {}", dir.display()))?;
+        let manager = Arc::new(Self {
+            dir,
+            proxies,
+            requests: RequestManager::new()?,
+            jobs: RwLock::new(HashMap::new()),
+        });
+
+        let mut entries = tokio::fs::read_dir(&manager.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let content = tokio::fs::read_to_string(&path).await?;
+            let record: JobRecord = match serde_json::from_str(&content) {
+                Ok(record) => record,
+                Err(e) => {
+                    warn!("Skipping unreadable job file {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            manager.drop_partial_result(&record.id).await?;
+            let job = Arc::new(BatchJob::new(record.clone()));
+            let mut done = HashSet::new();
+            for result in manager.read_results(&record.id).await? {
+                if done.insert(result.key()) {
+                    job.count(&result);
+                }
+            }
+            manager.jobs.write().await.insert(record.id.clone(), job.clone());
+            if record.state == JobState::Running {
+                info!("Resuming batch job {} with {} results", record.id, done.len());
+                tokio::spawn(manager.clone().run(job, done));
+            }
+        }
+        Ok(manager)
+    }
+
+    /// Validate and start a job
+    pub async fn submit(self: &Arc<Self>, mut spec: BatchJobSpec) -> Result<BatchJobProgress> {
+        let mut seen = HashSet::new();
+        spec.urls.retain(|u| seen.insert(u.clone()));
+        let mut seen = HashSet::new();
+        spec.countries = spec
+            .countries
+            .iter()
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| seen.insert(c.clone()))
+            .collect();
+
+        if spec.urls.is_empty() {
+            return Err(anyhow!("A job needs at least one URL"));
+        }
+        for raw in &spec.urls {
+            let url = url::Url::parse(raw).map_err(|e| anyhow!("Invalid URL {}: {}", raw, e))?;
+            if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+                return Err(anyhow!("Only http(s) URLs can be fetched: {}", raw));
+            }
+        }
+        if spec.direct && !spec.countries.is_empty() {
+            return Err(anyhow!("Direct jobs cannot fetch from countries"));
+        }
+        if !spec.direct && self.proxies.is_none() {
+            return Err(anyhow!("No proxy pool is configured; only direct jobs can run"));
+        }
+        if spec.concurrency == 0 || spec.per_domain_concurrency == 0 || spec.max_attempts == 0 || spec.timeout_secs == 0
+        {
+            return Err(anyhow!("Concurrency, attempts and timeout must be positive"));
+        }
+
+        let record = JobRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            spec,
+            state: JobState::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+        self.save(&record).await?;
+        let job = Arc::new(BatchJob::new(record.clone()));
+        self.jobs.write().await.insert(record.id.clone(), job.clone());
+        info!(
+            "Started batch job {}: {} URLs from {} countries",
+            record.id,
+            record.spec.urls.len(),
+            record.spec.countries.len()
+        );
+        tokio::spawn(self.clone().run(job.clone(), HashSet::new()));
+        Ok(job.progress().await)
+    }
+
+    /// Every job, newest first
+    pub async fn list(&self) -> Vec<BatchJobProgress> {
+        let jobs: Vec<Arc<BatchJob>> = self.jobs.read().await.values().cloned().collect();
+        let mut progress = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            progress.push(job.progress().await);
+        }
+        progress.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+        progress
+    }
+
+    /// Progress of one job
+    pub async fn get(&self, id: &str) -> Option<BatchJobProgress> {
+        let job = self.jobs.read().await.get(id).cloned()?;
+        Some(job.progress().await)
+    }
+
+    /// Stop starting new fetches for a running job; fetches in flight
+    /// finish but are not recorded. Finished jobs are left as they are.
+    pub async fn cancel(&self, id: &str) -> Result<Option<BatchJobProgress>> {
+        let Some(job) = self.jobs.read().await.get(id).cloned() else {
+            return Ok(None);
+        };
+        {
+            let mut record = job.record.write().await;
+            if record.state == JobState::Running {
+                job.cancelled.store(true, Ordering::Relaxed);
+                record.state = JobState::Cancelled;
+                record.finished_at = Some(Utc::now());
+                self.save(&record).await?;
+                info!("Cancelled batch job {}", id);
+            }
+        }
+        Ok(Some(job.progress().await))
+    }
+
+    /// Results recorded so far, in completion order
+    pub async fn results(&self, id: &str) -> Result<Option<Vec<FetchResult>>> {
+        if !self.jobs.read().await.contains_key(id) {
+            return Ok(None);
+        }
+        self.read_results(id).await.map(Some)
+    }
+
+    fn record_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    fn results_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.results.jsonl"))
+    }
+
+    async fn save(&self, record: &JobRecord) -> Result<()> {
+        let path = self.record_path(&record.id);
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_string_pretty(record)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn read_results(&self, id: &str) -> Result<Vec<FetchResult>> {
+        let path = self.results_path(id);
+        if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(Vec::new());
+        }
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read job results from {}", path.display()))?;
+        Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+
+    /// Cut a result line left incomplete by a crash, so appends start on a
+    /// fresh line; its fetch runs again on resume
+    async fn drop_partial_result(&self, id: &str) -> Result<()> {
+        let path = self.results_path(id);
+        let Ok(content) = tokio::fs::read_to_string(&path).await else {
+            return Ok(());
+        };
+        if !content.is_empty() && !content.ends_with('\n') {
+            let keep = content.rfind('\n').map_or(0, |i| i + 1);
+            tokio::fs::write(&path, &content[..keep]).await?;
+        }
+        Ok(())
+    }
+
+    async fn append_result(&self, job: &BatchJob, result: &FetchResult) -> Result<()> {
+        let id = job.record.read().await.id.clone();
+        let mut line = serde_json::to_string(result)?;
+        line.push('\n');
+        let _guard = job.results.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.results_path(&id))
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Fetch everything not in `done`, then mark the job completed
+    async fn run(self: Arc<Self>, job: Arc<BatchJob>, done: HashSet<(String, Option<String>)>) {
+        let (id, spec) = {
+            let record = job.record.read().await;
+            (record.id.clone(), Arc::new(record.spec.clone()))
+        };
+        let countries: Vec<Option<String>> = if spec.countries.is_empty() {
+            vec![None]
+        } else {
+            spec.countries.iter().cloned().map(Some).collect()
+        };
+
+        // Workers pull from a queue bounded to the job concurrency, so a
+        // large job never has more than that many fetches queued up
+        let (queue, work) = mpsc::channel::<(String, Option<String>, Arc<Semaphore>)>(spec.concurrency);
+        let work = Arc::new(Mutex::new(work));
+        let mut workers = JoinSet::new();
+        for _ in 0..spec.concurrency.min(countries.len() * spec.urls.len()) {
+            let (manager, job, spec, id, work) = (self.clone(), job.clone(), spec.clone(), id.clone(), work.clone());
+            workers.spawn(async move {
+                loop {
+                    let Some((url, country, domain_slots)) = work.lock().await.recv().await else {
+                        break;
+                    };
+                    let Ok(_domain) = domain_slots.acquire_owned().await else { continue };
+                    let Some(result) = manager.fetch(&job, &id, &spec, &url, country.as_deref()).await else {
+                        continue;
+                    };
+                    if job.cancelled.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    if let Err(e) = manager.append_result(&job, &result).await {
+                        error!("Failed to store result of batch job {}: {}", id, e);
+                    }
+                    job.count(&result);
+                }
+            });
+        }
+
+        let mut domains: HashMap<String, Arc<Semaphore>> = HashMap::new();
+        'feed: for country in &countries {
+            for url in &spec.urls {
+                if job.cancelled.load(Ordering::Relaxed) {
+                    break 'feed;
+                }
+                if done.contains(&(url.clone(), country.clone())) {
+                    continue;
+                }
+                let domain = url::Url::parse(url)
+                    .ok()
+                    .and_then(|u| u.host_str().map(str::to_string))
+                    .unwrap_or_default();
+                let domain_slots = domains
+                    .entry(domain)
+                    .or_insert_with(|| Arc::new(Semaphore::new(spec.per_domain_concurrency)))
+                    .clone();
+                if queue.send((url.clone(), country.clone(), domain_slots)).await.is_err() {
+                    break 'feed;
+                }
+            }
+        }
+        drop(queue);
+        while workers.join_next().await.is_some() {}
+
+        if let Some(services) = &self.proxies {
+            let rotation = services.rotation.read().await;
+            for country in &countries {
+                rotation.end_session(&session_id(&id, country.as_deref())).await;
+            }
+        }
+        let mut record = job.record.write().await;
+        if record.state == JobState::Running {
+            record.state = JobState::Completed;
+            record.finished_at = Some(Utc::now());
+            if let Err(e) = self.save(&record).await {
+                error!("Failed to store batch job {}: {}", id, e);
+            }
+            info!("Batch job {} completed", id);
+        }
+    }
+
+    /// Fetch `url` with retries, rotating away from proxies that fail.
+    /// `None` when the job was cancelled before a result.
+    async fn fetch(
+        &self,
+        job: &BatchJob,
+        id: &str,
+        spec: &BatchJobSpec,
+        url: &str,
+        country: Option<&str>,
+    ) -> Option<FetchResult> {
+        let session = session_id(id, country);
+        let mut exclude = HashSet::new();
+        let mut result = FetchResult {
+            url: url.to_string(),
+            country: country.map(str::to_string),
+            status: None,
+            headers: BTreeMap::new(),
+            body_sha256: None,
+            body_bytes: None,
+            elapsed_ms: 0,
+            attempts: 0,
+            proxy: None,
+            error: None,
+            finished_at: Utc::now(),
+        };
+
+        while result.attempts < spec.max_attempts {
+            if job.cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let proxy = match self.pick_proxy(spec, &session, country, &mut exclude).await {
+                Ok(proxy) => proxy,
+                Err(e) => {
+                    result.error = Some(e.to_string());
+                    break;
+                }
+            };
+            result.attempts += 1;
+            result.proxy = proxy.as_ref().map(proxy_id);
+
+            let mut request = self.requests.get(url).timeout(Duration::from_secs(spec.timeout_secs));
+            if let Some(proxy) = &proxy {
+                request = request.proxy(proxy.to_proxy_settings());
+            }
+            let started = Instant::now();
+            let outcome = match request.send_bytes().await {
+                Ok(response) if response.status == 407 => Err(anyhow!("Proxy authentication required")),
+                other => other,
+            };
+            result.elapsed_ms = started.elapsed().as_millis() as u64;
+
+            match outcome {
+                Ok(response) => {
+                    if let Some(proxy) = &proxy {
+                        self.report(proxy, Ok((result.elapsed_ms, response.body.len()))).await;
+                    }
+                    result.status = Some(response.status);
+                    result.headers = response.headers.into_iter().collect();
+                    result.body_sha256 = Some(hex::encode(Sha256::digest(&response.body)));
+                    result.body_bytes = Some(response.body.len());
+                    result.error = None;
+                    break;
+                }
+                Err(e) => {
+                    result.error = Some(e.to_string());
+                    match &proxy {
+                        Some(proxy) => {
+                            self.report(proxy, Err(&e.to_string())).await;
+                            exclude.insert(proxy_id(proxy));
+                        }
+                        None => tokio::time::sleep(Duration::from_millis(250 * result.attempts as u64)).await,
+                    }
+                }
+            }
+        }
+        result.finished_at = Utc::now();
+        Some(result)
+    }
+
+    /// Next proxy for an attempt, skipping excluded and quarantined ones;
+    /// `None` for direct jobs
+    async fn pick_proxy(
+        &self,
+        spec: &BatchJobSpec,
+        session: &str,
+        country: Option<&str>,
+        exclude: &mut HashSet<String>,
+    ) -> Result<Option<FreeProxy>> {
+        if spec.direct {
+            return Ok(None);
+        }
+        let services = self.proxies.as_ref().ok_or_else(|| anyhow!("No proxy pool is configured"))?;
+        loop {
+            let proxy = services
+                .rotation
+                .read()
+                .await
+                .get_proxy_for_country(session, country, exclude)
+                .await?;
+            if !services.quarantine.is_quarantined(&proxy).await {
+                return Ok(Some(proxy));
+            }
+            exclude.insert(proxy_id(&proxy));
+        }
+    }
+
+    /// Feed an attempt's outcome to health, rotation and quarantine
+    async fn report(&self, proxy: &FreeProxy, outcome: std::result::Result<(u64, usize), &str>) {
+        let Some(services) = &self.proxies else {
+            return;
+        };
+        let id = proxy_id(proxy);
+        match outcome {
+            Ok((elapsed_ms, bytes)) => {
+                services.health.record_success(&id, elapsed_ms as f64, 0, bytes as u64).await;
+                services.quarantine.record_success(proxy).await;
+                services
+                    .rotation
+                    .read()
+                    .await
+                    .record_performance(&proxy.ip, true, Some(elapsed_ms as f64))
+                    .await;
+            }
+            Err(error) => {
+                services.health.record_failure(&id, error).await;
+                services.quarantine.record_failure(proxy, error.to_string()).await;
+                services.rotation.read().await.record_performance(&proxy.ip, false, None).await;
+            }
+        }
+    }
+}
+
+/// Rotation session of one country of a job
+fn session_id(job_id: &str, country: Option<&str>) -> String {
+    format!("job-{}-{}", job_id, country.unwrap_or("any"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use browser_core::{
+        FreeIpProviderManager, ProxyHealthMonitor, ProxyManager, ProxyQuarantineManager, ProxyRotationManager,
+        ProxyRotationStrategy, ProxyType, ProxyValidator, ProxyValidatorConfig,
+    };
+    use tempfile::TempDir;
+
+    async fn spawn_target() -> String {
+        let app = Router::new()
+            .route("/a", get(|| async { "alpha" }))
+            .route("/b", get(|| async { ([("x-test", "1")], "beta") }))
+            .route("/bin", get(|| async { vec![0xffu8, 0xfe, 0x00, 0x80] }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+        let addr = listener.local_addr().expect("Local address should be available");
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}")
+    }
+
+    fn spec(urls: Vec<String>) -> BatchJobSpec {
+        serde_json::from_value(serde_json::json!({ "urls": urls })).expect("Spec should deserialize")
+    }
+
+    async fn wait_finished(manager: &BatchJobManager, id: &str) -> BatchJobProgress {
+        for _ in 0..200 {
+            let progress = manager.get(id).await.expect("Job should exist");
+            if progress.state != JobState::Running {
+                return progress;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("Job {id} did not finish");
+    }
+
+    fn dead_proxy(port: u16) -> FreeProxy {
+        FreeProxy {
+            ip: "127.0.0.1".to_string(),
+            port,
+            protocol: ProxyType::Http,
+            country: "Germany".to_string(),
+            country_code: "DE".to_string(),
+            anonymity: "elite".to_string(),
+            speed: 100,
+            uptime: 99.0,
+            last_checked: Utc::now().to_rfc3339(),
+            provider: "test".to_string(),
+            is_working: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_direct_job_records_results() {
+        let target = spawn_target().await;
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+
+        let mut job = spec(vec![format!("{target}/a"), format!("{target}/b"), format!("{target}/a")]);
+        job.direct = true;
+        let progress = manager.submit(job).await.expect("Submit should succeed");
+        assert_eq!(progress.total, 2);
+
+        let progress = wait_finished(&manager, &progress.id).await;
+        assert_eq!(progress.state, JobState::Completed);
+        assert_eq!((progress.completed, progress.fetched, progress.failed), (2, 2, 0));
+
+        let results = manager.results(&progress.id).await.expect("Results should read").expect("Job should exist");
+        let beta = results.iter().find(|r| r.url.ends_with("/b")).expect("Result for /b");
+        assert_eq!(beta.status, Some(200));
+        assert_eq!(beta.headers.get("x-test").map(String::as_str), Some("1"));
+        assert_eq!(beta.body_sha256.as_deref(), Some(hex::encode(Sha256::digest(b"beta")).as_str()));
+        assert_eq!(beta.attempts, 1);
+        assert!(beta.to_csv_row().starts_with(&format!("{target}/b,,200,")));
+    }
+
+    #[tokio::test]
+    async fn test_binary_bodies_are_hashed_as_received() {
+        let target = spawn_target().await;
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+
+        let mut job = spec(vec![format!("{target}/bin")]);
+        job.direct = true;
+        let progress = manager.submit(job).await.expect("Submit should succeed");
+        let progress = wait_finished(&manager, &progress.id).await;
+
+        let results = manager.results(&progress.id).await.expect("Results should read").expect("Job should exist");
+        let body = [0xffu8, 0xfe, 0x00, 0x80];
+        assert_eq!(results[0].body_sha256.as_deref(), Some(hex::encode(Sha256::digest(body)).as_str()));
+        assert_eq!(results[0].body_bytes, Some(body.len()));
+    }
+
+    /// Target whose responses take 100 ms; returns its address and the
+    /// peak number of requests it served at once
+    async fn spawn_slow_target() -> (std::net::SocketAddr, Arc<AtomicUsize>) {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (counter, max) = (in_flight.clone(), peak.clone());
+        let app = Router::new().route(
+            "/slow/:n",
+            get(move || {
+                let (counter, max) = (counter.clone(), max.clone());
+                async move {
+                    let now = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    counter.fetch_sub(1, Ordering::SeqCst);
+                    "slow"
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+        let addr = listener.local_addr().expect("Local address should be available");
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (addr, peak)
+    }
+
+    #[tokio::test]
+    async fn test_per_domain_concurrency_is_respected() {
+        let (addr, peak) = spawn_slow_target().await;
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+        let mut job = spec((0..8).map(|n| format!("http://{addr}/slow/{n}")).collect());
+        job.direct = true;
+        job.concurrency = 8;
+        job.per_domain_concurrency = 2;
+        let progress = manager.submit(job).await.expect("Submit should succeed");
+        let progress = wait_finished(&manager, &progress.id).await;
+
+        assert_eq!(progress.fetched, 8);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_job_concurrency_is_respected() {
+        let (addr, peak) = spawn_slow_target().await;
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+        let mut job = spec((0..9).map(|n| format!("http://{addr}/slow/{n}")).collect());
+        job.direct = true;
+        job.concurrency = 3;
+        job.per_domain_concurrency = 9;
+        let progress = manager.submit(job).await.expect("Submit should succeed");
+        let progress = wait_finished(&manager, &progress.id).await;
+
+        assert_eq!(progress.fetched, 9);
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_submit_rejects_invalid_specs() {
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+
+        assert!(manager.submit(spec(vec![])).await.is_err());
+        assert!(manager.submit(spec(vec!["ftp://example.com/".to_string()])).await.is_err());
+        // Proxied jobs need a pool
+        assert!(manager.submit(spec(vec!["https://example.com/".to_string()])).await.is_err());
+        let mut job = spec(vec!["https://example.com/".to_string()]);
+        job.direct = true;
+        job.countries = vec!["DE".to_string()];
+        assert!(manager.submit(job).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_proxies_rotate_and_quarantine() {
+        let target = spawn_target().await;
+        let dir = TempDir::new().expect("Temp dir should be created");
+        // Ports with nothing listening, so every attempt fails to connect
+        let mut dead = Vec::new();
+        for _ in 0..2 {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Bind should succeed");
+            dead.push(dead_proxy(listener.local_addr().expect("Local address should be available").port()));
+        }
+
+        let providers = Arc::new(RwLock::new(FreeIpProviderManager::new().expect("Provider manager should build")));
+        providers.write().await.set_proxy_pool(dead.clone());
+        let quarantine = Arc::new(ProxyQuarantineManager::new(1, Duration::from_secs(300), Duration::from_secs(3600)));
+        let services = ProxyServices::new(
+            Arc::new(ProxyManager::new()),
+            providers.clone(),
+            Arc::new(RwLock::new(ProxyRotationManager::new(providers, ProxyRotationStrategy::RoundRobin))),
+            Arc::new(ProxyValidator::new(ProxyValidatorConfig::default())),
+            quarantine.clone(),
+            Arc::new(ProxyHealthMonitor::new()),
+        );
+        let manager = BatchJobManager::open(dir.path(), Some(services)).await.expect("Open should succeed");
+
+        let mut job = spec(vec![format!("{target}/a")]);
+        job.countries = vec!["de".to_string(), "FR".to_string()];
+        job.timeout_secs = 5;
+        let progress = manager.submit(job).await.expect("Submit should succeed");
+        assert_eq!(progress.countries, vec!["DE", "FR"]);
+
+        let progress = wait_finished(&manager, &progress.id).await;
+        assert_eq!((progress.total, progress.completed, progress.failed), (2, 2, 2));
+
+        let results = manager.results(&progress.id).await.expect("Results should read").expect("Job should exist");
+        let de = results.iter().find(|r| r.country.as_deref() == Some("DE")).expect("Result for DE");
+        // Both German proxies were tried once, then the pool ran dry
+        assert_eq!(de.attempts, 2);
+        assert!(de.error.as_deref().is_some_and(|e| e.contains("No working proxies")));
+        for proxy in &dead {
+            assert!(quarantine.is_quarantined(proxy).await);
+        }
+        let fr = results.iter().find(|r| r.country.as_deref() == Some("FR")).expect("Result for FR");
+        assert_eq!(fr.attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_reopen_resumes_unfinished_job() {
+        let target = spawn_target().await;
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let mut job = spec(vec![format!("{target}/a"), format!("{target}/b")]);
+        job.direct = true;
+
+        // A job interrupted after its first result
+        let record = JobRecord {
+            id: "resumed".to_string(),
+            spec: job,
+            state: JobState::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+        std::fs::write(dir.path().join("resumed.json"), serde_json::to_string(&record).expect("Record should serialize"))
+            .expect("Write should succeed");
+        let first = FetchResult {
+            url: format!("{target}/a"),
+            country: None,
+            status: Some(200),
+            headers: BTreeMap::new(),
+            body_sha256: Some("stored".to_string()),
+            body_bytes: Some(5),
+            elapsed_ms: 1,
+            attempts: 1,
+            proxy: None,
+            error: None,
+            finished_at: Utc::now(),
+        };
+        let line = serde_json::to_string(&first).expect("Result should serialize");
+        std::fs::write(dir.path().join("resumed.results.jsonl"), format!("{line}\n{{\"url\":")).expect("Write should succeed");
+
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+        let progress = wait_finished(&manager, "resumed").await;
+        assert_eq!(progress.state, JobState::Completed);
+        assert_eq!(progress.completed, 2);
+
+        let results = manager.results("resumed").await.expect("Results should read").expect("Job should exist");
+        assert_eq!(results.len(), 2);
+        // The stored result is kept, only /b was fetched again
+        assert_eq!(results[0].body_sha256.as_deref(), Some("stored"));
+        assert!(results[1].url.ends_with("/b"));
+    }
+}
@@ -0,0 +1,272 @@
+//! Jobs API Module
+//!
+//! Batch fetch jobs over the proxy pool (see `jobs`):
+//! - `POST /api/jobs` submits a URL list with the countries to fetch it from
+//! - `GET /api/jobs` and `/api/jobs/:id` report progress
+//! - `POST /api/jobs/:id/cancel` stops a running job
+//! - `GET /api/jobs/:id/results?format=jsonl|csv` downloads the results
+//!
+//! Every endpoint answers 503 when the server has no `BatchJobManager`.
+
+use crate::jobs::{BatchJobManager, BatchJobProgress, BatchJobSpec, CSV_HEADER};
+use crate::ApiServer;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, warn};
+use utoipa::IntoParams;
+
+/// Content type of the JSONL results
+pub const JSONL_CONTENT_TYPE: &str = "application/x-ndjson";
+/// Content type of the CSV results
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Routes for batch fetch jobs, ready to merge into the server router.
+pub fn router(server: Arc<ApiServer>) -> Router {
+    Router::new()
+        .route("/api/jobs", post(submit_job_handler).get(list_jobs_handler))
+        .route("/api/jobs/:id", get(get_job_handler))
+        .route("/api/jobs/:id/cancel", post(cancel_job_handler))
+        .route("/api/jobs/:id/results", get(job_results_handler))
+        .with_state(server)
+}
+
+fn jobs(state: &ApiServer) -> Result<&Arc<BatchJobManager>, StatusCode> {
+    state.jobs.as_ref().ok_or(StatusCode::SERVICE_UNAVAILABLE)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ResultsQuery {
+    /// `jsonl` (default) or `csv`
+    pub format: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/jobs",
+    tag = "jobs",
+    request_body = BatchJobSpec,
+    responses(
+        (status = 200, description = "Job started", body = BatchJobProgress),
+        (status = 400, description = "Invalid URLs or settings, or a proxied job without a proxy pool"),
+        (status = 503, description = "Batch jobs are not enabled"),
+    )
+)]
+async fn submit_job_handler(
+    State(state): State<Arc<ApiServer>>,
+    Json(spec): Json<BatchJobSpec>,
+) -> Result<Json<BatchJobProgress>, StatusCode> {
+    let progress = jobs(&state)?.submit(spec).await.map_err(|e| {
+        warn!("Rejected batch job: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    Ok(Json(progress))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs",
+    tag = "jobs",
+    responses(
+        (status = 200, description = "Jobs, newest first", body = Vec<BatchJobProgress>),
+        (status = 503, description = "Batch jobs are not enabled"),
+    )
+)]
+async fn list_jobs_handler(State(state): State<Arc<ApiServer>>) -> Result<Json<Vec<BatchJobProgress>>, StatusCode> {
+    Ok(Json(jobs(&state)?.list().await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job progress", body = BatchJobProgress),
+        (status = 404, description = "Unknown job"),
+        (status = 503, description = "Batch jobs are not enabled"),
+    )
+)]
+async fn get_job_handler(
+    State(state): State<Arc<ApiServer>>,
+    Path(id): Path<String>,
+) -> Result<Json<BatchJobProgress>, StatusCode> {
+    jobs(&state)?.get(&id).await.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/cancel",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job cancelled, or unchanged if it had finished", body = BatchJobProgress),
+        (status = 404, description = "Unknown job"),
+        (status = 503, description = "Batch jobs are not enabled"),
+    )
+)]
+async fn cancel_job_handler(
+    State(state): State<Arc<ApiServer>>,
+    Path(id): Path<String>,
+) -> Result<Json<BatchJobProgress>, StatusCode> {
+    let progress = jobs(&state)?.cancel(&id).await.map_err(|e| {
+        error!("Failed to cancel batch job {}: {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    progress.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}/results",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job ID"), ResultsQuery),
+    responses(
+        (status = 200, description = "Results so far, one `FetchResult` per line", content_type = "application/x-ndjson", body = String),
+        (status = 400, description = "Unknown format"),
+        (status = 404, description = "Unknown job"),
+        (status = 503, description = "Batch jobs are not enabled"),
+    )
+)]
+async fn job_results_handler(
+    State(state): State<Arc<ApiServer>>,
+    Path(id): Path<String>,
+    Query(query): Query<ResultsQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let manager = jobs(&state)?;
+    let format = query.format.as_deref().unwrap_or("jsonl");
+    if !matches!(format, "jsonl" | "csv") {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let results = manager
+        .results(&id)
+        .await
+        .map_err(|e| {
+            error!("Failed to read results of batch job {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut body = String::new();
+    let content_type = if format == "csv" {
+        body.push_str(CSV_HEADER);
+        body.push('\n');
+        for result in &results {
+            body.push_str(&result.to_csv_row());
+            body.push('\n');
+        }
+        CSV_CONTENT_TYPE
+    } else {
+        for result in &results {
+            body.push_str(&serde_json::to_string(result).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?);
+            body.push('\n');
+        }
+        JSONL_CONTENT_TYPE
+    };
+    Ok(([(header::CONTENT_TYPE, content_type)], body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::JobState;
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+    use browser_core::TabIPManager;
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+    use tokio::sync::Mutex;
+    use tower::ServiceExt;
+    use virtual_ip::demo_generator;
+
+    fn server() -> ApiServer {
+        let generator = demo_generator();
+        ApiServer::new(Arc::new(Mutex::new(TabIPManager::new(generator.clone()))), Arc::new(generator))
+    }
+
+    async fn call(server: &Arc<ApiServer>, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .expect("Request should build");
+        let response = server.clone().router().await.oneshot(request).await.expect("Request should succeed");
+        let status = response.status();
+        let bytes = response.into_body().collect().await.expect("Body should read").to_bytes();
+        (status, String::from_utf8(bytes.to_vec()).expect("Body should be UTF-8"))
+    }
+
+    #[tokio::test]
+    async fn test_jobs_unavailable_without_manager() {
+        let server = Arc::new(server());
+        let (status, _) = call(&server, Method::GET, "/api/jobs", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_job_lifecycle() {
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+        let target = format!("http://{}/", listener.local_addr().expect("Local address should be available"));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let dir = TempDir::new().expect("Temp dir should be created");
+        let manager = BatchJobManager::open(dir.path(), None).await.expect("Open should succeed");
+        let server = Arc::new(server().with_batch_jobs(manager));
+
+        let (status, _) = call(&server, Method::POST, "/api/jobs", Some(json!({ "urls": ["not a url"], "direct": true }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) =
+            call(&server, Method::POST, "/api/jobs", Some(json!({ "urls": [target], "direct": true }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let job: BatchJobProgress = serde_json::from_str(&body).expect("Progress should parse");
+        assert_eq!(job.total, 1);
+
+        let mut progress = job.clone();
+        for _ in 0..200 {
+            if progress.state != JobState::Running {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+            let (_, body) = call(&server, Method::GET, &format!("/api/jobs/{}", job.id), None).await;
+            progress = serde_json::from_str(&body).expect("Progress should parse");
+        }
+        assert_eq!(progress.state, JobState::Completed);
+        assert_eq!(progress.fetched, 1);
+
+        let (status, body) = call(&server, Method::GET, &format!("/api/jobs/{}/results", job.id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let line: Value = serde_json::from_str(body.trim_end()).expect("Result line should parse");
+        assert_eq!(line["status"], 200);
+
+        let (status, body) = call(&server, Method::GET, &format!("/api/jobs/{}/results?format=csv", job.id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut lines = body.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert!(lines.next().is_some_and(|row| row.starts_with(&format!("{target},,200,"))));
+
+        let (status, _) = call(&server, Method::GET, &format!("/api/jobs/{}/results?format=xml", job.id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Cancelling a finished job leaves it as it is
+        let (status, body) = call(&server, Method::POST, &format!("/api/jobs/{}/cancel", job.id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let cancelled: BatchJobProgress = serde_json::from_str(&body).expect("Progress should parse");
+        assert_eq!(cancelled.state, JobState::Completed);
+
+        let (status, _) = call(&server, Method::POST, "/api/jobs/missing/cancel", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, body) = call(&server, Method::GET, "/api/jobs", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_str::<Vec<Value>>(&body).expect("List should parse").len(), 1);
+    }
+}
@@ -3,28 +3,66 @@
 //! Provides the API server implementation including:
 //! - RESTful endpoints for tab management
 //! - IP rotation and validation endpoints
-//! - Proxy configuration management
+//! - Proxy pool, rotation, validation and quarantine endpoints (`proxy_api`)
+//! - Headless Chromium tabs for daemon mode (`browser_api`)
+//! - Batch fetch jobs across proxies and countries (`jobs`, `jobs_api`)
+//! - WebSocket and SSE event streams (`events_api`)
+//! - API key/JWT authentication, rate limits and auditing (`auth`)
+//! - OpenAPI 3.1 document at `/api/openapi.json` (`openapi`)
+//! - Prometheus metrics at `/metrics` (`metrics_api`)
 //! - Health check and monitoring endpoints
+//! - Server wiring from environment variables (`bootstrap`)
+
+pub mod auth;
+pub mod bootstrap;
+pub mod browser_api;
+pub mod events_api;
+pub mod jobs;
+pub mod jobs_api;
+pub mod metrics_api;
+pub mod openapi;
+pub mod proxy_api;
 
 use anyhow::Result;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
+    middleware,
     routing::{get, post},
     Json, Router,
 };
-use browser_core::TabIPManager;
+use browser_core::{
+    BrowserEngineManager, ErrorRecoveryManager, EventBus, LocalProxyManager, MetricsCollector, TabIPManager,
+};
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use utoipa::ToSchema;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{info, error};
+use axum_server::tls_rustls::RustlsConfig;
+use tracing::{info, error, warn};
 use virtual_ip::{Country, IPGenerator, IPValidator, VirtualIP};
 
+pub use auth::ApiAuth;
+pub use jobs::BatchJobManager;
+pub use proxy_api::ProxyServices;
+
 #[derive(Clone)]
 /// Represents a ApiServer.
 pub struct ApiServer {
     tab_manager: Arc<Mutex<TabIPManager>>,
     ip_generator: Arc<IPGenerator>,
+    proxy_services: Option<ProxyServices>,
+    events: Arc<EventBus>,
+    metrics: Arc<MetricsCollector>,
+    local_proxies: Option<Arc<LocalProxyManager>>,
+    error_recovery: Option<Arc<ErrorRecoveryManager>>,
+    browser: Option<Arc<BrowserEngineManager>>,
+    jobs: Option<Arc<BatchJobManager>>,
+    auth: Option<Arc<ApiAuth>>,
+    tls: Option<(PathBuf, PathBuf)>,
+    bind_host: String,
 }
 
 impl ApiServer {
@@ -33,12 +71,94 @@ impl ApiServer {
         Self {
             tab_manager,
             ip_generator,
+            proxy_services: None,
+            events: Arc::new(EventBus::new()),
+            metrics: Arc::new(MetricsCollector::new()),
+            local_proxies: None,
+            error_recovery: None,
+            browser: None,
+            jobs: None,
+            auth: None,
+            tls: None,
+            bind_host: "127.0.0.1".to_string(),
         }
     }
 
+    /// Require API keys or JWTs on every route except login and refresh
+    pub fn with_auth(mut self, auth: ApiAuth) -> Self {
+        self.auth = Some(Arc::new(auth));
+        self
+    }
+
+    /// Serve HTTPS with the given PEM certificate chain and private key
+    pub fn with_tls(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        self.tls = Some((cert_path.into(), key_path.into()));
+        self
+    }
+
+    /// Listen on `host` instead of loopback
+    pub fn with_bind_host(mut self, host: impl Into<String>) -> Self {
+        self.bind_host = host.into();
+        self
+    }
+
+    /// Stream events from `bus`; components publishing to it should share
+    /// the same instance
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = bus;
+        self
+    }
+
+    /// Export `metrics` at `/metrics`; components recording into it should
+    /// share the same instance
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Export per-tab connection and traffic figures of `local_proxies`
+    pub fn with_local_proxies(mut self, local_proxies: Arc<LocalProxyManager>) -> Self {
+        self.local_proxies = Some(local_proxies);
+        self
+    }
+
+    /// Export the circuit breaker states of `recovery`
+    pub fn with_error_recovery(mut self, recovery: Arc<ErrorRecoveryManager>) -> Self {
+        self.error_recovery = Some(recovery);
+        self
+    }
+
+    /// Drive real tabs through `browser` at `/api/browser`; its Chromium
+    /// engine must be launched for the tab endpoints to answer
+    pub fn with_browser_engine(mut self, browser: Arc<BrowserEngineManager>) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    /// Run batch fetch jobs through `jobs` at `/api/jobs`
+    pub fn with_batch_jobs(mut self, jobs: Arc<BatchJobManager>) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Serve the proxy pool, rotation and provider endpoints
+    pub fn with_proxy_services(mut self, services: ProxyServices) -> Self {
+        self.proxy_services = Some(services);
+        self
+    }
+
     /// Performs router operation.
     pub async fn router(self: Arc<Self>) -> Router {
-        Router::new()
+        let proxy_routes = self
+            .proxy_services
+            .clone()
+            .map(ProxyServices::router)
+            .unwrap_or_default();
+        let event_routes = events_api::router(self.events.clone());
+        let browser_routes = browser_api::router(self.clone());
+        let job_routes = jobs_api::router(self.clone());
+        let auth = self.auth.clone();
+        let router = Router::new()
             // Tab endpoints
             .route("/api/tabs", post(create_tab_handler).get(list_tabs_handler))
             .route(
@@ -47,30 +167,75 @@ impl ApiServer {
             )
             .route("/api/tabs/:id/rotate-ip", post(rotate_ip_handler))
             .route("/api/tabs/:id/validate", get(validate_ip_handler))
+            .route("/api/tabs/:id/navigate", post(navigate_tab_handler))
             // Country endpoints
             .route("/api/countries", get(list_countries_handler))
+            // Prometheus scrape endpoint
+            .route("/metrics", get(metrics_api::metrics_handler))
             .with_state(self)
+            .merge(proxy_routes)
+            .merge(event_routes)
+            .merge(browser_routes)
+            .merge(job_routes)
+            .merge(openapi::router());
+        match auth {
+            Some(auth) => router
+                .merge(auth.clone().router())
+                .layer(middleware::from_fn_with_state(auth, auth::authenticate)),
+            None => router,
+        }
+    }
+
+    /// URL the server is reachable at once running on `port`
+    pub fn listen_url(&self, port: u16) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        format!("{scheme}://{}:{port}", self.bind_host)
     }
 
-    /// Performs run operation.
+    /// Performs run operation. Logs through the caller's tracing subscriber.
     pub async fn run(self, port: u16) -> Result<()> {
-        tracing_subscriber::fmt::init();
+        if self.auth.is_none() {
+            warn!("API authentication is disabled");
+        }
+        let addr = format!("{}:{port}", self.bind_host);
+        let url = self.listen_url(port);
+        let tls = self.tls.clone();
         let app = Arc::new(self).router().await;
-        let addr = format!("127.0.0.1:{port}");
-        info!("API server listening on http://{addr}");
-        let listener = tokio::net::TcpListener::bind(&addr).await?;
-        axum::serve(listener, app).await?;
+        match tls {
+            Some((cert_path, key_path)) => {
+                let config = RustlsConfig::from_pem_file(&cert_path, &key_path).await?;
+                info!("API server listening on {url}");
+                axum_server::bind_rustls(addr.parse()?, config)
+                    .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                    .await?;
+            }
+            None => {
+                info!("API server listening on {url}");
+                let listener = tokio::net::TcpListener::bind(&addr).await?;
+                axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+            }
+        }
         Ok(())
     }
 }
 
 // ========= Handlers =========
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct CreateTabRequest {
     country_code: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tabs",
+    tag = "tabs",
+    request_body = CreateTabRequest,
+    responses(
+        (status = 200, description = "Tab created with a virtual IP", body = TabResponse),
+        (status = 500, description = "No IP available for the country"),
+    )
+)]
 async fn create_tab_handler(
     State(state): State<Arc<ApiServer>>,
     Json(payload): Json<CreateTabRequest>,
@@ -86,6 +251,12 @@ async fn create_tab_handler(
     Ok(Json(TabResponse::from(tab)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tabs",
+    tag = "tabs",
+    responses((status = 200, description = "Open tabs", body = Vec<TabResponse>))
+)]
 async fn list_tabs_handler(
     State(state): State<Arc<ApiServer>>,
 ) -> Result<Json<Vec<TabResponse>>, StatusCode> {
@@ -94,6 +265,16 @@ async fn list_tabs_handler(
     Ok(Json(tabs.into_iter().map(TabResponse::from).collect()))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tabs/{id}",
+    tag = "tabs",
+    params(("id" = String, Path, description = "Tab ID")),
+    responses(
+        (status = 200, description = "The tab", body = TabResponse),
+        (status = 404, description = "Unknown tab"),
+    )
+)]
 async fn get_tab_handler(
     State(state): State<Arc<ApiServer>>,
     Path(id): Path<String>,
@@ -106,6 +287,16 @@ async fn get_tab_handler(
     Ok(Json(TabResponse::from(tab)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tabs/{id}",
+    tag = "tabs",
+    params(("id" = String, Path, description = "Tab ID")),
+    responses(
+        (status = 204, description = "Tab closed"),
+        (status = 500, description = "Tab could not be closed"),
+    )
+)]
 async fn close_tab_handler(
     State(state): State<Arc<ApiServer>>,
     Path(id): Path<String>,
@@ -121,11 +312,51 @@ async fn close_tab_handler(
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct NavigateRequest {
+    pub(crate) url: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tabs/{id}/navigate",
+    tag = "tabs",
+    params(("id" = String, Path, description = "Tab ID")),
+    request_body = NavigateRequest,
+    responses(
+        (status = 204, description = "Navigation recorded"),
+        (status = 404, description = "Unknown tab"),
+    )
+)]
+async fn navigate_tab_handler(
+    State(state): State<Arc<ApiServer>>,
+    Path(id): Path<String>,
+    Json(payload): Json<NavigateRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let manager = state.tab_manager.lock().await;
+    manager
+        .navigate(&id, &payload.url)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize, ToSchema)]
 struct RotateIPRequest {
     new_country: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tabs/{id}/rotate-ip",
+    tag = "tabs",
+    params(("id" = String, Path, description = "Tab ID")),
+    request_body = RotateIPRequest,
+    responses(
+        (status = 200, description = "The tab's new virtual IP", body = VirtualIPResponse),
+        (status = 500, description = "Rotation failed"),
+    )
+)]
 async fn rotate_ip_handler(
     State(state): State<Arc<ApiServer>>,
     Path(id): Path<String>,
@@ -142,6 +373,16 @@ async fn rotate_ip_handler(
     Ok(Json(VirtualIPResponse::from(ip)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tabs/{id}/validate",
+    tag = "tabs",
+    params(("id" = String, Path, description = "Tab ID")),
+    responses(
+        (status = 200, description = "Leak and consistency checks for the tab's IP", body = ValidationResponse),
+        (status = 404, description = "Unknown tab"),
+    )
+)]
 async fn validate_ip_handler(
     State(state): State<Arc<ApiServer>>,
     Path(id): Path<String>,
@@ -161,6 +402,12 @@ async fn validate_ip_handler(
     Ok(Json(ValidationResponse::from(report)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/countries",
+    tag = "tabs",
+    responses((status = 200, description = "Countries virtual IPs can be generated for", body = Vec<CountryResponse>))
+)]
 async fn list_countries_handler(
     State(state): State<Arc<ApiServer>>,
 ) -> Result<Json<Vec<CountryResponse>>, StatusCode> {
@@ -175,7 +422,7 @@ async fn list_countries_handler(
 
 // ========= DTOs =========
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 /// Represents a TabResponse.
 pub struct TabResponse {
     pub tab_id: String,
@@ -203,7 +450,7 @@ impl From<browser_core::TabProfile> for TabResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 /// Represents a VirtualIPResponse.
 pub struct VirtualIPResponse {
     pub ip: String,
@@ -233,7 +480,7 @@ impl From<VirtualIP> for VirtualIPResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 /// Represents a CountryResponse.
 pub struct CountryResponse {
     pub code: String,
@@ -259,7 +506,7 @@ impl From<Country> for CountryResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 /// Represents a ValidationResponse.
 pub struct ValidationResponse {
     pub ip_matches: bool,
@@ -4,55 +4,25 @@
 //! - RESTful API endpoints for proxy management
 //! - Tab and IP management integration
 //! - Virtual IP generation and rotation
+//! - Proxy pool, rotation and provider endpoints
+//! - Real-time event stream
+//! - Prometheus metrics at `/metrics`
+//! - Batch fetch jobs at `/api/jobs`, resumed after a restart
+//! - API key/JWT authentication with optional TLS
+//! - Headless Chromium tabs at `/api/browser` in daemon mode
+//!
+//! Everything is configured from the environment; the variables are listed
+//! in `api_server::bootstrap`.
 
-use std::env;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-
-use api_server::ApiServer;
-use browser_core::TabIPManager;
-use virtual_ip::{
-    demo_generator,
-    load_countries_from_file,
-    load_ip_ranges,
-    load_ip_ranges_from_file,
-    CountryDatabase,
-    IPGenerator,
-};
+use api_server::bootstrap;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Load country and IP range data (file-based override via env).
-    let country_path = env::var("COUNTRIES_PATH").ok();
-    let ip_ranges_path = env::var("IP_RANGES_PATH").ok();
-
-    let countries = country_path
-        .as_deref()
-        .map(std::path::Path::new)
-        .map(load_countries_from_file)
-        .unwrap_or_else(CountryDatabase::load_all_countries);
-
-    let ranges = ip_ranges_path
-        .as_deref()
-        .map(std::path::Path::new)
-        .map(load_ip_ranges_from_file)
-        .unwrap_or_else(load_ip_ranges);
-    let ip_generator: IPGenerator = if countries.is_empty() || ranges.is_empty() {
-        demo_generator()
-    } else {
-        IPGenerator::new(countries, ranges)
-    };
-
-    // Create TabIPManager with in-memory storage (no database)
-    let tab_manager = Arc::new(Mutex::new(
-        TabIPManager::new(ip_generator.clone())
-    ));
-    let server = ApiServer::new(tab_manager, Arc::new(ip_generator));
-
-    let port: u16 = env::var("PORT")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(8080);
-
-    server.run(port).await
+    tracing_subscriber::fmt::init();
+    let bootstrapped = bootstrap::server_from_env(Vec::new()).await?;
+    if let Some(key) = &bootstrapped.admin_key {
+        // Only the hash is stored, so this is the one chance to see it
+        println!("Created admin API key (shown once): {key}");
+    }
+    bootstrapped.server.run(bootstrap::port_from_env()).await
 }
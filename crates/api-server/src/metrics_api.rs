@@ -0,0 +1,224 @@
+//! Metrics API Module
+//!
+//! Prometheus scrape endpoint at `GET /metrics`. Two sources are combined:
+//! - the server's long-lived `MetricsCollector`, which components such as
+//!   `ProxyHealthMonitor` feed with request counters and latency histograms
+//! - gauges read from the live components at scrape time (pool and
+//!   quarantine size, tabs, rotation sessions, local proxy connections and
+//!   traffic, circuit breaker states, the Chromium engine's tabs, page
+//!   loads and CDP commands), so closed tabs and removed proxies
+//!   drop out of the output
+//!
+//! Metric names and labels are listed in `browser_core::metric_names`.
+
+use crate::ApiServer;
+use axum::{extract::State, http::header, response::IntoResponse};
+use browser_core::error_recovery::CircuitState;
+use browser_core::{metric_names, MetricsCollector, TabStatus};
+use std::sync::Arc;
+
+/// Content type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "meta",
+    responses((
+        status = 200,
+        description = "Metrics in the Prometheus text format",
+        content_type = "text/plain",
+        body = String
+    ))
+)]
+pub(crate) async fn metrics_handler(State(state): State<Arc<ApiServer>>) -> impl IntoResponse {
+    let scrape = MetricsCollector::new();
+    collect_tabs(&state, &scrape).await;
+    collect_proxies(&state, &scrape).await;
+    collect_local_proxies(&state, &scrape).await;
+    collect_circuit_breakers(&state, &scrape).await;
+    collect_engine(&state, &scrape).await;
+
+    let mut body = scrape.render_prometheus().await;
+    body.push_str(&state.metrics.render_prometheus().await);
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body)
+}
+
+async fn collect_tabs(state: &ApiServer, scrape: &MetricsCollector) {
+    let tabs = state.tab_manager.lock().await.list_tabs().await;
+    let mut counts = [("creating", 0), ("active", 0), ("closed", 0)];
+    for tab in &tabs {
+        let index = match tab.status {
+            TabStatus::Creating => 0,
+            TabStatus::Active => 1,
+            TabStatus::Closed => 2,
+        };
+        counts[index].1 += 1;
+    }
+    for (status, count) in counts {
+        scrape
+            .set_gauge(&MetricsCollector::labeled(metric_names::TABS, &[("status", status)]), count as f64)
+            .await;
+    }
+}
+
+async fn collect_proxies(state: &ApiServer, scrape: &MetricsCollector) {
+    let Some(services) = &state.proxy_services else {
+        return;
+    };
+    let pool_size = services.providers.read().await.get_proxy_pool().len();
+    scrape.set_gauge(metric_names::PROXY_POOL_SIZE, pool_size as f64).await;
+
+    let quarantine = services.quarantine.get_stats().await;
+    scrape
+        .set_gauge(metric_names::QUARANTINED_PROXIES, quarantine.actively_quarantined as f64)
+        .await;
+
+    let sessions = services.rotation.read().await.list_session_stats().await;
+    scrape.set_gauge(metric_names::ROTATION_SESSIONS, sessions.len() as f64).await;
+
+    for health in services.health.all_health().await {
+        let name = MetricsCollector::labeled(metric_names::PROXY_HEALTH_SCORE, &[("proxy", &health.proxy_id)]);
+        scrape.set_gauge(&name, health.health_score).await;
+    }
+}
+
+async fn collect_local_proxies(state: &ApiServer, scrape: &MetricsCollector) {
+    let Some(local_proxies) = &state.local_proxies else {
+        return;
+    };
+    for stats in local_proxies.tab_stats().await {
+        let tab = stats.tab_id.as_str();
+        scrape
+            .set_gauge(
+                &MetricsCollector::labeled(metric_names::LOCAL_PROXY_ACTIVE_CONNECTIONS, &[("tab", tab)]),
+                stats.active_connections as f64,
+            )
+            .await;
+        for (direction, bytes) in [("sent", stats.bytes_sent), ("received", stats.bytes_received)] {
+            let name = MetricsCollector::labeled(metric_names::TAB_BYTES_TOTAL, &[("tab", tab), ("direction", direction)]);
+            scrape.increment_by(&name, bytes).await;
+        }
+    }
+}
+
+async fn collect_circuit_breakers(state: &ApiServer, scrape: &MetricsCollector) {
+    let Some(recovery) = &state.error_recovery else {
+        return;
+    };
+    for (breaker, current) in recovery.circuit_breaker_states().await {
+        for candidate in CircuitState::ALL {
+            let name = MetricsCollector::labeled(
+                metric_names::CIRCUIT_BREAKER_STATE,
+                &[("breaker", &breaker), ("state", candidate.as_str())],
+            );
+            let value = if candidate == current { 1.0 } else { 0.0 };
+            scrape.set_gauge(&name, value).await;
+        }
+    }
+}
+
+async fn collect_engine(state: &ApiServer, scrape: &MetricsCollector) {
+    let Some(browser) = &state.browser else {
+        return;
+    };
+    let Some(handle) = browser.get_chromium_engine().await else {
+        return;
+    };
+    let guard = handle.read().await;
+    let Some(engine) = guard.as_ref() else {
+        return;
+    };
+    let metrics = engine.get_metrics().await;
+    scrape.set_gauge(metric_names::ENGINE_TABS, engine.get_tabs().await.len() as f64).await;
+    scrape.increment_by(metric_names::ENGINE_PAGE_LOADS_TOTAL, metrics.page_loads).await;
+    scrape.increment_by(metric_names::ENGINE_CDP_COMMANDS_TOTAL, metrics.cdp_commands_sent).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy_api::ProxyServices;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use browser_core::{
+        ErrorRecoveryManager, FreeIpProviderManager, LocalProxyManager, ProxyHealthMonitor, ProxyManager,
+        ProxyQuarantineManager, ProxyRotationManager, ProxyRotationStrategy, ProxyValidator, ProxyValidatorConfig,
+        TabIPManager,
+    };
+    use http_body_util::BodyExt;
+    use std::time::Duration;
+    use tokio::sync::{Mutex, RwLock};
+    use tower::ServiceExt;
+    use virtual_ip::demo_generator;
+
+    async fn scrape(server: ApiServer) -> String {
+        let response = Arc::new(server)
+            .router()
+            .await
+            .oneshot(Request::builder().uri("/metrics").body(Body::empty()).expect("Request should build"))
+            .await
+            .expect("Request should succeed");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROMETHEUS_CONTENT_TYPE);
+        let body = response.into_body().collect().await.expect("Body should read").to_bytes();
+        String::from_utf8(body.to_vec()).expect("Metrics should be UTF-8")
+    }
+
+    #[tokio::test]
+    async fn test_metrics_exports_components() {
+        let generator = demo_generator();
+        let tabs = TabIPManager::new(generator.clone());
+        tabs.create_tab("US").await.expect("Tab creation should succeed");
+
+        let metrics = Arc::new(MetricsCollector::new());
+        let health = Arc::new(ProxyHealthMonitor::new().with_metrics(metrics.clone()));
+        health.record_success("10.0.0.1:8080", 120.0, 10, 20).await;
+        health.record_failure("10.0.0.1:8080", "timeout").await;
+
+        let providers = Arc::new(RwLock::new(FreeIpProviderManager::new().expect("Provider manager should build")));
+        let services = ProxyServices::new(
+            Arc::new(ProxyManager::new()),
+            providers.clone(),
+            Arc::new(RwLock::new(ProxyRotationManager::new(providers, ProxyRotationStrategy::RoundRobin))),
+            Arc::new(ProxyValidator::new(ProxyValidatorConfig::default())),
+            Arc::new(ProxyQuarantineManager::new(3, Duration::from_secs(300), Duration::from_secs(3600))),
+            health,
+        );
+
+        let recovery = Arc::new(ErrorRecoveryManager::new());
+        recovery.handle_error("proxy_pool", "network connection failed").await;
+
+        let server = ApiServer::new(Arc::new(Mutex::new(tabs)), Arc::new(generator))
+            .with_proxy_services(services)
+            .with_metrics(metrics)
+            .with_local_proxies(Arc::new(LocalProxyManager::new(0..0)))
+            .with_error_recovery(recovery);
+        let text = scrape(server).await;
+
+        assert!(text.contains("# TYPE proxy_browser_tabs gauge\n"));
+        assert!(text.contains("proxy_browser_tabs{status=\"creating\"} 1\n"));
+        assert!(text.contains("proxy_browser_proxy_pool_size 0\n"));
+        assert!(text.contains("proxy_browser_quarantined_proxies 0\n"));
+        assert!(text.contains("proxy_browser_rotation_sessions 0\n"));
+        assert!(text.contains("proxy_browser_proxy_requests_total{proxy=\"10.0.0.1:8080\",outcome=\"success\"} 1\n"));
+        assert!(text.contains("proxy_browser_proxy_requests_total{proxy=\"10.0.0.1:8080\",outcome=\"failure\"} 1\n"));
+        assert!(text.contains("proxy_browser_proxy_latency_seconds_bucket{proxy=\"10.0.0.1:8080\",le=\"0.25\"} 1\n"));
+        assert!(text.contains("proxy_browser_proxy_bytes_total{proxy=\"10.0.0.1:8080\",direction=\"received\"} 20\n"));
+        assert!(text.contains("proxy_browser_proxy_health_score{proxy=\"10.0.0.1:8080\"}"));
+        assert!(text.contains("proxy_browser_circuit_breaker_state{breaker=\"proxy_pool\",state=\"closed\"} 1\n"));
+        assert!(text.contains("proxy_browser_circuit_breaker_state{breaker=\"proxy_pool\",state=\"open\"} 0\n"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_without_optional_components() {
+        let generator = demo_generator();
+        let server = ApiServer::new(
+            Arc::new(Mutex::new(TabIPManager::new(generator.clone()))),
+            Arc::new(generator),
+        );
+        let text = scrape(server).await;
+        assert!(text.contains("proxy_browser_tabs{status=\"closed\"} 0\n"));
+        assert!(!text.contains(metric_names::PROXY_POOL_SIZE));
+    }
+}
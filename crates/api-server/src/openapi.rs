@@ -0,0 +1,350 @@
+//! OpenAPI Module
+//!
+//! The OpenAPI 3.1 document for the REST API, derived from the handler
+//! annotations and DTO schemas, and served at `/api/openapi.json`.
+//!
+//! Every handler registered on the server router must be listed in
+//! `ApiDoc`; the tests below fail when routes and spec diverge.
+
+use crate::auth::{self, ApiKeyResponse, CreatedKeyResponse, Principal, PrincipalKind, TokenResponse};
+use crate::browser_api::{
+    self, BrowserStatusResponse, BrowserTabResponse, CreateBrowserTabRequest, PageContentResponse, ScriptRequest,
+    ScriptResponse,
+};
+use crate::events_api;
+use crate::jobs::{BatchJobProgress, BatchJobSpec, FetchResult, JobState};
+use crate::jobs_api;
+use crate::metrics_api;
+use crate::proxy_api::{
+    self, ImportRequest, ImportResponse, NewProxyRequest, Page, ProviderResponse, ProxyHealthResponse,
+    ProxyResponse, ProxyValidationResponse, QuarantineResponse, RefreshResponse, StrategyRequest,
+};
+use crate::{CountryResponse, TabResponse, ValidationResponse, VirtualIPResponse};
+use axum::{routing::get, Json, Router};
+use browser_core::{
+    ApiScope, BandwidthStats, FreeProxy, NewApiKey, ProxyHealthStatus, ProxySessionStats, ProxyType,
+    QuarantineStats, QuarantinedProxy, ValidationResult,
+};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Proxy Desktop Browser API",
+        description = "Tabs with virtual IPs, the proxy pool, rotation, validation, batch jobs and events."
+    ),
+    paths(
+        crate::create_tab_handler,
+        crate::list_tabs_handler,
+        crate::get_tab_handler,
+        crate::close_tab_handler,
+        crate::rotate_ip_handler,
+        crate::validate_ip_handler,
+        crate::navigate_tab_handler,
+        crate::list_countries_handler,
+        proxy_api::list_proxies_handler,
+        proxy_api::add_proxy_handler,
+        proxy_api::import_proxies_handler,
+        proxy_api::validate_proxies_handler,
+        proxy_api::get_active_proxy_handler,
+        proxy_api::set_active_proxy_handler,
+        proxy_api::get_proxy_handler,
+        proxy_api::delete_proxy_handler,
+        proxy_api::validate_proxy_handler,
+        proxy_api::quarantine_proxy_handler,
+        proxy_api::release_proxy_handler,
+        proxy_api::proxy_health_handler,
+        proxy_api::list_quarantine_handler,
+        proxy_api::list_health_handler,
+        proxy_api::get_strategy_handler,
+        proxy_api::set_strategy_handler,
+        proxy_api::list_sessions_handler,
+        proxy_api::get_session_handler,
+        proxy_api::end_session_handler,
+        proxy_api::rotate_session_handler,
+        proxy_api::list_providers_handler,
+        proxy_api::refresh_providers_handler,
+        browser_api::browser_status_handler,
+        browser_api::create_browser_tab_handler,
+        browser_api::list_browser_tabs_handler,
+        browser_api::get_browser_tab_handler,
+        browser_api::close_browser_tab_handler,
+        browser_api::navigate_browser_tab_handler,
+        browser_api::execute_script_handler,
+        browser_api::page_content_handler,
+        browser_api::screenshot_handler,
+        jobs_api::submit_job_handler,
+        jobs_api::list_jobs_handler,
+        jobs_api::get_job_handler,
+        jobs_api::cancel_job_handler,
+        jobs_api::job_results_handler,
+        events_api::events_ws_handler,
+        events_api::events_sse_handler,
+        auth::login_handler,
+        auth::refresh_handler,
+        auth::whoami_handler,
+        auth::issue_token_handler,
+        auth::list_keys_handler,
+        auth::create_key_handler,
+        auth::revoke_key_handler,
+        metrics_api::metrics_handler,
+        openapi_handler,
+    ),
+    components(schemas(
+        TabResponse,
+        VirtualIPResponse,
+        CountryResponse,
+        ValidationResponse,
+        ProxyResponse,
+        Page<ProxyResponse>,
+        Page<ProxyHealthStatus>,
+        Page<ProxySessionStats>,
+        Page<QuarantinedProxy>,
+        NewProxyRequest,
+        ImportRequest,
+        ImportResponse,
+        ProxyValidationResponse,
+        QuarantineResponse,
+        ProxyHealthResponse,
+        StrategyRequest,
+        ProviderResponse,
+        RefreshResponse,
+        FreeProxy,
+        ProxyType,
+        ValidationResult,
+        QuarantinedProxy,
+        QuarantineStats,
+        ProxyHealthStatus,
+        BandwidthStats,
+        ProxySessionStats,
+        BrowserStatusResponse,
+        BrowserTabResponse,
+        CreateBrowserTabRequest,
+        ScriptRequest,
+        ScriptResponse,
+        PageContentResponse,
+        BatchJobSpec,
+        BatchJobProgress,
+        JobState,
+        FetchResult,
+        TokenResponse,
+        Principal,
+        PrincipalKind,
+        ApiScope,
+        NewApiKey,
+        ApiKeyResponse,
+        CreatedKeyResponse,
+    )),
+    modifiers(&SecuritySchemes),
+    security(("api_key" = []), ("bearer" = [])),
+    tags(
+        (name = "tabs", description = "Tabs and their virtual IPs"),
+        (name = "proxies", description = "Proxy pool"),
+        (name = "validation", description = "Proxy validation and quarantine"),
+        (name = "health", description = "Proxy health and bandwidth"),
+        (name = "rotation", description = "Rotation strategy and per-tab sessions"),
+        (name = "providers", description = "Free proxy providers"),
+        (name = "browser", description = "Headless Chromium tabs"),
+        (name = "jobs", description = "Batch fetch jobs across proxies and countries"),
+        (name = "events", description = "Live event streams"),
+        (name = "auth", description = "Tokens and API keys"),
+        (name = "meta", description = "This document and Prometheus metrics"),
+    )
+)]
+/// The API's OpenAPI document.
+pub struct ApiDoc;
+
+/// API keys and JWTs, as accepted by `auth::authenticate`
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT or pxb_ API key")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Route serving the document, ready to merge into the server router.
+pub fn router() -> Router {
+    Router::new().route("/api/openapi.json", get(openapi_handler))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "meta",
+    security(()),
+    responses((status = 200, description = "This OpenAPI document", content_type = "application/json"))
+)]
+async fn openapi_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use crate::{ApiAuth, ApiServer, BatchJobManager, ProxyServices};
+    use browser_core::{
+        ApiKeyStore, ApiScope, AuthManager, BrowserEngineManager, FreeIpProviderManager, NewApiKey,
+        ProxyHealthMonitor, ProxyManager, ProxyQuarantineManager, ProxyRotationManager, ProxyRotationStrategy,
+        ProxyValidator, ProxyValidatorConfig, TabIPManager,
+    };
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::{Mutex, RwLock};
+    use tower::ServiceExt;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "delete", "patch"];
+
+    /// Marks responses from the test router's fallback, so a 404 from a
+    /// handler (e.g. an unknown tab) is not mistaken for a missing route
+    const UNROUTED: &str = "x-unrouted";
+
+    fn documented_routes(spec: &Value) -> BTreeSet<(String, String)> {
+        let mut routes = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().expect("Spec should have paths") {
+            for method in METHODS {
+                if item.get(method).is_some() {
+                    routes.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        routes
+    }
+
+    /// The full server router, with every optional component attached and
+    /// an admin API key for its authentication layer
+    async fn full_router(dir: &std::path::Path) -> (Router, String) {
+        let generator = virtual_ip::demo_generator();
+        let providers = Arc::new(RwLock::new(
+            FreeIpProviderManager::new().expect("Provider manager should build"),
+        ));
+        let services = ProxyServices::new(
+            Arc::new(ProxyManager::new()),
+            providers.clone(),
+            Arc::new(RwLock::new(ProxyRotationManager::new(providers, ProxyRotationStrategy::RoundRobin))),
+            Arc::new(ProxyValidator::new(ProxyValidatorConfig::default())),
+            Arc::new(ProxyQuarantineManager::new(3, Duration::from_secs(300), Duration::from_secs(3600))),
+            Arc::new(ProxyHealthMonitor::new()),
+        );
+        let jobs = BatchJobManager::open(dir, Some(services.clone())).await.expect("Open should succeed");
+        let auth = ApiAuth::new(
+            Arc::new(AuthManager::new("test-secret".to_string())),
+            Arc::new(ApiKeyStore::new()),
+        )
+        .with_public_rate_limit(10_000);
+        let (_, key) = auth
+            .keys()
+            .create(NewApiKey {
+                name: "spec".to_string(),
+                scopes: vec![ApiScope::Admin],
+                rate_limit_per_minute: Some(10_000),
+                expires_at: None,
+            })
+            .await
+            .expect("Create should succeed");
+
+        let server = ApiServer::new(
+            Arc::new(Mutex::new(TabIPManager::new(generator.clone()))),
+            Arc::new(generator),
+        )
+        .with_proxy_services(services)
+        .with_batch_jobs(jobs)
+        .with_browser_engine(Arc::new(BrowserEngineManager::new()))
+        .with_auth(auth);
+        let router = Arc::new(server)
+            .router()
+            .await
+            .fallback(|| async { (StatusCode::NOT_FOUND, [(UNROUTED, "1")]) });
+        (router, key)
+    }
+
+    #[tokio::test]
+    async fn test_every_spec_operation_is_routed() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let (router, key) = full_router(dir.path()).await;
+        let spec = serde_json::to_value(ApiDoc::openapi()).expect("Spec should serialize");
+        let documented = documented_routes(&spec);
+        assert!(documented.len() > 30, "Spec has too few operations: {:?}", documented);
+
+        let mut unrouted = Vec::new();
+        for (method, path) in &documented {
+            let uri = path
+                .split('/')
+                .map(|segment| if segment.starts_with('{') { "spec-test" } else { segment })
+                .collect::<Vec<_>>()
+                .join("/");
+            let request = Request::builder()
+                .method(method.to_uppercase().as_str())
+                .uri(&uri)
+                .header("x-api-key", &key)
+                .header("content-type", "application/json")
+                .body(if method == "get" || method == "delete" { Body::empty() } else { Body::from("{}") })
+                .expect("Request should build");
+
+            // A handler still working when the timeout hits was routed
+            let Ok(response) = tokio::time::timeout(Duration::from_secs(2), router.clone().oneshot(request)).await
+            else {
+                continue;
+            };
+            let response = response.expect("Request should succeed");
+            let status = response.status();
+            if status == StatusCode::METHOD_NOT_ALLOWED
+                || (status == StatusCode::NOT_FOUND && response.headers().contains_key(UNROUTED))
+            {
+                unrouted.push(format!("{} {} -> {}", method.to_uppercase(), uri, status));
+            }
+        }
+        assert!(unrouted.is_empty(), "Spec operations without a route: {:?}", unrouted);
+    }
+
+    #[test]
+    fn test_spec_references_resolve() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).expect("Spec should serialize");
+        assert!(spec["openapi"].as_str().is_some_and(|v| v.starts_with("3.1")));
+
+        let schemas = spec["components"]["schemas"].as_object().expect("Spec should have schemas");
+        let text = spec.to_string();
+        for reference in text.split("\"$ref\":\"#/components/schemas/").skip(1) {
+            let name = reference.split('"').next().unwrap_or_default();
+            assert!(schemas.contains_key(name), "Unresolved schema reference {}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serves_spec() {
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/openapi.json")
+                    .body(Body::empty())
+                    .expect("Request should build"),
+            )
+            .await
+            .expect("Request should succeed");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.expect("Body should read").to_bytes();
+        let spec: Value = serde_json::from_slice(&body).expect("Spec should be JSON");
+        assert!(spec["components"]["schemas"]["TabResponse"].is_object());
+        assert!(spec["paths"]["/api/tabs/{id}"]["get"].is_object());
+    }
+}
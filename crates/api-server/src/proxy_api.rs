@@ -0,0 +1,1252 @@
+//! Proxy API Module
+//!
+//! REST endpoints for scripting the proxy layer:
+//! - Pool listing with filtering and pagination, adding and bulk import
+//! - On-demand validation and manual quarantine
+//! - Rotation strategy switching and per-tab session stats
+//! - Proxy health and bandwidth
+//! - Free provider listing and refresh
+//!
+//! Proxies are addressed by their `ip:port` ID, the same key used by
+//! `ProxyQuarantineManager` and `ProxyHealthMonitor`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use browser_core::{
+    BandwidthStats, FreeIpProvider, FreeIpProviderManager, FreeProxy, ProxyConfig, ProxyHealthMonitor,
+    ProxyHealthStatus, ProxyManager, ProxyQuarantineManager, ProxyRotationManager,
+    ProxyRotationStrategy, ProxySessionStats, ProxyType, ProxyValidator, QuarantineStats, QuarantinedProxy,
+    ValidationResult,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+use utoipa::{IntoParams, ToSchema};
+
+/// Page size used when a request does not give one
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// Largest page size a request may ask for
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// The proxy components the API operates on
+#[derive(Clone)]
+pub struct ProxyServices {
+    pub proxy_manager: Arc<ProxyManager>,
+    pub providers: Arc<RwLock<FreeIpProviderManager>>,
+    pub rotation: Arc<RwLock<ProxyRotationManager>>,
+    pub validator: Arc<ProxyValidator>,
+    pub quarantine: Arc<ProxyQuarantineManager>,
+    pub health: Arc<ProxyHealthMonitor>,
+}
+
+impl ProxyServices {
+    /// Creates a new instance from its components.
+    pub fn new(
+        proxy_manager: Arc<ProxyManager>,
+        providers: Arc<RwLock<FreeIpProviderManager>>,
+        rotation: Arc<RwLock<ProxyRotationManager>>,
+        validator: Arc<ProxyValidator>,
+        quarantine: Arc<ProxyQuarantineManager>,
+        health: Arc<ProxyHealthMonitor>,
+    ) -> Self {
+        Self {
+            proxy_manager,
+            providers,
+            rotation,
+            validator,
+            quarantine,
+            health,
+        }
+    }
+
+    /// Routes for the proxy API, ready to merge into the server router.
+    pub fn router(self) -> Router {
+        Router::new()
+            // Pool endpoints
+            .route("/api/proxies", get(list_proxies_handler).post(add_proxy_handler))
+            .route("/api/proxies/import", post(import_proxies_handler))
+            .route("/api/proxies/validate", post(validate_proxies_handler))
+            .route(
+                "/api/proxies/active",
+                get(get_active_proxy_handler).put(set_active_proxy_handler),
+            )
+            .route(
+                "/api/proxies/:id",
+                get(get_proxy_handler).delete(delete_proxy_handler),
+            )
+            .route("/api/proxies/:id/validate", post(validate_proxy_handler))
+            .route(
+                "/api/proxies/:id/quarantine",
+                post(quarantine_proxy_handler).delete(release_proxy_handler),
+            )
+            .route("/api/proxies/:id/health", get(proxy_health_handler))
+            // Quarantine and health endpoints
+            .route("/api/quarantine", get(list_quarantine_handler))
+            .route("/api/health/proxies", get(list_health_handler))
+            // Rotation endpoints
+            .route(
+                "/api/rotation/strategy",
+                get(get_strategy_handler).put(set_strategy_handler),
+            )
+            .route("/api/rotation/sessions", get(list_sessions_handler))
+            .route(
+                "/api/rotation/sessions/:tab_id",
+                get(get_session_handler).delete(end_session_handler),
+            )
+            .route("/api/rotation/sessions/:tab_id/rotate", post(rotate_session_handler))
+            // Provider endpoints
+            .route("/api/providers", get(list_providers_handler))
+            .route("/api/providers/refresh", post(refresh_providers_handler))
+            .with_state(self)
+    }
+}
+
+// ========= Pagination and filtering =========
+
+/// One page of a listing
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl<T> Page<T> {
+    fn slice(items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> Self {
+        let total = items.len();
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+        Self {
+            items: items.into_iter().skip(offset).take(limit).collect(),
+            total,
+            offset,
+            limit,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PageQuery {
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+/// Filters for pool listings and batch validation
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ProxyQuery {
+    country: Option<String>,
+    protocol: Option<String>,
+    provider: Option<String>,
+    working: Option<bool>,
+    quarantined: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+impl ProxyQuery {
+    fn matches(&self, proxy: &FreeProxy, protocol: Option<&ProxyType>, quarantined: &HashSet<String>) -> bool {
+        self.country
+            .as_ref()
+            .is_none_or(|c| proxy.country_code.eq_ignore_ascii_case(c))
+            && protocol.is_none_or(|p| &proxy.protocol == p)
+            && self
+                .provider
+                .as_ref()
+                .is_none_or(|p| proxy.provider.eq_ignore_ascii_case(p))
+            && self.working.is_none_or(|w| proxy.is_working == w)
+            && self
+                .quarantined
+                .is_none_or(|q| quarantined.contains(&proxy_id(proxy)) == q)
+    }
+}
+
+/// `ip:port` ID of a proxy
+pub fn proxy_id(proxy: &FreeProxy) -> String {
+    format!("{}:{}", proxy.ip, proxy.port)
+}
+
+/// Parse an `ip:port` ID; IPv6 addresses may be bracketed (`[::1]:8080`)
+fn parse_proxy_id(id: &str) -> Result<(String, u16), StatusCode> {
+    let (ip, port) = id.rsplit_once(':').ok_or(StatusCode::BAD_REQUEST)?;
+    let port = port.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let ip = ip.strip_prefix('[').and_then(|ip| ip.strip_suffix(']')).unwrap_or(ip);
+    Ok((ip.to_string(), port))
+}
+
+fn parse_protocol(name: &str) -> Option<ProxyType> {
+    match name.to_ascii_lowercase().as_str() {
+        "http" => Some(ProxyType::Http),
+        "https" => Some(ProxyType::Https),
+        "socks4" => Some(ProxyType::Socks4),
+        "socks5" | "socks" => Some(ProxyType::Socks5),
+        _ => None,
+    }
+}
+
+async fn quarantined_ids(services: &ProxyServices) -> HashSet<String> {
+    services
+        .quarantine
+        .get_quarantined()
+        .await
+        .iter()
+        .map(|q| proxy_id(&q.proxy))
+        .collect()
+}
+
+/// Pooled proxies matching the query, in pool order
+async fn filtered_pool(services: &ProxyServices, query: &ProxyQuery) -> Result<Vec<FreeProxy>, StatusCode> {
+    let protocol = match &query.protocol {
+        Some(name) => Some(parse_protocol(name).ok_or(StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    let quarantined = quarantined_ids(services).await;
+    let providers = services.providers.read().await;
+    Ok(providers
+        .get_proxy_pool()
+        .iter()
+        .filter(|p| query.matches(p, protocol.as_ref(), &quarantined))
+        .cloned()
+        .collect())
+}
+
+pub(crate) async fn find_pooled(services: &ProxyServices, id: &str) -> Result<FreeProxy, StatusCode> {
+    let (ip, port) = parse_proxy_id(id)?;
+    services
+        .providers
+        .read()
+        .await
+        .find_proxy(&ip, port)
+        .cloned()
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// ========= Pool handlers =========
+
+#[utoipa::path(
+    get,
+    path = "/api/proxies",
+    tag = "proxies",
+    params(ProxyQuery),
+    responses(
+        (status = 200, description = "A page of pooled proxies", body = Page<ProxyResponse>),
+        (status = 400, description = "Unknown protocol filter"),
+    )
+)]
+async fn list_proxies_handler(
+    State(services): State<ProxyServices>,
+    Query(query): Query<ProxyQuery>,
+) -> Result<Json<Page<ProxyResponse>>, StatusCode> {
+    let pool = filtered_pool(&services, &query).await?;
+    let quarantined = quarantined_ids(&services).await;
+    let page = Page::slice(pool, query.offset, query.limit);
+    Ok(Json(Page {
+        items: page
+            .items
+            .into_iter()
+            .map(|p| ProxyResponse::new(p, &quarantined))
+            .collect(),
+        total: page.total,
+        offset: page.offset,
+        limit: page.limit,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/proxies/{id}",
+    tag = "proxies",
+    params(("id" = String, Path, description = "Proxy ID (`ip:port`)")),
+    responses(
+        (status = 200, description = "The pooled proxy", body = ProxyResponse),
+        (status = 404, description = "Proxy not in the pool"),
+    )
+)]
+async fn get_proxy_handler(
+    State(services): State<ProxyServices>,
+    Path(id): Path<String>,
+) -> Result<Json<ProxyResponse>, StatusCode> {
+    let proxy = find_pooled(&services, &id).await?;
+    let quarantined = quarantined_ids(&services).await;
+    Ok(Json(ProxyResponse::new(proxy, &quarantined)))
+}
+
+/// A proxy to add to the pool
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct NewProxyRequest {
+    pub ip: String,
+    pub port: u16,
+    #[serde(default)]
+    pub protocol: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub country_code: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+impl NewProxyRequest {
+    fn into_proxy(self) -> Option<FreeProxy> {
+        self.ip.parse::<IpAddr>().ok()?;
+        if self.port == 0 {
+            return None;
+        }
+        let protocol = match self.protocol.as_deref() {
+            Some(name) => parse_protocol(name)?,
+            None => ProxyType::Http,
+        };
+        Some(FreeProxy {
+            ip: self.ip,
+            port: self.port,
+            protocol,
+            country: self.country.unwrap_or_else(|| "Unknown".to_string()),
+            country_code: self
+                .country_code
+                .map(|c| c.to_ascii_uppercase())
+                .unwrap_or_else(|| "XX".to_string()),
+            anonymity: "unknown".to_string(),
+            speed: 0,
+            uptime: 0.0,
+            last_checked: String::new(),
+            provider: self.provider.unwrap_or_else(|| "manual".to_string()),
+            is_working: true,
+        })
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/proxies",
+    tag = "proxies",
+    request_body = NewProxyRequest,
+    responses(
+        (status = 201, description = "Proxy added", body = ProxyResponse),
+        (status = 400, description = "Invalid address or protocol"),
+        (status = 409, description = "Proxy already pooled"),
+    )
+)]
+async fn add_proxy_handler(
+    State(services): State<ProxyServices>,
+    Json(payload): Json<NewProxyRequest>,
+) -> Result<(StatusCode, Json<ProxyResponse>), StatusCode> {
+    let proxy = payload.into_proxy().ok_or(StatusCode::BAD_REQUEST)?;
+    let added = services.providers.write().await.add_proxies(vec![proxy.clone()]);
+    if added == 0 {
+        return Err(StatusCode::CONFLICT);
+    }
+    info!("Added proxy {} to the pool", proxy_id(&proxy));
+    Ok((StatusCode::CREATED, Json(ProxyResponse::new(proxy, &HashSet::new()))))
+}
+
+/// Bulk import: structured entries, text lines (`ip:port` or
+/// `scheme://ip:port`), or both
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ImportRequest {
+    #[serde(default)]
+    pub proxies: Vec<NewProxyRequest>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Provider recorded for text lines
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+/// Outcome of a bulk import
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ImportResponse {
+    pub imported: usize,
+    pub duplicates: usize,
+    /// Entries that could not be parsed, as given
+    pub invalid: Vec<String>,
+}
+
+/// Parse an `ip:port` or `scheme://ip:port` line into a pool entry
+pub fn parse_proxy_line(line: &str, provider: Option<&String>) -> Option<FreeProxy> {
+    let (protocol, address) = match line.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme.to_string()), rest),
+        None => (None, line),
+    };
+    let (ip, port) = address.trim_end_matches('/').rsplit_once(':')?;
+    NewProxyRequest {
+        ip: ip.to_string(),
+        port: port.parse().ok()?,
+        protocol,
+        country: None,
+        country_code: None,
+        provider: provider.cloned(),
+    }
+    .into_proxy()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/proxies/import",
+    tag = "proxies",
+    request_body = ImportRequest,
+    responses((status = 200, description = "Import outcome", body = ImportResponse))
+)]
+async fn import_proxies_handler(
+    State(services): State<ProxyServices>,
+    Json(payload): Json<ImportRequest>,
+) -> Result<Json<ImportResponse>, StatusCode> {
+    let mut proxies = Vec::new();
+    let mut invalid = Vec::new();
+
+    for entry in payload.proxies {
+        let label = format!("{}:{}", entry.ip, entry.port);
+        match entry.into_proxy() {
+            Some(proxy) => proxies.push(proxy),
+            None => invalid.push(label),
+        }
+    }
+    for line in payload.text.as_deref().unwrap_or_default().lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_proxy_line(line, payload.provider.as_ref()) {
+            Some(proxy) => proxies.push(proxy),
+            None => invalid.push(line.to_string()),
+        }
+    }
+
+    let candidates = proxies.len();
+    let imported = services.providers.write().await.add_proxies(proxies);
+    info!("Imported {} proxies ({} duplicates, {} invalid)", imported, candidates - imported, invalid.len());
+    Ok(Json(ImportResponse {
+        imported,
+        duplicates: candidates - imported,
+        invalid,
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/proxies/{id}",
+    tag = "proxies",
+    params(("id" = String, Path, description = "Proxy ID (`ip:port`)")),
+    responses(
+        (status = 204, description = "Proxy removed"),
+        (status = 404, description = "Proxy not in the pool"),
+    )
+)]
+async fn delete_proxy_handler(
+    State(services): State<ProxyServices>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let (ip, port) = parse_proxy_id(&id)?;
+    let removed = services
+        .providers
+        .write()
+        .await
+        .remove_proxy(&ip, port)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    services.quarantine.release(&removed).await;
+    info!("Removed proxy {} from the pool", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/proxies/active",
+    tag = "proxies",
+    responses((status = 200, description = "The active proxy, or null for a direct connection", body = Option<FreeProxy>))
+)]
+async fn get_active_proxy_handler(State(services): State<ProxyServices>) -> Json<Option<FreeProxy>> {
+    Json(services.proxy_manager.get_active_proxy().await)
+}
+
+#[derive(Deserialize, ToSchema)]
+struct SetActiveProxyRequest {
+    /// `ip:port` of a pooled proxy, or null for a direct connection
+    id: Option<String>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/proxies/active",
+    tag = "proxies",
+    request_body = SetActiveProxyRequest,
+    responses(
+        (status = 200, description = "The new active proxy", body = Option<FreeProxy>),
+        (status = 404, description = "Proxy not in the pool"),
+    )
+)]
+async fn set_active_proxy_handler(
+    State(services): State<ProxyServices>,
+    Json(payload): Json<SetActiveProxyRequest>,
+) -> Result<Json<Option<FreeProxy>>, StatusCode> {
+    let proxy = match payload.id {
+        Some(id) => Some(find_pooled(&services, &id).await?),
+        None => None,
+    };
+    services.proxy_manager.set_active_proxy(proxy.clone()).await;
+    Ok(Json(proxy))
+}
+
+// ========= Validation and quarantine handlers =========
+
+/// Validation outcome for one proxy
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProxyValidationResponse {
+    pub id: String,
+    pub result: ValidationResult,
+    pub quarantined: bool,
+}
+
+/// Store a validation result on the pooled proxy and feed quarantine and
+/// health tracking
+async fn record_validation(services: &ProxyServices, proxy: &FreeProxy, result: &ValidationResult) -> bool {
+    let id = proxy_id(proxy);
+    if let Some(pooled) = services.providers.write().await.find_proxy_mut(&proxy.ip, proxy.port) {
+        pooled.is_working = result.is_working;
+        pooled.last_checked = result.validated_at.to_rfc3339();
+        if result.is_working {
+            pooled.speed = result.response_time_ms.min(u32::MAX as u64) as u32;
+        }
+    }
+
+    if result.is_working {
+        services.quarantine.record_success(proxy).await;
+        services
+            .health
+            .record_success(&id, result.response_time_ms as f64, 0, 0)
+            .await;
+        false
+    } else {
+        let reason = result.error.clone().unwrap_or_else(|| "validation failed".to_string());
+        services.health.record_failure(&id, &reason).await;
+        services.quarantine.record_failure(proxy, reason).await
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/proxies/{id}/validate",
+    tag = "validation",
+    params(("id" = String, Path, description = "Proxy ID (`ip:port`)")),
+    responses(
+        (status = 200, description = "Validation outcome", body = ProxyValidationResponse),
+        (status = 404, description = "Proxy not in the pool"),
+    )
+)]
+async fn validate_proxy_handler(
+    State(services): State<ProxyServices>,
+    Path(id): Path<String>,
+) -> Result<Json<ProxyValidationResponse>, StatusCode> {
+    let proxy = find_pooled(&services, &id).await?;
+    let result = services.validator.validate_proxy(&proxy).await.map_err(|e| {
+        error!("Failed to validate proxy '{}': {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let quarantined = record_validation(&services, &proxy, &result).await;
+    Ok(Json(ProxyValidationResponse {
+        id,
+        result,
+        quarantined,
+    }))
+}
+
+/// Validates every pooled proxy matching the filters (the page parameters
+/// bound the batch)
+#[utoipa::path(
+    post,
+    path = "/api/proxies/validate",
+    tag = "validation",
+    params(ProxyQuery),
+    responses(
+        (status = 200, description = "Validation outcome per proxy", body = Vec<ProxyValidationResponse>),
+        (status = 400, description = "Unknown protocol filter"),
+    )
+)]
+async fn validate_proxies_handler(
+    State(services): State<ProxyServices>,
+    Query(query): Query<ProxyQuery>,
+) -> Result<Json<Vec<ProxyValidationResponse>>, StatusCode> {
+    let pool = filtered_pool(&services, &query).await?;
+    let batch = Page::slice(pool, query.offset, query.limit).items;
+    let results = services.validator.validate_batch(&batch).await;
+
+    let mut responses = Vec::with_capacity(results.len());
+    for (proxy, result) in results {
+        let quarantined = record_validation(&services, &proxy, &result).await;
+        responses.push(ProxyValidationResponse {
+            id: proxy_id(&proxy),
+            result,
+            quarantined,
+        });
+    }
+    Ok(Json(responses))
+}
+
+#[derive(Default, Deserialize, ToSchema)]
+struct QuarantineRequest {
+    reason: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/proxies/{id}/quarantine",
+    tag = "validation",
+    params(("id" = String, Path, description = "Proxy ID (`ip:port`)")),
+    request_body(content = Option<QuarantineRequest>),
+    responses(
+        (status = 200, description = "Proxy quarantined", body = QuarantinedProxy),
+        (status = 404, description = "Proxy not in the pool"),
+    )
+)]
+async fn quarantine_proxy_handler(
+    State(services): State<ProxyServices>,
+    Path(id): Path<String>,
+    payload: Option<Json<QuarantineRequest>>,
+) -> Result<Json<QuarantinedProxy>, StatusCode> {
+    let proxy = find_pooled(&services, &id).await?;
+    let reason = payload
+        .and_then(|Json(p)| p.reason)
+        .unwrap_or_else(|| "quarantined via API".to_string());
+    Ok(Json(services.quarantine.quarantine(&proxy, reason).await))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/proxies/{id}/quarantine",
+    tag = "validation",
+    params(("id" = String, Path, description = "Proxy ID (`ip:port`)")),
+    responses(
+        (status = 204, description = "Proxy released"),
+        (status = 404, description = "Proxy not pooled or not quarantined"),
+    )
+)]
+async fn release_proxy_handler(
+    State(services): State<ProxyServices>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let proxy = find_pooled(&services, &id).await?;
+    if services.quarantine.release(&proxy).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+/// Quarantined proxies with aggregate stats
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct QuarantineResponse {
+    pub stats: QuarantineStats,
+    pub proxies: Page<QuarantinedProxy>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/quarantine",
+    tag = "validation",
+    params(PageQuery),
+    responses((status = 200, description = "Quarantined proxies", body = QuarantineResponse))
+)]
+async fn list_quarantine_handler(
+    State(services): State<ProxyServices>,
+    Query(page): Query<PageQuery>,
+) -> Json<QuarantineResponse> {
+    let mut proxies = services.quarantine.get_quarantined().await;
+    proxies.sort_by_key(|q| q.quarantined_at);
+    Json(QuarantineResponse {
+        stats: services.quarantine.get_stats().await,
+        proxies: Page::slice(proxies, page.offset, page.limit),
+    })
+}
+
+// ========= Health handlers =========
+
+/// Health and bandwidth of one proxy
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProxyHealthResponse {
+    pub id: String,
+    pub health: Option<ProxyHealthStatus>,
+    pub bandwidth: Option<BandwidthStats>,
+    pub quarantined: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/proxies/{id}/health",
+    tag = "health",
+    params(("id" = String, Path, description = "Proxy ID (`ip:port`)")),
+    responses(
+        (status = 200, description = "Health and bandwidth of the proxy", body = ProxyHealthResponse),
+        (status = 404, description = "Proxy not in the pool"),
+    )
+)]
+async fn proxy_health_handler(
+    State(services): State<ProxyServices>,
+    Path(id): Path<String>,
+) -> Result<Json<ProxyHealthResponse>, StatusCode> {
+    let proxy = find_pooled(&services, &id).await?;
+    Ok(Json(ProxyHealthResponse {
+        health: services.health.get_health(&id).await,
+        bandwidth: services.health.get_bandwidth_stats(&id).await,
+        quarantined: services.quarantine.is_quarantined(&proxy).await,
+        id,
+    }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HealthQuery {
+    healthy: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/health/proxies",
+    tag = "health",
+    params(HealthQuery),
+    responses((status = 200, description = "A page of proxy health records", body = Page<ProxyHealthStatus>))
+)]
+async fn list_health_handler(
+    State(services): State<ProxyServices>,
+    Query(query): Query<HealthQuery>,
+) -> Json<Page<ProxyHealthStatus>> {
+    let health: Vec<ProxyHealthStatus> = services
+        .health
+        .all_health()
+        .await
+        .into_iter()
+        .filter(|h| query.healthy.is_none_or(|healthy| h.is_healthy == healthy))
+        .collect();
+    Json(Page::slice(health, query.offset, query.limit))
+}
+
+// ========= Rotation handlers =========
+
+/// A rotation strategy with its parameters. Parameters that do not apply
+/// to the named strategy are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct StrategyRequest {
+    pub strategy: String,
+    /// Interval for `per_duration` and sticky duration for `sticky`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Request count for `per_request`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests: Option<usize>,
+    /// Rotation probability for `random`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability: Option<f64>,
+    /// Countries for `geographic`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub country_codes: Vec<String>,
+}
+
+impl From<&ProxyRotationStrategy> for StrategyRequest {
+    fn from(strategy: &ProxyRotationStrategy) -> Self {
+        let mut dto = Self {
+            strategy: strategy.name().to_string(),
+            ..Self::default()
+        };
+        match strategy {
+            ProxyRotationStrategy::PerRequest(n) => dto.requests = Some(*n),
+            ProxyRotationStrategy::PerDuration(d) | ProxyRotationStrategy::Sticky { duration: d } => {
+                dto.interval_secs = Some(d.num_seconds().max(0) as u64)
+            }
+            ProxyRotationStrategy::Random { probability } => dto.probability = Some(*probability),
+            ProxyRotationStrategy::Geographic { country_codes } => dto.country_codes = country_codes.clone(),
+            _ => {}
+        }
+        dto
+    }
+}
+
+impl StrategyRequest {
+    /// Parsed the same way as the config file's rotation settings, with
+    /// the config defaults for anything left out
+    fn to_strategy(&self) -> Option<ProxyRotationStrategy> {
+        let defaults = ProxyConfig::default();
+        let config = ProxyConfig {
+            rotation_strategy: self.strategy.clone(),
+            rotation_interval_secs: self.interval_secs.unwrap_or(defaults.rotation_interval_secs),
+            rotation_requests: self.requests.unwrap_or(defaults.rotation_requests),
+            rotation_probability: self.probability.unwrap_or(defaults.rotation_probability),
+            preferred_countries: self.country_codes.clone(),
+            ..defaults
+        };
+        ProxyRotationStrategy::from_config(&config).ok()
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rotation/strategy",
+    tag = "rotation",
+    responses((status = 200, description = "The current rotation strategy", body = StrategyRequest))
+)]
+async fn get_strategy_handler(State(services): State<ProxyServices>) -> Json<StrategyRequest> {
+    let rotation = services.rotation.read().await;
+    Json(StrategyRequest::from(rotation.strategy()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/rotation/strategy",
+    tag = "rotation",
+    request_body = StrategyRequest,
+    responses(
+        (status = 200, description = "The new rotation strategy", body = StrategyRequest),
+        (status = 400, description = "Unknown strategy or invalid parameters"),
+    )
+)]
+async fn set_strategy_handler(
+    State(services): State<ProxyServices>,
+    Json(payload): Json<StrategyRequest>,
+) -> Result<Json<StrategyRequest>, StatusCode> {
+    let strategy = payload.to_strategy().ok_or(StatusCode::BAD_REQUEST)?;
+    let mut rotation = services.rotation.write().await;
+    rotation.update_strategy(strategy).await;
+    info!("Rotation strategy switched to {}", rotation.strategy().name());
+    Ok(Json(StrategyRequest::from(rotation.strategy())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rotation/sessions",
+    tag = "rotation",
+    params(PageQuery),
+    responses((status = 200, description = "A page of per-tab proxy sessions", body = Page<ProxySessionStats>))
+)]
+async fn list_sessions_handler(
+    State(services): State<ProxyServices>,
+    Query(page): Query<PageQuery>,
+) -> Json<Page<ProxySessionStats>> {
+    let sessions = services.rotation.read().await.list_session_stats().await;
+    Json(Page::slice(sessions, page.offset, page.limit))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rotation/sessions/{tab_id}",
+    tag = "rotation",
+    params(("tab_id" = String, Path, description = "Tab ID")),
+    responses(
+        (status = 200, description = "The tab's proxy session", body = ProxySessionStats),
+        (status = 404, description = "No session for the tab"),
+    )
+)]
+async fn get_session_handler(
+    State(services): State<ProxyServices>,
+    Path(tab_id): Path<String>,
+) -> Result<Json<ProxySessionStats>, StatusCode> {
+    services
+        .rotation
+        .read()
+        .await
+        .get_session_stats(&tab_id)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/rotation/sessions/{tab_id}/rotate",
+    tag = "rotation",
+    params(("tab_id" = String, Path, description = "Tab ID")),
+    responses(
+        (status = 200, description = "The tab's new proxy", body = FreeProxy),
+        (status = 503, description = "No proxy available"),
+    )
+)]
+async fn rotate_session_handler(
+    State(services): State<ProxyServices>,
+    Path(tab_id): Path<String>,
+) -> Result<Json<FreeProxy>, StatusCode> {
+    let proxy = services
+        .rotation
+        .read()
+        .await
+        .force_rotate(&tab_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to rotate proxy for tab '{}': {}", tab_id, e);
+            StatusCode::SERVICE_UNAVAILABLE
+        })?;
+    Ok(Json(proxy))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/rotation/sessions/{tab_id}",
+    tag = "rotation",
+    params(("tab_id" = String, Path, description = "Tab ID")),
+    responses(
+        (status = 204, description = "Session ended"),
+        (status = 404, description = "No session for the tab"),
+    )
+)]
+async fn end_session_handler(
+    State(services): State<ProxyServices>,
+    Path(tab_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    if services.rotation.read().await.end_session(&tab_id).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+// ========= Provider handlers =========
+
+/// A free proxy provider and its share of the pool
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProviderResponse {
+    pub name: String,
+    pub api_based: bool,
+    pub pooled: usize,
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/providers",
+    tag = "providers",
+    responses((status = 200, description = "Free proxy providers", body = Vec<ProviderResponse>))
+)]
+async fn list_providers_handler(State(services): State<ProxyServices>) -> Json<Vec<ProviderResponse>> {
+    let providers = services.providers.read().await;
+    Json(
+        FreeIpProvider::all()
+            .into_iter()
+            .map(|provider| ProviderResponse {
+                name: provider.name().to_string(),
+                api_based: provider.is_api_based(),
+                pooled: providers
+                    .get_proxy_pool()
+                    .iter()
+                    .filter(|p| p.provider.eq_ignore_ascii_case(provider.name()))
+                    .count(),
+                last_updated: providers.last_updated(&provider),
+            })
+            .collect(),
+    )
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RefreshQuery {
+    /// Refresh a single provider; all providers when absent
+    provider: Option<String>,
+}
+
+/// Outcome of a provider refresh
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RefreshResponse {
+    pub fetched: usize,
+    pub added: usize,
+    pub pool_size: usize,
+    /// Providers that failed, with their errors
+    pub failed: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/providers/refresh",
+    tag = "providers",
+    params(RefreshQuery),
+    responses(
+        (status = 200, description = "Refresh outcome", body = RefreshResponse),
+        (status = 404, description = "Unknown provider"),
+        (status = 502, description = "Every provider failed"),
+    )
+)]
+async fn refresh_providers_handler(
+    State(services): State<ProxyServices>,
+    Query(query): Query<RefreshQuery>,
+) -> Result<Json<RefreshResponse>, StatusCode> {
+    let targets = match &query.provider {
+        Some(name) => vec![FreeIpProvider::from_name(name).ok_or(StatusCode::NOT_FOUND)?],
+        None => FreeIpProvider::all(),
+    };
+
+    let mut response = RefreshResponse {
+        fetched: 0,
+        added: 0,
+        pool_size: 0,
+        failed: Vec::new(),
+    };
+    // Fetch with a separate manager so the pool is not locked for the
+    // duration of the network requests
+    let mut fetcher = FreeIpProviderManager::new().map_err(|e| {
+        error!("Failed to create provider client: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    for provider in &targets {
+        match fetcher.fetch_from_provider(provider).await {
+            Ok(proxies) => {
+                let (fetched, added) = services.providers.write().await.record_refresh(provider, proxies);
+                response.fetched += fetched;
+                response.added += added;
+            }
+            Err(e) => {
+                error!("Failed to refresh provider {}: {}", provider.name(), e);
+                response.failed.push(format!("{}: {}", provider.name(), e));
+            }
+        }
+    }
+    response.pool_size = services.providers.read().await.get_proxy_pool().len();
+
+    if response.failed.len() == targets.len() {
+        return Err(StatusCode::BAD_GATEWAY);
+    }
+    Ok(Json(response))
+}
+
+// ========= DTOs =========
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+/// Represents a ProxyResponse.
+pub struct ProxyResponse {
+    pub id: String,
+    pub ip: String,
+    pub port: u16,
+    pub protocol: ProxyType,
+    pub country: String,
+    pub country_code: String,
+    pub anonymity: String,
+    pub speed: u32,
+    pub uptime: f32,
+    pub last_checked: String,
+    pub provider: String,
+    pub is_working: bool,
+    pub quarantined: bool,
+}
+
+impl ProxyResponse {
+    fn new(proxy: FreeProxy, quarantined: &HashSet<String>) -> Self {
+        let id = proxy_id(&proxy);
+        Self {
+            quarantined: quarantined.contains(&id),
+            id,
+            ip: proxy.ip,
+            port: proxy.port,
+            protocol: proxy.protocol,
+            country: proxy.country,
+            country_code: proxy.country_code,
+            anonymity: proxy.anonymity,
+            speed: proxy.speed,
+            uptime: proxy.uptime,
+            last_checked: proxy.last_checked,
+            provider: proxy.provider,
+            is_working: proxy.is_working,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+    use browser_core::ProxyValidatorConfig;
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn services() -> ProxyServices {
+        let providers = Arc::new(RwLock::new(
+            FreeIpProviderManager::new().expect("Provider manager should build"),
+        ));
+        ProxyServices::new(
+            Arc::new(ProxyManager::new()),
+            providers.clone(),
+            Arc::new(RwLock::new(ProxyRotationManager::new(
+                providers,
+                ProxyRotationStrategy::RoundRobin,
+            ))),
+            Arc::new(ProxyValidator::new(ProxyValidatorConfig::default())),
+            Arc::new(ProxyQuarantineManager::new(
+                3,
+                Duration::from_secs(300),
+                Duration::from_secs(3600),
+            )),
+            Arc::new(ProxyHealthMonitor::new()),
+        )
+    }
+
+    async fn call(services: &ProxyServices, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .expect("Request should build");
+        let response = services
+            .clone()
+            .router()
+            .oneshot(request)
+            .await
+            .expect("Request should succeed");
+        let status = response.status();
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("Body should be readable")
+            .to_bytes();
+        let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, value)
+    }
+
+    async fn seed(services: &ProxyServices) {
+        let text = "10.0.0.1:8080\nsocks5://10.0.0.2:1080\nhttp://10.0.0.3:3128\nnot-a-proxy\n10.0.0.1:8080";
+        let (status, body) = call(
+            services,
+            Method::POST,
+            "/api/proxies/import",
+            Some(json!({
+                "text": text,
+                "provider": "manual",
+                "proxies": [{ "ip": "10.0.0.4", "port": 8000, "country_code": "de", "protocol": "https" }]
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["imported"], 4);
+        assert_eq!(body["duplicates"], 1);
+        assert_eq!(body["invalid"], json!(["not-a-proxy"]));
+    }
+
+    #[tokio::test]
+    async fn test_import_filter_and_paginate() {
+        let services = services();
+        seed(&services).await;
+
+        let (_, page) = call(&services, Method::GET, "/api/proxies?limit=2&offset=1", None).await;
+        assert_eq!(page["total"], 4);
+        assert_eq!(page["items"].as_array().map(Vec::len), Some(2));
+        // Structured entries are imported ahead of text lines
+        assert_eq!(page["items"][0]["id"], "10.0.0.1:8080");
+
+        let (_, page) = call(&services, Method::GET, "/api/proxies?protocol=socks5", None).await;
+        assert_eq!(page["total"], 1);
+
+        let (_, page) = call(&services, Method::GET, "/api/proxies?country=DE", None).await;
+        assert_eq!(page["items"][0]["id"], "10.0.0.4:8000");
+
+        let (status, _) = call(&services, Method::GET, "/api/proxies?protocol=ftp", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_add_get_and_delete_proxy() {
+        let services = services();
+        let new_proxy = json!({ "ip": "192.0.2.10", "port": 3128 });
+
+        let (status, body) = call(&services, Method::POST, "/api/proxies", Some(new_proxy.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["id"], "192.0.2.10:3128");
+
+        let (status, _) = call(&services, Method::POST, "/api/proxies", Some(new_proxy)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, _) = call(&services, Method::POST, "/api/proxies", Some(json!({ "ip": "nope", "port": 1 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = call(&services, Method::GET, "/api/proxies/192.0.2.10:3128", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["provider"], "manual");
+
+        let (status, _) = call(&services, Method::DELETE, "/api/proxies/192.0.2.10:3128", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = call(&services, Method::GET, "/api/proxies/192.0.2.10:3128", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_ipv6_proxy_ids_may_be_bracketed() {
+        let services = services();
+        let (status, body) =
+            call(&services, Method::POST, "/api/proxies", Some(json!({ "ip": "2001:db8::1", "port": 3128 }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["id"], "2001:db8::1:3128");
+
+        for id in ["2001:db8::1:3128", "[2001:db8::1]:3128", "%5B2001:db8::1%5D:3128"] {
+            let (status, body) = call(&services, Method::GET, &format!("/api/proxies/{}", id), None).await;
+            assert_eq!(status, StatusCode::OK, "{} should be found", id);
+            assert_eq!(body["ip"], "2001:db8::1");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manual_quarantine_and_release() {
+        let services = services();
+        seed(&services).await;
+
+        let (status, body) = call(
+            &services,
+            Method::POST,
+            "/api/proxies/10.0.0.1:8080/quarantine",
+            Some(json!({ "reason": "slow" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["failure_reasons"], json!(["slow"]));
+
+        let (_, page) = call(&services, Method::GET, "/api/proxies?quarantined=true", None).await;
+        assert_eq!(page["total"], 1);
+        assert_eq!(page["items"][0]["quarantined"], true);
+
+        let (_, quarantine) = call(&services, Method::GET, "/api/quarantine", None).await;
+        assert_eq!(quarantine["stats"]["total_quarantined"], 1);
+
+        let (status, _) = call(&services, Method::DELETE, "/api/proxies/10.0.0.1:8080/quarantine", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = call(&services, Method::DELETE, "/api/proxies/10.0.0.1:8080/quarantine", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_switch_rotation_strategy() {
+        let services = services();
+
+        let (_, body) = call(&services, Method::GET, "/api/rotation/strategy", None).await;
+        assert_eq!(body["strategy"], "round_robin");
+
+        let (status, body) = call(
+            &services,
+            Method::PUT,
+            "/api/rotation/strategy",
+            Some(json!({ "strategy": "geographic", "country_codes": ["us", "de"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["country_codes"], json!(["US", "DE"]));
+
+        let (status, _) = call(
+            &services,
+            Method::PUT,
+            "/api/rotation/strategy",
+            Some(json!({ "strategy": "random", "probability": 2.0 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, body) = call(&services, Method::GET, "/api/rotation/strategy", None).await;
+        assert_eq!(body["strategy"], "geographic");
+
+        let (status, _) = call(&services, Method::GET, "/api/rotation/sessions/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,942 @@
+//! Application Backup Module
+//!
+//! Full-state backups of everything outside browsing data:
+//! - `AppConfig` from the `ConfigManager`
+//! - Browser profiles, automation workflows
+//! - The free proxy pool and quarantine state
+//! - Users from any `UserDirectory` (e.g. the UI's `AuthManager`)
+//!
+//! Each backup carries a manifest with per-component checksums. Restores are
+//! selective and can be previewed as a per-component diff before applying.
+//! Profile proxy passwords are redacted unless secrets are explicitly
+//! included, and user password hashes are only written to encrypted backups;
+//! restoring a redacted password or a stripped hash keeps the local one.
+//! A restore that fails part-way rolls back the components already applied.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::automation::{VisualAutomationBuilder, Workflow};
+use crate::browser_profile::{BrowserProfile, BrowserProfileManager};
+use crate::config_manager::{AppConfig, ConfigManager};
+use crate::free_ip_providers::FreeIpProviderManager;
+use crate::proxy::FreeProxy;
+use crate::proxy_validator::{ProxyQuarantineManager, QuarantinedProxy};
+use crate::secrets_vault::{keep_redacted, RedactSecrets, SecretsVault, REDACTED_SECRET};
+use crate::secure_container::{self, KdfParams};
+
+/// Version of the application backup format
+pub const APP_BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// A part of the application state that can be backed up on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupComponent {
+    Config,
+    Profiles,
+    Workflows,
+    ProxyPool,
+    Quarantine,
+    Users,
+}
+
+impl BackupComponent {
+    /// Every component, in restore order
+    pub fn all() -> Vec<Self> {
+        vec![
+            BackupComponent::Config,
+            BackupComponent::Profiles,
+            BackupComponent::Workflows,
+            BackupComponent::ProxyPool,
+            BackupComponent::Quarantine,
+            BackupComponent::Users,
+        ]
+    }
+}
+
+/// A user account as stored in a backup. The password hash is only kept in
+/// encrypted backups.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub id: String,
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+    pub last_login: Option<DateTime<Utc>>,
+    pub enterprise_id: Option<String>,
+    pub password_hash: Option<String>,
+}
+
+/// User store that can take part in application backups
+#[async_trait]
+pub trait UserDirectory: Send + Sync {
+    /// Export every user
+    async fn export_users(&self) -> Result<Vec<UserRecord>>;
+
+    /// Replace all users with `users`
+    async fn import_users(&self, users: Vec<UserRecord>) -> Result<()>;
+}
+
+/// Manifest entry for one component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentEntry {
+    pub component: BackupComponent,
+    pub item_count: usize,
+    pub sha256: String,
+}
+
+/// Describes the contents of an application backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppBackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub components: Vec<ComponentEntry>,
+}
+
+impl AppBackupManifest {
+    /// Whether the backup contains `component`
+    pub fn contains(&self, component: BackupComponent) -> bool {
+        self.components.iter().any(|c| c.component == component)
+    }
+}
+
+/// Captured application state; components not selected are `None`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppBackup {
+    pub manifest: AppBackupManifest,
+    #[serde(default, deserialize_with = "crate::config_migration::deserialize_migrated")]
+    pub config: Option<AppConfig>,
+    pub profiles: Option<Vec<BrowserProfile>>,
+    pub workflows: Option<Vec<Workflow>>,
+    pub proxy_pool: Option<Vec<FreeProxy>>,
+    pub quarantine: Option<Vec<QuarantinedProxy>>,
+    pub users: Option<Vec<UserRecord>>,
+}
+
+/// Changes restoring one component would make, by item key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComponentDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl ComponentDiff {
+    /// Whether restoring would change nothing
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Preview of a selective restore
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppRestorePreview {
+    pub components: BTreeMap<BackupComponent, ComponentDiff>,
+    /// Selected components the backup does not contain
+    pub missing: Vec<BackupComponent>,
+}
+
+/// Result of a selective restore
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppRestoreReport {
+    pub restored: Vec<BackupComponent>,
+    pub diff: AppRestorePreview,
+}
+
+/// Creates, previews and restores application backups
+pub struct AppBackupManager {
+    config: Option<Arc<ConfigManager>>,
+    profiles: Option<Arc<BrowserProfileManager>>,
+    workflows: Option<Arc<RwLock<VisualAutomationBuilder>>>,
+    proxy_pool: Option<Arc<RwLock<FreeIpProviderManager>>>,
+    quarantine: Option<Arc<ProxyQuarantineManager>>,
+    users: Option<Arc<dyn UserDirectory>>,
+    kdf: KdfParams,
+    secrets: Option<Arc<SecretsVault>>,
+    include_secrets: bool,
+}
+
+impl AppBackupManager {
+    /// Create a manager with no components attached
+    pub fn new() -> Self {
+        Self {
+            config: None,
+            profiles: None,
+            workflows: None,
+            proxy_pool: None,
+            quarantine: None,
+            users: None,
+            kdf: KdfParams::default(),
+            secrets: None,
+            include_secrets: false,
+        }
+    }
+
+    /// Include the application configuration
+    pub fn with_config(mut self, config: Arc<ConfigManager>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Include browser profiles
+    pub fn with_profiles(mut self, profiles: Arc<BrowserProfileManager>) -> Self {
+        self.profiles = Some(profiles);
+        self
+    }
+
+    /// Include automation workflows
+    pub fn with_workflows(mut self, workflows: Arc<RwLock<VisualAutomationBuilder>>) -> Self {
+        self.workflows = Some(workflows);
+        self
+    }
+
+    /// Include the free proxy pool
+    pub fn with_proxy_pool(mut self, pool: Arc<RwLock<FreeIpProviderManager>>) -> Self {
+        self.proxy_pool = Some(pool);
+        self
+    }
+
+    /// Include proxy quarantine state
+    pub fn with_quarantine(mut self, quarantine: Arc<ProxyQuarantineManager>) -> Self {
+        self.quarantine = Some(quarantine);
+        self
+    }
+
+    /// Include users
+    pub fn with_users(mut self, users: Arc<dyn UserDirectory>) -> Self {
+        self.users = Some(users);
+        self
+    }
+
+    /// Use custom Argon2id cost parameters for encrypted backups
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Vault used to resolve secret references when secrets are included
+    pub fn with_secrets_vault(mut self, vault: Arc<SecretsVault>) -> Self {
+        self.secrets = Some(vault);
+        self
+    }
+
+    /// Keep profile proxy passwords in backups instead of redacting them
+    pub fn with_include_secrets(mut self, include: bool) -> Self {
+        self.include_secrets = include;
+        self
+    }
+
+    /// Components this manager can back up and restore
+    pub fn available_components(&self) -> Vec<BackupComponent> {
+        BackupComponent::all()
+            .into_iter()
+            .filter(|c| self.is_attached(*c))
+            .collect()
+    }
+
+    fn is_attached(&self, component: BackupComponent) -> bool {
+        match component {
+            BackupComponent::Config => self.config.is_some(),
+            BackupComponent::Profiles => self.profiles.is_some(),
+            BackupComponent::Workflows => self.workflows.is_some(),
+            BackupComponent::ProxyPool => self.proxy_pool.is_some(),
+            BackupComponent::Quarantine => self.quarantine.is_some(),
+            BackupComponent::Users => self.users.is_some(),
+        }
+    }
+
+    /// Capture the selected components; an empty selection means all attached
+    pub async fn capture(&self, components: &[BackupComponent]) -> Result<AppBackup> {
+        let selected = self.resolve_selection(components)?;
+        let mut backup = empty_backup();
+
+        for component in selected {
+            match component {
+                BackupComponent::Config => backup.config = Some(self.local_config().await?),
+                BackupComponent::Profiles => backup.profiles = Some(self.exported_profiles().await?),
+                BackupComponent::Workflows => backup.workflows = Some(self.local_workflows().await?),
+                BackupComponent::ProxyPool => backup.proxy_pool = Some(self.local_proxy_pool().await?),
+                BackupComponent::Quarantine => backup.quarantine = Some(self.local_quarantine().await?),
+                BackupComponent::Users => backup.users = Some(self.local_users().await?),
+            }
+            let items = component_items(&backup, component)?
+                .ok_or_else(|| anyhow!("Component {:?} was not captured", component))?;
+            backup.manifest.components.push(ComponentEntry {
+                component,
+                item_count: items.len(),
+                sha256: checksum(&items)?,
+            });
+        }
+
+        Ok(backup)
+    }
+
+    /// Capture the selected components and write them to `path`, encrypted
+    /// when a password is given
+    pub async fn create_backup(
+        &self,
+        path: &Path,
+        components: &[BackupComponent],
+        password: Option<&str>,
+    ) -> Result<AppBackupManifest> {
+        let mut backup = self.capture(components).await?;
+        if password.is_none() {
+            strip_password_hashes(&mut backup)?;
+        }
+        let json = serde_json::to_vec_pretty(&backup)?;
+        let content = match password {
+            Some(password) => secure_container::encrypt_bytes(&json, password, self.kdf).await?,
+            None => json,
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, content).await?;
+
+        info!(
+            "Application backup written to {:?} ({} components)",
+            path,
+            backup.manifest.components.len()
+        );
+        Ok(backup.manifest)
+    }
+
+    /// Read a backup written by `create_backup`, checking component checksums
+    pub async fn read_backup(&self, path: &Path, password: Option<&str>) -> Result<AppBackup> {
+        let content = tokio::fs::read(path).await?;
+        let json = if secure_container::is_container(&content) {
+            let password = password.ok_or_else(|| anyhow!("Backup is encrypted; a password is required"))?;
+            secure_container::decrypt_bytes(&content, password).await?
+        } else {
+            content
+        };
+
+        let backup: AppBackup = serde_json::from_slice(&json).context("Invalid application backup")?;
+        if backup.manifest.format_version > APP_BACKUP_FORMAT_VERSION {
+            return Err(anyhow!(
+                "Unsupported application backup format version {}",
+                backup.manifest.format_version
+            ));
+        }
+        for entry in &backup.manifest.components {
+            let items = component_items(&backup, entry.component)?
+                .ok_or_else(|| anyhow!("Backup is missing component {:?}", entry.component))?;
+            if checksum(&items)? != entry.sha256 {
+                return Err(anyhow!("Checksum mismatch for component {:?}", entry.component));
+            }
+        }
+        Ok(backup)
+    }
+
+    /// Show what restoring the selected components would change; an empty
+    /// selection means every component in the backup
+    pub async fn preview_restore(
+        &self,
+        backup: &AppBackup,
+        components: &[BackupComponent],
+    ) -> Result<AppRestorePreview> {
+        let mut preview = AppRestorePreview::default();
+
+        for component in self.restore_selection(backup, components)? {
+            let Some(incoming) = component_items(backup, component)? else {
+                preview.missing.push(component);
+                continue;
+            };
+            let mut local = self.local_items(component).await?;
+            match component {
+                BackupComponent::Profiles => mask_redacted_passwords(&mut local, &incoming),
+                BackupComponent::Users => mask_stripped_hashes(&mut local, &incoming),
+                _ => {}
+            }
+            preview.components.insert(component, diff_items(&local, &incoming));
+        }
+
+        Ok(preview)
+    }
+
+    /// Restore the selected components, making each match the backup. If a
+    /// component fails to apply, every component touched so far is put back
+    /// to its state before the restore.
+    pub async fn restore(&self, backup: &AppBackup, components: &[BackupComponent]) -> Result<AppRestoreReport> {
+        let diff = self.preview_restore(backup, components).await?;
+        if !diff.missing.is_empty() {
+            return Err(anyhow!("Backup does not contain {:?}", diff.missing));
+        }
+
+        if let Some(manager) = &self.profiles {
+            if diff.components.contains_key(&BackupComponent::Profiles) {
+                for profile in manager.list_profiles().await {
+                    if manager.is_loaded(&profile.id).await {
+                        return Err(anyhow!("Profile {} is open; close it before restoring profiles", profile.name));
+                    }
+                }
+            }
+        }
+
+        let selected: Vec<BackupComponent> = diff.components.keys().copied().collect();
+        let snapshot = self.snapshot(&selected).await?;
+
+        let mut report = AppRestoreReport::default();
+        for component in selected {
+            if let Err(e) = self.apply_component(backup, component).await {
+                report.restored.push(component);
+                self.roll_back(&snapshot, &report.restored).await;
+                return Err(e.context(format!("Failed to restore {:?}; restore rolled back", component)));
+            }
+            report.restored.push(component);
+        }
+        report.diff = diff;
+
+        info!("Restored application components: {:?}", report.restored);
+        Ok(report)
+    }
+
+    /// Local state of the given components, unredacted, for rolling back
+    async fn snapshot(&self, components: &[BackupComponent]) -> Result<AppBackup> {
+        let mut snapshot = empty_backup();
+        for component in components {
+            match component {
+                BackupComponent::Config => snapshot.config = Some(self.local_config().await?),
+                BackupComponent::Profiles => snapshot.profiles = Some(self.local_profiles().await?),
+                BackupComponent::Workflows => snapshot.workflows = Some(self.local_workflows().await?),
+                BackupComponent::ProxyPool => snapshot.proxy_pool = Some(self.local_proxy_pool().await?),
+                BackupComponent::Quarantine => snapshot.quarantine = Some(self.local_quarantine().await?),
+                BackupComponent::Users => snapshot.users = Some(self.local_users().await?),
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Put `components` back to their snapshot, newest first
+    async fn roll_back(&self, snapshot: &AppBackup, components: &[BackupComponent]) {
+        for component in components.iter().rev() {
+            if let Err(e) = self.apply_component(snapshot, *component).await {
+                warn!("Failed to roll back {:?} after a failed restore: {}", component, e);
+            }
+        }
+    }
+
+    async fn apply_component(&self, backup: &AppBackup, component: BackupComponent) -> Result<()> {
+        match component {
+            BackupComponent::Config => {
+                let (Some(manager), Some(config)) = (&self.config, &backup.config) else {
+                    return Ok(());
+                };
+                let config = config.clone();
+                manager.update(move |current| *current = config).await?;
+            }
+            BackupComponent::Profiles => {
+                let (Some(manager), Some(profiles)) = (&self.profiles, &backup.profiles) else {
+                    return Ok(());
+                };
+                // Profiles missing from the backup are detached rather than
+                // deleted, so their browsing data survives on disk
+                for local in manager.list_profiles().await {
+                    if !profiles.iter().any(|p| p.id == local.id) {
+                        manager.detach_profile(&local.id).await?;
+                    }
+                }
+                for profile in profiles {
+                    let mut profile = profile.clone();
+                    let local = manager.get_profile(&profile.id).await;
+                    if let Some(proxy) = profile.settings.proxy_config.as_mut() {
+                        let local_password = local
+                            .as_ref()
+                            .and_then(|l| l.settings.proxy_config.as_ref())
+                            .and_then(|p| p.password.as_ref());
+                        keep_redacted(&mut proxy.password, local_password);
+                    }
+                    manager.insert_profile(profile).await?;
+                }
+            }
+            BackupComponent::Workflows => {
+                let (Some(builder), Some(workflows)) = (&self.workflows, &backup.workflows) else {
+                    return Ok(());
+                };
+                let mut builder = builder.write().await;
+                let stale: Vec<String> = builder
+                    .list_workflows()
+                    .into_iter()
+                    .filter(|w| !workflows.iter().any(|b| b.id == w.id))
+                    .map(|w| w.id.clone())
+                    .collect();
+                for id in stale {
+                    builder.delete_workflow(&id);
+                }
+                for workflow in workflows {
+                    builder.upsert_workflow(workflow.clone());
+                }
+            }
+            BackupComponent::ProxyPool => {
+                let (Some(pool), Some(proxies)) = (&self.proxy_pool, &backup.proxy_pool) else {
+                    return Ok(());
+                };
+                pool.write().await.set_proxy_pool(proxies.clone());
+            }
+            BackupComponent::Quarantine => {
+                let (Some(quarantine), Some(entries)) = (&self.quarantine, &backup.quarantine) else {
+                    return Ok(());
+                };
+                quarantine.replace_all(entries.clone()).await;
+            }
+            BackupComponent::Users => {
+                let (Some(directory), Some(users)) = (&self.users, &backup.users) else {
+                    return Ok(());
+                };
+                let local = directory.export_users().await?;
+                let mut users = users.clone();
+                for user in &mut users {
+                    if user.password_hash.is_none() {
+                        user.password_hash = local
+                            .iter()
+                            .find(|l| l.id == user.id)
+                            .and_then(|l| l.password_hash.clone());
+                    }
+                }
+                directory.import_users(users).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_selection(&self, components: &[BackupComponent]) -> Result<Vec<BackupComponent>> {
+        if components.is_empty() {
+            return Ok(self.available_components());
+        }
+        let mut selected = Vec::new();
+        for component in BackupComponent::all() {
+            if components.contains(&component) {
+                if !self.is_attached(component) {
+                    return Err(anyhow!("Component {:?} is not available", component));
+                }
+                selected.push(component);
+            }
+        }
+        Ok(selected)
+    }
+
+    fn restore_selection(&self, backup: &AppBackup, components: &[BackupComponent]) -> Result<Vec<BackupComponent>> {
+        if components.is_empty() {
+            let in_backup: Vec<BackupComponent> =
+                backup.manifest.components.iter().map(|c| c.component).collect();
+            return self.resolve_selection(&in_backup);
+        }
+        self.resolve_selection(components)
+    }
+
+    async fn local_config(&self) -> Result<AppConfig> {
+        let manager = self.config.as_ref().ok_or_else(|| anyhow!("Config is not attached"))?;
+        Ok(manager.get().await)
+    }
+
+    /// Profiles as written to a backup, with secrets redacted or resolved
+    async fn exported_profiles(&self) -> Result<Vec<BrowserProfile>> {
+        let mut profiles = self.local_profiles().await?;
+        for profile in &mut profiles {
+            match (&self.secrets, self.include_secrets) {
+                (Some(vault), true) => vault.resolve_profile(profile).await?,
+                (None, true) => {}
+                (_, false) => {
+                    profile.redact_secrets();
+                }
+            }
+        }
+        Ok(profiles)
+    }
+
+    async fn local_profiles(&self) -> Result<Vec<BrowserProfile>> {
+        let manager = self.profiles.as_ref().ok_or_else(|| anyhow!("Profiles are not attached"))?;
+        let mut profiles = manager.list_profiles().await;
+        profiles.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(profiles)
+    }
+
+    async fn local_workflows(&self) -> Result<Vec<Workflow>> {
+        let builder = self.workflows.as_ref().ok_or_else(|| anyhow!("Workflows are not attached"))?;
+        let mut workflows: Vec<Workflow> = builder.read().await.list_workflows().into_iter().cloned().collect();
+        workflows.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(workflows)
+    }
+
+    async fn local_proxy_pool(&self) -> Result<Vec<FreeProxy>> {
+        let pool = self.proxy_pool.as_ref().ok_or_else(|| anyhow!("Proxy pool is not attached"))?;
+        Ok(pool.read().await.get_proxy_pool().to_vec())
+    }
+
+    async fn local_quarantine(&self) -> Result<Vec<QuarantinedProxy>> {
+        let quarantine = self.quarantine.as_ref().ok_or_else(|| anyhow!("Quarantine is not attached"))?;
+        let mut entries = quarantine.get_quarantined().await;
+        entries.sort_by(|a, b| (&a.proxy.ip, a.proxy.port).cmp(&(&b.proxy.ip, b.proxy.port)));
+        Ok(entries)
+    }
+
+    async fn local_users(&self) -> Result<Vec<UserRecord>> {
+        let directory = self.users.as_ref().ok_or_else(|| anyhow!("Users are not attached"))?;
+        let mut users = directory.export_users().await?;
+        users.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(users)
+    }
+
+    /// Current local items for a component, keyed like `component_items`
+    async fn local_items(&self, component: BackupComponent) -> Result<BTreeMap<String, serde_json::Value>> {
+        let items = match component {
+            BackupComponent::Config => config_items(&self.local_config().await?)?,
+            BackupComponent::Profiles => profile_items(&self.local_profiles().await?)?,
+            BackupComponent::Workflows => keyed(&self.local_workflows().await?, |w| w.id.clone())?,
+            BackupComponent::ProxyPool => keyed(&self.local_proxy_pool().await?, proxy_key)?,
+            BackupComponent::Quarantine => keyed(&self.local_quarantine().await?, |q| proxy_key(&q.proxy))?,
+            BackupComponent::Users => keyed(&self.local_users().await?, |u| u.id.clone())?,
+        };
+        Ok(items)
+    }
+}
+
+impl Default for AppBackupManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A backup of the current version holding no components
+fn empty_backup() -> AppBackup {
+    AppBackup {
+        manifest: AppBackupManifest {
+            format_version: APP_BACKUP_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            components: Vec::new(),
+        },
+        config: None,
+        profiles: None,
+        workflows: None,
+        proxy_pool: None,
+        quarantine: None,
+        users: None,
+    }
+}
+
+/// Items of a component in a backup, keyed for diffing; `None` when absent
+fn component_items(
+    backup: &AppBackup,
+    component: BackupComponent,
+) -> Result<Option<BTreeMap<String, serde_json::Value>>> {
+    let items = match component {
+        BackupComponent::Config => backup.config.as_ref().map(config_items).transpose()?,
+        BackupComponent::Profiles => backup.profiles.as_deref().map(profile_items).transpose()?,
+        BackupComponent::Workflows => backup.workflows.as_deref().map(|w| keyed(w, |w| w.id.clone())).transpose()?,
+        BackupComponent::ProxyPool => backup.proxy_pool.as_deref().map(|p| keyed(p, proxy_key)).transpose()?,
+        BackupComponent::Quarantine => backup
+            .quarantine
+            .as_deref()
+            .map(|q| keyed(q, |q| proxy_key(&q.proxy)))
+            .transpose()?,
+        BackupComponent::Users => backup.users.as_deref().map(|u| keyed(u, |u| u.id.clone())).transpose()?,
+    };
+    Ok(items)
+}
+
+fn keyed<T: Serialize>(items: &[T], key: impl Fn(&T) -> String) -> Result<BTreeMap<String, serde_json::Value>> {
+    items
+        .iter()
+        .map(|item| Ok((key(item), serde_json::to_value(item)?)))
+        .collect()
+}
+
+/// Config is compared per top-level section
+fn config_items(config: &AppConfig) -> Result<BTreeMap<String, serde_json::Value>> {
+    match serde_json::to_value(config)? {
+        serde_json::Value::Object(sections) => Ok(sections.into_iter().collect()),
+        _ => Err(anyhow!("Config must serialize to an object")),
+    }
+}
+
+/// Redacted passwords are kept on restore, so they are not a difference
+fn mask_redacted_passwords(
+    local: &mut BTreeMap<String, serde_json::Value>,
+    incoming: &BTreeMap<String, serde_json::Value>,
+) {
+    const PASSWORD: &str = "/settings/proxy_config/password";
+    for (key, item) in incoming {
+        if item.pointer(PASSWORD).and_then(|v| v.as_str()) != Some(REDACTED_SECRET) {
+            continue;
+        }
+        if let Some(password) = local.get_mut(key).and_then(|l| l.pointer_mut(PASSWORD)) {
+            *password = serde_json::Value::from(REDACTED_SECRET);
+        }
+    }
+}
+
+/// Drop user password hashes from a backup that will not be encrypted
+fn strip_password_hashes(backup: &mut AppBackup) -> Result<()> {
+    let Some(users) = backup.users.as_mut() else {
+        return Ok(());
+    };
+    for user in users.iter_mut() {
+        user.password_hash = None;
+    }
+    let items = keyed(users, |u| u.id.clone())?;
+    if let Some(entry) = backup
+        .manifest
+        .components
+        .iter_mut()
+        .find(|c| c.component == BackupComponent::Users)
+    {
+        entry.sha256 = checksum(&items)?;
+    }
+    Ok(())
+}
+
+/// Stripped password hashes are kept on restore, so they are not a difference
+fn mask_stripped_hashes(
+    local: &mut BTreeMap<String, serde_json::Value>,
+    incoming: &BTreeMap<String, serde_json::Value>,
+) {
+    for (key, item) in incoming {
+        if !item.get("password_hash").is_some_and(|hash| hash.is_null()) {
+            continue;
+        }
+        if let Some(fields) = local.get_mut(key).and_then(|l| l.as_object_mut()) {
+            fields.insert("password_hash".to_string(), serde_json::Value::Null);
+        }
+    }
+}
+
+/// Profiles are compared without their data directory and last-used time,
+/// which are machine-specific and change on every use
+fn profile_items(profiles: &[BrowserProfile]) -> Result<BTreeMap<String, serde_json::Value>> {
+    let mut items = keyed(profiles, |p| p.id.clone())?;
+    for value in items.values_mut() {
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("data_dir");
+            fields.remove("last_used");
+        }
+    }
+    Ok(items)
+}
+
+fn proxy_key(proxy: &FreeProxy) -> String {
+    format!("{}:{}", proxy.ip, proxy.port)
+}
+
+fn checksum(items: &BTreeMap<String, serde_json::Value>) -> Result<String> {
+    Ok(hex::encode(Sha256::digest(serde_json::to_vec(items)?)))
+}
+
+fn diff_items(
+    local: &BTreeMap<String, serde_json::Value>,
+    incoming: &BTreeMap<String, serde_json::Value>,
+) -> ComponentDiff {
+    let mut diff = ComponentDiff::default();
+    for (key, value) in incoming {
+        match local.get(key) {
+            None => diff.added.push(key.clone()),
+            Some(existing) if existing != value => diff.changed.push(key.clone()),
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.removed = local.keys().filter(|k| !incoming.contains_key(*k)).cloned().collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(dir: &Path) -> (AppBackupManager, Arc<ConfigManager>, Arc<RwLock<VisualAutomationBuilder>>) {
+        let config = Arc::new(ConfigManager::new());
+        let workflows = Arc::new(RwLock::new(VisualAutomationBuilder::new()));
+        let profiles = Arc::new(BrowserProfileManager::new(dir.join("profiles")));
+        let manager = AppBackupManager::new()
+            .with_config(config.clone())
+            .with_workflows(workflows.clone())
+            .with_profiles(profiles)
+            .with_quarantine(Arc::new(ProxyQuarantineManager::new(
+                3,
+                std::time::Duration::from_secs(60),
+                std::time::Duration::from_secs(3600),
+            )))
+            .with_kdf_params(KdfParams { m_cost: 1024, t_cost: 1, p_cost: 1 });
+        (manager, config, workflows)
+    }
+
+    /// In-memory user store whose imports can be made to fail
+    #[derive(Default)]
+    struct MemoryUsers {
+        users: RwLock<Vec<UserRecord>>,
+        fail_imports: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl UserDirectory for MemoryUsers {
+        async fn export_users(&self) -> Result<Vec<UserRecord>> {
+            Ok(self.users.read().await.clone())
+        }
+
+        async fn import_users(&self, users: Vec<UserRecord>) -> Result<()> {
+            if self.fail_imports.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(anyhow!("user store is read-only"));
+            }
+            *self.users.write().await = users;
+            Ok(())
+        }
+    }
+
+    fn user(id: &str, hash: &str) -> UserRecord {
+        UserRecord {
+            id: id.to_string(),
+            username: id.to_string(),
+            email: format!("{}@example.com", id),
+            role: "user".to_string(),
+            created_at: Utc::now(),
+            last_login: None,
+            enterprise_id: None,
+            password_hash: Some(hash.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backup_round_trip_with_manifest() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let (manager, _, workflows) = manager(dir.path());
+        workflows.write().await.create_workflow("Login", "Sign in");
+
+        let path = dir.path().join("app.vipa");
+        let manifest = manager
+            .create_backup(&path, &[], Some("secret"))
+            .await
+            .expect("backup should succeed");
+        assert_eq!(manifest.components.len(), 4);
+        assert!(!manifest.contains(BackupComponent::Users));
+
+        let backup = manager.read_backup(&path, Some("secret")).await.expect("read should succeed");
+        assert_eq!(backup.workflows.expect("workflows should be included").len(), 1);
+        assert!(manager.read_backup(&path, Some("wrong")).await.is_err());
+        assert!(manager.capture(&[BackupComponent::Users]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_selective_restore_with_preview() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let (manager, config, workflows) = manager(dir.path());
+        let kept = workflows.write().await.create_workflow("Kept", "");
+        let backup = manager.capture(&[]).await.expect("capture should succeed");
+
+        let extra = workflows.write().await.create_workflow("Extra", "");
+        workflows.write().await.delete_workflow(&kept);
+        config.update(|c| c.general.theme = "changed".into()).await.expect("update should succeed");
+
+        let preview = manager
+            .preview_restore(&backup, &[BackupComponent::Workflows])
+            .await
+            .expect("preview should succeed");
+        let diff = &preview.components[&BackupComponent::Workflows];
+        assert_eq!(diff.added, vec![kept.clone()]);
+        assert_eq!(diff.removed, vec![extra.clone()]);
+        assert!(!preview.components.contains_key(&BackupComponent::Config));
+
+        let report = manager
+            .restore(&backup, &[BackupComponent::Workflows])
+            .await
+            .expect("restore should succeed");
+        assert_eq!(report.restored, vec![BackupComponent::Workflows]);
+        assert!(workflows.read().await.get_workflow(&kept).is_some());
+        assert!(workflows.read().await.get_workflow(&extra).is_none());
+        // Config was not selected and keeps the local change
+        assert_eq!(config.get().await.general.theme, "changed");
+    }
+
+    #[tokio::test]
+    async fn test_password_hashes_only_in_encrypted_backups() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let users = Arc::new(MemoryUsers::default());
+        users.users.write().await.push(user("alice", "$argon2id$original"));
+        let (manager, _, _) = manager(dir.path());
+        let manager = manager.with_users(users.clone());
+
+        let plain = dir.path().join("plain.json");
+        manager
+            .create_backup(&plain, &[BackupComponent::Users], None)
+            .await
+            .expect("backup should succeed");
+        let raw = std::fs::read_to_string(&plain).expect("read should succeed");
+        assert!(!raw.contains("$argon2id$original"));
+
+        let encrypted = dir.path().join("users.enc");
+        manager
+            .create_backup(&encrypted, &[BackupComponent::Users], Some("secret"))
+            .await
+            .expect("backup should succeed");
+        let backup = manager.read_backup(&encrypted, Some("secret")).await.expect("read should succeed");
+        assert_eq!(
+            backup.users.expect("users should be included")[0].password_hash.as_deref(),
+            Some("$argon2id$original")
+        );
+
+        // Restoring a stripped backup keeps the local hash and is not a change
+        users.users.write().await[0].password_hash = Some("$argon2id$changed".to_string());
+        let backup = manager.read_backup(&plain, None).await.expect("read should succeed");
+        let preview = manager.preview_restore(&backup, &[]).await.expect("preview should succeed");
+        assert!(preview.components[&BackupComponent::Users].is_empty());
+        manager.restore(&backup, &[]).await.expect("restore should succeed");
+        assert_eq!(
+            users.users.read().await[0].password_hash.as_deref(),
+            Some("$argon2id$changed")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_restore_rolls_back() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let users = Arc::new(MemoryUsers::default());
+        let (manager, _, workflows) = manager(dir.path());
+        let manager = manager.with_users(users.clone());
+        let kept = workflows.write().await.create_workflow("Kept", "");
+        let backup = manager.capture(&[]).await.expect("capture should succeed");
+
+        workflows.write().await.delete_workflow(&kept);
+        let extra = workflows.write().await.create_workflow("Extra", "");
+        users.users.write().await.push(user("bob", "$argon2id$bob"));
+        users.fail_imports.store(true, std::sync::atomic::Ordering::SeqCst);
+
+        assert!(manager.restore(&backup, &[]).await.is_err());
+        assert!(workflows.read().await.get_workflow(&extra).is_some());
+        assert!(workflows.read().await.get_workflow(&kept).is_none());
+        assert_eq!(users.users.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_profile_restore_keeps_browsing_data() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let profiles = Arc::new(BrowserProfileManager::new(dir.path().join("profiles")));
+        let manager = AppBackupManager::new().with_profiles(profiles.clone());
+
+        let existing = profiles.create_profile("Existing", true).await.expect("create should succeed");
+        let cookies = existing.data_dir.join("chromium").join("Cookies");
+        std::fs::create_dir_all(cookies.parent().expect("cookie dir should exist")).expect("mkdir should succeed");
+        std::fs::write(&cookies, b"session").expect("write should succeed");
+        let backup = manager.capture(&[BackupComponent::Profiles]).await.expect("capture should succeed");
+
+        let later = profiles.create_profile("Later", false).await.expect("create should succeed");
+        std::fs::write(later.data_dir.join("storage.json"), b"{}").expect("write should succeed");
+
+        manager.restore(&backup, &[]).await.expect("restore should succeed");
+        assert_eq!(std::fs::read(&cookies).expect("cookies should survive"), b"session");
+        assert!(profiles.get_profile(&later.id).await.is_none());
+        assert!(later.data_dir.join("storage.json").exists());
+
+        // A detached profile is not rediscovered on the next start
+        let reopened = BrowserProfileManager::new(dir.path().join("profiles"));
+        assert_eq!(reopened.discover_profiles().await.expect("discover should succeed"), 1);
+    }
+}
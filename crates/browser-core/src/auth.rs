@@ -0,0 +1,773 @@
+//! Authentication Module
+//!
+//! Shared authentication used by the desktop UI and the API server:
+//! - User registration and login with Argon2 password hashes
+//! - JWT access tokens, refresh tokens and scoped service tokens
+//! - API keys with scopes and per-key rate limits, stored hashed
+//! - Role and scope based access control
+
+use anyhow::{anyhow, Context, Result};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::app_backup::{UserDirectory, UserRecord};
+
+/// Prefix identifying API keys, as opposed to JWTs
+pub const API_KEY_PREFIX: &str = "pxb_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Enumeration of UserRole variants.
+pub enum UserRole {
+    User,
+    Admin,
+    Enterprise,
+}
+
+/// What a token or key may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ApiScope {
+    /// Read any resource
+    ReadOnly,
+    /// Create, navigate and close tabs
+    Tabs,
+    /// Manage the proxy pool, rotation, providers and batch jobs
+    Proxies,
+    /// Everything, including key management
+    Admin,
+}
+
+impl ApiScope {
+    /// Wire name of the scope
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::ReadOnly => "read_only",
+            ApiScope::Tabs => "tabs",
+            ApiScope::Proxies => "proxies",
+            ApiScope::Admin => "admin",
+        }
+    }
+
+    /// Parse a wire name
+    pub fn parse(name: &str) -> Option<Self> {
+        [ApiScope::ReadOnly, ApiScope::Tabs, ApiScope::Proxies, ApiScope::Admin]
+            .into_iter()
+            .find(|s| s.as_str() == name.trim())
+    }
+
+    /// Whether holding this scope allows an action requiring `required`.
+    /// Every scope can read; admin can do anything.
+    pub fn grants(&self, required: ApiScope) -> bool {
+        *self == ApiScope::Admin || *self == required || required == ApiScope::ReadOnly
+    }
+}
+
+/// Whether any of `scopes` allows `required`
+pub fn scopes_grant(scopes: &[ApiScope], required: ApiScope) -> bool {
+    scopes.iter().any(|s| s.grants(required))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents a User.
+pub struct User {
+    pub id: String,
+    pub username: String,
+    pub email: String,
+    pub role: UserRole,
+    pub created_at: chrono::DateTime<Utc>,
+    pub last_login: Option<chrono::DateTime<Utc>>,
+    pub enterprise_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents a Claims.
+pub struct Claims {
+    pub sub: String, // User ID
+    pub username: String,
+    pub role: UserRole,
+    pub enterprise_id: Option<String>,
+    pub exp: i64, // Expiration time
+    pub iat: i64, // Issued at
+    pub jti: String, // JWT ID
+    /// Explicit scopes; tokens without any get scopes from their role
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<ApiScope>,
+}
+
+impl Claims {
+    /// Scopes the token carries, falling back to the role's defaults
+    pub fn effective_scopes(&self) -> Vec<ApiScope> {
+        if !self.scopes.is_empty() {
+            return self.scopes.clone();
+        }
+        match self.role {
+            UserRole::Admin => vec![ApiScope::Admin],
+            UserRole::User | UserRole::Enterprise => vec![ApiScope::ReadOnly],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents a RefreshToken.
+pub struct RefreshToken {
+    pub id: String,
+    pub user_id: String,
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<Utc>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// Represents a AuthManager.
+pub struct AuthManager {
+    jwt_secret: String,
+    users: Arc<RwLock<HashMap<String, User>>>, // In-memory for demo, use DB in production
+    password_hashes: Arc<RwLock<HashMap<String, String>>>, // user_id -> password_hash
+    refresh_tokens: Arc<RwLock<HashMap<String, RefreshToken>>>, // token_id -> token
+    argon2: Argon2<'static>,
+
+}
+
+impl AuthManager {
+    /// Create a new AuthManager with the specified JWT secret
+    ///
+    /// # Arguments
+    /// * `jwt_secret` - The secret key used for JWT token signing
+    pub fn new(jwt_secret: String) -> Self {
+        Self {
+            jwt_secret,
+            users: Arc::new(RwLock::new(HashMap::new())),
+            password_hashes: Arc::new(RwLock::new(HashMap::new())),
+            refresh_tokens: Arc::new(RwLock::new(HashMap::new())),
+            argon2: Argon2::default(),
+        }
+    }
+
+
+    /// Register a new user
+    pub async fn register(&self, username: String, email: String, password: String) -> Result<User> {
+        // Validate input
+        if username.len() < 3 {
+            return Err(anyhow!("Username must be at least 3 characters"));
+        }
+        if !email.contains('@') {
+            return Err(anyhow!("Invalid email format"));
+        }
+        if password.len() < 8 {
+            return Err(anyhow!("Password must be at least 8 characters"));
+        }
+
+        // Check if user exists
+        let users = self.users.read().await;
+        if users.values().any(|u| u.username == username || u.email == email) {
+            return Err(anyhow!("User already exists"));
+        }
+        drop(users);
+
+        // Hash password
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = self
+            .argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Failed to hash password: {}", e))?
+            .to_string();
+
+        // Create user (in production, store in database)
+        let user = User {
+            id: Uuid::new_v4().to_string(),
+            username: username.clone(),
+            email,
+            role: UserRole::User,
+            created_at: Utc::now(),
+            last_login: None,
+            enterprise_id: None,
+        };
+
+        // Store user and password hash
+        let mut users = self.users.write().await;
+        users.insert(user.id.clone(), user.clone());
+        drop(users);
+
+        // Store password hash separately for security
+        let mut hashes = self.password_hashes.write().await;
+        hashes.insert(user.id.clone(), password_hash);
+
+        Ok(user)
+    }
+
+
+    /// Authenticate user and return tokens
+    pub async fn login(&self, username: String, password: String) -> Result<(String, String)> {
+        // Find user
+        let users = self.users.read().await;
+        let user = users
+            .values()
+            .find(|u| u.username == username)
+            .ok_or_else(|| anyhow!("Invalid credentials"))?
+            .clone();
+        drop(users);
+
+        // Verify password hash
+        let hashes = self.password_hashes.read().await;
+        let stored_hash = hashes
+            .get(&user.id)
+            .ok_or_else(|| anyhow!("Invalid credentials"))?;
+
+        let parsed_hash = PasswordHash::new(stored_hash)
+            .map_err(|_| anyhow!("Invalid credentials"))?;
+
+        self.argon2
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .map_err(|_| anyhow!("Invalid credentials"))?;
+        drop(hashes);
+
+        // Update last login
+
+        let mut users = self.users.write().await;
+        if let Some(stored_user) = users.get_mut(&user.id) {
+            stored_user.last_login = Some(Utc::now());
+        }
+
+        // Generate tokens
+        let access_token = self.generate_access_token(&user)?;
+        let refresh_token = self.generate_refresh_token(&user.id).await?;
+
+        Ok((access_token, refresh_token))
+    }
+
+    /// Refresh access token
+    pub async fn refresh_access_token(&self, refresh_token: String) -> Result<String> {
+        // Look the token up by its ID, then verify the one stored hash
+        let (token_id, _) = refresh_token
+            .split_once('.')
+            .ok_or_else(|| anyhow!("Invalid refresh token"))?;
+        let tokens = self.refresh_tokens.read().await;
+        let token_data = tokens
+            .get(token_id)
+            .filter(|t| self.verify_refresh_token(&refresh_token, t))
+            .ok_or_else(|| anyhow!("Invalid refresh token"))?
+            .clone();
+        drop(tokens);
+
+        // Check if expired
+        if token_data.expires_at < Utc::now() {
+            return Err(anyhow!("Refresh token expired"));
+        }
+
+        // Get user
+        let users = self.users.read().await;
+        let user = users
+            .get(&token_data.user_id)
+            .ok_or_else(|| anyhow!("User not found"))?
+            .clone();
+
+        // Generate new access token
+        self.generate_access_token(&user)
+    }
+
+    /// Validate JWT token
+    pub fn validate_token(&self, token: &str) -> Result<Claims> {
+        let token_data = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(self.jwt_secret.as_ref()),
+            &Validation::default(),
+        )?;
+
+        Ok(token_data.claims)
+    }
+
+    /// Issue a token for an automation client rather than a user
+    pub fn issue_service_token(&self, subject: &str, scopes: Vec<ApiScope>, ttl: Duration) -> Result<String> {
+        if scopes.is_empty() {
+            return Err(anyhow!("Service tokens need at least one scope"));
+        }
+        let now = Utc::now();
+        let claims = Claims {
+            sub: subject.to_string(),
+            username: subject.to_string(),
+            role: UserRole::User,
+            enterprise_id: None,
+            exp: (now + ttl).timestamp(),
+            iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            scopes,
+        };
+        Ok(encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.jwt_secret.as_ref()),
+        )?)
+    }
+
+    /// Generate access token
+    fn generate_access_token(&self, user: &User) -> Result<String> {
+        let now = Utc::now();
+        let claims = Claims {
+            sub: user.id.clone(),
+            username: user.username.clone(),
+            role: user.role.clone(),
+            enterprise_id: user.enterprise_id.clone(),
+            exp: (now + Duration::minutes(15)).timestamp(), // 15 minutes
+            iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            scopes: Vec::new(),
+        };
+
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.jwt_secret.as_ref()),
+        )?;
+
+        Ok(token)
+    }
+
+    /// Generate refresh token. The token is `<id>.<secret>`; the ID selects
+    /// the stored hash so refreshing verifies a single hash.
+    async fn generate_refresh_token(&self, user_id: &str) -> Result<String> {
+        let token_id = Uuid::new_v4().simple().to_string();
+        let token_string = format!("{}.{}", token_id, Uuid::new_v4().simple());
+
+        // Hash the refresh token
+        let salt = SaltString::generate(&mut OsRng);
+        let token_hash = self
+            .argon2
+            .hash_password(token_string.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Failed to hash refresh token: {}", e))?
+            .to_string();
+
+        let refresh_token = RefreshToken {
+            id: token_id.clone(),
+            user_id: user_id.to_string(),
+            token_hash,
+            expires_at: Utc::now() + Duration::days(30), // 30 days
+            created_at: Utc::now(),
+        };
+
+        // Store refresh token
+        let mut tokens = self.refresh_tokens.write().await;
+        tokens.insert(token_id, refresh_token);
+
+        Ok(token_string)
+    }
+
+    /// Verify refresh token
+    fn verify_refresh_token(&self, token: &str, stored: &RefreshToken) -> bool {
+        let parsed_hash = PasswordHash::new(&stored.token_hash);
+        match parsed_hash {
+            Ok(hash) => self.argon2.verify_password(token.as_bytes(), &hash).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// Revoke refresh token
+    pub async fn revoke_refresh_token(&self, token: String) -> Result<()> {
+        let Some((token_id, _)) = token.split_once('.') else {
+            return Ok(());
+        };
+        let mut tokens = self.refresh_tokens.write().await;
+        if tokens.get(token_id).is_some_and(|t| self.verify_refresh_token(&token, t)) {
+            tokens.remove(token_id);
+        }
+        Ok(())
+    }
+
+    /// Get user by ID
+    pub async fn get_user(&self, user_id: &str) -> Option<User> {
+        let users = self.users.read().await;
+        users.get(user_id).cloned()
+    }
+
+    /// Create enterprise user
+    pub async fn create_enterprise_user(
+        &self,
+        username: String,
+        email: String,
+        password: String,
+        enterprise_id: String,
+    ) -> Result<User> {
+        // Similar to register but with enterprise role
+        if username.len() < 3 {
+            return Err(anyhow!("Username must be at least 3 characters"));
+        }
+        if !email.contains('@') {
+            return Err(anyhow!("Invalid email format"));
+        }
+        if password.len() < 8 {
+            return Err(anyhow!("Password must be at least 8 characters"));
+        }
+
+        let users = self.users.read().await;
+        if users.values().any(|u| u.username == username || u.email == email) {
+            return Err(anyhow!("User already exists"));
+        }
+        drop(users);
+
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = self
+            .argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Failed to hash password: {}", e))?
+            .to_string();
+
+        let user = User {
+            id: Uuid::new_v4().to_string(),
+            username: username.clone(),
+            email,
+            role: UserRole::Enterprise,
+            created_at: Utc::now(),
+            last_login: None,
+            enterprise_id: Some(enterprise_id),
+        };
+
+        // Store user and password hash
+        let mut users = self.users.write().await;
+        users.insert(user.id.clone(), user.clone());
+        drop(users);
+
+        // Store password hash
+        let mut hashes = self.password_hashes.write().await;
+        hashes.insert(user.id.clone(), password_hash);
+
+        Ok(user)
+    }
+
+
+    /// Promote user to admin
+    pub async fn promote_to_admin(&self, user_id: &str) -> Result<()> {
+        let mut users = self.users.write().await;
+        if let Some(user) = users.get_mut(user_id) {
+            user.role = UserRole::Admin;
+            Ok(())
+        } else {
+            Err(anyhow!("User not found"))
+        }
+    }
+}
+
+#[async_trait]
+impl UserDirectory for AuthManager {
+    async fn export_users(&self) -> Result<Vec<UserRecord>> {
+        let users = self.users.read().await;
+        let hashes = self.password_hashes.read().await;
+        Ok(users
+            .values()
+            .map(|user| UserRecord {
+                id: user.id.clone(),
+                username: user.username.clone(),
+                email: user.email.clone(),
+                role: format!("{:?}", user.role),
+                created_at: user.created_at,
+                last_login: user.last_login,
+                enterprise_id: user.enterprise_id.clone(),
+                password_hash: hashes.get(&user.id).cloned(),
+            })
+            .collect())
+    }
+
+    async fn import_users(&self, records: Vec<UserRecord>) -> Result<()> {
+        let mut users = HashMap::new();
+        let mut hashes = HashMap::new();
+        for record in records {
+            let role = match record.role.as_str() {
+                "User" => UserRole::User,
+                "Admin" => UserRole::Admin,
+                "Enterprise" => UserRole::Enterprise,
+                other => return Err(anyhow!("Unknown user role: {}", other)),
+            };
+            if let Some(hash) = record.password_hash {
+                hashes.insert(record.id.clone(), hash);
+            }
+            users.insert(record.id.clone(), User {
+                id: record.id,
+                username: record.username,
+                email: record.email,
+                role,
+                created_at: record.created_at,
+                last_login: record.last_login,
+                enterprise_id: record.enterprise_id,
+            });
+        }
+
+        // Restored users must sign in again
+        self.refresh_tokens.write().await.clear();
+        *self.password_hashes.write().await = hashes;
+        *self.users.write().await = users;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// API Keys
+// ============================================================================
+
+/// A stored API key. Only a SHA-256 hash of the secret is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    /// Requests per minute; the server default applies when absent
+    pub rate_limit_per_minute: Option<u32>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+    secret_hash: String,
+}
+
+impl ApiKey {
+    /// Whether the key has passed its expiry
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+}
+
+/// Options for a new API key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NewApiKey {
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<u32>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// API keys, optionally persisted to a JSON file
+pub struct ApiKeyStore {
+    keys: RwLock<HashMap<String, ApiKey>>,
+    path: Option<PathBuf>,
+}
+
+impl Default for ApiKeyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiKeyStore {
+    /// In-memory store
+    pub fn new() -> Self {
+        Self {
+            keys: RwLock::new(HashMap::new()),
+            path: None,
+        }
+    }
+
+    /// Store persisted at `path`, loading existing keys
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let keys: Vec<ApiKey> = if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            let content = tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("Failed to read API keys from {}", path.display()))?;
+            serde_json::from_str(&content).context("Failed to parse API key file")?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            keys: RwLock::new(keys.into_iter().map(|k| (k.id.clone(), k)).collect()),
+            path: Some(path),
+        })
+    }
+
+    /// Create a key, returning it with the secret token. The token is not
+    /// stored and cannot be recovered later.
+    pub async fn create(&self, options: NewApiKey) -> Result<(ApiKey, String)> {
+        if options.scopes.is_empty() {
+            return Err(anyhow!("API keys need at least one scope"));
+        }
+        if options.rate_limit_per_minute == Some(0) {
+            return Err(anyhow!("Rate limit must be at least one request per minute"));
+        }
+
+        let mut id_bytes = [0u8; 6];
+        let mut secret_bytes = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut id_bytes);
+        rand::thread_rng().fill_bytes(&mut secret_bytes);
+        let id = hex::encode(id_bytes);
+        let secret = hex::encode(secret_bytes);
+
+        let key = ApiKey {
+            id: id.clone(),
+            name: options.name,
+            scopes: options.scopes,
+            rate_limit_per_minute: options.rate_limit_per_minute,
+            created_at: Utc::now(),
+            expires_at: options.expires_at,
+            last_used_at: None,
+            secret_hash: hash_secret(&secret),
+        };
+
+        let mut keys = self.keys.write().await;
+        keys.insert(id.clone(), key.clone());
+        self.save(&keys).await?;
+        info!("Created API key {} ({})", id, key.name);
+        Ok((key, format!("{}{}_{}", API_KEY_PREFIX, id, secret)))
+    }
+
+    /// Check a presented token, returning its key when valid and unexpired
+    pub async fn verify(&self, token: &str) -> Option<ApiKey> {
+        let (id, secret) = token.strip_prefix(API_KEY_PREFIX)?.split_once('_')?;
+        let mut keys = self.keys.write().await;
+        let key = keys.get_mut(id)?;
+        if !constant_time_eq(key.secret_hash.as_bytes(), hash_secret(secret).as_bytes()) || key.is_expired() {
+            return None;
+        }
+        key.last_used_at = Some(Utc::now());
+        Some(key.clone())
+    }
+
+    /// Revoke a key, returning whether it existed
+    pub async fn revoke(&self, id: &str) -> Result<bool> {
+        let mut keys = self.keys.write().await;
+        let removed = keys.remove(id).is_some();
+        if removed {
+            self.save(&keys).await?;
+            info!("Revoked API key {}", id);
+        }
+        Ok(removed)
+    }
+
+    /// All keys, oldest first
+    pub async fn list(&self) -> Vec<ApiKey> {
+        let mut keys: Vec<ApiKey> = self.keys.read().await.values().cloned().collect();
+        keys.sort_by_key(|k| k.created_at);
+        keys
+    }
+
+    /// Whether no keys exist yet
+    pub async fn is_empty(&self) -> bool {
+        self.keys.read().await.is_empty()
+    }
+
+    async fn save(&self, keys: &HashMap<String, ApiKey>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut sorted: Vec<&ApiKey> = keys.values().collect();
+        sorted.sort_by_key(|k| k.created_at);
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_string_pretty(&sorted)?).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(())
+    }
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scope_grants() {
+        assert!(ApiScope::Tabs.grants(ApiScope::ReadOnly));
+        assert!(!ApiScope::Tabs.grants(ApiScope::Proxies));
+        assert!(!ApiScope::ReadOnly.grants(ApiScope::Tabs));
+        assert!(ApiScope::Admin.grants(ApiScope::Proxies));
+        assert_eq!(ApiScope::parse("proxies"), Some(ApiScope::Proxies));
+    }
+
+    #[test]
+    fn test_service_token_scopes() {
+        let auth = AuthManager::new("test-secret".to_string());
+        let token = auth
+            .issue_service_token("ci", vec![ApiScope::Proxies], Duration::minutes(5))
+            .expect("Issue should succeed");
+        let claims = auth.validate_token(&token).expect("Validate should succeed");
+        assert_eq!(claims.sub, "ci");
+        assert_eq!(claims.effective_scopes(), vec![ApiScope::Proxies]);
+
+        let other = AuthManager::new("other-secret".to_string());
+        assert!(other.validate_token(&token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_lookup_and_revoke() {
+        let auth = AuthManager::new("test-secret".to_string());
+        auth.register("alice".to_string(), "alice@example.com".to_string(), "correct horse".to_string())
+            .await
+            .expect("Register should succeed");
+        let (_, refresh) = auth
+            .login("alice".to_string(), "correct horse".to_string())
+            .await
+            .expect("Login should succeed");
+
+        let access = auth
+            .refresh_access_token(refresh.clone())
+            .await
+            .expect("Refresh should succeed");
+        assert_eq!(auth.validate_token(&access).expect("Validate should succeed").username, "alice");
+
+        // A right ID with the wrong secret, or no ID, is rejected
+        let (id, _) = refresh.split_once('.').expect("Token should carry an ID");
+        assert!(auth.refresh_access_token(format!("{}.forged", id)).await.is_err());
+        assert!(auth.refresh_access_token("no-id".to_string()).await.is_err());
+        auth.revoke_refresh_token(format!("{}.forged", id))
+            .await
+            .expect("Revoke should succeed");
+        assert!(auth.refresh_access_token(refresh.clone()).await.is_ok());
+
+        auth.revoke_refresh_token(refresh.clone()).await.expect("Revoke should succeed");
+        assert!(auth.refresh_access_token(refresh).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_lifecycle() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("keys.json");
+        let store = ApiKeyStore::open(&path).await.expect("Open should succeed");
+
+        let (key, token) = store
+            .create(NewApiKey {
+                name: "scraper".to_string(),
+                scopes: vec![ApiScope::Tabs],
+                rate_limit_per_minute: Some(30),
+                expires_at: None,
+            })
+            .await
+            .expect("Create should succeed");
+        assert!(token.starts_with(API_KEY_PREFIX));
+
+        let stored = std::fs::read_to_string(&path).expect("Key file should exist");
+        assert!(!stored.contains(token.rsplit('_').next().expect("token has a secret")));
+
+        // Keys survive a reopen
+        let reopened = ApiKeyStore::open(&path).await.expect("Open should succeed");
+        let verified = reopened.verify(&token).await.expect("Key should verify");
+        assert_eq!(verified.id, key.id);
+        assert_eq!(verified.rate_limit_per_minute, Some(30));
+        assert!(reopened.verify(&format!("{}x", token)).await.is_none());
+
+        assert!(reopened.revoke(&key.id).await.expect("Revoke should succeed"));
+        assert!(reopened.verify(&token).await.is_none());
+        assert!(reopened.is_empty().await);
+    }
+}
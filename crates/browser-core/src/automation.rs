@@ -155,6 +155,11 @@ impl VisualAutomationBuilder {
         self.workflows.values().collect()
     }
 
+    /// Insert a workflow under its own ID, replacing any existing one
+    pub fn upsert_workflow(&mut self, workflow: Workflow) {
+        self.workflows.insert(workflow.id.clone(), workflow);
+    }
+
     /// Delete workflow
     pub fn delete_workflow(&mut self, id: &str) -> bool {
         self.workflows.remove(id).is_some()
@@ -5,12 +5,17 @@
 //! - Backup scheduling and automation
 //! - Data compression and encryption
 //! - Import/Export with various formats
+//! - Reading of backups written before the versioned encrypted container
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
 use crate::proxy::ProxySettings;
+use crate::secrets_vault::{RedactSecrets, SecretsVault};
+use crate::secure_container::{self, KdfParams};
 use crate::storage::{Cookie, HistoryEntry, Bookmark};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +58,10 @@ pub struct BackupOptions {
     pub include_bookmarks: bool,
     pub include_local_storage: bool,
     pub password: Option<String>,
+    /// Keep proxy passwords instead of redacting them; vault references are
+    /// resolved when the manager has a vault
+    #[serde(default)]
+    pub include_secrets: bool,
 }
 
 impl Default for BackupOptions {
@@ -65,6 +74,7 @@ impl Default for BackupOptions {
             include_bookmarks: true,
             include_local_storage: true,
             password: None,
+            include_secrets: false,
         }
     }
 }
@@ -88,6 +98,28 @@ pub struct AutoBackupSettings {
     pub max_backups: u32,
     pub include_storage: bool,
     pub backup_path: PathBuf,
+    /// Extra grandfather-father-son tiers kept beyond `max_backups`
+    #[serde(default)]
+    pub gfs: Option<GfsRetention>,
+}
+
+/// Grandfather-father-son retention: keep the newest backup of each of the
+/// last `daily` days, `weekly` ISO weeks and `monthly` months
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GfsRetention {
+    pub daily: u32,
+    pub weekly: u32,
+    pub monthly: u32,
+}
+
+impl Default for GfsRetention {
+    fn default() -> Self {
+        Self {
+            daily: 7,
+            weekly: 4,
+            monthly: 12,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,6 +130,17 @@ pub enum BackupFrequency {
     Monthly,
 }
 
+impl BackupFrequency {
+    /// Time between scheduled backups
+    pub fn interval(&self) -> chrono::Duration {
+        match self {
+            BackupFrequency::Daily => chrono::Duration::days(1),
+            BackupFrequency::Weekly => chrono::Duration::weeks(1),
+            BackupFrequency::Monthly => chrono::Duration::days(30),
+        }
+    }
+}
+
 impl Default for AutoBackupSettings {
     fn default() -> Self {
         Self {
@@ -106,6 +149,7 @@ impl Default for AutoBackupSettings {
             max_backups: 5,
             include_storage: true,
             backup_path: PathBuf::from("./backups"),
+            gfs: None,
         }
     }
 }
@@ -113,6 +157,8 @@ impl Default for AutoBackupSettings {
 /// Represents a BackupManager.
 pub struct BackupManager {
     backup_dir: PathBuf,
+    kdf: KdfParams,
+    secrets: Option<Arc<SecretsVault>>,
 }
 
 impl BackupManager {
@@ -121,25 +167,50 @@ impl BackupManager {
         std::fs::create_dir_all(backup_dir)?;
         Ok(Self {
             backup_dir: backup_dir.to_path_buf(),
+            kdf: KdfParams::default(),
+            secrets: None,
         })
     }
 
+    /// Vault used to resolve secret references when secrets are included
+    pub fn with_secrets_vault(mut self, vault: Arc<SecretsVault>) -> Self {
+        self.secrets = Some(vault);
+        self
+    }
+
+    /// Use custom Argon2id cost parameters for new encrypted backups
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
     /// Creates a new backup.
-    pub async fn create_backup(&self, data: BackupData, options: &BackupOptions) -> Result<BackupInfo> {
+    pub async fn create_backup(&self, mut data: BackupData, options: &BackupOptions) -> Result<BackupInfo> {
+        if let Some(settings) = data.proxy_settings.as_mut() {
+            match (&self.secrets, options.include_secrets) {
+                (Some(vault), true) => vault.resolve_in_place(&mut settings.password).await?,
+                (None, true) => {}
+                (_, false) => {
+                    settings.redact_secrets();
+                }
+            }
+        }
+
         let id = uuid::Uuid::new_v4().to_string();
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
         let filename = format!("backup_{}_{}.vipb", timestamp, &id[..8]);
         let path = self.backup_dir.join(&filename);
 
-        let json = serde_json::to_string_pretty(&data)?;
-        
-        let content = if let Some(password) = &options.password {
-            self.encrypt(&json, password)?
-        } else {
-            json
-        };
+        let json = serde_json::to_vec_pretty(&data)?;
 
-        std::fs::write(&path, &content)?;
+        if let Some(password) = &options.password {
+            let mut reader = json.as_slice();
+            let mut writer = tokio::io::BufWriter::new(tokio::fs::File::create(&path).await?);
+            secure_container::encrypt_stream(&mut reader, &mut writer, password, self.kdf).await?;
+            writer.shutdown().await?;
+        } else {
+            std::fs::write(&path, &json)?;
+        }
 
         let metadata = std::fs::metadata(&path)?;
 
@@ -154,16 +225,36 @@ impl BackupManager {
     }
 
     /// Restores backup.
+    ///
+    /// Reads the versioned encrypted container as well as plain JSON and
+    /// legacy encrypted backups.
     pub async fn restore_backup(&self, path: &Path, password: Option<&str>) -> Result<BackupData> {
-        let content = std::fs::read_to_string(path)?;
-        
+        // Containers are decrypted straight from the file; only the
+        // plaintext is held in memory
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut content = Vec::new();
+        (&mut file)
+            .take(secure_container::CONTAINER_MAGIC.len() as u64)
+            .read_to_end(&mut content)
+            .await?;
+        if secure_container::is_container(&content) {
+            let pwd = password.ok_or_else(|| anyhow!("Backup is encrypted; a password is required"))?;
+            file.seek(std::io::SeekFrom::Start(0)).await?;
+            let mut reader = tokio::io::BufReader::new(file);
+            let mut json = Vec::new();
+            secure_container::decrypt_stream(&mut reader, &mut json, pwd).await?;
+            return Ok(serde_json::from_slice(&json)?);
+        }
+
+        file.read_to_end(&mut content).await?;
         let json = if let Some(pwd) = password {
-            self.decrypt(&content, pwd)?
+            let text = String::from_utf8(content).map_err(|e| anyhow!("Invalid backup file: {}", e))?;
+            self.decrypt_legacy(text.trim(), pwd)?.into_bytes()
         } else {
             content
         };
 
-        let data: BackupData = serde_json::from_str(&json)?;
+        let data: BackupData = serde_json::from_slice(&json)?;
         Ok(data)
     }
 
@@ -190,9 +281,10 @@ impl BackupManager {
                     .unwrap_or("unknown")
                     .to_string();
 
-                // Check if encrypted by trying to parse as JSON
-                let content = std::fs::read_to_string(&path).unwrap_or_default();
-                let is_encrypted = serde_json::from_str::<BackupData>(&content).is_err();
+                // Encrypted if it is a container or, for legacy files, not JSON
+                let content = std::fs::read(&path).unwrap_or_default();
+                let is_encrypted = secure_container::is_container(&content)
+                    || serde_json::from_slice::<BackupData>(&content).is_err();
 
                 backups.push(BackupInfo {
                     id,
@@ -213,6 +305,11 @@ impl BackupManager {
         Ok(backups)
     }
 
+    /// Directory backups are written to
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_dir
+    }
+
     /// Removes the backup.
     pub async fn delete_backup(&self, id: &str) -> Result<()> {
         let backups = self.list_backups().await?;
@@ -240,48 +337,16 @@ impl BackupManager {
         Ok(deleted)
     }
 
-    fn encrypt(&self, data: &str, password: &str) -> Result<String> {
-        use aes_gcm::{
-            aead::{Aead, KeyInit, OsRng},
-            Aes256Gcm, Nonce,
-        };
-        use aes_gcm::aead::generic_array::GenericArray;
-
-        // Derive a 256-bit key from password using simple padding (in production, use PBKDF2)
-        let mut key_bytes = [0u8; 32];
-        let password_bytes = password.as_bytes();
-        for (i, byte) in password_bytes.iter().enumerate().take(32) {
-            key_bytes[i] = *byte;
-        }
-
-        let key = GenericArray::from_slice(&key_bytes);
-        let cipher = Aes256Gcm::new(key);
-
-        // Generate random nonce
-        let mut nonce_bytes = [0u8; 12];
-        use rand::RngCore;
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let ciphertext = cipher
-            .encrypt(nonce, data.as_bytes())
-            .map_err(|e| anyhow!("Encryption failed: {}", e))?;
-
-        // Combine nonce + ciphertext and encode as base64
-        let mut combined = nonce_bytes.to_vec();
-        combined.extend(ciphertext);
-
-        Ok(base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &combined))
-    }
-
-    fn decrypt(&self, encrypted: &str, password: &str) -> Result<String> {
+    /// Decrypt a backup written before the versioned container: base64 of
+    /// nonce + ciphertext, keyed with the zero-padded password bytes
+    fn decrypt_legacy(&self, encrypted: &str, password: &str) -> Result<String> {
         use aes_gcm::{
             aead::{Aead, KeyInit},
             Aes256Gcm, Nonce,
         };
         use aes_gcm::aead::generic_array::GenericArray;
 
-        // Derive key from password
+        // Legacy key: password bytes padded to 32 bytes
         let mut key_bytes = [0u8; 32];
         let password_bytes = password.as_bytes();
         for (i, byte) in password_bytes.iter().enumerate().take(32) {
@@ -309,3 +374,110 @@ impl BackupManager {
         String::from_utf8(plaintext).map_err(|e| anyhow!("Invalid UTF-8: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_data() -> BackupData {
+        BackupData {
+            version: "1.0".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            proxy_settings: None,
+            browser_config: Some(BrowserConfig {
+                language: Some("en-US".to_string()),
+                ..Default::default()
+            }),
+            cookies: None,
+            history: None,
+            bookmarks: None,
+            local_storage: None,
+        }
+    }
+
+    /// Writes a backup the way releases before the container format did
+    fn encrypt_legacy(data: &str, password: &str) -> String {
+        use aes_gcm::aead::{Aead, KeyInit};
+        use aes_gcm::{Aes256Gcm, Nonce};
+
+        let mut key_bytes = [0u8; 32];
+        for (i, byte) in password.as_bytes().iter().enumerate().take(32) {
+            key_bytes[i] = *byte;
+        }
+        let cipher = Aes256Gcm::new_from_slice(&key_bytes).expect("Key should be valid");
+        let nonce_bytes = [3u8; 12];
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), data.as_bytes())
+            .expect("Encryption should succeed");
+        let mut combined = nonce_bytes.to_vec();
+        combined.extend(ciphertext);
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &combined)
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_backup_uses_container() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let manager = BackupManager::new(temp_dir.path())
+            .expect("Backup manager should be created")
+            .with_kdf_params(KdfParams { m_cost: 1024, t_cost: 1, p_cost: 1 });
+        let options = BackupOptions {
+            password: Some("s3cret".to_string()),
+            ..Default::default()
+        };
+
+        let info = manager.create_backup(sample_data(), &options).await.expect("Backup should succeed");
+        let raw = std::fs::read(&info.path).expect("Operation should succeed in test");
+        assert!(secure_container::is_container(&raw));
+        assert!(manager.list_backups().await.expect("List should succeed")[0].is_encrypted);
+
+        assert!(manager.restore_backup(&info.path, None).await.is_err());
+        assert!(manager.restore_backup(&info.path, Some("wrong")).await.is_err());
+        let restored = manager.restore_backup(&info.path, Some("s3cret")).await.expect("Restore should succeed");
+        assert_eq!(restored.browser_config.and_then(|c| c.language), Some("en-US".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_backup_redacts_secrets_unless_requested() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let vault = Arc::new(SecretsVault::new(Arc::new(
+            crate::secrets_vault::EncryptedFileBackend::new(temp_dir.path().join("vault.bin"), "master")
+                .with_kdf_params(KdfParams { m_cost: 1024, t_cost: 1, p_cost: 1 }),
+        )));
+        vault.put("proxy/default", "hunter2").await.expect("Put should succeed");
+        let manager = BackupManager::new(&temp_dir.path().join("backups"))
+            .expect("Backup manager should be created")
+            .with_secrets_vault(vault);
+
+        let mut data = sample_data();
+        data.proxy_settings = Some(ProxySettings { password: Some("plaintext".to_string()), ..Default::default() });
+        let info = manager.create_backup(data.clone(), &BackupOptions::default()).await.expect("Backup should succeed");
+        let restored = manager.restore_backup(&info.path, None).await.expect("Restore should succeed");
+        assert_eq!(restored.proxy_settings.and_then(|p| p.password).as_deref(), Some(crate::secrets_vault::REDACTED_SECRET));
+
+        data.proxy_settings = Some(ProxySettings { password: Some("secret://proxy/default".to_string()), ..Default::default() });
+        let options = BackupOptions { include_secrets: true, ..Default::default() };
+        let info = manager.create_backup(data, &options).await.expect("Backup should succeed");
+        let restored = manager.restore_backup(&info.path, None).await.expect("Restore should succeed");
+        assert_eq!(restored.proxy_settings.and_then(|p| p.password).as_deref(), Some("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_legacy_backups() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let manager = BackupManager::new(temp_dir.path()).expect("Backup manager should be created");
+        let json = serde_json::to_string_pretty(&sample_data()).expect("Serialization should succeed");
+
+        let encrypted_path = temp_dir.path().join("backup_20240101_000000_legacy01.vipb");
+        std::fs::write(&encrypted_path, encrypt_legacy(&json, "old-password")).expect("Operation should succeed in test");
+        let restored = manager
+            .restore_backup(&encrypted_path, Some("old-password"))
+            .await
+            .expect("Legacy restore should succeed");
+        assert_eq!(restored.version, "1.0");
+
+        let plain_path = temp_dir.path().join("backup_20240101_000000_legacy02.vipb");
+        std::fs::write(&plain_path, &json).expect("Operation should succeed in test");
+        assert!(manager.restore_backup(&plain_path, None).await.is_ok());
+    }
+}
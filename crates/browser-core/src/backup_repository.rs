@@ -0,0 +1,561 @@
+//! Backup Repository Module
+//!
+//! Incremental, deduplicated backups stored in a local directory:
+//! - Each section of `BackupData` is split into content-defined chunks
+//! - Chunks are stored once under their SHA-256 and shared between snapshots
+//! - Snapshot manifests reference the chunks that make up each section
+//! - Point-in-time restore, integrity verification, pruning and garbage
+//!   collection of unreferenced chunks
+//!
+//! Layout:
+//! ```text
+//! <root>/chunks/<hh>/<sha256>
+//! <root>/snapshots/<snapshot id>.json
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::backup::BackupData;
+
+/// Version of the snapshot manifest format
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const CHUNKS_DIR: &str = "chunks";
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+const MIN_CHUNK_SIZE: usize = 2 * 1024;
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+/// Cut-point mask giving an average chunk size of roughly 8 KiB
+const CHUNK_MASK: u64 = (1 << 13) - 1;
+
+/// Gear hash table for content-defined chunking, generated with splitmix64
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Reference to a stored chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Manifest describing one snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    /// `BackupData::version` of the captured data
+    pub data_version: String,
+    /// Chunks per `BackupData` field, in order
+    pub sections: BTreeMap<String, Vec<ChunkRef>>,
+    pub total_bytes: u64,
+}
+
+impl SnapshotManifest {
+    /// All chunk hashes referenced by this snapshot
+    pub fn chunk_hashes(&self) -> impl Iterator<Item = &str> {
+        self.sections.values().flatten().map(|c| c.hash.as_str())
+    }
+}
+
+/// Statistics for a newly created snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotStats {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub total_chunks: usize,
+    pub new_chunks: usize,
+    pub total_bytes: u64,
+    pub new_bytes: u64,
+}
+
+/// Result of verifying snapshots against the chunk store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub snapshots_checked: usize,
+    pub chunks_checked: usize,
+    /// (snapshot id, chunk hash) pairs whose chunk is missing
+    pub missing: Vec<(String, String)>,
+    /// Chunks whose content no longer matches their hash
+    pub corrupt: Vec<String>,
+}
+
+impl VerifyReport {
+    /// Whether every referenced chunk is present and intact
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+}
+
+/// Which snapshots `prune` keeps; a policy with no rules keeps everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrunePolicy {
+    /// Keep the newest N snapshots
+    pub keep_last: Option<usize>,
+    /// Keep snapshots created at or after this time
+    pub keep_since: Option<DateTime<Utc>>,
+}
+
+/// Result of pruning snapshots and collecting garbage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub removed_snapshots: Vec<String>,
+    pub removed_chunks: usize,
+    pub freed_bytes: u64,
+}
+
+/// Content-addressed snapshot store in a local directory
+pub struct BackupRepository {
+    root: PathBuf,
+    /// Serialises writers so garbage collection never races a new snapshot
+    write_lock: Mutex<()>,
+}
+
+impl BackupRepository {
+    /// Open (creating if needed) a repository at `root`
+    pub fn open(root: &Path) -> Result<Self> {
+        std::fs::create_dir_all(root.join(CHUNKS_DIR))?;
+        std::fs::create_dir_all(root.join(SNAPSHOTS_DIR))?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// Repository root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Store `data` as a new snapshot, writing only chunks not already present
+    pub async fn create_snapshot(&self, data: &BackupData) -> Result<SnapshotStats> {
+        let _guard = self.write_lock.lock().await;
+
+        let value = serde_json::to_value(data)?;
+        let fields = value
+            .as_object()
+            .ok_or_else(|| anyhow!("Backup data must serialize to an object"))?;
+
+        let mut sections = BTreeMap::new();
+        let mut total_chunks = 0;
+        let mut new_chunks = 0;
+        let mut total_bytes = 0u64;
+        let mut new_bytes = 0u64;
+
+        for (name, field) in fields {
+            // Version and timestamp live in the manifest itself
+            if name == "version" || name == "timestamp" || field.is_null() {
+                continue;
+            }
+            let bytes = serde_json::to_vec(field)?;
+            let mut refs = Vec::new();
+            for chunk in split_chunks(&bytes) {
+                let hash = hex::encode(Sha256::digest(chunk));
+                if self.write_chunk(&hash, chunk)? {
+                    new_chunks += 1;
+                    new_bytes += chunk.len() as u64;
+                }
+                total_chunks += 1;
+                total_bytes += chunk.len() as u64;
+                refs.push(ChunkRef {
+                    hash,
+                    size: chunk.len() as u64,
+                });
+            }
+            sections.insert(name.clone(), refs);
+        }
+
+        let created_at = Utc::now();
+        let id = format!(
+            "snap_{}_{}",
+            created_at.format("%Y%m%d_%H%M%S"),
+            &uuid::Uuid::new_v4().to_string()[..8]
+        );
+        let manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            id: id.clone(),
+            created_at,
+            data_version: data.version.clone(),
+            sections,
+            total_bytes,
+        };
+        write_atomic(&self.snapshot_path(&id)?, &serde_json::to_vec_pretty(&manifest)?)?;
+
+        info!(
+            "Snapshot {} created: {} chunks ({} new, {} bytes new)",
+            id, total_chunks, new_chunks, new_bytes
+        );
+
+        Ok(SnapshotStats {
+            id,
+            created_at,
+            total_chunks,
+            new_chunks,
+            total_bytes,
+            new_bytes,
+        })
+    }
+
+    /// List snapshots, newest first
+    pub async fn list_snapshots(&self) -> Result<Vec<SnapshotManifest>> {
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(self.root.join(SNAPSHOTS_DIR))? {
+            let path = entry?.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                snapshots.push(read_manifest(&path)?);
+            }
+        }
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+        Ok(snapshots)
+    }
+
+    /// Load a snapshot manifest
+    pub async fn get_snapshot(&self, id: &str) -> Result<SnapshotManifest> {
+        let path = self.snapshot_path(id)?;
+        if !path.exists() {
+            return Err(anyhow!("Snapshot not found: {}", id));
+        }
+        read_manifest(&path)
+    }
+
+    /// Reassemble the backup data captured by a snapshot
+    pub async fn restore_snapshot(&self, id: &str) -> Result<BackupData> {
+        let manifest = self.get_snapshot(id).await?;
+        self.assemble(&manifest)
+    }
+
+    /// Restore the newest snapshot taken at or before `at`
+    pub async fn restore_at(&self, at: DateTime<Utc>) -> Result<BackupData> {
+        let snapshot = self
+            .list_snapshots()
+            .await?
+            .into_iter()
+            .find(|s| s.created_at <= at)
+            .ok_or_else(|| anyhow!("No snapshot exists at or before {}", at.to_rfc3339()))?;
+        self.assemble(&snapshot)
+    }
+
+    /// Check that every referenced chunk exists and matches its hash
+    pub async fn verify(&self) -> Result<VerifyReport> {
+        let mut report = VerifyReport::default();
+        let mut checked: HashSet<String> = HashSet::new();
+        let mut corrupt: HashSet<String> = HashSet::new();
+
+        for snapshot in self.list_snapshots().await? {
+            report.snapshots_checked += 1;
+            for hash in snapshot.chunk_hashes() {
+                let Ok(path) = self.chunk_path(hash) else {
+                    warn!("Snapshot {} references invalid chunk hash {:?}", snapshot.id, hash);
+                    corrupt.insert(hash.to_string());
+                    continue;
+                };
+                if !path.exists() {
+                    report.missing.push((snapshot.id.clone(), hash.to_string()));
+                    continue;
+                }
+                if checked.insert(hash.to_string()) {
+                    let content = std::fs::read(&path)?;
+                    if hex::encode(Sha256::digest(&content)) != hash {
+                        warn!("Chunk {} is corrupt", hash);
+                        corrupt.insert(hash.to_string());
+                    }
+                }
+            }
+        }
+
+        report.chunks_checked = checked.len();
+        report.corrupt = corrupt.into_iter().collect();
+        report.corrupt.sort();
+        Ok(report)
+    }
+
+    /// Delete a single snapshot; its chunks are reclaimed by `gc`
+    pub async fn delete_snapshot(&self, id: &str) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let path = self.snapshot_path(id)?;
+        if !path.exists() {
+            return Err(anyhow!("Snapshot not found: {}", id));
+        }
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    /// Remove snapshots outside the policy, then collect unreferenced chunks
+    pub async fn prune(&self, policy: &PrunePolicy) -> Result<PruneReport> {
+        let snapshots = self.list_snapshots().await?;
+        let mut removed = Vec::new();
+        {
+            let _guard = self.write_lock.lock().await;
+            for (index, snapshot) in snapshots.iter().enumerate() {
+                let by_count = policy.keep_last.map(|n| index < n).unwrap_or(false);
+                let by_age = policy.keep_since.map(|t| snapshot.created_at >= t).unwrap_or(false);
+                let no_rules = policy.keep_last.is_none() && policy.keep_since.is_none();
+                if by_count || by_age || no_rules {
+                    continue;
+                }
+                std::fs::remove_file(self.snapshot_path(&snapshot.id)?)?;
+                removed.push(snapshot.id.clone());
+            }
+        }
+
+        let mut report = self.gc().await?;
+        report.removed_snapshots = removed;
+        Ok(report)
+    }
+
+    /// Delete chunks no snapshot references
+    pub async fn gc(&self) -> Result<PruneReport> {
+        let _guard = self.write_lock.lock().await;
+
+        let mut referenced = HashSet::new();
+        for entry in std::fs::read_dir(self.root.join(SNAPSHOTS_DIR))? {
+            let path = entry?.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                let manifest = read_manifest(&path)?;
+                referenced.extend(manifest.chunk_hashes().map(String::from));
+            }
+        }
+
+        let mut report = PruneReport::default();
+        for prefix in std::fs::read_dir(self.root.join(CHUNKS_DIR))? {
+            let prefix = prefix?.path();
+            if !prefix.is_dir() {
+                continue;
+            }
+            for chunk in std::fs::read_dir(&prefix)? {
+                let chunk = chunk?;
+                let name = chunk.file_name().to_string_lossy().to_string();
+                if referenced.contains(&name) {
+                    continue;
+                }
+                report.freed_bytes += chunk.metadata()?.len();
+                std::fs::remove_file(chunk.path())?;
+                report.removed_chunks += 1;
+            }
+        }
+
+        info!(
+            "Backup repository GC removed {} chunks ({} bytes)",
+            report.removed_chunks, report.freed_bytes
+        );
+        Ok(report)
+    }
+
+    fn assemble(&self, manifest: &SnapshotManifest) -> Result<BackupData> {
+        let mut fields = serde_json::Map::new();
+        fields.insert("version".into(), manifest.data_version.clone().into());
+        fields.insert("timestamp".into(), manifest.created_at.to_rfc3339().into());
+
+        for (name, chunks) in &manifest.sections {
+            let mut bytes = Vec::new();
+            for chunk in chunks {
+                bytes.extend(self.read_chunk(&chunk.hash)?);
+            }
+            let value: serde_json::Value = serde_json::from_slice(&bytes)
+                .with_context(|| format!("Snapshot {} section {} is invalid", manifest.id, name))?;
+            fields.insert(name.clone(), value);
+        }
+
+        Ok(serde_json::from_value(serde_json::Value::Object(fields))?)
+    }
+
+    /// Path of a chunk; the hash must be 64 lowercase hex digits
+    fn chunk_path(&self, hash: &str) -> Result<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(anyhow!("Invalid chunk hash: {:?}", hash));
+        }
+        Ok(self.root.join(CHUNKS_DIR).join(&hash[..2]).join(hash))
+    }
+
+    /// Path of a snapshot manifest; IDs are limited to ASCII letters,
+    /// digits, `-` and `_` so they cannot leave the snapshots directory
+    fn snapshot_path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty()
+            || id.len() > 128
+            || !id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(anyhow!("Invalid snapshot ID: {:?}", id));
+        }
+        Ok(self.root.join(SNAPSHOTS_DIR).join(format!("{}.json", id)))
+    }
+
+    /// Store a chunk unless already present; returns whether it was written
+    fn write_chunk(&self, hash: &str, content: &[u8]) -> Result<bool> {
+        let path = self.chunk_path(hash)?;
+        if path.exists() {
+            return Ok(false);
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(&path, content)?;
+        Ok(true)
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Vec<u8>> {
+        let content = std::fs::read(self.chunk_path(hash)?)
+            .with_context(|| format!("Chunk {} is missing", hash))?;
+        if hex::encode(Sha256::digest(&content)) != hash {
+            return Err(anyhow!("Chunk {} failed integrity check", hash));
+        }
+        Ok(content)
+    }
+}
+
+/// Split data at content-defined boundaries using a gear rolling hash, so an
+/// insertion only changes the chunks around it
+fn split_chunks(data: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut hash: u64 = 0;
+
+    for (i, byte) in data.iter().enumerate() {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+        let len = i + 1 - start;
+        if (len >= MIN_CHUNK_SIZE && hash & CHUNK_MASK == 0) || len >= MAX_CHUNK_SIZE {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            hash = 0;
+        }
+    }
+    if start < data.len() || data.is_empty() {
+        chunks.push(&data[start..]);
+    }
+    chunks
+}
+
+fn read_manifest(path: &Path) -> Result<SnapshotManifest> {
+    let content = std::fs::read(path)?;
+    let manifest: SnapshotManifest = serde_json::from_slice(&content)
+        .with_context(|| format!("Invalid snapshot manifest {}", path.display()))?;
+    if manifest.format_version > SNAPSHOT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Snapshot {} uses unsupported format version {}",
+            manifest.id,
+            manifest.format_version
+        ));
+    }
+    Ok(manifest)
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::HistoryEntry;
+
+    fn data_with_history(count: i64) -> BackupData {
+        let history = (0..count)
+            .map(|i| HistoryEntry {
+                id: i,
+                url: format!("https://example.com/page/{}", i),
+                title: Some(format!("Page number {} with a reasonably long title", i)),
+                visit_count: 1,
+                last_visit: 1_700_000_000 + i,
+            })
+            .collect();
+        BackupData {
+            version: "1.0.0".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            proxy_settings: None,
+            browser_config: None,
+            cookies: Some(Vec::new()),
+            history: Some(history),
+            bookmarks: None,
+            local_storage: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_share_chunks_and_restore() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let repo = BackupRepository::open(dir.path()).expect("open should succeed");
+
+        let first = repo.create_snapshot(&data_with_history(2000)).await.expect("snapshot should succeed");
+        assert!(first.total_chunks > 4);
+        assert_eq!(first.new_chunks, first.total_chunks);
+
+        let second = repo.create_snapshot(&data_with_history(2010)).await.expect("snapshot should succeed");
+        assert!(second.new_chunks < 4, "appending should only add a few chunks, got {}", second.new_chunks);
+
+        let restored = repo.restore_snapshot(&first.id).await.expect("restore should succeed");
+        assert_eq!(restored.history.expect("history should be restored").len(), 2000);
+        assert!(restored.bookmarks.is_none());
+
+        let latest = repo.restore_at(Utc::now()).await.expect("restore should succeed");
+        assert_eq!(latest.history.expect("history should be restored").len(), 2010);
+        assert!(repo.restore_at(first.created_at - chrono::Duration::days(1)).await.is_err());
+
+        assert!(repo.verify().await.expect("verify should succeed").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_prune_and_gc() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let repo = BackupRepository::open(dir.path()).expect("open should succeed");
+
+        let old = repo.create_snapshot(&data_with_history(500)).await.expect("snapshot should succeed");
+        let new = repo.create_snapshot(&data_with_history(50)).await.expect("snapshot should succeed");
+
+        let report = repo
+            .prune(&PrunePolicy { keep_last: Some(1), keep_since: None })
+            .await
+            .expect("prune should succeed");
+        assert_eq!(report.removed_snapshots, vec![old.id.clone()]);
+        assert!(report.removed_chunks > 0);
+        assert!(repo.restore_snapshot(&new.id).await.is_ok());
+
+        // Corrupt a chunk of the remaining snapshot
+        let manifest = repo.get_snapshot(&new.id).await.expect("snapshot should exist");
+        let hash = manifest.chunk_hashes().next().expect("snapshot should have chunks").to_string();
+        std::fs::write(repo.chunk_path(&hash).expect("hash should be valid"), b"tampered").expect("write should succeed");
+
+        let verify = repo.verify().await.expect("verify should succeed");
+        assert_eq!(verify.corrupt, vec![hash]);
+        assert!(repo.restore_snapshot(&new.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ids_and_hashes_cannot_escape_repository() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let repo = BackupRepository::open(&dir.path().join("repo")).expect("open should succeed");
+        let outside = dir.path().join("x.json");
+        std::fs::write(&outside, b"{}").expect("write should succeed");
+
+        for id in ["../../x", "../x", "", "a/b", "snap\\x"] {
+            assert!(repo.delete_snapshot(id).await.is_err());
+            assert!(repo.get_snapshot(id).await.is_err());
+        }
+        assert!(outside.exists());
+
+        for hash in ["é", "ab", "../../../../etc/passwd", &"A".repeat(64)] {
+            assert!(repo.chunk_path(hash).is_err());
+        }
+    }
+}
@@ -0,0 +1,658 @@
+//! Backup Scheduler Module
+//!
+//! Runs `AutoBackupSettings` in the background:
+//! - Takes a backup whenever the configured frequency elapses
+//! - Skips runs whose content hash matches the previous backup
+//! - Applies `max_backups` retention plus optional grandfather-father-son tiers
+//!   to its own backups; manual backups in the same directory are left alone
+//! - Runs one backup at a time, so `run_now` waits for a scheduled run
+//! - Persists its last run so schedules missed while stopped catch up on start
+//! - Reports every run through a broadcast event channel
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::backup::{
+    AutoBackupSettings, BackupData, BackupInfo, BackupManager, BackupOptions, GfsRetention,
+    LocalStorageEntry,
+};
+use crate::event_stream::{BrowserEvent, EventBus};
+use crate::storage::{ExportOptions, StorageEngine};
+
+/// File in the backup directory recording the scheduler's last run
+pub const SCHEDULER_STATE_FILE: &str = ".auto_backup_state.json";
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Supplies the data captured by each scheduled backup
+#[async_trait]
+pub trait BackupSource: Send + Sync {
+    /// Collect the data selected by `options`; collections should be returned
+    /// in a stable order so unchanged data hashes identically
+    async fn collect(&self, options: &BackupOptions) -> Result<BackupData>;
+}
+
+#[async_trait]
+impl BackupSource for StorageEngine {
+    async fn collect(&self, options: &BackupOptions) -> Result<BackupData> {
+        let export = self
+            .export_with_options(&ExportOptions {
+                export_cookies: options.include_cookies,
+                export_history: options.include_history,
+                export_bookmarks: options.include_bookmarks,
+                export_local_storage: options.include_local_storage,
+            })
+            .await?;
+
+        let mut cookies = export.cookies;
+        cookies.sort_by(|a, b| (&a.domain, &a.name, &a.path).cmp(&(&b.domain, &b.name, &b.path)));
+        let mut history = export.history;
+        history.sort_by(|a, b| a.url.cmp(&b.url));
+        let mut bookmarks = export.bookmarks;
+        bookmarks.sort_by_key(|b| b.id);
+        let mut local_storage: Vec<LocalStorageEntry> = export
+            .local_storage
+            .into_iter()
+            .flat_map(|(origin, entries)| {
+                entries.into_iter().map(move |(key, value)| LocalStorageEntry {
+                    origin: origin.clone(),
+                    key,
+                    value,
+                })
+            })
+            .collect();
+        local_storage.sort_by(|a, b| (&a.origin, &a.key).cmp(&(&b.origin, &b.key)));
+
+        Ok(BackupData {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            proxy_settings: None,
+            browser_config: None,
+            cookies: options.include_cookies.then_some(cookies),
+            history: options.include_history.then_some(history),
+            bookmarks: options.include_bookmarks.then_some(bookmarks),
+            local_storage: options.include_local_storage.then_some(local_storage),
+        })
+    }
+}
+
+/// Persisted record of the scheduler's most recent activity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchedulerState {
+    /// Last run that produced or skipped a backup
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_content_hash: Option<String>,
+    pub last_backup_file: Option<String>,
+    /// Backups written by the scheduler; only these are subject to retention
+    #[serde(default)]
+    pub scheduled_backups: Vec<String>,
+}
+
+/// Result of a scheduler run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BackupEvent {
+    Completed {
+        info: BackupInfo,
+        content_hash: String,
+        /// Backups removed by retention after this run
+        pruned: Vec<String>,
+        catch_up: bool,
+    },
+    Skipped {
+        content_hash: String,
+        catch_up: bool,
+    },
+    Failed {
+        error: String,
+        catch_up: bool,
+    },
+}
+
+/// Outcome of a single backup run
+#[derive(Debug, Clone)]
+pub enum BackupRunOutcome {
+    Created { info: BackupInfo, pruned: Vec<String> },
+    Unchanged { content_hash: String },
+}
+
+/// Background scheduler driving `AutoBackupSettings`
+pub struct BackupScheduler {
+    settings: Arc<RwLock<AutoBackupSettings>>,
+    manager: BackupManager,
+    source: Arc<dyn BackupSource>,
+    options: BackupOptions,
+    state: RwLock<SchedulerState>,
+    state_path: PathBuf,
+    events: broadcast::Sender<BackupEvent>,
+    event_bus: Option<Arc<EventBus>>,
+    shutdown: Notify,
+    run_lock: Mutex<()>,
+    poll_interval: Duration,
+    retry_delay: chrono::Duration,
+}
+
+impl BackupScheduler {
+    /// Create a scheduler writing to `settings.backup_path`
+    pub fn new(settings: AutoBackupSettings, source: Arc<dyn BackupSource>) -> Result<Self> {
+        let manager = BackupManager::new(&settings.backup_path)?;
+        Self::with_manager(settings, manager, source)
+    }
+
+    /// Create a scheduler around an existing backup manager
+    pub fn with_manager(
+        settings: AutoBackupSettings,
+        manager: BackupManager,
+        source: Arc<dyn BackupSource>,
+    ) -> Result<Self> {
+        let state_path = manager.backup_dir().join(SCHEDULER_STATE_FILE);
+        let state = load_state(&state_path)?;
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        Ok(Self {
+            settings: Arc::new(RwLock::new(settings)),
+            manager,
+            source,
+            options: BackupOptions::default(),
+            state: RwLock::new(state),
+            state_path,
+            events,
+            event_bus: None,
+            shutdown: Notify::new(),
+            run_lock: Mutex::new(()),
+            poll_interval: Duration::from_secs(60),
+            retry_delay: chrono::Duration::minutes(15),
+        })
+    }
+
+    /// Options (including the encryption password) used for each backup
+    pub fn with_backup_options(mut self, options: BackupOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// How often the background task re-checks settings and due time
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Wait before retrying after a failed run
+    pub fn with_retry_delay(mut self, delay: chrono::Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Also publish each run's outcome to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Receive an event for every completed, skipped or failed run
+    pub fn subscribe(&self) -> broadcast::Receiver<BackupEvent> {
+        self.events.subscribe()
+    }
+
+    /// Get the current settings
+    pub async fn settings(&self) -> AutoBackupSettings {
+        self.settings.read().await.clone()
+    }
+
+    /// Replace the settings; the running task picks them up on its next poll
+    pub async fn update_settings(&self, settings: AutoBackupSettings) {
+        *self.settings.write().await = settings;
+    }
+
+    /// Get the persisted scheduler state
+    pub async fn state(&self) -> SchedulerState {
+        self.state.read().await.clone()
+    }
+
+    /// When the next run is due; `None` means immediately
+    pub async fn next_due(&self) -> Option<DateTime<Utc>> {
+        let interval = self.settings.read().await.frequency.interval();
+        let state = self.state.read().await;
+        let scheduled = state.last_run_at.map(|t| t + interval);
+
+        match (state.last_failure_at, state.last_run_at) {
+            (Some(failed), last) if last.map(|l| failed > l).unwrap_or(true) => {
+                let retry = failed + self.retry_delay;
+                Some(scheduled.map(|s| s.min(retry)).unwrap_or(retry))
+            }
+            _ => scheduled,
+        }
+    }
+
+    /// Take a backup now regardless of the schedule, after any run already
+    /// in progress
+    pub async fn run_now(&self) -> Result<BackupRunOutcome> {
+        self.run(false).await
+    }
+
+    /// Spawn the background task
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut first = true;
+            loop {
+                let enabled = self.settings.read().await.enabled;
+                let mut wait = self.poll_interval;
+
+                if enabled {
+                    let now = Utc::now();
+                    match self.next_due().await {
+                        Some(due) if due > now => {
+                            let until_due = (due - now).to_std().unwrap_or_default();
+                            wait = wait.min(until_due);
+                        }
+                        due => {
+                            // A due time in the past on the first pass means a
+                            // schedule was missed while the scheduler was stopped
+                            let catch_up = first && due.is_some();
+                            if catch_up {
+                                info!("Catching up missed scheduled backup");
+                            }
+                            let _ = self.run(catch_up).await;
+                            continue;
+                        }
+                    }
+                }
+                first = false;
+
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = self.shutdown.notified() => break,
+                }
+            }
+        })
+    }
+
+    /// Stop the background task after its current run
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+    }
+
+    async fn run(&self, catch_up: bool) -> Result<BackupRunOutcome> {
+        let _running = self.run_lock.lock().await;
+        let result = self.try_run().await;
+        let now = Utc::now();
+
+        let event = {
+            let mut state = self.state.write().await;
+            let event = match &result {
+                Ok(BackupRunOutcome::Created { info, pruned }) => {
+                    state.last_run_at = Some(now);
+                    state.last_error = None;
+                    state.last_backup_file = Some(info.filename.clone());
+                    BackupEvent::Completed {
+                        info: info.clone(),
+                        content_hash: state.last_content_hash.clone().unwrap_or_default(),
+                        pruned: pruned.clone(),
+                        catch_up,
+                    }
+                }
+                Ok(BackupRunOutcome::Unchanged { content_hash }) => {
+                    state.last_run_at = Some(now);
+                    state.last_error = None;
+                    BackupEvent::Skipped {
+                        content_hash: content_hash.clone(),
+                        catch_up,
+                    }
+                }
+                Err(e) => {
+                    warn!("Scheduled backup failed: {}", e);
+                    state.last_failure_at = Some(now);
+                    state.last_error = Some(e.to_string());
+                    BackupEvent::Failed {
+                        error: e.to_string(),
+                        catch_up,
+                    }
+                }
+            };
+            if let Err(e) = save_state(&self.state_path, &state) {
+                warn!("Failed to record backup scheduler state: {}", e);
+            }
+            event
+        };
+
+        if let Some(bus) = &self.event_bus {
+            bus.publish(match &event {
+                BackupEvent::Completed { info, .. } => BrowserEvent::BackupFinished {
+                    backup_id: Some(info.id.clone()),
+                    skipped: false,
+                    error: None,
+                },
+                BackupEvent::Skipped { .. } => BrowserEvent::BackupFinished {
+                    backup_id: None,
+                    skipped: true,
+                    error: None,
+                },
+                BackupEvent::Failed { error, .. } => BrowserEvent::BackupFinished {
+                    backup_id: None,
+                    skipped: false,
+                    error: Some(error.clone()),
+                },
+            });
+        }
+        let _ = self.events.send(event);
+        result
+    }
+
+    async fn try_run(&self) -> Result<BackupRunOutcome> {
+        let settings = self.settings.read().await.clone();
+
+        let mut options = self.options.clone();
+        if !settings.include_storage {
+            options.include_cookies = false;
+            options.include_history = false;
+            options.include_bookmarks = false;
+            options.include_local_storage = false;
+        }
+
+        let data = self.source.collect(&options).await?;
+        let content_hash = content_hash(&data)?;
+
+        let backups = self.manager.list_backups().await?;
+        {
+            let state = self.state.read().await;
+            let previous_exists = state
+                .last_backup_file
+                .as_ref()
+                .map(|file| backups.iter().any(|b| &b.filename == file))
+                .unwrap_or(false);
+            if previous_exists && state.last_content_hash.as_deref() == Some(content_hash.as_str()) {
+                info!("Skipping scheduled backup; content unchanged");
+                return Ok(BackupRunOutcome::Unchanged { content_hash });
+            }
+        }
+
+        let info = self.manager.create_backup(data, &options).await?;
+        {
+            let mut state = self.state.write().await;
+            state.last_content_hash = Some(content_hash);
+            state.scheduled_backups.push(info.filename.clone());
+        }
+        info!("Scheduled backup created: {}", info.filename);
+
+        let pruned = self.apply_retention(settings.max_backups, settings.gfs).await?;
+        Ok(BackupRunOutcome::Created { info, pruned })
+    }
+
+    /// Delete scheduled backups outside `max_backups` and the GFS tiers;
+    /// returns the deleted filenames. Backups the scheduler did not write are
+    /// neither counted nor deleted.
+    pub async fn apply_retention(&self, max_backups: u32, gfs: Option<GfsRetention>) -> Result<Vec<String>> {
+        let mut state = self.state.write().await;
+        let backups: Vec<BackupInfo> = self
+            .manager
+            .list_backups()
+            .await?
+            .into_iter()
+            .filter(|b| state.scheduled_backups.contains(&b.filename))
+            .collect();
+        let dated: Vec<(String, DateTime<Utc>)> = backups
+            .iter()
+            .filter_map(|b| backup_time(b).map(|t| (b.filename.clone(), t)))
+            .collect();
+        let keep = select_retained(&dated, max_backups, gfs);
+
+        let mut pruned = Vec::new();
+        for backup in &backups {
+            // Backups without a readable timestamp are never pruned
+            if backup_time(backup).is_none() || keep.contains(&backup.filename) {
+                continue;
+            }
+            std::fs::remove_file(&backup.path)?;
+            pruned.push(backup.filename.clone());
+        }
+
+        // Forget pruned backups and any deleted by hand
+        state
+            .scheduled_backups
+            .retain(|file| backups.iter().any(|b| &b.filename == file) && !pruned.contains(file));
+        Ok(pruned)
+    }
+}
+
+/// Maps a backup time to its retention bucket
+type BucketFn = fn(&DateTime<Utc>) -> (i32, u32);
+
+/// Pick which backups survive retention: the newest `max_backups`, plus the
+/// newest backup in each daily, weekly and monthly bucket of the GFS tiers
+pub fn select_retained(
+    backups: &[(String, DateTime<Utc>)],
+    max_backups: u32,
+    gfs: Option<GfsRetention>,
+) -> HashSet<String> {
+    let mut sorted: Vec<&(String, DateTime<Utc>)> = backups.iter().collect();
+    sorted.sort_by_key(|b| std::cmp::Reverse(b.1));
+
+    let mut keep: HashSet<String> = sorted
+        .iter()
+        .take(max_backups as usize)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    if let Some(gfs) = gfs {
+        let tiers: [(u32, BucketFn); 3] = [
+            (gfs.daily, |t| (t.year(), t.ordinal())),
+            (gfs.weekly, |t| (t.iso_week().year(), t.iso_week().week())),
+            (gfs.monthly, |t| (t.year(), t.month())),
+        ];
+
+        for (limit, bucket_of) in tiers {
+            let mut buckets = HashSet::new();
+            for (name, time) in &sorted {
+                if buckets.len() >= limit as usize {
+                    break;
+                }
+                if buckets.insert(bucket_of(time)) {
+                    keep.insert(name.clone());
+                }
+            }
+        }
+    }
+
+    keep
+}
+
+/// SHA-256 of the backup data, ignoring its timestamp
+pub fn content_hash(data: &BackupData) -> Result<String> {
+    let mut data = data.clone();
+    data.timestamp.clear();
+    let json = serde_json::to_vec(&data)?;
+    Ok(hex::encode(Sha256::digest(&json)))
+}
+
+/// Creation time of a backup, from its `backup_<date>_<time>_<id>` filename
+/// or the recorded creation time
+fn backup_time(info: &BackupInfo) -> Option<DateTime<Utc>> {
+    info.filename
+        .strip_prefix("backup_")
+        .and_then(|rest| rest.get(..15))
+        .and_then(|stamp| NaiveDateTime::parse_from_str(stamp, "%Y%m%d_%H%M%S").ok())
+        .map(|t| t.and_utc())
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(&info.created_at)
+                .ok()
+                .map(|t| t.with_timezone(&Utc))
+        })
+}
+
+fn load_state(path: &Path) -> Result<SchedulerState> {
+    if !path.exists() {
+        return Ok(SchedulerState::default());
+    }
+    let content = std::fs::read(path)?;
+    serde_json::from_slice(&content).map_err(|e| anyhow!("Invalid backup scheduler state: {}", e))
+}
+
+fn save_state(path: &Path, state: &SchedulerState) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn settings(dir: &Path) -> AutoBackupSettings {
+        AutoBackupSettings {
+            enabled: true,
+            backup_path: dir.to_path_buf(),
+            max_backups: 2,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skips_unchanged_and_records_state() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let storage = Arc::new(StorageEngine::new(dir.path()).expect("storage should succeed"));
+        let backups = dir.path().join("backups");
+        let scheduler = BackupScheduler::new(settings(&backups), storage.clone())
+            .expect("scheduler should succeed");
+        let mut events = scheduler.subscribe();
+
+        storage.add_bookmark("https://example.com", "Example", None).await.expect("bookmark should succeed");
+        let first = scheduler.run_now().await.expect("first run should succeed");
+        assert!(matches!(first, BackupRunOutcome::Created { .. }));
+        assert!(matches!(events.recv().await, Ok(BackupEvent::Completed { .. })));
+
+        let second = scheduler.run_now().await.expect("second run should succeed");
+        assert!(matches!(second, BackupRunOutcome::Unchanged { .. }));
+        assert!(matches!(events.recv().await, Ok(BackupEvent::Skipped { .. })));
+
+        // A restarted scheduler sees the recorded run and is not yet due
+        let restarted = BackupScheduler::new(settings(&backups), storage.clone())
+            .expect("scheduler should succeed");
+        assert!(restarted.state().await.last_run_at.is_some());
+        assert!(restarted.next_due().await.expect("due time should be set") > Utc::now());
+
+        storage.add_bookmark("https://example.org", "Other", None).await.expect("bookmark should succeed");
+        let third = restarted.run_now().await.expect("third run should succeed");
+        assert!(matches!(third, BackupRunOutcome::Created { .. }));
+        assert_eq!(restarted.manager.list_backups().await.expect("list should succeed").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retention_ignores_manual_backups() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let storage = Arc::new(StorageEngine::new(dir.path()).expect("storage should succeed"));
+        let backups = dir.path().join("backups");
+        let scheduler = BackupScheduler::new(settings(&backups), storage.clone())
+            .expect("scheduler should succeed");
+
+        let manual = scheduler
+            .manager
+            .create_backup(
+                storage.collect(&BackupOptions::default()).await.expect("collect should succeed"),
+                &BackupOptions::default(),
+            )
+            .await
+            .expect("manual backup should succeed");
+
+        for n in 0..3 {
+            storage
+                .add_bookmark(&format!("https://example.com/{}", n), "Example", None)
+                .await
+                .expect("bookmark should succeed");
+            scheduler.run_now().await.expect("run should succeed");
+        }
+
+        let remaining = scheduler.manager.list_backups().await.expect("list should succeed");
+        assert_eq!(remaining.len(), 3);
+        assert!(remaining.iter().any(|b| b.filename == manual.filename));
+        assert_eq!(scheduler.state().await.scheduled_backups.len(), 2);
+    }
+
+    /// Source that records how many collections overlap
+    #[derive(Default)]
+    struct SlowSource {
+        calls: std::sync::atomic::AtomicUsize,
+        in_flight: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BackupSource for SlowSource {
+        async fn collect(&self, _options: &BackupOptions) -> Result<BackupData> {
+            use std::sync::atomic::Ordering;
+
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(BackupData {
+                version: call.to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+                proxy_settings: None,
+                browser_config: None,
+                cookies: None,
+                history: None,
+                bookmarks: None,
+                local_storage: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_do_not_overlap() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let source = Arc::new(SlowSource::default());
+        let scheduler = Arc::new(
+            BackupScheduler::new(settings(dir.path()), source.clone()).expect("scheduler should succeed"),
+        );
+
+        let runs: Vec<_> = (0..3)
+            .map(|_| {
+                let scheduler = scheduler.clone();
+                tokio::spawn(async move { scheduler.run_now().await })
+            })
+            .collect();
+        for run in runs {
+            run.await.expect("task should join").expect("run should succeed");
+        }
+
+        assert_eq!(source.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(source.peak.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_gfs_retention_selection() {
+        let backups: Vec<(String, DateTime<Utc>)> = (0..60)
+            .map(|day| {
+                let time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap() + chrono::Duration::days(day);
+                (format!("b{}", day), time)
+            })
+            .collect();
+
+        let only_max = select_retained(&backups, 3, None);
+        assert_eq!(only_max.len(), 3);
+        assert!(only_max.contains("b59"));
+
+        let gfs = GfsRetention { daily: 7, weekly: 4, monthly: 2 };
+        let kept = select_retained(&backups, 3, Some(gfs));
+        // Seven daily, the last Sunday of weeks 6-8 and the end of January
+        let mut expected: HashSet<String> = (53..60).map(|d| format!("b{}", d)).collect();
+        expected.extend(["b55", "b48", "b41", "b30"].map(String::from));
+        assert_eq!(kept, expected);
+    }
+}
@@ -12,6 +12,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use crate::chromium_engine::BrowserEngineType;
+use crate::event_stream::{BrowserEvent, EventBus};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a BrowserState.
@@ -310,6 +311,7 @@ pub struct DownloadManager {
     downloads: Arc<RwLock<HashMap<String, DownloadItem>>>,
     download_dir: PathBuf,
     max_concurrent_downloads: usize,
+    events: Option<Arc<EventBus>>,
 }
 
 impl DownloadManager {
@@ -319,6 +321,25 @@ impl DownloadManager {
             downloads: Arc::new(RwLock::new(HashMap::new())),
             download_dir,
             max_concurrent_downloads: 5,
+            events: None,
+        }
+    }
+
+    /// Publish progress and state changes to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    fn publish_progress(&self, item: &DownloadItem) {
+        if let Some(bus) = &self.events {
+            bus.publish(BrowserEvent::DownloadProgress {
+                download_id: item.id.clone(),
+                tab_id: item.tab_id.clone(),
+                received_bytes: item.received_bytes,
+                total_bytes: item.total_bytes,
+                state: item.state.clone(),
+            });
         }
     }
 
@@ -385,6 +406,7 @@ impl DownloadManager {
         let mut downloads = self.downloads.write().await;
         if let Some(item) = downloads.get_mut(download_id) {
             item.state = DownloadState::Cancelled;
+            self.publish_progress(item);
         }
         Ok(())
     }
@@ -398,6 +420,7 @@ impl DownloadManager {
                 item.total_bytes = total_bytes;
             }
             item.state = DownloadState::InProgress;
+            self.publish_progress(item);
         }
     }
 
@@ -410,6 +433,7 @@ impl DownloadManager {
             if let Some(total) = item.total_bytes {
                 item.received_bytes = total;
             }
+            self.publish_progress(item);
         }
     }
 
@@ -419,6 +443,7 @@ impl DownloadManager {
         if let Some(item) = downloads.get_mut(download_id) {
             item.state = DownloadState::Failed;
             item.error = Some(error.to_string());
+            self.publish_progress(item);
         }
     }
 
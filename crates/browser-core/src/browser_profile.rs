@@ -5,15 +5,42 @@
 //! - Profile switching
 //! - Profile import/export
 //! - Isolated storage per profile
+//! - Clear-on-close profiles
+//! - Exclusive lock files so a profile is opened by one process at a time
+//! - Several profiles loaded at once for separate windows or headless engines
+//! - Proxy passwords kept in a secrets vault, with references in the metadata
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use tokio::sync::RwLock;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::chromium_engine::{ChromiumEngineConfig, Geolocation, ProxyAuth};
+use crate::fingerprint::BrowserFingerprint;
+use crate::performance_optimizer::PerformanceOptimizer;
+use crate::proxy::{ProxySettings, ProxyType};
+use crate::secrets_vault::{is_secret_ref, SecretsVault, REDACTED_SECRET};
+use crate::storage::StorageEngine;
+use virtual_ip::VirtualIP;
+
+/// File holding profile metadata inside its data directory
+pub const PROFILE_METADATA_FILE: &str = "profile.json";
+/// Metadata file of a profile detached by `detach_profile`
+pub const PROFILE_DETACHED_FILE: &str = "profile.json.detached";
+/// Lock file held while a profile is open
+pub const PROFILE_LOCK_FILE: &str = "profile.lock";
+/// Cookie jar, history, bookmarks and local storage of a profile
+pub const PROFILE_STORAGE_FILE: &str = "storage.json";
+/// Chromium user-data-dir inside a profile's data directory
+pub const PROFILE_CHROMIUM_DIR: &str = "chromium";
+
 /// Browser profile configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserProfile {
@@ -36,6 +63,15 @@ pub struct ProfileSettings {
     pub timezone: Option<String>,
     pub geolocation: Option<GeoLocation>,
     pub fingerprint_protection: bool,
+    /// Wipe the profile's data directory whenever the profile is closed
+    #[serde(default)]
+    pub clear_on_close: bool,
+    /// Fingerprint presented by this profile
+    #[serde(default)]
+    pub fingerprint: Option<BrowserFingerprint>,
+    /// Virtual IP identity this profile was generated for
+    #[serde(default)]
+    pub virtual_ip: Option<VirtualIP>,
 }
 
 /// Profile proxy configuration
@@ -48,6 +84,27 @@ pub struct ProfileProxyConfig {
     pub password: Option<String>,
 }
 
+impl ProfileProxyConfig {
+    /// Convert to proxy settings usable by the proxy layer and engines
+    pub fn to_proxy_settings(&self) -> ProxySettings {
+        let proxy_type = match self.protocol.to_ascii_lowercase().as_str() {
+            "https" => ProxyType::Https,
+            "socks4" => ProxyType::Socks4,
+            "socks5" | "socks" => ProxyType::Socks5,
+            _ => ProxyType::Http,
+        };
+
+        ProxySettings {
+            proxy_type,
+            host: Some(self.host.clone()),
+            port: Some(self.port),
+            username: self.username.clone(),
+            password: self.password.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Geolocation settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeoLocation {
@@ -56,11 +113,190 @@ pub struct GeoLocation {
     pub accuracy: f64,
 }
 
+/// Exclusive lock on a profile's data directory.
+///
+/// Backed by an OS advisory lock on the lock file, so the lock dies with the
+/// owning process even if it crashes. The file records the owner's process
+/// ID for error messages and is removed when the lock is released or dropped.
+#[derive(Debug)]
+pub struct ProfileLock {
+    path: PathBuf,
+    file: std::fs::File,
+}
+
+impl ProfileLock {
+    /// Acquire the lock for a profile data directory
+    pub fn acquire(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(PROFILE_LOCK_FILE);
+
+        for _ in 0..3 {
+            let mut file = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .context("Failed to open profile lock")?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    let owner = std::fs::read_to_string(&path)
+                        .ok()
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .unwrap_or_else(|| "unknown".to_string());
+                    return Err(anyhow!(
+                        "Profile at {} is locked by process {}",
+                        data_dir.display(),
+                        owner
+                    ));
+                }
+                Err(std::fs::TryLockError::Error(e)) => return Err(e).context("Failed to lock profile"),
+            }
+
+            // The previous owner removes the file before unlocking it, so a
+            // lock won on a removed file is worthless; open the path again
+            if !is_same_file(&file, &path) {
+                continue;
+            }
+            file.set_len(0)?;
+            write!(file, "{}", std::process::id())?;
+            return Ok(Self { path, file });
+        }
+
+        Err(anyhow!("Failed to acquire profile lock {}", path.display()))
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ProfileLock {
+    fn drop(&mut self) {
+        // Remove while still locked so no one can win a lock on it afterwards
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove profile lock {}: {}", self.path.display(), e);
+        }
+        self.file.unlock().ok();
+    }
+}
+
+/// Whether `path` still names the file behind `file`
+#[cfg(unix)]
+fn is_same_file(file: &std::fs::File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(held), Ok(current)) => held.dev() == current.dev() && held.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// Whether `path` still names the file behind `file`. Windows refuses to
+/// remove a file another handle has open, so the path cannot have moved on.
+#[cfg(not(unix))]
+fn is_same_file(_file: &std::fs::File, path: &Path) -> bool {
+    path.exists()
+}
+
+/// A profile that is open, owning its storage, cache and lock
+pub struct LoadedProfile {
+    pub profile: BrowserProfile,
+    pub storage: Arc<StorageEngine>,
+    pub cache: Arc<PerformanceOptimizer>,
+    pub loaded_at: DateTime<Utc>,
+    lock: Mutex<Option<ProfileLock>>,
+}
+
+impl LoadedProfile {
+    async fn open(profile: BrowserProfile) -> Result<Self> {
+        tokio::fs::create_dir_all(&profile.data_dir).await?;
+        let lock = ProfileLock::acquire(&profile.data_dir)?;
+
+        tokio::fs::create_dir_all(profile.data_dir.join(PROFILE_CHROMIUM_DIR)).await?;
+        let storage = Arc::new(StorageEngine::new(&profile.data_dir)?);
+        let storage_file = profile.data_dir.join(PROFILE_STORAGE_FILE);
+        if storage_file.exists() {
+            storage
+                .import_from_file(&storage_file)
+                .await
+                .with_context(|| format!("Failed to load storage for profile {}", profile.id))?;
+        }
+
+        Ok(Self {
+            profile,
+            storage,
+            cache: Arc::new(PerformanceOptimizer::new()),
+            loaded_at: Utc::now(),
+            lock: Mutex::new(Some(lock)),
+        })
+    }
+
+    /// Chromium user-data-dir owned by this profile
+    pub fn chromium_user_data_dir(&self) -> PathBuf {
+        self.profile.data_dir.join(PROFILE_CHROMIUM_DIR)
+    }
+
+    /// Proxy settings for this profile, if its proxy is enabled
+    pub fn proxy_settings(&self) -> Option<ProxySettings> {
+        if !self.profile.settings.proxy_enabled {
+            return None;
+        }
+        self.profile.settings.proxy_config.as_ref().map(|p| p.to_proxy_settings())
+    }
+
+    /// Derive a Chromium engine configuration bound to this profile
+    pub fn chromium_config(&self, base: ChromiumEngineConfig) -> ChromiumEngineConfig {
+        let settings = &self.profile.settings;
+        let mut config = base;
+        config.user_data_dir = Some(self.chromium_user_data_dir());
+        config.proxy = self.proxy_settings();
+        config.proxy_auth = settings.proxy_config.as_ref().and_then(|p| {
+            p.username.as_ref().map(|username| ProxyAuth {
+                username: username.clone(),
+                password: p.password.clone().unwrap_or_default(),
+            })
+        });
+        if settings.user_agent.is_some() {
+            config.user_agent = settings.user_agent.clone();
+        }
+        if let Some(geo) = &settings.geolocation {
+            config.geolocation = Some(Geolocation {
+                latitude: geo.latitude,
+                longitude: geo.longitude,
+                accuracy: geo.accuracy,
+            });
+        }
+        config
+    }
+
+    /// Write the profile's storage to its data directory
+    pub async fn persist(&self) -> Result<()> {
+        self.storage
+            .export_to_file(&self.profile.data_dir.join(PROFILE_STORAGE_FILE))
+            .await?;
+        Ok(())
+    }
+
+    /// Whether this profile still holds its lock
+    pub fn is_locked(&self) -> bool {
+        self.lock.lock().is_some()
+    }
+
+    fn release(&self) {
+        self.lock.lock().take();
+    }
+}
+
 /// Browser profile manager
 pub struct BrowserProfileManager {
     profiles: RwLock<HashMap<String, BrowserProfile>>,
     active_profile_id: RwLock<Option<String>>,
     base_data_dir: PathBuf,
+    loaded: RwLock<HashMap<String, Arc<LoadedProfile>>>,
+    pinned: RwLock<HashSet<String>>,
+    secrets: Option<Arc<SecretsVault>>,
 }
 
 impl BrowserProfileManager {
@@ -70,18 +306,79 @@ impl BrowserProfileManager {
             profiles: RwLock::new(HashMap::new()),
             active_profile_id: RwLock::new(None),
             base_data_dir,
+            loaded: RwLock::new(HashMap::new()),
+            pinned: RwLock::new(HashSet::new()),
+            secrets: None,
+        }
+    }
+
+    /// Move proxy passwords into a vault before profile metadata is written,
+    /// leaving `secret://` references on disk
+    pub fn with_secrets_vault(mut self, vault: Arc<SecretsVault>) -> Self {
+        self.secrets = Some(vault);
+        self
+    }
+
+    async fn seal(&self, profile: &mut BrowserProfile) -> Result<()> {
+        match &self.secrets {
+            Some(vault) => vault.seal_profile(profile).await,
+            None => Ok(()),
         }
     }
 
+    /// Load profile metadata from the data directories under the base directory
+    pub async fn discover_profiles(&self) -> Result<usize> {
+        if !self.base_data_dir.exists() {
+            return Ok(0);
+        }
+
+        let mut found = 0;
+        let mut entries = tokio::fs::read_dir(&self.base_data_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata_path = entry.path().join(PROFILE_METADATA_FILE);
+            if !metadata_path.exists() {
+                continue;
+            }
+            match tokio::fs::read_to_string(&metadata_path).await
+                .map_err(anyhow::Error::from)
+                .and_then(|json| serde_json::from_str::<BrowserProfile>(&json).map_err(anyhow::Error::from))
+            {
+                Ok(mut profile) => {
+                    profile.data_dir = entry.path();
+                    // Profiles written before the vault was attached still
+                    // hold plaintext passwords
+                    let plaintext = profile.settings.proxy_config.as_ref()
+                        .and_then(|p| p.password.as_deref())
+                        .is_some_and(|p| !is_secret_ref(p) && p != REDACTED_SECRET);
+                    if plaintext && self.secrets.is_some() {
+                        self.seal(&mut profile).await?;
+                        Self::write_metadata(&profile).await?;
+                    }
+                    self.profiles.write().await.insert(profile.id.clone(), profile);
+                    found += 1;
+                }
+                Err(e) => warn!("Skipping unreadable profile {}: {}", metadata_path.display(), e),
+            }
+        }
+        Ok(found)
+    }
+
+    async fn write_metadata(profile: &BrowserProfile) -> Result<()> {
+        tokio::fs::create_dir_all(&profile.data_dir).await?;
+        tokio::fs::write(
+            profile.data_dir.join(PROFILE_METADATA_FILE),
+            serde_json::to_string_pretty(profile)?,
+        )
+        .await?;
+        Ok(())
+    }
+
     /// Create a new profile
     pub async fn create_profile(&self, name: &str, is_default: bool) -> Result<BrowserProfile> {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now();
         let data_dir = self.base_data_dir.join(&id);
-        
-        // Create profile directory
-        tokio::fs::create_dir_all(&data_dir).await?;
-        
+
         let profile = BrowserProfile {
             id: id.clone(),
             name: name.to_string(),
@@ -91,7 +388,10 @@ impl BrowserProfileManager {
             settings: ProfileSettings::default(),
             is_default,
         };
-        
+
+        // Create profile directory
+        Self::write_metadata(&profile).await?;
+
         self.profiles.write().await.insert(id, profile.clone());
         Ok(profile)
     }
@@ -101,25 +401,153 @@ impl BrowserProfileManager {
         self.profiles.read().await.get(profile_id).cloned()
     }
 
+    /// Directory holding every profile's data directory
+    pub fn base_data_dir(&self) -> &Path {
+        &self.base_data_dir
+    }
+
+    /// Register a profile under its own ID, placing its data directory under
+    /// the base directory. Replaces any profile with the same ID.
+    pub async fn insert_profile(&self, mut profile: BrowserProfile) -> Result<BrowserProfile> {
+        profile.data_dir = self.base_data_dir.join(&profile.id);
+        self.seal(&mut profile).await?;
+        Self::write_metadata(&profile).await?;
+        self.profiles.write().await.insert(profile.id.clone(), profile.clone());
+        Ok(profile)
+    }
+
+    /// Whether a profile is currently loaded
+    pub async fn is_loaded(&self, profile_id: &str) -> bool {
+        self.loaded.read().await.contains_key(profile_id)
+    }
+
     /// Get all profiles
     pub async fn list_profiles(&self) -> Vec<BrowserProfile> {
         self.profiles.read().await.values().cloned().collect()
     }
 
-    /// Switch to a profile
-    pub async fn switch_profile(&self, profile_id: &str) -> Result<()> {
-        let mut profiles = self.profiles.write().await;
-        if let Some(profile) = profiles.get_mut(profile_id) {
+    async fn load(&self, profile_id: &str) -> Result<Arc<LoadedProfile>> {
+        let mut loaded = self.loaded.write().await;
+        if let Some(existing) = loaded.get(profile_id) {
+            return Ok(existing.clone());
+        }
+
+        let profile = {
+            let mut profiles = self.profiles.write().await;
+            let profile = profiles
+                .get_mut(profile_id)
+                .ok_or_else(|| anyhow!("Profile not found"))?;
             profile.last_used = Utc::now();
-            *self.active_profile_id.write().await = Some(profile_id.to_string());
+            profile.clone()
+        };
+        Self::write_metadata(&profile).await?;
+
+        let session = Arc::new(LoadedProfile::open(profile).await?);
+        loaded.insert(profile_id.to_string(), session.clone());
+        info!("Loaded profile {}", profile_id);
+        Ok(session)
+    }
+
+    /// Open a profile alongside the active one, e.g. for a separate window or
+    /// headless engine. It stays loaded until `unload_profile` is called.
+    pub async fn open_profile(&self, profile_id: &str) -> Result<Arc<LoadedProfile>> {
+        let session = self.load(profile_id).await?;
+        self.pinned.write().await.insert(profile_id.to_string());
+        Ok(session)
+    }
+
+    /// Unload a profile, persisting its storage and releasing its lock.
+    /// Returns false if the profile was not loaded.
+    pub async fn unload_profile(&self, profile_id: &str) -> Result<bool> {
+        self.pinned.write().await.remove(profile_id);
+        let Some(session) = self.loaded.write().await.remove(profile_id) else {
+            return Ok(false);
+        };
+
+        let result = if session.profile.settings.clear_on_close {
             Ok(())
         } else {
-            Err(anyhow::anyhow!("Profile not found"))
+            session.persist().await
+        };
+        session.release();
+
+        {
+            let mut active = self.active_profile_id.write().await;
+            if active.as_deref() == Some(profile_id) {
+                *active = None;
+            }
         }
+
+        info!("Unloaded profile {}", profile_id);
+        result.map(|_| true)
+    }
+
+    /// Get a loaded profile
+    pub async fn get_loaded(&self, profile_id: &str) -> Option<Arc<LoadedProfile>> {
+        self.loaded.read().await.get(profile_id).cloned()
+    }
+
+    /// IDs of all loaded profiles
+    pub async fn list_loaded(&self) -> Vec<String> {
+        self.loaded.read().await.keys().cloned().collect()
+    }
+
+    /// Switch to a profile, loading it and unloading the previously active
+    /// profile unless that one was opened with `open_profile`
+    pub async fn switch_profile(&self, profile_id: &str) -> Result<()> {
+        self.load(profile_id).await?;
+
+        let previous = self.active_profile_id.write().await.replace(profile_id.to_string());
+        if let Some(previous) = previous {
+            if previous != profile_id && !self.pinned.read().await.contains(&previous) {
+                self.unload_profile(&previous).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Close a profile, unloading it and deactivating it if active.
+    ///
+    /// Profiles with `clear_on_close` have their data directory wiped and
+    /// recreated empty. Returns true if data was cleared.
+    pub async fn close_profile(&self, profile_id: &str) -> Result<bool> {
+        let profile = self
+            .get_profile(profile_id)
+            .await
+            .ok_or_else(|| anyhow!("Profile not found"))?;
+
+        self.unload_profile(profile_id).await?;
+        {
+            let mut active = self.active_profile_id.write().await;
+            if active.as_deref() == Some(profile_id) {
+                *active = None;
+            }
+        }
+
+        if !profile.settings.clear_on_close {
+            return Ok(false);
+        }
+
+        if profile.data_dir.exists() {
+            tokio::fs::remove_dir_all(&profile.data_dir).await?;
+        }
+        Self::write_metadata(&profile).await?;
+        Ok(true)
     }
 
     /// Delete a profile
     pub async fn delete_profile(&self, profile_id: &str) -> Result<()> {
+        self.pinned.write().await.remove(profile_id);
+        if let Some(session) = self.loaded.write().await.remove(profile_id) {
+            session.release();
+        }
+        {
+            let mut active = self.active_profile_id.write().await;
+            if active.as_deref() == Some(profile_id) {
+                *active = None;
+            }
+        }
+
         let mut profiles = self.profiles.write().await;
         if let Some(profile) = profiles.remove(profile_id) {
             // Remove profile directory
@@ -130,14 +558,42 @@ impl BrowserProfileManager {
         Ok(())
     }
 
+    /// Unregister a profile without touching its browsing data. The metadata
+    /// file is renamed so discovery skips the directory; inserting a profile
+    /// with the same ID attaches the data again.
+    pub async fn detach_profile(&self, profile_id: &str) -> Result<Option<BrowserProfile>> {
+        if self.is_loaded(profile_id).await {
+            return Err(anyhow!("Profile {} is open; close it before detaching", profile_id));
+        }
+
+        let Some(profile) = self.profiles.write().await.remove(profile_id) else {
+            return Ok(None);
+        };
+        let metadata = profile.data_dir.join(PROFILE_METADATA_FILE);
+        if metadata.exists() {
+            tokio::fs::rename(&metadata, profile.data_dir.join(PROFILE_DETACHED_FILE)).await?;
+        }
+        {
+            let mut active = self.active_profile_id.write().await;
+            if active.as_deref() == Some(profile_id) {
+                *active = None;
+            }
+        }
+        Ok(Some(profile))
+    }
+
     /// Update profile settings
     pub async fn update_settings(&self, profile_id: &str, settings: ProfileSettings) -> Result<()> {
         let mut profiles = self.profiles.write().await;
         if let Some(profile) = profiles.get_mut(profile_id) {
-            profile.settings = settings;
+            let mut updated = profile.clone();
+            updated.settings = settings;
+            self.seal(&mut updated).await?;
+            Self::write_metadata(&updated).await?;
+            *profile = updated;
             Ok(())
         } else {
-            Err(anyhow::anyhow!("Profile not found"))
+            Err(anyhow!("Profile not found"))
         }
     }
 
@@ -147,7 +603,7 @@ impl BrowserProfileManager {
         if let Some(profile) = profiles.get(profile_id) {
             Ok(serde_json::to_string_pretty(profile)?)
         } else {
-            Err(anyhow::anyhow!("Profile not found"))
+            Err(anyhow!("Profile not found"))
         }
     }
 
@@ -156,10 +612,11 @@ impl BrowserProfileManager {
         let mut profile: BrowserProfile = serde_json::from_str(json)?;
         profile.id = Uuid::new_v4().to_string();
         profile.data_dir = self.base_data_dir.join(&profile.id);
-        
-        tokio::fs::create_dir_all(&profile.data_dir).await?;
+        self.seal(&mut profile).await?;
+
+        Self::write_metadata(&profile).await?;
         self.profiles.write().await.insert(profile.id.clone(), profile.clone());
-        
+
         Ok(profile)
     }
 
@@ -172,6 +629,20 @@ impl BrowserProfileManager {
             None
         }
     }
+
+    /// Get the loaded state of the active profile
+    pub async fn get_active_session(&self) -> Option<Arc<LoadedProfile>> {
+        let active_id = self.active_profile_id.read().await.clone()?;
+        self.get_loaded(&active_id).await
+    }
+
+    /// Persist and unload every loaded profile, e.g. on shutdown
+    pub async fn unload_all(&self) -> Result<()> {
+        for profile_id in self.list_loaded().await {
+            self.unload_profile(&profile_id).await?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for BrowserProfileManager {
@@ -179,3 +650,105 @@ impl Default for BrowserProfileManager {
         Self::new(PathBuf::from("./profiles"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_profile_lock_is_exclusive() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let first = BrowserProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = first.create_profile("Work", false).await.expect("Create should succeed");
+        first.switch_profile(&profile.id).await.expect("Switch should succeed");
+
+        let second = BrowserProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(second.discover_profiles().await.expect("Discover should succeed"), 1);
+        assert!(second.switch_profile(&profile.id).await.is_err());
+
+        // A lock file left behind by a dead owner does not block anyone
+        let stale = temp_dir.path().join("stale");
+        std::fs::create_dir_all(&stale).expect("Create dir should succeed");
+        std::fs::write(stale.join(PROFILE_LOCK_FILE), "4294967295").expect("Write should succeed");
+        let lock = ProfileLock::acquire(&stale).expect("Stale lock should be taken over");
+        assert!(ProfileLock::acquire(&stale).is_err());
+        drop(lock);
+
+        first.unload_profile(&profile.id).await.expect("Unload should succeed");
+        assert!(!profile.data_dir.join(PROFILE_LOCK_FILE).exists());
+        second.switch_profile(&profile.id).await.expect("Switch should succeed after unlock");
+    }
+
+    #[tokio::test]
+    async fn test_storage_isolated_and_persisted_across_switch() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let manager = BrowserProfileManager::new(temp_dir.path().to_path_buf());
+        let work = manager.create_profile("Work", false).await.expect("Create should succeed");
+        let home = manager.create_profile("Home", false).await.expect("Create should succeed");
+
+        manager.switch_profile(&work.id).await.expect("Switch should succeed");
+        let session = manager.get_active_session().await.expect("Active session should exist");
+        session.storage.add_history("https://work.example", None).await.expect("Add history should succeed");
+
+        manager.switch_profile(&home.id).await.expect("Switch should succeed");
+        assert!(manager.get_loaded(&work.id).await.is_none());
+        let home_session = manager.get_active_session().await.expect("Active session should exist");
+        assert_eq!(home_session.storage.get_stats().await.history_count, 0);
+
+        manager.switch_profile(&work.id).await.expect("Switch should succeed");
+        let session = manager.get_active_session().await.expect("Active session should exist");
+        assert_eq!(session.storage.get_stats().await.history_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_opened_profiles_stay_loaded() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let manager = BrowserProfileManager::new(temp_dir.path().to_path_buf());
+        let a = manager.create_profile("A", false).await.expect("Create should succeed");
+        let b = manager.create_profile("B", false).await.expect("Create should succeed");
+
+        let headless = manager.open_profile(&a.id).await.expect("Open should succeed");
+        manager.switch_profile(&b.id).await.expect("Switch should succeed");
+        manager.switch_profile(&a.id).await.expect("Switch should succeed");
+
+        assert_eq!(manager.list_loaded().await.len(), 1);
+        assert!(headless.is_locked());
+        assert_eq!(
+            headless.chromium_config(ChromiumEngineConfig::default()).user_data_dir,
+            Some(a.data_dir.join(PROFILE_CHROMIUM_DIR))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_proxy_passwords_are_sealed_in_vault() {
+        use crate::secrets_vault::EncryptedFileBackend;
+        use crate::secure_container::KdfParams;
+
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let vault = Arc::new(SecretsVault::new(Arc::new(
+            EncryptedFileBackend::new(temp_dir.path().join("vault.bin"), "master")
+                .with_kdf_params(KdfParams { m_cost: 1024, t_cost: 1, p_cost: 1 }),
+        )));
+        let manager = BrowserProfileManager::new(temp_dir.path().join("profiles")).with_secrets_vault(vault.clone());
+        let profile = manager.create_profile("Work", false).await.expect("Create should succeed");
+
+        let mut settings = profile.settings.clone();
+        settings.proxy_config = Some(ProfileProxyConfig {
+            host: "10.0.0.1".to_string(),
+            port: 8080,
+            protocol: "http".to_string(),
+            username: Some("user".to_string()),
+            password: Some("hunter2".to_string()),
+        });
+        manager.update_settings(&profile.id, settings).await.expect("Update should succeed");
+
+        let metadata = std::fs::read_to_string(profile.data_dir.join(PROFILE_METADATA_FILE))
+            .expect("Read metadata should succeed");
+        assert!(!metadata.contains("hunter2"));
+        let stored = manager.get_profile(&profile.id).await.expect("Profile should exist");
+        let reference = stored.settings.proxy_config.and_then(|p| p.password).expect("Password reference should exist");
+        assert!(is_secret_ref(&reference));
+        assert_eq!(vault.resolve(&reference).await.expect("Resolve should succeed"), "hunter2");
+    }
+}
@@ -7,6 +7,7 @@
 //! - IP rotation per tab
 //! - Session isolation between tabs
 //! - Browsing data management
+//! - Ephemeral tabs whose data is destroyed on close
 
 use anyhow::{Result, anyhow};
 use std::collections::HashMap;
@@ -22,6 +23,7 @@ use crate::tab_manager::TabIPManager;
 use crate::webview_manager::{WebviewManager, WebviewTab};
 use crate::tab_isolation::TabProfile;
 use crate::proxy::ProxySettings;
+use crate::storage::StorageEngine;
 use virtual_ip::VirtualIP;
 use virtual_ip::IPGenerator;
 // Database removed - using in-memory storage
@@ -39,6 +41,8 @@ pub struct BrowserTab {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub last_active: DateTime<Utc>,
+    #[serde(default)]
+    pub ephemeral: bool,
 }
 
 /// Configuration for creating a new browser tab
@@ -51,6 +55,8 @@ pub struct CreateTabConfig {
     pub user_agent: Option<String>,
     pub title: Option<String>,
     pub background: bool,
+    /// Keep all tab data in memory and destroy it when the tab closes
+    pub ephemeral: bool,
 }
 
 impl Default for CreateTabConfig {
@@ -62,6 +68,7 @@ impl Default for CreateTabConfig {
             user_agent: None,
             title: None,
             background: false,
+            ephemeral: false,
         }
     }
 }
@@ -123,10 +130,16 @@ impl BrowserTabManager {
         };
 
         // Create the WebView
-        let webview = self.webview_manager
-            .create_tab(config.url.clone())
-            .await
-            .map_err(|e| anyhow!("Failed to create WebView: {}", e))?;
+        let webview = if config.ephemeral {
+            self.webview_manager
+                .create_ephemeral_tab(config.url.clone(), proxy_config.clone())
+                .await
+        } else {
+            self.webview_manager
+                .create_tab(config.url.clone())
+                .await
+        }
+        .map_err(|e| anyhow!("Failed to create WebView: {}", e))?;
 
         // Create the combined browser tab
         let browser_tab = BrowserTab {
@@ -138,6 +151,7 @@ impl BrowserTabManager {
             is_active: !config.background,
             created_at: Utc::now(),
             last_active: Utc::now(),
+            ephemeral: config.ephemeral,
         };
 
         // Store the tab
@@ -169,7 +183,8 @@ impl BrowserTabManager {
         let mut tabs = self.tabs.write().await;
         
         if let Some(tab) = tabs.remove(tab_id) {
-            // Close the WebView
+            // Close the WebView; this also releases its container and proxies
+            // when the window itself fails to close
             if let Err(e) = self.webview_manager.close_tab(&tab.webview.tab_id).await {
                 warn!("Failed to close WebView: {}", e);
             }
@@ -366,6 +381,21 @@ impl BrowserTabManager {
         }
     }
 
+    /// Get the storage a tab should use
+    ///
+    /// Ephemeral tabs get their memory-only container storage; all other
+    /// tabs share the given persistent storage.
+    pub async fn storage_for_tab(&self, tab_id: &str, persistent: &Arc<StorageEngine>) -> Arc<StorageEngine> {
+        let webview_tab_id = match self.tabs.read().await.get(tab_id) {
+            Some(tab) if tab.ephemeral => tab.webview.tab_id.clone(),
+            _ => return persistent.clone(),
+        };
+        self.webview_manager
+            .ephemeral_containers()
+            .storage_for(&webview_tab_id, persistent)
+            .await
+    }
+
     /// Get all browser tabs
     /// Get all open tabs
     pub async fn get_tabs(&self) -> Vec<BrowserTab> {
@@ -556,11 +586,13 @@ pub async fn create_browser_tab(
     url: Option<String>,
     country_code: Option<String>,
     background: Option<bool>,
+    ephemeral: Option<bool>,
 ) -> Result<BrowserTab, String> {
     let config = CreateTabConfig {
         url,
         country_code,
         background: background.unwrap_or(false),
+        ephemeral: ephemeral.unwrap_or(false),
         ..Default::default()
     };
     
@@ -755,5 +787,6 @@ mod tests {
         assert_eq!(config.country_code, Some("US".to_string()));
         assert_eq!(config.proxy_config, None);
         assert_eq!(config.background, false);
+        assert_eq!(config.ephemeral, false);
     }
 }
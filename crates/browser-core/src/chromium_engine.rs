@@ -16,6 +16,9 @@
 //! - Full CDP (Chrome DevTools Protocol) access
 
 use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::browser::BrowserContextId;
+use chromiumoxide::cdp::browser_protocol::target::{CreateBrowserContextParams, CreateTargetParams};
+use chromiumoxide::auth::Credentials;
 use chromiumoxide::{Browser, BrowserConfig, Page};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -27,6 +30,7 @@ use tokio::sync::RwLock;
 use tracing::{info, debug, warn};
 
 use crate::proxy::ProxySettings;
+use crate::secrets_vault::SecretsVault;
 
 /// Engine version - v1000 (1.0.0.0)
 pub const ENGINE_VERSION: u32 = 1000;
@@ -299,6 +303,16 @@ pub struct ChromiumEngine {
     is_running: Arc<RwLock<bool>>,
     metrics: Arc<RwLock<EngineMetrics>>,
     start_time: Instant,
+    /// CDP page behind each tab
+    pages: Arc<RwLock<HashMap<String, TabPage>>>,
+    /// Resolves `secret://` proxy passwords when a tab authenticates
+    secrets: Option<Arc<SecretsVault>>,
+}
+
+/// A tab's page, and the browser context holding its proxy if it has one
+struct TabPage {
+    page: Page,
+    context: Option<BrowserContextId>,
 }
 
 impl ChromiumEngine {
@@ -313,8 +327,16 @@ impl ChromiumEngine {
             is_running: Arc::new(RwLock::new(false)),
             metrics: Arc::new(RwLock::new(EngineMetrics::default())),
             start_time: Instant::now(),
+            pages: Arc::new(RwLock::new(HashMap::new())),
+            secrets: None,
         }
     }
+
+    /// Resolve `secret://` proxy passwords of new tabs through a vault
+    pub fn with_secrets_vault(mut self, vault: Arc<SecretsVault>) -> Self {
+        self.secrets = Some(vault);
+        self
+    }
     
     /// Get engine version information
     pub fn get_version_info(&self) -> EngineInfo {
@@ -458,15 +480,61 @@ impl ChromiumEngine {
     }
 
     /// Create a new tab with optional proxy settings
+    ///
+    /// A tab with a proxy gets its own browser context routed through it;
+    /// the proxy's credentials (or `proxy_auth`) answer its auth challenges.
     pub async fn create_tab(&self, url: Option<&str>, proxy: Option<ProxySettings>) -> Result<ChromiumTab> {
         let start_time = Instant::now();
         
         let browser = self.browser.as_ref()
             .ok_or_else(|| anyhow!("Browser not launched"))?;
 
-        let page = browser.new_page("about:blank")
-            .await
-            .map_err(|e| anyhow!("Failed to create new tab: {}", e))?;
+        let context = match proxy.as_ref().and_then(|p| p.server_url().map(|url| (p, url))) {
+            Some((proxy, server_url)) => {
+                let mut params = CreateBrowserContextParams::builder().proxy_server(server_url);
+                if !proxy.bypass_list.is_empty() {
+                    params = params.proxy_bypass_list(proxy.bypass_list.join(","));
+                }
+                let context = browser
+                    .create_browser_context(params.build())
+                    .await
+                    .map_err(|e| anyhow!("Failed to create browser context: {}", e))?;
+                Some(context)
+            }
+            None => None,
+        };
+
+        let mut target = CreateTargetParams::builder().url("about:blank");
+        if let Some(context) = &context {
+            target = target.browser_context_id(context.clone());
+        }
+        let page = match target.build() {
+            Ok(target) => browser.new_page(target)
+                .await
+                .map_err(|e| anyhow!("Failed to create new tab: {}", e)),
+            Err(e) => Err(anyhow!("Invalid tab target: {}", e)),
+        };
+        let page = match page {
+            Ok(page) => page,
+            Err(e) => {
+                self.release_page(None, context, "new tab").await;
+                return Err(e);
+            }
+        };
+
+        // The tab keeps the stored proxy; only its page sees the password.
+        // A tab that fails to set up must not leave its page or context behind
+        let prepared = match self.resolve_proxy(proxy.as_ref()).await {
+            Ok(resolved) => self.prepare_page(&page, url, resolved.as_ref(), context.is_some()).await,
+            Err(e) => Err(e),
+        };
+        let (final_url, title) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                self.release_page(Some(page), context, "new tab").await;
+                return Err(e);
+            }
+        };
 
         let tab_id = uuid::Uuid::new_v4().to_string();
         
@@ -476,6 +544,69 @@ impl ChromiumEngine {
             metrics.tabs_created += 1;
         }
 
+        let tab = ChromiumTab {
+            id: tab_id.clone(),
+            url: final_url,
+            title,
+            proxy,
+            is_loading: false,
+            can_go_back: false,
+            can_go_forward: false,
+        };
+
+        self.tabs.write().await.insert(tab_id.clone(), tab.clone());
+        self.pages.write().await.insert(tab_id.clone(), TabPage { page, context });
+        *self.active_tab_id.write().await = Some(tab_id);
+        
+        // Update load metrics
+        {
+            let elapsed = start_time.elapsed().as_millis();
+            let mut metrics = self.metrics.write().await;
+            metrics.page_loads += 1;
+            metrics.total_load_time_ms += elapsed;
+            metrics.avg_load_time_ms = if metrics.page_loads > 0 {
+                metrics.total_load_time_ms / metrics.page_loads as u128
+            } else {
+                0
+            };
+        }
+
+        Ok(tab)
+    }
+
+    /// Proxy settings with the password resolved from the vault
+    async fn resolve_proxy(&self, proxy: Option<&ProxySettings>) -> Result<Option<ProxySettings>> {
+        match (proxy, &self.secrets) {
+            (Some(proxy), Some(vault)) => Ok(Some(vault.resolve_proxy_settings(proxy).await?)),
+            (proxy, _) => Ok(proxy.cloned()),
+        }
+    }
+
+    /// Apply credentials, overrides and the initial navigation to a new
+    /// page, returning its URL and title
+    async fn prepare_page(
+        &self,
+        page: &Page,
+        url: Option<&str>,
+        proxy: Option<&ProxySettings>,
+        has_context: bool,
+    ) -> Result<(String, String)> {
+        let credentials = match proxy.filter(|_| has_context) {
+            Some(ProxySettings { username: Some(username), password, .. }) => Some(Credentials {
+                username: username.clone(),
+                password: password.clone().unwrap_or_default(),
+            }),
+            _ => self.config.proxy_auth.as_ref().map(|auth| Credentials {
+                username: auth.username.clone(),
+                password: auth.password.clone(),
+            }),
+        };
+        if let Some(credentials) = credentials {
+            page.authenticate(credentials)
+                .await
+                .map_err(|e| anyhow!("Failed to set proxy credentials: {}", e))?;
+        }
+
         // Apply custom user agent if configured
         if let Some(user_agent) = &self.config.user_agent {
             page.set_user_agent(user_agent)
@@ -485,25 +616,25 @@ impl ChromiumEngine {
 
         // Apply network throttling if configured
         if !matches!(self.config.network_condition, NetworkCondition::None) {
-            self.apply_network_throttling(&page).await?;
+            self.apply_network_throttling(page).await?;
         }
 
         // Apply geolocation spoofing if configured
         if let Some(geo) = &self.config.geolocation {
-            self.apply_geolocation(&page, geo).await?;
+            self.apply_geolocation(page, geo).await?;
         }
 
         // Apply stealth scripts to avoid detection
         if self.config.stealth_mode {
-            self.inject_stealth_scripts(&page).await?;
+            self.inject_stealth_scripts(page).await?;
         }
 
         // Apply fingerprint spoofing
-        self.apply_fingerprint_spoofing(&page).await?;
+        self.apply_fingerprint_spoofing(page).await?;
         
         // Apply request interception if enabled
         if self.config.enable_interception && !self.config.blocked_urls.is_empty() {
-            self.apply_request_interception(&page).await?;
+            self.apply_request_interception(page).await?;
         }
 
         // Navigate to URL if provided
@@ -521,60 +652,41 @@ impl ChromiumEngine {
             .map_err(|e| anyhow!("Failed to get title: {}", e))?
             .unwrap_or_else(|| "New Tab".to_string());
 
-        let tab = ChromiumTab {
-            id: tab_id.clone(),
-            url: final_url,
-            title,
-            proxy,
-            is_loading: false,
-            can_go_back: false,
-            can_go_forward: false,
-        };
+        Ok((final_url, title))
+    }
 
-        self.tabs.write().await.insert(tab_id.clone(), tab.clone());
-        *self.active_tab_id.write().await = Some(tab_id);
-        
-        // Update load metrics
-        {
-            let elapsed = start_time.elapsed().as_millis();
-            let mut metrics = self.metrics.write().await;
-            metrics.page_loads += 1;
-            metrics.total_load_time_ms += elapsed;
-            metrics.avg_load_time_ms = if metrics.page_loads > 0 {
-                metrics.total_load_time_ms / metrics.page_loads as u128
-            } else {
-                0
-            };
+    /// Close a page and dispose its browser context, logging failures
+    async fn release_page(&self, page: Option<Page>, context: Option<BrowserContextId>, label: &str) {
+        if let Some(page) = page {
+            if let Err(e) = page.close().await {
+                warn!("Failed to close page for {}: {}", label, e);
+            }
+        }
+        if let (Some(browser), Some(context)) = (self.browser.as_ref(), context) {
+            if let Err(e) = browser.dispose_browser_context(context).await {
+                warn!("Failed to dispose browser context for {}: {}", label, e);
+            }
         }
-
-        Ok(tab)
     }
 
     /// Navigate a tab to a URL
     pub async fn navigate(&self, tab_id: &str, url: &str) -> Result<()> {
-        let browser = self.browser.as_ref()
-            .ok_or_else(|| anyhow!("Browser not launched"))?;
+        let page = self.get_page_for_tab(tab_id).await?;
 
-        let pages = browser.pages().await.map_err(|e| anyhow!("Failed to get pages: {}", e))?;
-        
-        // Find the page by checking tabs
-        let tabs = self.tabs.read().await;
-        if !tabs.contains_key(tab_id) {
-            return Err(anyhow!("Tab not found: {}", tab_id));
-        }
-        drop(tabs);
+        page.goto(url)
+            .await
+            .map_err(|e| anyhow!("Failed to navigate: {}", e))?;
 
-        // Navigate the first available page (simplified for now)
-        if let Some(page) = pages.first() {
-            page.goto(url)
-                .await
-                .map_err(|e| anyhow!("Failed to navigate: {}", e))?;
-            
-            // Update tab state
-            let mut tabs = self.tabs.write().await;
-            if let Some(tab) = tabs.get_mut(tab_id) {
-                tab.url = url.to_string();
-                tab.is_loading = true;
+        let title = page.get_title().await.ok().flatten();
+
+        // Update tab state
+        let mut tabs = self.tabs.write().await;
+        if let Some(tab) = tabs.get_mut(tab_id) {
+            tab.url = url.to_string();
+            tab.is_loading = false;
+            tab.can_go_back = true;
+            if let Some(title) = title {
+                tab.title = title;
             }
         }
 
@@ -583,6 +695,11 @@ impl ChromiumEngine {
 
     /// Close a tab
     pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
+        let removed = self.pages.write().await.remove(tab_id);
+        if let Some(TabPage { page, context }) = removed {
+            self.release_page(Some(page), context, &format!("tab {}", tab_id)).await;
+        }
+
         let mut tabs = self.tabs.write().await;
         tabs.remove(tab_id);
         
@@ -1028,6 +1145,7 @@ impl ChromiumEngine {
         }
 
         self.tabs.write().await.clear();
+        self.pages.write().await.clear();
         *self.active_tab_id.write().await = None;
         *self.is_running.write().await = false;
 
@@ -1052,26 +1170,17 @@ impl ChromiumEngine {
     
     // ===== Enhanced CDP Commands =====
     
-    /// Helper method to get a page for a specific tab
-    /// Note: Due to chromiumoxide's architecture, we use the active tab for CDP operations.
-    /// This is a known limitation where tab_id is validated but operations target the active page.
+    /// Helper method to get the page behind a specific tab
     async fn get_page_for_tab(&self, tab_id: &str) -> Result<Page> {
-        // Verify the tab exists
-        let tabs = self.tabs.read().await;
-        if !tabs.contains_key(tab_id) {
-            return Err(anyhow!("Tab not found: {}", tab_id));
+        if self.browser.is_none() {
+            return Err(anyhow!("Browser not launched"));
         }
-        drop(tabs);
-        
-        let browser = self.browser.as_ref()
-            .ok_or_else(|| anyhow!("Browser not launched"))?;
-        
-        let pages = browser.pages().await.map_err(|e| anyhow!("Failed to get pages: {}", e))?;
-        
-        // Use the first available page (active page)
-        // TODO: Implement proper page-to-tab mapping when chromiumoxide supports target IDs
-        pages.into_iter().next()
-            .ok_or_else(|| anyhow!("No pages available"))
+        self.pages
+            .read()
+            .await
+            .get(tab_id)
+            .map(|tab| tab.page.clone())
+            .ok_or_else(|| anyhow!("Tab not found: {}", tab_id))
     }
     
     /// Execute custom JavaScript in a tab
@@ -1177,6 +1286,7 @@ pub struct BrowserEngineManager {
     engine_type: Arc<RwLock<BrowserEngineType>>,
     chromium_engine: Arc<RwLock<Option<ChromiumEngine>>>,
     config: Arc<RwLock<ChromiumEngineConfig>>,
+    secrets: Option<Arc<SecretsVault>>,
 }
 
 impl BrowserEngineManager {
@@ -1186,9 +1296,17 @@ impl BrowserEngineManager {
             engine_type: Arc::new(RwLock::new(BrowserEngineType::System)),
             chromium_engine: Arc::new(RwLock::new(None)),
             config: Arc::new(RwLock::new(ChromiumEngineConfig::default())),
+            secrets: None,
         }
     }
 
+    /// Resolve `secret://` proxy passwords through a vault when the engine
+    /// launches and when tabs open. The stored config keeps the references.
+    pub fn with_secrets_vault(mut self, vault: Arc<SecretsVault>) -> Self {
+        self.secrets = Some(vault);
+        self
+    }
+
     /// Get current engine type
     pub async fn get_engine_type(&self) -> BrowserEngineType {
         *self.engine_type.read().await
@@ -1216,7 +1334,11 @@ impl BrowserEngineManager {
             BrowserEngineType::IntegratedChromium => {
                 // Launch Chromium engine
                 let config = self.config.read().await.clone();
-                let mut engine = ChromiumEngine::new(config);
+                let mut engine = match &self.secrets {
+                    Some(vault) => ChromiumEngine::new(vault.resolve_chromium_config(config).await?)
+                        .with_secrets_vault(vault.clone()),
+                    None => ChromiumEngine::new(config),
+                };
                 engine.launch().await?;
                 *self.chromium_engine.write().await = Some(engine);
             }
@@ -0,0 +1,496 @@
+//! Layered Configuration Module
+//!
+//! Resolves `AppConfig` from explicit layers, lowest precedence first:
+//! built-in defaults < system file < profile file < environment < runtime
+//! overrides. Provides:
+//! - Per-key provenance ("where did this value come from?")
+//! - Schema-driven typing of environment overrides
+//! - Reporting of unknown keys instead of silently ignoring them
+//! - JSON Schema export for editors and deployment tooling
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config_manager::AppConfig;
+use crate::config_migration::{migrate, read_document};
+
+/// Legacy single-underscore environment names, kept for compatibility
+const ENV_ALIASES: &[(&str, &str)] = &[
+    ("THEME", "general.theme"),
+    ("LANGUAGE", "general.language"),
+    ("PROXY_ENABLED", "proxy.enabled"),
+    ("PROXY_HOST", "proxy.default_host"),
+    ("PROXY_PORT", "proxy.default_port"),
+    ("LOG_LEVEL", "logging.level"),
+    ("EXPERIMENTAL", "features.experimental"),
+];
+
+/// Configuration layer, ordered by precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigLayer {
+    Defaults,
+    System,
+    Profile,
+    Env,
+    Runtime,
+}
+
+/// Where a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSource {
+    pub layer: ConfigLayer,
+    /// File path or environment variable name, when applicable
+    pub origin: Option<String>,
+}
+
+/// A key that does not exist in `AppConfig`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnknownKey {
+    /// Dotted key path, e.g. `network.max_bandwith_bps`
+    pub path: String,
+    pub source: ConfigSource,
+}
+
+/// Result of resolving all layers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedConfig {
+    pub config: AppConfig,
+    /// Source of every leaf key, by dotted path
+    pub sources: BTreeMap<String, ConfigSource>,
+    pub unknown_keys: Vec<UnknownKey>,
+}
+
+impl ResolvedConfig {
+    /// Source of a dotted key path
+    pub fn source_of(&self, path: &str) -> Option<&ConfigSource> {
+        self.sources.get(path)
+    }
+}
+
+/// JSON Schema for `AppConfig`. Objects reject additional properties so
+/// editors flag the same unknown keys the resolver reports.
+pub fn config_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(AppConfig))
+        .expect("AppConfig schema is always serializable");
+    close_objects(&mut schema);
+    schema
+}
+
+fn close_objects(schema: &mut Value) {
+    match schema {
+        Value::Object(map) => {
+            if map.contains_key("properties") {
+                map.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            for value in map.values_mut() {
+                close_objects(value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(close_objects),
+        _ => {}
+    }
+}
+
+/// Known key paths and leaf types, derived from the schema
+struct SchemaIndex {
+    objects: HashSet<String>,
+    leaves: HashMap<String, Vec<String>>,
+}
+
+impl SchemaIndex {
+    fn build() -> Self {
+        let schema = config_schema();
+        let definitions = schema.get("definitions").cloned().unwrap_or(Value::Null);
+        let mut index = Self { objects: HashSet::new(), leaves: HashMap::new() };
+        index.walk(&schema, &definitions, "");
+        index
+    }
+
+    fn walk(&mut self, schema: &Value, definitions: &Value, prefix: &str) {
+        let schema = resolve_ref(schema, definitions);
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            return;
+        };
+        for (key, property) in properties {
+            let path = join(prefix, key);
+            let property = resolve_ref(property, definitions);
+            if property.get("properties").is_some() {
+                self.objects.insert(path.clone());
+                self.walk(property, definitions, &path);
+            } else {
+                let types = match property.get("type") {
+                    Some(Value::String(t)) => vec![t.clone()],
+                    Some(Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str().map(String::from)).collect(),
+                    _ => Vec::new(),
+                };
+                self.leaves.insert(path, types);
+            }
+        }
+    }
+
+    fn is_known(&self, path: &str) -> bool {
+        self.objects.contains(path) || self.leaves.contains_key(path)
+    }
+}
+
+/// Follow `$ref` (directly or as a single `allOf` entry) into definitions
+fn resolve_ref<'a>(schema: &'a Value, definitions: &'a Value) -> &'a Value {
+    let reference = schema.get("$ref").or_else(|| {
+        schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .filter(|all| all.len() == 1)
+            .and_then(|all| all[0].get("$ref"))
+    });
+    reference
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/definitions/"))
+        .and_then(|name| definitions.get(name))
+        .unwrap_or(schema)
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Builder describing where each configuration layer comes from
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    system_file: Option<PathBuf>,
+    profile_file: Option<PathBuf>,
+    env_prefix: Option<String>,
+    overrides: BTreeMap<String, Value>,
+}
+
+impl ConfigLayers {
+    /// Defaults only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Machine-wide config file; skipped when missing
+    pub fn with_system_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.system_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Per-profile config file; skipped when missing
+    pub fn with_profile_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.profile_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Read `<PREFIX>__SECTION__KEY` variables (plus the legacy
+    /// `<PREFIX>_THEME`-style names)
+    pub fn with_env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// The profile file, or the system file when no profile file is set
+    pub fn writable_file(&self) -> Option<&Path> {
+        self.profile_file.as_deref().or(self.system_file.as_deref())
+    }
+
+    /// Set a runtime override for a dotted key path
+    pub fn set_override(&mut self, path: &str, value: Value) -> Result<()> {
+        let index = SchemaIndex::build();
+        if !index.leaves.contains_key(path) {
+            bail!("Unknown configuration key: {}", path);
+        }
+        self.overrides.insert(path.to_string(), value);
+        Ok(())
+    }
+
+    /// Remove a runtime override, returning whether one was set
+    pub fn clear_override(&mut self, path: &str) -> bool {
+        self.overrides.remove(path).is_some()
+    }
+
+    /// Runtime overrides by dotted key path
+    pub fn overrides(&self) -> &BTreeMap<String, Value> {
+        &self.overrides
+    }
+
+    /// Merge every layer and deserialize the result
+    pub async fn resolve(&self) -> Result<ResolvedConfig> {
+        let index = SchemaIndex::build();
+        let mut merged = serde_json::to_value(AppConfig::default())?;
+        let mut sources = BTreeMap::new();
+        let defaults = ConfigSource { layer: ConfigLayer::Defaults, origin: None };
+        for path in index.leaves.keys() {
+            sources.insert(path.clone(), defaults.clone());
+        }
+        let mut resolver = Resolver { index: &index, sources, unknown_keys: Vec::new() };
+
+        for (layer, path) in [
+            (ConfigLayer::System, &self.system_file),
+            (ConfigLayer::Profile, &self.profile_file),
+        ] {
+            let Some(path) = path else { continue };
+            if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+                debug!("{:?} config file {:?} not found, skipping", layer, path);
+                continue;
+            }
+            let mut value = read_document(path).await?;
+            migrate(&mut value).with_context(|| format!("Failed to migrate {}", path.display()))?;
+            let source = ConfigSource { layer, origin: Some(path.display().to_string()) };
+            resolver.merge(&mut merged, &value, "", &source);
+        }
+
+        if let Some(prefix) = &self.env_prefix {
+            let mut vars: Vec<(String, String)> = std::env::vars().collect();
+            vars.sort();
+            for (name, path, raw) in env_overrides(prefix, &vars) {
+                let source = ConfigSource { layer: ConfigLayer::Env, origin: Some(name.clone()) };
+                resolver.set_typed(&mut merged, &path, &raw, source)
+                    .with_context(|| format!("Invalid value in {}", name))?;
+            }
+        }
+
+        for (path, value) in &self.overrides {
+            let source = ConfigSource { layer: ConfigLayer::Runtime, origin: None };
+            resolver.set(&mut merged, path, value.clone(), source);
+        }
+
+        for unknown in &resolver.unknown_keys {
+            warn!(
+                "Unknown configuration key '{}' in {:?} layer{}",
+                unknown.path,
+                unknown.source.layer,
+                unknown.source.origin.as_ref().map(|o| format!(" ({})", o)).unwrap_or_default()
+            );
+        }
+
+        let config: AppConfig = serde_json::from_value(merged).context("Invalid layered configuration")?;
+        Ok(ResolvedConfig {
+            config,
+            sources: resolver.sources,
+            unknown_keys: resolver.unknown_keys,
+        })
+    }
+}
+
+/// Environment variables that map onto config keys, as (name, path, value)
+fn env_overrides(prefix: &str, vars: &[(String, String)]) -> Vec<(String, String, String)> {
+    let mut overrides = Vec::new();
+    // Aliases first so the explicit `__` form wins when both are set
+    for (suffix, path) in ENV_ALIASES {
+        let name = format!("{}_{}", prefix, suffix);
+        if let Some((_, raw)) = vars.iter().find(|(n, _)| *n == name) {
+            overrides.push((name, path.to_string(), raw.clone()));
+        }
+    }
+    let nested = format!("{}__", prefix);
+    for (name, raw) in vars {
+        if let Some(rest) = name.strip_prefix(&nested) {
+            let path = rest.split("__").map(str::to_lowercase).collect::<Vec<_>>().join(".");
+            overrides.push((name.clone(), path, raw.clone()));
+        }
+    }
+    overrides
+}
+
+struct Resolver<'a> {
+    index: &'a SchemaIndex,
+    sources: BTreeMap<String, ConfigSource>,
+    unknown_keys: Vec<UnknownKey>,
+}
+
+impl Resolver<'_> {
+    /// Deep-merge a layer's object into `target`
+    fn merge(&mut self, target: &mut Value, layer: &Value, prefix: &str, source: &ConfigSource) {
+        let Some(entries) = layer.as_object() else { return };
+        for (key, value) in entries {
+            let path = join(prefix, key);
+            if self.index.objects.contains(&path) && value.is_object() {
+                let child = object_entry(target, key);
+                self.merge(child, value, &path, source);
+            } else if self.index.is_known(&path) {
+                // A non-object at a section path is left for deserialization
+                // to reject with a type error
+                if let Value::Object(map) = target {
+                    map.insert(key.clone(), value.clone());
+                }
+                if self.index.leaves.contains_key(&path) {
+                    self.sources.insert(path, source.clone());
+                }
+            } else {
+                self.unknown_keys.push(UnknownKey { path, source: source.clone() });
+            }
+        }
+    }
+
+    /// Set a dotted path relative to `target`
+    fn set(&mut self, target: &mut Value, path: &str, value: Value, source: ConfigSource) {
+        let mut node = target;
+        let mut parts = path.split('.').peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                if let Value::Object(map) = node {
+                    map.insert(part.to_string(), value);
+                }
+                break;
+            }
+            node = object_entry(node, part);
+        }
+        if self.index.leaves.contains_key(path) {
+            self.sources.insert(path.to_string(), source);
+        }
+    }
+
+    /// Parse an environment string using the key's schema type
+    fn set_typed(&mut self, target: &mut Value, path: &str, raw: &str, source: ConfigSource) -> Result<()> {
+        let Some(types) = self.index.leaves.get(path) else {
+            self.unknown_keys.push(UnknownKey { path: path.to_string(), source });
+            return Ok(());
+        };
+        let value = typed_value(types, raw)?;
+        self.set(target, path, value, source);
+        Ok(())
+    }
+}
+
+fn object_entry<'v>(node: &'v mut Value, key: &str) -> &'v mut Value {
+    if !node.is_object() {
+        *node = Value::Object(Map::new());
+    }
+    node.as_object_mut()
+        .expect("node was just made an object")
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Object(Map::new()))
+}
+
+fn typed_value(types: &[String], raw: &str) -> Result<Value> {
+    let has = |t: &str| types.iter().any(|x| x == t);
+    if has("null") && (raw.is_empty() || raw.eq_ignore_ascii_case("null")) {
+        return Ok(Value::Null);
+    }
+    if has("boolean") {
+        return match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(Value::Bool(true)),
+            "false" | "0" | "no" | "off" => Ok(Value::Bool(false)),
+            _ => Err(anyhow!("expected a boolean, got '{}'", raw)),
+        };
+    }
+    if has("integer") {
+        if let Ok(n) = raw.parse::<u64>() {
+            return Ok(Value::from(n));
+        }
+        return raw.parse::<i64>().map(Value::from).map_err(|_| anyhow!("expected an integer, got '{}'", raw));
+    }
+    if has("number") {
+        return raw.parse::<f64>().map(Value::from).map_err(|_| anyhow!("expected a number, got '{}'", raw));
+    }
+    if has("array") {
+        if raw.trim_start().starts_with('[') {
+            return serde_json::from_str(raw).context("expected a JSON array");
+        }
+        return Ok(Value::Array(
+            raw.split(',').map(str::trim).filter(|s| !s.is_empty()).map(|s| Value::String(s.to_string())).collect(),
+        ));
+    }
+    Ok(Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    /// Sets process environment variables and restores their previous
+    /// values when dropped
+    #[derive(Default)]
+    struct EnvGuard {
+        saved: Vec<(String, Option<OsString>)>,
+    }
+
+    impl EnvGuard {
+        fn set(&mut self, key: &str, value: &str) {
+            if !self.saved.iter().any(|(k, _)| k == key) {
+                self.saved.push((key.to_string(), std::env::var_os(key)));
+            }
+            std::env::set_var(key, value);
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for (key, value) in self.saved.drain(..).rev() {
+                match value {
+                    Some(value) => std::env::set_var(&key, value),
+                    None => std::env::remove_var(&key),
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_layers_precedence_and_provenance() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let system = dir.path().join("system.toml");
+        let profile = dir.path().join("profile.json");
+        tokio::fs::write(&system, "[general]\ntheme = \"dark\"\nlanguage = \"de\"\n[network]\nmax_bandwith_bps = 5\n")
+            .await
+            .expect("write should succeed");
+        tokio::fs::write(&profile, r#"{"general": {"theme": "light"}, "network": {"max_redirects": 3}}"#)
+            .await
+            .expect("write should succeed");
+        let mut env = EnvGuard::default();
+        env.set("LAYERS_TEST__NETWORK__MAX_REDIRECTS", "7");
+        env.set("LAYERS_TEST__NETWORK__DNS_SERVERS", "1.1.1.1, 9.9.9.9");
+        env.set("LAYERS_TEST_PROXY_ENABLED", "yes");
+        env.set("LAYERS_TEST__PRIVACY__NOPE", "1");
+
+        let mut layers = ConfigLayers::new()
+            .with_system_file(&system)
+            .with_profile_file(&profile)
+            .with_env_prefix("LAYERS_TEST");
+        layers.set_override("general.theme", Value::from("system")).expect("override should succeed");
+        assert!(layers.set_override("general.colour", Value::from("red")).is_err());
+
+        let resolved = layers.resolve().await.expect("resolve should succeed");
+        let config = &resolved.config;
+        assert_eq!(config.general.theme, "system");
+        assert_eq!(config.general.language, "de");
+        assert_eq!(config.network.max_redirects, 7);
+        assert_eq!(config.network.dns_servers, vec!["1.1.1.1", "9.9.9.9"]);
+        assert!(config.proxy.enabled);
+
+        assert_eq!(resolved.source_of("general.theme").map(|s| s.layer), Some(ConfigLayer::Runtime));
+        assert_eq!(resolved.source_of("general.language").map(|s| s.layer), Some(ConfigLayer::System));
+        assert_eq!(
+            resolved.source_of("network.max_redirects"),
+            Some(&ConfigSource {
+                layer: ConfigLayer::Env,
+                origin: Some("LAYERS_TEST__NETWORK__MAX_REDIRECTS".to_string())
+            })
+        );
+        assert_eq!(resolved.source_of("logging.level").map(|s| s.layer), Some(ConfigLayer::Defaults));
+
+        let unknown: Vec<&str> = resolved.unknown_keys.iter().map(|k| k.path.as_str()).collect();
+        assert_eq!(unknown, vec!["network.max_bandwith_bps", "privacy.nope"]);
+
+        env.set("LAYERS_TEST__NETWORK__MAX_REDIRECTS", "lots");
+        assert!(layers.resolve().await.is_err());
+    }
+
+    #[test]
+    fn test_schema_closes_objects() {
+        let schema = config_schema();
+        assert_eq!(schema["title"], "AppConfig");
+        assert_eq!(schema["additionalProperties"], Value::Bool(false));
+        let network = &schema["definitions"]["NetworkConfig"];
+        assert_eq!(network["additionalProperties"], Value::Bool(false));
+        assert!(network["properties"]["max_bandwidth_bps"].is_object());
+    }
+}
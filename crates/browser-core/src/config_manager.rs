@@ -8,17 +8,25 @@
 
 use anyhow::{Context, Result};
 use toml;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
+use crate::config_layers::{config_schema, ConfigLayers, ConfigSource, UnknownKey};
+use crate::config_migration::{migrate_config, migrate_file, read_document, CURRENT_CONFIG_VERSION};
+use crate::proxy_rotation::ProxyRotationStrategy;
+
 /// Main application configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a AppConfig.
-#[derive(Default)]
 pub struct AppConfig {
+    /// Config document version, see `config_migration`
+    #[serde(default = "current_config_version")]
+    pub version: u32,
     /// General application settings
     pub general: GeneralConfig,
     /// Proxy settings
@@ -37,9 +45,28 @@ pub struct AppConfig {
     pub features: FeatureFlags,
 }
 
+fn current_config_version() -> u32 {
+    CURRENT_CONFIG_VERSION
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_CONFIG_VERSION,
+            general: GeneralConfig::default(),
+            proxy: ProxyConfig::default(),
+            privacy: PrivacyConfig::default(),
+            performance: PerformanceConfig::default(),
+            network: NetworkConfig::default(),
+            storage: StorageConfig::default(),
+            logging: LoggingConfig::default(),
+            features: FeatureFlags::default(),
+        }
+    }
+}
 
 /// General application settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a GeneralConfig.
 pub struct GeneralConfig {
     /// Application name
@@ -70,7 +97,7 @@ impl Default for GeneralConfig {
 }
 
 /// Proxy configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a ProxyConfig.
 pub struct ProxyConfig {
     /// Enable proxy by default
@@ -87,6 +114,12 @@ pub struct ProxyConfig {
     pub rotation_strategy: String,
     /// Rotation interval in seconds
     pub rotation_interval_secs: u64,
+    /// Requests served by a proxy before the `per_request` strategy rotates
+    #[serde(default = "default_rotation_requests")]
+    pub rotation_requests: usize,
+    /// Chance of rotating on each request with the `random` strategy
+    #[serde(default = "default_rotation_probability")]
+    pub rotation_probability: f64,
     /// Maximum proxy failures before blacklisting
     pub max_failures: u32,
     /// Proxy validation timeout in milliseconds
@@ -107,6 +140,8 @@ impl Default for ProxyConfig {
             rotation_enabled: true,
             rotation_strategy: "performance_based".to_string(),
             rotation_interval_secs: 300,
+            rotation_requests: default_rotation_requests(),
+            rotation_probability: default_rotation_probability(),
             max_failures: 3,
             validation_timeout_ms: 5000,
             use_free_providers: true,
@@ -115,8 +150,16 @@ impl Default for ProxyConfig {
     }
 }
 
+fn default_rotation_requests() -> usize {
+    100
+}
+
+fn default_rotation_probability() -> f64 {
+    0.1
+}
+
 /// Privacy configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a PrivacyConfig.
 pub struct PrivacyConfig {
     /// Block trackers
@@ -137,6 +180,12 @@ pub struct PrivacyConfig {
     pub global_privacy_control: bool,
     /// Cookie isolation level (none, session, strict)
     pub cookie_isolation: String,
+    /// Automatic expiry of stored browsing data
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Extra URL patterns blocked by the tracker blocker
+    #[serde(default)]
+    pub blocked_patterns: Vec<String>,
 }
 
 impl Default for PrivacyConfig {
@@ -151,12 +200,40 @@ impl Default for PrivacyConfig {
             do_not_track: true,
             global_privacy_control: true,
             cookie_isolation: "session".to_string(),
+            retention: RetentionConfig::default(),
+            blocked_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Data retention configuration. `None` keeps data of that type forever;
+/// every limit is opt-in.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Remove history entries not visited for this many days
+    pub history_days: Option<u32>,
+    /// Remove cookies set more than this many days ago (expired cookies are always removed)
+    pub cookie_max_age_days: Option<u32>,
+    /// Remove local storage for origins not accessed for this many days
+    pub local_storage_inactive_days: Option<u32>,
+    /// Interval between background sweeps in seconds
+    pub sweep_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            history_days: None,
+            cookie_max_age_days: None,
+            local_storage_inactive_days: None,
+            sweep_interval_secs: 3600,
         }
     }
 }
 
 /// Performance configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a PerformanceConfig.
 pub struct PerformanceConfig {
     /// Maximum concurrent tabs
@@ -196,7 +273,7 @@ impl Default for PerformanceConfig {
 }
 
 /// Network configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a NetworkConfig.
 pub struct NetworkConfig {
     /// Connection timeout in milliseconds
@@ -219,6 +296,9 @@ pub struct NetworkConfig {
     pub doh_server: String,
     /// Custom DNS servers
     pub dns_servers: Vec<String>,
+    /// Bandwidth cap in bytes per second (0 = unlimited)
+    #[serde(default)]
+    pub max_bandwidth_bps: u64,
 }
 
 impl Default for NetworkConfig {
@@ -234,12 +314,13 @@ impl Default for NetworkConfig {
             doh_enabled: true,
             doh_server: "https://cloudflare-dns.com/dns-query".to_string(),
             dns_servers: vec!["1.1.1.1".to_string(), "8.8.8.8".to_string()],
+            max_bandwidth_bps: 0,
         }
     }
 }
 
 /// Storage configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a StorageConfig.
 pub struct StorageConfig {
     /// Data directory path
@@ -252,8 +333,6 @@ pub struct StorageConfig {
     pub backup_interval_hours: u32,
     /// Maximum backups to keep
     pub max_backups: u32,
-    /// History retention days
-    pub history_retention_days: u32,
     /// Maximum history entries
     pub max_history_entries: u32,
 }
@@ -266,14 +345,13 @@ impl Default for StorageConfig {
             auto_backup: true,
             backup_interval_hours: 24,
             max_backups: 7,
-            history_retention_days: 90,
             max_history_entries: 10000,
         }
     }
 }
 
 /// Logging configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a LoggingConfig.
 pub struct LoggingConfig {
     /// Log level (trace, debug, info, warn, error)
@@ -310,7 +388,7 @@ impl Default for LoggingConfig {
 }
 
 /// Feature flags for enabling/disabling features
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// Represents a FeatureFlags.
 pub struct FeatureFlags {
     /// Enable experimental features
@@ -346,6 +424,21 @@ impl Default for FeatureFlags {
     }
 }
 
+/// Result of validating a configuration: errors make it unusable, warnings
+/// are advisory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigValidation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ConfigValidation {
+    /// Whether the configuration can be applied
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
 /// Configuration manager for loading, saving, and managing configuration
 #[allow(clippy::type_complexity)]
 pub struct ConfigManager {
@@ -353,6 +446,9 @@ pub struct ConfigManager {
     config_path: Option<PathBuf>,
     env_prefix: String,
     watchers: Arc<RwLock<Vec<Box<dyn Fn(&AppConfig) + Send + Sync>>>>,
+    layers: Option<Arc<RwLock<ConfigLayers>>>,
+    sources: Arc<RwLock<BTreeMap<String, ConfigSource>>>,
+    unknown_keys: Arc<RwLock<Vec<UnknownKey>>>,
 }
 
 impl ConfigManager {
@@ -363,6 +459,9 @@ impl ConfigManager {
             config_path: None,
             env_prefix: "PROXY_BROWSER".to_string(),
             watchers: Arc::new(RwLock::new(Vec::new())),
+            layers: None,
+            sources: Arc::new(RwLock::new(BTreeMap::new())),
+            unknown_keys: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -373,6 +472,9 @@ impl ConfigManager {
             config_path: Some(path.as_ref().to_path_buf()),
             env_prefix: "PROXY_BROWSER".to_string(),
             watchers: Arc::new(RwLock::new(Vec::new())),
+            layers: None,
+            sources: Arc::new(RwLock::new(BTreeMap::new())),
+            unknown_keys: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -382,19 +484,63 @@ impl ConfigManager {
         self
     }
 
+    /// Resolve configuration from explicit layers instead of a single file.
+    /// The profile (or system) file becomes the config path used for saving
+    /// and hot reload.
+    pub fn with_layers(mut self, layers: ConfigLayers) -> Self {
+        if self.config_path.is_none() {
+            self.config_path = layers.writable_file().map(Path::to_path_buf);
+        }
+        self.layers = Some(Arc::new(RwLock::new(layers)));
+        self
+    }
+
+    /// JSON Schema describing `AppConfig`
+    pub fn json_schema() -> serde_json::Value {
+        config_schema()
+    }
+
+    /// Path of the backing config file, if any
+    pub fn config_path(&self) -> Option<&Path> {
+        self.config_path.as_deref()
+    }
+
+    /// Parse a config file without applying it, migrating older versions
+    /// in memory
+    pub async fn parse_file(path: &Path) -> Result<AppConfig> {
+        let (config, _) = migrate_config(read_document(path).await?)?;
+        Ok(config)
+    }
+
+    /// Read the config file with environment overrides applied, without
+    /// touching the active configuration
+    pub async fn read_file(&self) -> Result<AppConfig> {
+        if let Some(layers) = &self.layers {
+            return Ok(layers.read().await.resolve().await?.config);
+        }
+        let path = self.config_path.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No config file path configured"))?;
+        let mut config = Self::parse_file(path).await?;
+        self.apply_env_overrides_to(&mut config);
+        Ok(config)
+    }
+
     /// Load configuration from file
     pub async fn load(&self) -> Result<()> {
+        if let Some(layers) = &self.layers {
+            let resolved = layers.read().await.resolve().await?;
+            *self.config.write().await = resolved.config;
+            *self.sources.write().await = resolved.sources;
+            *self.unknown_keys.write().await = resolved.unknown_keys;
+            self.notify_watchers().await;
+            return Ok(());
+        }
+
         if let Some(path) = &self.config_path {
             if path.exists() {
-                let content = tokio::fs::read_to_string(path).await
-                    .context("Failed to read config file")?;
-                
-                let loaded_config: AppConfig = if path.extension().is_some_and(|e| e == "json") {
-                    serde_json::from_str(&content).context("Failed to parse JSON config")?
-                } else {
-                    toml::from_str(&content).context("Failed to parse TOML config")?
-                };
-
+                // Upgrade older files on disk first, keeping a backup
+                migrate_file(path).await?;
+                let loaded_config = Self::parse_file(path).await?;
                 *self.config.write().await = loaded_config;
                 info!("Loaded configuration from {:?}", path);
             } else {
@@ -507,6 +653,56 @@ impl ConfigManager {
         self.config.read().await.features.clone()
     }
 
+    /// Which layer a dotted key's current value came from (layered mode)
+    pub async fn source_of(&self, path: &str) -> Option<ConfigSource> {
+        self.sources.read().await.get(path).cloned()
+    }
+
+    /// Unknown keys found by the last layered load
+    pub async fn unknown_keys(&self) -> Vec<UnknownKey> {
+        self.unknown_keys.read().await.clone()
+    }
+
+    /// Set a runtime override (highest precedence layer) and apply it. The
+    /// override is dropped again if the resulting configuration is invalid.
+    pub async fn set_override(&self, path: &str, value: serde_json::Value) -> Result<()> {
+        let layers = self.layers.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Runtime overrides require layered configuration"))?;
+        let mut layers = layers.write().await;
+        let previous = layers.overrides().get(path).cloned();
+        layers.set_override(path, value)?;
+
+        let resolved = match layers.resolve().await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                Self::restore_override(&mut layers, path, previous)?;
+                return Err(e);
+            }
+        };
+        let validation = Self::check(&resolved.config);
+        if !validation.is_valid() {
+            Self::restore_override(&mut layers, path, previous)?;
+            anyhow::bail!("Invalid override for {}: {}", path, validation.errors.join("; "));
+        }
+
+        *self.config.write().await = resolved.config;
+        *self.sources.write().await = resolved.sources;
+        *self.unknown_keys.write().await = resolved.unknown_keys;
+        drop(layers);
+        self.notify_watchers().await;
+        Ok(())
+    }
+
+    fn restore_override(layers: &mut ConfigLayers, path: &str, previous: Option<serde_json::Value>) -> Result<()> {
+        match previous {
+            Some(value) => layers.set_override(path, value),
+            None => {
+                layers.clear_override(path);
+                Ok(())
+            }
+        }
+    }
+
     /// Check if a feature is enabled
     pub async fn is_feature_enabled(&self, feature: &str) -> bool {
         let features = self.config.read().await.features.clone();
@@ -526,7 +722,11 @@ impl ConfigManager {
     /// Apply environment variable overrides
     async fn apply_env_overrides(&self) -> Result<()> {
         let mut config = self.config.write().await;
-        
+        self.apply_env_overrides_to(&mut config);
+        Ok(())
+    }
+
+    fn apply_env_overrides_to(&self, config: &mut AppConfig) {
         // General overrides
         if let Ok(val) = std::env::var(format!("{}_THEME", self.env_prefix)) {
             config.general.theme = val;
@@ -557,7 +757,6 @@ impl ConfigManager {
         }
 
         debug!("Applied environment variable overrides");
-        Ok(())
     }
 
     /// Notify configuration watchers
@@ -569,6 +768,14 @@ impl ConfigManager {
         }
     }
 
+    /// Swap in a new configuration without saving it, returning the previous
+    /// one. Used by hot reload, where the file is already the source.
+    pub async fn replace(&self, config: AppConfig) -> AppConfig {
+        let previous = std::mem::replace(&mut *self.config.write().await, config);
+        self.notify_watchers().await;
+        previous
+    }
+
     /// Reset configuration to defaults
     pub async fn reset(&self) -> Result<()> {
         *self.config.write().await = AppConfig::default();
@@ -582,9 +789,15 @@ impl ConfigManager {
         Ok(())
     }
 
-    /// Validate the current configuration
+    /// Validate the current configuration, returning errors and warnings
     pub async fn validate(&self) -> Result<Vec<String>> {
-        let config = self.config.read().await;
+        let validation = Self::check(&*self.config.read().await);
+        Ok(validation.errors.into_iter().chain(validation.warnings).collect())
+    }
+
+    /// Validate a configuration, separating errors from warnings
+    pub fn check(config: &AppConfig) -> ConfigValidation {
+        let mut errors = Vec::new();
         let mut warnings = Vec::new();
 
         // Validate proxy settings
@@ -599,12 +812,17 @@ impl ConfigManager {
 
         // Validate performance settings
         if config.performance.max_tabs == 0 {
-            warnings.push("max_tabs cannot be 0, using default".to_string());
+            errors.push("max_tabs cannot be 0".to_string());
         }
         if config.performance.tab_memory_limit_mb < 64 {
             warnings.push("tab_memory_limit_mb is very low, may cause issues".to_string());
         }
 
+        // Validate retention settings
+        if config.privacy.retention.sweep_interval_secs < 60 {
+            warnings.push("privacy.retention.sweep_interval_secs is very low".to_string());
+        }
+
         // Validate network settings
         if config.network.connection_timeout_ms < 1000 {
             warnings.push("connection_timeout_ms is very low".to_string());
@@ -613,10 +831,15 @@ impl ConfigManager {
         // Validate logging settings
         let valid_levels = ["trace", "debug", "info", "warn", "error"];
         if !valid_levels.contains(&config.logging.level.to_lowercase().as_str()) {
-            warnings.push(format!("Invalid log level: {}", config.logging.level));
+            errors.push(format!("Invalid log level: {}", config.logging.level));
         }
 
-        Ok(warnings)
+        // Validate rotation strategy
+        if let Err(e) = ProxyRotationStrategy::from_config(&config.proxy) {
+            errors.push(e.to_string());
+        }
+
+        ConfigValidation { errors, warnings }
     }
 
     /// Export configuration as JSON string
@@ -627,8 +850,9 @@ impl ConfigManager {
 
     /// Import configuration from JSON string
     pub async fn import_json(&self, json: &str) -> Result<()> {
-        let imported: AppConfig = serde_json::from_str(json)
+        let document = serde_json::from_str(json)
             .context("Failed to parse JSON config")?;
+        let (imported, _) = migrate_config(document)?;
         *self.config.write().await = imported;
         self.notify_watchers().await;
         
@@ -744,4 +968,28 @@ mod tests {
         let config = manager.get().await;
         assert_eq!(config.general.theme, "custom_theme");
     }
+
+    #[tokio::test]
+    async fn test_layered_runtime_override() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let profile = temp_dir.path().join("profile.toml");
+        tokio::fs::write(&profile, "[logging]\nlevel = \"debug\"\n").await
+            .expect("Write operation should succeed");
+
+        let manager = ConfigManager::new()
+            .with_layers(ConfigLayers::new().with_profile_file(&profile));
+        manager.load().await.expect("Load operation should succeed");
+        assert_eq!(manager.config_path(), Some(profile.as_path()));
+        assert_eq!(manager.get().await.logging.level, "debug");
+
+        manager.set_override("logging.level", serde_json::json!("warn")).await
+            .expect("Override should succeed");
+        assert_eq!(manager.get().await.logging.level, "warn");
+        assert_eq!(manager.source_of("logging.level").await.map(|s| s.layer),
+            Some(crate::config_layers::ConfigLayer::Runtime));
+
+        assert!(manager.set_override("logging.level", serde_json::json!("loud")).await.is_err());
+        assert!(manager.set_override("performance.max_tabs", serde_json::json!("many")).await.is_err());
+        assert_eq!(manager.get().await.logging.level, "warn");
+    }
 }
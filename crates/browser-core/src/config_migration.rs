@@ -0,0 +1,293 @@
+//! Configuration Migration Module
+//!
+//! Upgrades older config documents to the current `AppConfig` shape:
+//! - Documents carry a top-level `version`; files without one are version 1
+//! - Migrations run step by step on the raw document, before deserializing,
+//!   so renamed and moved keys are carried over instead of dropped
+//! - Helpers for the usual edits: renames, moves and unit changes
+//! - Files are backed up before being rewritten in the new shape
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::config_manager::AppConfig;
+
+/// Version written by this build
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+/// Version assumed for documents without a `version` key
+const UNVERSIONED_CONFIG_VERSION: u32 = 1;
+
+/// Default of version 1's `storage.history_retention_days`
+const LEGACY_HISTORY_RETENTION_DAYS: u64 = 90;
+
+/// One upgrade step from `from_version` to `from_version + 1`
+pub struct ConfigMigration {
+    pub from_version: u32,
+    pub description: &'static str,
+    apply: fn(&mut Value) -> Result<()>,
+}
+
+/// The migration chain, in order
+pub fn migrations() -> Vec<ConfigMigration> {
+    vec![ConfigMigration {
+        from_version: 1,
+        description: "Move storage.history_retention_days into privacy.retention.history_days",
+        apply: |doc| {
+            // The retention policy owns history age now and is opt-in. Version 1
+            // wrote its default into every saved file, so only a value other
+            // than that default was chosen by the user. An explicit policy
+            // value wins over the legacy storage setting.
+            let legacy = remove_path(doc, "storage.history_retention_days")
+                .filter(|days| days.as_u64() != Some(LEGACY_HISTORY_RETENTION_DAYS));
+            if let Some(days) = legacy {
+                if get_path(doc, "privacy.retention.history_days").is_none() {
+                    set_path(doc, "privacy.retention.history_days", days);
+                }
+            }
+            Ok(())
+        },
+    }]
+}
+
+/// What a migration run did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Descriptions of the steps applied, in order
+    pub applied: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Whether the document was already current
+    pub fn is_noop(&self) -> bool {
+        self.applied.is_empty()
+    }
+}
+
+/// Version of a raw config document
+pub fn document_version(doc: &Value) -> Result<u32> {
+    match doc.get("version") {
+        None => Ok(UNVERSIONED_CONFIG_VERSION),
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_context(|| format!("Invalid config version: {}", value)),
+    }
+}
+
+/// Upgrade a raw document in place to `CURRENT_CONFIG_VERSION`
+pub fn migrate(doc: &mut Value) -> Result<MigrationReport> {
+    if !doc.is_object() {
+        bail!("Config document must be a table of sections");
+    }
+    let from_version = document_version(doc)?;
+    if from_version > CURRENT_CONFIG_VERSION {
+        bail!(
+            "Config version {} is newer than the supported version {}",
+            from_version,
+            CURRENT_CONFIG_VERSION
+        );
+    }
+
+    let mut report = MigrationReport { from_version, to_version: from_version, applied: Vec::new() };
+    for migration in migrations() {
+        if migration.from_version < report.to_version {
+            continue;
+        }
+        (migration.apply)(doc)
+            .with_context(|| format!("Config migration from version {} failed", migration.from_version))?;
+        report.to_version = migration.from_version + 1;
+        report.applied.push(migration.description.to_string());
+    }
+    if let Value::Object(map) = doc {
+        map.insert("version".to_string(), Value::from(CURRENT_CONFIG_VERSION));
+    }
+    Ok(report)
+}
+
+/// Migrate a raw document and deserialize it
+pub fn migrate_config(mut doc: Value) -> Result<(AppConfig, MigrationReport)> {
+    let report = migrate(&mut doc)?;
+    let config = serde_json::from_value(doc).context("Failed to parse migrated config")?;
+    Ok((config, report))
+}
+
+/// `deserialize_with` helper for stored configs that may predate the
+/// current version, such as those inside application backups
+pub fn deserialize_migrated<'de, D>(deserializer: D) -> std::result::Result<Option<AppConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(doc) = Option::<Value>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    migrate_config(doc)
+        .map(|(config, _)| Some(config))
+        .map_err(|e| serde::de::Error::custom(format!("{:#}", e)))
+}
+
+/// Read a TOML or JSON config file (by extension) as a raw document
+pub async fn read_document(path: &Path) -> Result<Value> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    if path.extension().is_some_and(|e| e == "json") {
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse JSON config {}", path.display()))
+    } else {
+        let table: toml::Value =
+            toml::from_str(&content).with_context(|| format!("Failed to parse TOML config {}", path.display()))?;
+        Ok(serde_json::to_value(table)?)
+    }
+}
+
+/// Migrate a config file on disk. The original is copied to
+/// `<name>.v<version>.bak` before the upgraded document replaces it.
+pub async fn migrate_file(path: &Path) -> Result<MigrationReport> {
+    let mut doc = read_document(path).await?;
+    let report = migrate(&mut doc)?;
+    if report.is_noop() {
+        return Ok(report);
+    }
+
+    let backup = backup_path(path, report.from_version).await;
+    tokio::fs::copy(path, &backup)
+        .await
+        .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+
+    let content = if path.extension().is_some_and(|e| e == "json") {
+        serde_json::to_string_pretty(&doc)?
+    } else {
+        toml::to_string_pretty(&doc)?
+    };
+    let tmp = path.with_extension("migrating");
+    tokio::fs::write(&tmp, content).await?;
+    tokio::fs::rename(&tmp, path).await?;
+
+    info!(
+        "Migrated config {:?} from version {} to {} (backup at {:?})",
+        path, report.from_version, report.to_version, backup
+    );
+    Ok(report)
+}
+
+/// First free backup name, so earlier backups are never overwritten
+async fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut candidate = path.with_file_name(format!("{}.v{}.bak", name, version));
+    let mut n = 1;
+    while tokio::fs::try_exists(&candidate).await.unwrap_or(false) {
+        candidate = path.with_file_name(format!("{}.v{}.{}.bak", name, version, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Value at a dotted path
+pub fn get_path<'a>(doc: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(doc, |node, key| node.get(key))
+}
+
+/// Remove and return the value at a dotted path
+pub fn remove_path(doc: &mut Value, path: &str) -> Option<Value> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (get_path_mut(doc, parent)?, key),
+        None => (doc, path),
+    };
+    parent.as_object_mut()?.remove(key)
+}
+
+/// Set the value at a dotted path, creating intermediate tables
+pub fn set_path(doc: &mut Value, path: &str, value: Value) {
+    let mut node = doc;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        let map = node.as_object_mut().expect("node was just made an object");
+        if parts.peek().is_none() {
+            map.insert(part.to_string(), value);
+            return;
+        }
+        node = map.entry(part.to_string()).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+fn get_path_mut<'a>(doc: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.').try_fold(doc, |node, key| node.get_mut(key))
+}
+
+/// Move a key (or whole section) to a new dotted path. An existing value at
+/// the destination is kept. Returns whether anything moved.
+pub fn rename_key(doc: &mut Value, from: &str, to: &str) -> bool {
+    if get_path(doc, to).is_some() {
+        return false;
+    }
+    match remove_path(doc, from) {
+        Some(value) => {
+            set_path(doc, to, value);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Rewrite the value at a dotted path, e.g. for a unit change. Returns
+/// whether the key was present.
+pub fn convert_value(doc: &mut Value, path: &str, convert: impl FnOnce(Value) -> Result<Value>) -> Result<bool> {
+    let Some(slot) = get_path_mut(doc, path) else {
+        return Ok(false);
+    };
+    *slot = convert(slot.take()).with_context(|| format!("Failed to convert {}", path))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_edit_helpers() {
+        let mut doc = json!({
+            "network": { "timeout_secs": 5 },
+            "legacy": { "theme": "dark" },
+            "general": {}
+        });
+
+        assert!(rename_key(&mut doc, "legacy.theme", "general.theme"));
+        assert!(!rename_key(&mut doc, "legacy.missing", "general.language"));
+        assert!(rename_key(&mut doc, "network.timeout_secs", "network.connection_timeout_ms"));
+        assert!(convert_value(&mut doc, "network.connection_timeout_ms", |v| {
+            Ok(Value::from(v.as_u64().unwrap_or_default() * 1000))
+        })
+        .expect("convert should succeed"));
+        assert!(rename_key(&mut doc, "legacy", "archive.legacy"));
+
+        assert_eq!(
+            doc,
+            json!({
+                "network": { "connection_timeout_ms": 5000 },
+                "general": { "theme": "dark" },
+                "archive": { "legacy": {} }
+            })
+        );
+    }
+
+    #[test]
+    fn test_version_checks() {
+        let mut current = json!({ "version": CURRENT_CONFIG_VERSION });
+        assert!(migrate(&mut current).expect("migrate should succeed").is_noop());
+
+        let mut future = json!({ "version": CURRENT_CONFIG_VERSION + 1 });
+        assert!(migrate(&mut future).is_err());
+
+        let mut garbage = json!({ "version": "two" });
+        assert!(migrate(&mut garbage).is_err());
+    }
+}
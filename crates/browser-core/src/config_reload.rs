@@ -0,0 +1,495 @@
+//! Configuration Hot Reload Module
+//!
+//! Watches the `ConfigManager`'s file and applies edits without a restart:
+//! - Polls the file for changes (modification time and content hash)
+//! - Validates a new configuration before swapping it in
+//! - Publishes typed per-section change notifications
+//! - Reconfigures live components through `ConfigListener`s
+//! - Rolls back to the last good configuration when an edit is rejected or a
+//!   component fails to apply it
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config_manager::{
+    AppConfig, ConfigManager, FeatureFlags, GeneralConfig, LoggingConfig, NetworkConfig, PerformanceConfig,
+    PrivacyConfig, ProxyConfig, StorageConfig,
+};
+use crate::network_intelligence::BandwidthManager;
+use crate::privacy_fortress::{BlockingRule, BlockingRuleType, TrackerBlocker};
+use crate::proxy_rotation::{ProxyRotationManager, ProxyRotationStrategy};
+use crate::retention::DataRetentionManager;
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Top-level section of `AppConfig`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    General,
+    Proxy,
+    Privacy,
+    Performance,
+    Network,
+    Storage,
+    Logging,
+    Features,
+}
+
+impl ConfigSection {
+    /// Sections whose values differ between two configurations
+    pub fn changed(old: &AppConfig, new: &AppConfig) -> Vec<ConfigSection> {
+        fn differs<T: Serialize>(a: &T, b: &T) -> bool {
+            serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+        }
+
+        let mut sections = Vec::new();
+        let checks = [
+            (ConfigSection::General, differs(&old.general, &new.general)),
+            (ConfigSection::Proxy, differs(&old.proxy, &new.proxy)),
+            (ConfigSection::Privacy, differs(&old.privacy, &new.privacy)),
+            (ConfigSection::Performance, differs(&old.performance, &new.performance)),
+            (ConfigSection::Network, differs(&old.network, &new.network)),
+            (ConfigSection::Storage, differs(&old.storage, &new.storage)),
+            (ConfigSection::Logging, differs(&old.logging, &new.logging)),
+            (ConfigSection::Features, differs(&old.features, &new.features)),
+        ];
+        for (section, changed) in checks {
+            if changed {
+                sections.push(section);
+            }
+        }
+        sections
+    }
+}
+
+/// A typed change to one section, carrying its old and new values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "section", rename_all = "snake_case")]
+pub enum ConfigChange {
+    General { old: GeneralConfig, new: GeneralConfig },
+    Proxy { old: ProxyConfig, new: ProxyConfig },
+    Privacy { old: PrivacyConfig, new: PrivacyConfig },
+    Performance { old: PerformanceConfig, new: PerformanceConfig },
+    Network { old: NetworkConfig, new: NetworkConfig },
+    Storage { old: StorageConfig, new: StorageConfig },
+    Logging { old: LoggingConfig, new: LoggingConfig },
+    Features { old: FeatureFlags, new: FeatureFlags },
+}
+
+impl ConfigChange {
+    /// Typed changes for every section that differs
+    pub fn between(old: &AppConfig, new: &AppConfig) -> Vec<ConfigChange> {
+        ConfigSection::changed(old, new)
+            .into_iter()
+            .map(|section| match section {
+                ConfigSection::General => ConfigChange::General { old: old.general.clone(), new: new.general.clone() },
+                ConfigSection::Proxy => ConfigChange::Proxy { old: old.proxy.clone(), new: new.proxy.clone() },
+                ConfigSection::Privacy => ConfigChange::Privacy { old: old.privacy.clone(), new: new.privacy.clone() },
+                ConfigSection::Performance => ConfigChange::Performance {
+                    old: old.performance.clone(),
+                    new: new.performance.clone(),
+                },
+                ConfigSection::Network => ConfigChange::Network { old: old.network.clone(), new: new.network.clone() },
+                ConfigSection::Storage => ConfigChange::Storage { old: old.storage.clone(), new: new.storage.clone() },
+                ConfigSection::Logging => ConfigChange::Logging { old: old.logging.clone(), new: new.logging.clone() },
+                ConfigSection::Features => ConfigChange::Features {
+                    old: old.features.clone(),
+                    new: new.features.clone(),
+                },
+            })
+            .collect()
+    }
+
+    /// Section this change belongs to
+    pub fn section(&self) -> ConfigSection {
+        match self {
+            ConfigChange::General { .. } => ConfigSection::General,
+            ConfigChange::Proxy { .. } => ConfigSection::Proxy,
+            ConfigChange::Privacy { .. } => ConfigSection::Privacy,
+            ConfigChange::Performance { .. } => ConfigSection::Performance,
+            ConfigChange::Network { .. } => ConfigSection::Network,
+            ConfigChange::Storage { .. } => ConfigSection::Storage,
+            ConfigChange::Logging { .. } => ConfigSection::Logging,
+            ConfigChange::Features { .. } => ConfigSection::Features,
+        }
+    }
+}
+
+/// Outcome of a reload attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ConfigReloadEvent {
+    /// The new configuration is active
+    Applied { sections: Vec<ConfigSection>, warnings: Vec<String> },
+    /// The file could not be parsed or failed validation; nothing changed
+    Rejected { error: String },
+    /// A component could not apply the configuration; the previous one was
+    /// restored everywhere
+    RolledBack { error: String, sections: Vec<ConfigSection> },
+}
+
+/// A live component reconfigured when its sections change
+#[async_trait]
+pub trait ConfigListener: Send + Sync {
+    /// Sections this listener depends on
+    fn sections(&self) -> Vec<ConfigSection>;
+
+    /// Apply `config`; called with the previous configuration on rollback
+    async fn apply_config(&self, config: &AppConfig) -> Result<()>;
+}
+
+#[async_trait]
+impl ConfigListener for RwLock<ProxyRotationManager> {
+    fn sections(&self) -> Vec<ConfigSection> {
+        vec![ConfigSection::Proxy]
+    }
+
+    async fn apply_config(&self, config: &AppConfig) -> Result<()> {
+        let strategy = ProxyRotationStrategy::from_config(&config.proxy)?;
+        self.write().await.update_strategy(strategy).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConfigListener for RwLock<TrackerBlocker> {
+    fn sections(&self) -> Vec<ConfigSection> {
+        vec![ConfigSection::Privacy]
+    }
+
+    async fn apply_config(&self, config: &AppConfig) -> Result<()> {
+        let rules = config
+            .privacy
+            .blocked_patterns
+            .iter()
+            .map(|pattern| BlockingRule {
+                pattern: pattern.clone(),
+                rule_type: BlockingRuleType::Custom,
+                enabled: true,
+            })
+            .collect();
+        let mut blocker = self.write().await;
+        blocker.set_enabled(config.privacy.block_trackers);
+        blocker.set_config_rules(rules);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConfigListener for RwLock<BandwidthManager> {
+    fn sections(&self) -> Vec<ConfigSection> {
+        vec![ConfigSection::Network]
+    }
+
+    async fn apply_config(&self, config: &AppConfig) -> Result<()> {
+        self.write().await.set_max_bandwidth(config.network.max_bandwidth_bps);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConfigListener for DataRetentionManager {
+    fn sections(&self) -> Vec<ConfigSection> {
+        vec![ConfigSection::Privacy]
+    }
+
+    async fn apply_config(&self, config: &AppConfig) -> Result<()> {
+        self.set_policy(config.privacy.retention.clone()).await;
+        Ok(())
+    }
+}
+
+/// File fingerprint used to detect edits
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    sha256: String,
+}
+
+/// Watches the config file and applies changes to the manager and listeners
+pub struct ConfigHotReloader {
+    manager: Arc<ConfigManager>,
+    listeners: RwLock<Vec<Arc<dyn ConfigListener>>>,
+    changes: broadcast::Sender<ConfigChange>,
+    events: broadcast::Sender<ConfigReloadEvent>,
+    last_stamp: Mutex<Option<FileStamp>>,
+    /// Serialises reloads so a manual reload never races the watcher
+    reload_lock: Mutex<()>,
+    shutdown: Notify,
+    poll_interval: Duration,
+}
+
+impl ConfigHotReloader {
+    /// Create a reloader for a manager with a config path
+    pub fn new(manager: Arc<ConfigManager>) -> Result<Self> {
+        if manager.config_path().is_none() {
+            return Err(anyhow!("Hot reload requires a config file path"));
+        }
+        let (changes, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Ok(Self {
+            manager,
+            listeners: RwLock::new(Vec::new()),
+            changes,
+            events,
+            last_stamp: Mutex::new(None),
+            reload_lock: Mutex::new(()),
+            shutdown: Notify::new(),
+            poll_interval: Duration::from_secs(2),
+        })
+    }
+
+    /// How often the file is checked for edits
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Register a live component and bring it in line with the current config
+    pub async fn register(&self, listener: Arc<dyn ConfigListener>) -> Result<()> {
+        listener.apply_config(&self.manager.get().await).await?;
+        self.listeners.write().await.push(listener);
+        Ok(())
+    }
+
+    /// Receive typed per-section changes
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<ConfigChange> {
+        self.changes.subscribe()
+    }
+
+    /// Receive the outcome of every reload attempt
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ConfigReloadEvent> {
+        self.events.subscribe()
+    }
+
+    /// Reload the file now if it changed since the last check. A missing
+    /// file is not an error: the defaults stay in effect until one is written.
+    pub async fn check_now(&self) -> Result<Option<ConfigReloadEvent>> {
+        if !self.manager.config_path().is_some_and(|path| path.exists()) {
+            return Ok(None);
+        }
+        let stamp = self.stamp().await?;
+        {
+            let mut last = self.last_stamp.lock().await;
+            if last.as_ref() == Some(&stamp) {
+                return Ok(None);
+            }
+            *last = Some(stamp);
+        }
+        Ok(Some(self.reload().await))
+    }
+
+    /// Read, validate and apply the config file unconditionally
+    pub async fn reload(&self) -> ConfigReloadEvent {
+        let _guard = self.reload_lock.lock().await;
+        let event = self.try_reload().await;
+        match &event {
+            ConfigReloadEvent::Applied { sections, .. } => {
+                if !sections.is_empty() {
+                    info!("Configuration reloaded: {:?}", sections);
+                }
+            }
+            ConfigReloadEvent::Rejected { error } => warn!("Configuration edit rejected: {}", error),
+            ConfigReloadEvent::RolledBack { error, .. } => warn!("Configuration rolled back: {}", error),
+        }
+        let _ = self.events.send(event.clone());
+        event
+    }
+
+    /// Spawn the file watcher
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            // Record the current file so only later edits trigger a reload
+            if let Ok(stamp) = self.stamp().await {
+                *self.last_stamp.lock().await = Some(stamp);
+            }
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(self.poll_interval) => {}
+                    _ = self.shutdown.notified() => break,
+                }
+                if let Err(e) = self.check_now().await {
+                    warn!("Failed to check config file: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Stop the file watcher
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+    }
+
+    async fn try_reload(&self) -> ConfigReloadEvent {
+        let candidate = match self.manager.read_file().await {
+            Ok(config) => config,
+            Err(e) => return ConfigReloadEvent::Rejected { error: format!("{:#}", e) },
+        };
+
+        let validation = ConfigManager::check(&candidate);
+        if !validation.is_valid() {
+            return ConfigReloadEvent::Rejected { error: validation.errors.join("; ") };
+        }
+
+        let current = self.manager.get().await;
+        let changes = ConfigChange::between(&current, &candidate);
+        let sections: Vec<ConfigSection> = changes.iter().map(|c| c.section()).collect();
+        if sections.is_empty() {
+            return ConfigReloadEvent::Applied { sections, warnings: validation.warnings };
+        }
+
+        let previous = self.manager.replace(candidate.clone()).await;
+        let listeners: Vec<Arc<dyn ConfigListener>> = self
+            .listeners
+            .read()
+            .await
+            .iter()
+            .filter(|l| l.sections().iter().any(|s| sections.contains(s)))
+            .cloned()
+            .collect();
+
+        let mut applied: Vec<&Arc<dyn ConfigListener>> = Vec::new();
+        for listener in &listeners {
+            if let Err(e) = listener.apply_config(&candidate).await {
+                // Undo in reverse so every component is back on the old config
+                for done in applied.iter().rev() {
+                    if let Err(undo) = done.apply_config(&previous).await {
+                        warn!("Failed to roll back component configuration: {}", undo);
+                    }
+                }
+                self.manager.replace(previous).await;
+                return ConfigReloadEvent::RolledBack { error: format!("{:#}", e), sections };
+            }
+            applied.push(listener);
+        }
+
+        for change in changes {
+            let _ = self.changes.send(change);
+        }
+        ConfigReloadEvent::Applied { sections, warnings: validation.warnings }
+    }
+
+    async fn stamp(&self) -> Result<FileStamp> {
+        let path = self
+            .manager
+            .config_path()
+            .ok_or_else(|| anyhow!("No config file path configured"))?;
+        let content = tokio::fs::read(path).await?;
+        let modified = tokio::fs::metadata(path).await?.modified().ok();
+        Ok(FileStamp {
+            modified,
+            sha256: hex::encode(Sha256::digest(&content)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingListener;
+
+    #[async_trait]
+    impl ConfigListener for FailingListener {
+        fn sections(&self) -> Vec<ConfigSection> {
+            vec![ConfigSection::Network]
+        }
+
+        async fn apply_config(&self, config: &AppConfig) -> Result<()> {
+            if config.network.max_bandwidth_bps == 42 {
+                return Err(anyhow!("refusing bandwidth 42"));
+            }
+            Ok(())
+        }
+    }
+
+    async fn write_config(path: &std::path::Path, edit: impl FnOnce(&mut AppConfig)) {
+        let mut config = AppConfig::default();
+        edit(&mut config);
+        tokio::fs::write(path, serde_json::to_string_pretty(&config).expect("serialize should succeed"))
+            .await
+            .expect("write should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_and_notifies() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let path = dir.path().join("config.json");
+        write_config(&path, |_| {}).await;
+
+        let manager = Arc::new(ConfigManager::with_path(&path));
+        manager.load().await.expect("load should succeed");
+        let reloader = ConfigHotReloader::new(manager.clone()).expect("reloader should succeed");
+        let blocker = Arc::new(RwLock::new(TrackerBlocker::new()));
+        let bandwidth = Arc::new(RwLock::new(BandwidthManager::new(0)));
+        reloader.register(blocker.clone()).await.expect("register should succeed");
+        reloader.register(bandwidth.clone()).await.expect("register should succeed");
+        let mut changes = reloader.subscribe_changes();
+        assert!(reloader.check_now().await.expect("check should succeed").is_some());
+
+        write_config(&path, |c| {
+            c.privacy.block_trackers = false;
+            c.network.max_bandwidth_bps = 1_000_000;
+        })
+        .await;
+        let event = reloader.check_now().await.expect("check should succeed");
+        assert!(matches!(
+            event,
+            Some(ConfigReloadEvent::Applied { ref sections, .. })
+                if sections == &vec![ConfigSection::Privacy, ConfigSection::Network]
+        ));
+        assert!(matches!(changes.recv().await, Ok(ConfigChange::Privacy { .. })));
+        assert!(!blocker.write().await.should_block("https://doubleclick.net/ad"));
+        assert_eq!(bandwidth.read().await.get_report().max_bandwidth_bps, 1_000_000);
+        assert!(reloader.check_now().await.expect("check should succeed").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bad_edits_are_rejected_or_rolled_back() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let path = dir.path().join("config.json");
+        write_config(&path, |_| {}).await;
+
+        let manager = Arc::new(ConfigManager::with_path(&path));
+        manager.load().await.expect("load should succeed");
+        let reloader = ConfigHotReloader::new(manager.clone()).expect("reloader should succeed");
+        let bandwidth = Arc::new(RwLock::new(BandwidthManager::new(0)));
+        reloader.register(bandwidth.clone()).await.expect("register should succeed");
+        reloader.register(Arc::new(FailingListener)).await.expect("register should succeed");
+
+        tokio::fs::write(&path, "{ not json").await.expect("write should succeed");
+        assert!(matches!(reloader.reload().await, ConfigReloadEvent::Rejected { .. }));
+
+        write_config(&path, |c| c.proxy.rotation_strategy = "bogus".into()).await;
+        assert!(matches!(reloader.reload().await, ConfigReloadEvent::Rejected { .. }));
+        assert_eq!(manager.get().await.proxy.rotation_strategy, "performance_based");
+
+        write_config(&path, |c| c.network.max_bandwidth_bps = 42).await;
+        assert!(matches!(reloader.reload().await, ConfigReloadEvent::RolledBack { .. }));
+        assert_eq!(manager.get().await.network.max_bandwidth_bps, 0);
+        assert_eq!(bandwidth.read().await.get_report().max_bandwidth_bps, 0);
+    }
+
+    #[tokio::test]
+    async fn test_config_file_written_after_start_is_picked_up() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let path = dir.path().join("config.json");
+
+        let manager = Arc::new(ConfigManager::with_path(&path));
+        manager.load().await.expect("load should succeed");
+        let reloader = ConfigHotReloader::new(manager.clone()).expect("reloader should succeed");
+        assert!(reloader.check_now().await.expect("missing file should not be an error").is_none());
+
+        write_config(&path, |c| c.network.max_bandwidth_bps = 5).await;
+        assert!(reloader.check_now().await.expect("check should succeed").is_some());
+        assert_eq!(manager.get().await.network.max_bandwidth_bps, 5);
+    }
+}
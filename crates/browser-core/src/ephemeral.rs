@@ -0,0 +1,337 @@
+//! Ephemeral Container Module
+//!
+//! Provides incognito-style containers for tabs including:
+//! - Memory-only storage that never records history or leaves the container
+//! - A per-container network interceptor for captured traffic
+//! - Teardown of the local proxy listener and sticky proxy session on close
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::local_proxy::{LocalProxyManager, NetworkInterceptor};
+use crate::proxy::ProxySettings;
+use crate::proxy_rotation::ProxyRotationManager;
+use crate::storage::StorageEngine;
+
+/// Data scoped to a single ephemeral tab
+pub struct EphemeralContainer {
+    pub container_id: String,
+    pub tab_id: String,
+    pub created_at: DateTime<Utc>,
+    pub proxy_url: Option<String>,
+    storage: Arc<StorageEngine>,
+    interceptor: Arc<NetworkInterceptor>,
+}
+
+impl EphemeralContainer {
+    /// Memory-only storage for this container
+    pub fn storage(&self) -> Arc<StorageEngine> {
+        self.storage.clone()
+    }
+
+    /// Traffic captured for this container
+    pub fn interceptor(&self) -> Arc<NetworkInterceptor> {
+        self.interceptor.clone()
+    }
+}
+
+/// Summary of what was destroyed when a container closed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EphemeralTeardownReport {
+    pub tab_id: String,
+    pub container_id: String,
+    pub cookies_cleared: usize,
+    pub local_storage_origins_cleared: usize,
+    pub captured_requests_cleared: usize,
+    pub proxy_listener_stopped: bool,
+    pub proxy_session_ended: bool,
+}
+
+/// Registry of ephemeral containers keyed by tab ID
+pub struct EphemeralContainerManager {
+    containers: Arc<RwLock<HashMap<String, Arc<EphemeralContainer>>>>,
+    /// Tab IDs whose container is being created; only changed while the
+    /// `containers` write lock is held
+    reserved: Mutex<HashSet<String>>,
+    local_proxies: Option<Arc<LocalProxyManager>>,
+    proxy_rotation: Option<Arc<RwLock<ProxyRotationManager>>>,
+}
+
+impl EphemeralContainerManager {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            containers: Arc::new(RwLock::new(HashMap::new())),
+            reserved: Mutex::new(HashSet::new()),
+            local_proxies: None,
+            proxy_rotation: None,
+        }
+    }
+
+    /// Start a dedicated local proxy listener for each container
+    pub fn with_local_proxies(mut self, local_proxies: Arc<LocalProxyManager>) -> Self {
+        self.local_proxies = Some(local_proxies);
+        self
+    }
+
+    /// End the tab's sticky proxy session when its container closes
+    pub fn with_proxy_rotation(mut self, rotation: Arc<RwLock<ProxyRotationManager>>) -> Self {
+        self.proxy_rotation = Some(rotation);
+        self
+    }
+
+    /// Create a container for a tab
+    ///
+    /// # Arguments
+    /// * `tab_id` - ID under which the tab's proxy listener and session are registered
+    /// * `upstream_proxy` - Upstream proxy for the container's local listener
+    pub async fn create_container(
+        &self,
+        tab_id: &str,
+        upstream_proxy: Option<ProxySettings>,
+    ) -> Result<Arc<EphemeralContainer>> {
+        // Reserved before the listener starts, so a concurrent call for the
+        // same tab cannot start a second one
+        {
+            let containers = self.containers.write().await;
+            let mut reserved = self.reserved.lock().unwrap_or_else(|e| e.into_inner());
+            if containers.contains_key(tab_id) || !reserved.insert(tab_id.to_string()) {
+                return Err(anyhow!("Tab {} already has an ephemeral container", tab_id));
+            }
+        }
+
+        // The container's listener feeds its interceptor
+        let interceptor = Arc::new(NetworkInterceptor::new());
+        let proxy_url = match &self.local_proxies {
+            Some(local_proxies) => local_proxies
+                .create_intercepted_proxy_for_tab(tab_id, upstream_proxy, Some(interceptor.clone()))
+                .await
+                .map(Some),
+            None => Ok(None),
+        };
+
+        let mut containers = self.containers.write().await;
+        self.reserved.lock().unwrap_or_else(|e| e.into_inner()).remove(tab_id);
+        let proxy_url = proxy_url?;
+        let container = Arc::new(EphemeralContainer {
+            container_id: Uuid::new_v4().to_string(),
+            tab_id: tab_id.to_string(),
+            created_at: Utc::now(),
+            proxy_url,
+            storage: Arc::new(StorageEngine::ephemeral()),
+            interceptor,
+        });
+        containers.insert(tab_id.to_string(), container.clone());
+        info!("Created ephemeral container {} for tab {}", container.container_id, tab_id);
+        Ok(container)
+    }
+
+    /// Get the container for a tab
+    pub async fn get(&self, tab_id: &str) -> Option<Arc<EphemeralContainer>> {
+        self.containers.read().await.get(tab_id).cloned()
+    }
+
+    /// Whether a tab is ephemeral
+    pub async fn is_ephemeral(&self, tab_id: &str) -> bool {
+        self.containers.read().await.contains_key(tab_id)
+    }
+
+    /// Storage a tab should write to: its container's storage if the tab is
+    /// ephemeral, otherwise the given persistent storage
+    pub async fn storage_for(&self, tab_id: &str, persistent: &Arc<StorageEngine>) -> Arc<StorageEngine> {
+        match self.get(tab_id).await {
+            Some(container) => container.storage(),
+            None => persistent.clone(),
+        }
+    }
+
+    /// Number of open containers
+    pub async fn count(&self) -> usize {
+        self.containers.read().await.len()
+    }
+
+    /// Destroy a tab's container and everything scoped to it.
+    /// Returns `None` if the tab is not ephemeral.
+    pub async fn destroy(&self, tab_id: &str) -> Result<Option<EphemeralTeardownReport>> {
+        let Some(container) = self.containers.write().await.remove(tab_id) else {
+            return Ok(None);
+        };
+
+        let stats = container.storage.get_stats().await;
+        let captured = container.interceptor.get_intercepted_requests().await.len();
+        container.storage.clear_all().await?;
+        container.interceptor.clear_requests().await;
+
+        let mut report = EphemeralTeardownReport {
+            tab_id: tab_id.to_string(),
+            container_id: container.container_id.clone(),
+            cookies_cleared: stats.cookies_count,
+            local_storage_origins_cleared: stats.local_storage_origins,
+            captured_requests_cleared: captured,
+            ..Default::default()
+        };
+
+        if let (Some(local_proxies), Some(_)) = (&self.local_proxies, &container.proxy_url) {
+            match local_proxies.remove_proxy_for_tab(tab_id).await {
+                Ok(()) => report.proxy_listener_stopped = true,
+                Err(e) => warn!("Failed to stop proxy listener for ephemeral tab {}: {}", tab_id, e),
+            }
+        }
+
+        if let Some(rotation) = &self.proxy_rotation {
+            report.proxy_session_ended = rotation.read().await.end_session(tab_id).await;
+        }
+
+        info!("Destroyed ephemeral container {} for tab {}", report.container_id, tab_id);
+        Ok(Some(report))
+    }
+
+    /// Destroy every container, e.g. on shutdown
+    pub async fn destroy_all(&self) -> Result<Vec<EphemeralTeardownReport>> {
+        let tab_ids: Vec<String> = self.containers.read().await.keys().cloned().collect();
+        let mut reports = Vec::with_capacity(tab_ids.len());
+        for tab_id in tab_ids {
+            if let Some(report) = self.destroy(&tab_id).await? {
+                reports.push(report);
+            }
+        }
+        Ok(reports)
+    }
+}
+
+impl Default for EphemeralContainerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Cookie;
+
+    #[tokio::test]
+    async fn test_container_lifecycle() {
+        let manager = EphemeralContainerManager::new();
+        let container = manager.create_container("tab-1", None).await.expect("Create should succeed");
+        assert!(manager.is_ephemeral("tab-1").await);
+        assert!(manager.create_container("tab-1", None).await.is_err());
+
+        let storage = container.storage();
+        storage.set_cookie(Cookie {
+            domain: "example.com".to_string(),
+            name: "sid".to_string(),
+            value: "1".to_string(),
+            path: "/".to_string(),
+            expires: None,
+            http_only: false,
+            secure: false,
+            same_site: "Lax".to_string(),
+        }).await.expect("Async operation should succeed");
+        storage.add_history("https://example.com", Some("Example")).await.expect("Add history should succeed");
+        assert_eq!(storage.get_stats().await.history_count, 0);
+        assert!(storage.export_all().await.is_err());
+
+        let report = manager.destroy("tab-1").await.expect("Destroy should succeed").expect("Container should exist");
+        assert_eq!(report.cookies_cleared, 1);
+        assert_eq!(storage.get_stats().await.cookies_count, 0);
+        assert!(!manager.is_ephemeral("tab-1").await);
+        assert!(manager.destroy("tab-1").await.expect("Destroy should succeed").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_storage_for_routes_by_tab() {
+        let temp_dir = tempfile::TempDir::new().expect("Operation should succeed in test");
+        let persistent = Arc::new(StorageEngine::new(temp_dir.path()).expect("Failed to create storage engine"));
+        let manager = EphemeralContainerManager::new();
+        manager.create_container("private", None).await.expect("Create should succeed");
+
+        manager.storage_for("normal", &persistent).await
+            .add_history("https://a.com", None).await.expect("Add history should succeed");
+        manager.storage_for("private", &persistent).await
+            .add_history("https://b.com", None).await.expect("Add history should succeed");
+
+        let history = persistent.get_history(10).await.expect("Get operation should succeed");
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].url, "https://a.com");
+    }
+
+    #[tokio::test]
+    async fn test_container_proxy_feeds_interceptor() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::{TcpListener, TcpStream};
+
+        let target = TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+        let target_port = target.local_addr().expect("Address should be available").port();
+        tokio::spawn(async move {
+            while let Ok((_stream, _)) = target.accept().await {}
+        });
+
+        let manager = EphemeralContainerManager::new()
+            .with_local_proxies(Arc::new(LocalProxyManager::new(47100..47200)));
+        let container = manager.create_container("tab-1", None).await.expect("Create should succeed");
+        let proxy_addr = container.proxy_url.clone().expect("Container should have a proxy");
+        container.interceptor().block_pattern("blocked.invalid".to_string()).await;
+
+        for (target, expected) in [(format!("127.0.0.1:{}", target_port), "200"), ("blocked.invalid:443".to_string(), "403")] {
+            let mut stream = TcpStream::connect(proxy_addr.trim_start_matches("http://")).await.expect("Connect should succeed");
+            stream
+                .write_all(format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n\r\n", target, target).as_bytes())
+                .await
+                .expect("Write should succeed");
+            let mut response = vec![0u8; 64];
+            let n = stream.read(&mut response).await.expect("Read should succeed");
+            assert!(String::from_utf8_lossy(&response[..n]).contains(expected));
+        }
+
+        let requests = container.interceptor().get_intercepted_requests().await;
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].response_status, Some(200));
+        assert!(!requests[0].blocked);
+        assert!(requests[1].blocked);
+
+        let report = manager.destroy("tab-1").await.expect("Destroy should succeed").expect("Container should exist");
+        assert_eq!(report.captured_requests_cleared, 2);
+        assert!(report.proxy_listener_stopped);
+        TcpListener::bind(proxy_addr.trim_start_matches("http://")).await.expect("Port should be free again");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_creates_start_one_listener() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("Bind should succeed")
+            .port();
+        let local_proxies = Arc::new(LocalProxyManager::new(port..port + 2));
+        let manager = EphemeralContainerManager::new().with_local_proxies(local_proxies.clone());
+
+        let (first, second) = tokio::join!(
+            manager.create_container("tab-1", None),
+            manager.create_container("tab-1", None)
+        );
+        assert!(first.is_ok() != second.is_ok());
+        assert_eq!(local_proxies.get_active_proxies().await.len(), 1);
+        manager.destroy_all().await.expect("Destroy should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_failed_create_releases_the_tab() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").expect("Bind should succeed");
+        let port = taken.local_addr().expect("Address should be available").port();
+        let manager = EphemeralContainerManager::new()
+            .with_local_proxies(Arc::new(LocalProxyManager::new(port..port + 1)));
+
+        assert!(manager.create_container("tab-1", None).await.is_err());
+        assert!(!manager.is_ephemeral("tab-1").await);
+
+        drop(taken);
+        manager.create_container("tab-1", None).await.expect("Create should succeed");
+        manager.destroy_all().await.expect("Destroy should succeed");
+    }
+}
@@ -12,6 +12,8 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
+use crate::event_stream::{BrowserEvent, EventBus};
+
 /// Global error counter
 static TOTAL_ERRORS: AtomicU64 = AtomicU64::new(0);
 static RECOVERED_ERRORS: AtomicU64 = AtomicU64::new(0);
@@ -23,6 +25,7 @@ pub struct ErrorRecoveryManager {
     circuit_breakers: Arc<RwLock<HashMap<String, CircuitBreaker>>>,
     start_time: Instant,
     config: ErrorRecoveryConfig,
+    events: Option<Arc<EventBus>>,
 }
 
 /// Error recovery configuration
@@ -185,6 +188,20 @@ pub enum CircuitState {
     HalfOpen,  // Testing if service recovered
 }
 
+impl CircuitState {
+    /// Every state, in the order they are exported
+    pub const ALL: [CircuitState; 3] = [CircuitState::Closed, CircuitState::Open, CircuitState::HalfOpen];
+
+    /// Lowercase name used in logs and metric labels
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
 impl CircuitBreaker {
     /// Create a new circuit breaker
     pub fn new(name: &str, threshold: u32, reset_timeout: Duration) -> Self {
@@ -310,6 +327,23 @@ impl ErrorRecoveryManager {
             circuit_breakers: Arc::new(RwLock::new(HashMap::new())),
             start_time: Instant::now(),
             config,
+            events: None,
+        }
+    }
+
+    /// Publish every handled or recorded error to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    fn publish_error(&self, record: &ErrorRecord) {
+        if let Some(bus) = &self.events {
+            bus.publish(BrowserEvent::Error {
+                component: record.component.clone(),
+                category: record.category.clone(),
+                message: record.message.clone(),
+            });
         }
     }
 
@@ -333,6 +367,7 @@ impl ErrorRecoveryManager {
             retry_count: 0,
         };
         
+        self.publish_error(&record);
         self.add_error_record(record.clone()).await;
         error!("Error in {}: {} (category: {:?})", component, error, category);
         
@@ -506,6 +541,17 @@ impl ErrorRecoveryManager {
         None
     }
 
+    /// Current state of every circuit breaker, ordered by component
+    pub async fn circuit_breaker_states(&self) -> Vec<(String, CircuitState)> {
+        let breakers = self.circuit_breakers.read().await;
+        let mut states: Vec<(String, CircuitState)> = breakers
+            .iter()
+            .map(|(name, breaker)| (name.clone(), breaker.state.clone()))
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        states
+    }
+
     /// Reset all circuit breakers
     pub async fn reset_circuit_breakers(&self) {
         let mut breakers = self.circuit_breakers.write().await;
@@ -549,6 +595,7 @@ impl ErrorRecoveryManager {
             retry_count: 0,
         };
         
+        self.publish_error(&record);
         self.error_history.write().await.push(record);
         error_id
     }
@@ -715,6 +762,17 @@ mod tests {
         assert!(!breaker.can_proceed());
     }
 
+    #[tokio::test]
+    async fn test_circuit_breaker_states() {
+        let manager = ErrorRecoveryManager::new();
+        assert!(manager.circuit_breaker_states().await.is_empty());
+
+        manager.handle_error("proxy_pool", "network connection failed").await;
+        let states = manager.circuit_breaker_states().await;
+        assert_eq!(states, vec![("proxy_pool".to_string(), CircuitState::Closed)]);
+        assert_eq!(states[0].1.as_str(), "closed");
+    }
+
     #[tokio::test]
     async fn test_crash_prediction() {
         let manager = ErrorRecoveryManager::new();
@@ -0,0 +1,463 @@
+//! Event Stream Module
+//!
+//! A process-wide bus of typed browser events for push clients:
+//! - Tab, proxy, validation, download, backup and error events
+//! - Every event gets a sequence number and lands in a bounded replay buffer
+//! - Subscribers filter by topic and tab, and may resume after a sequence
+//!   number; events that fell out of the buffer are reported as a gap
+//!
+//! Components publish through an optional `Arc<EventBus>` set with their
+//! `with_event_bus` builders.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use tokio::sync::broadcast;
+
+use crate::browser_controls::DownloadState;
+use crate::error_recovery::ErrorCategory;
+
+/// Events kept for resuming subscribers
+pub const DEFAULT_REPLAY_CAPACITY: usize = 1024;
+
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Coarse event groups clients subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    Tab,
+    Proxy,
+    Validation,
+    Download,
+    Backup,
+    Error,
+}
+
+impl EventTopic {
+    /// All topics
+    pub const ALL: [EventTopic; 6] = [
+        EventTopic::Tab,
+        EventTopic::Proxy,
+        EventTopic::Validation,
+        EventTopic::Download,
+        EventTopic::Backup,
+        EventTopic::Error,
+    ];
+
+    /// Wire name of the topic
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventTopic::Tab => "tab",
+            EventTopic::Proxy => "proxy",
+            EventTopic::Validation => "validation",
+            EventTopic::Download => "download",
+            EventTopic::Backup => "backup",
+            EventTopic::Error => "error",
+        }
+    }
+
+    /// Parse a wire name, ignoring case
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// A typed event. Proxies are identified by their `ip:port` ID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BrowserEvent {
+    TabCreated {
+        tab_id: String,
+        country_code: String,
+        ip: String,
+    },
+    TabClosed {
+        tab_id: String,
+    },
+    TabNavigated {
+        tab_id: String,
+        url: String,
+    },
+    ProxyRotated {
+        tab_id: String,
+        previous_proxy: Option<String>,
+        proxy_id: String,
+        country_code: String,
+    },
+    ProxyQuarantined {
+        proxy_id: String,
+        reason: String,
+        release_at: DateTime<Utc>,
+    },
+    ProxyReleased {
+        proxy_id: String,
+    },
+    ValidationCompleted {
+        proxy_id: String,
+        is_working: bool,
+        response_time_ms: u64,
+        error: Option<String>,
+    },
+    DownloadProgress {
+        download_id: String,
+        tab_id: Option<String>,
+        received_bytes: u64,
+        total_bytes: Option<u64>,
+        state: DownloadState,
+    },
+    BackupFinished {
+        backup_id: Option<String>,
+        skipped: bool,
+        error: Option<String>,
+    },
+    Error {
+        component: String,
+        category: ErrorCategory,
+        message: String,
+    },
+}
+
+impl BrowserEvent {
+    /// Topic the event belongs to
+    pub fn topic(&self) -> EventTopic {
+        match self {
+            BrowserEvent::TabCreated { .. }
+            | BrowserEvent::TabClosed { .. }
+            | BrowserEvent::TabNavigated { .. } => EventTopic::Tab,
+            BrowserEvent::ProxyRotated { .. }
+            | BrowserEvent::ProxyQuarantined { .. }
+            | BrowserEvent::ProxyReleased { .. } => EventTopic::Proxy,
+            BrowserEvent::ValidationCompleted { .. } => EventTopic::Validation,
+            BrowserEvent::DownloadProgress { .. } => EventTopic::Download,
+            BrowserEvent::BackupFinished { .. } => EventTopic::Backup,
+            BrowserEvent::Error { .. } => EventTopic::Error,
+        }
+    }
+
+    /// Tab the event concerns, if any
+    pub fn tab_id(&self) -> Option<&str> {
+        match self {
+            BrowserEvent::TabCreated { tab_id, .. }
+            | BrowserEvent::TabClosed { tab_id }
+            | BrowserEvent::TabNavigated { tab_id, .. }
+            | BrowserEvent::ProxyRotated { tab_id, .. } => Some(tab_id),
+            BrowserEvent::DownloadProgress { tab_id, .. } => tab_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// A published event with its sequence number
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub topic: EventTopic,
+    #[serde(flatten)]
+    pub event: BrowserEvent,
+}
+
+/// Which events a subscriber receives
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Topics to receive; empty means all
+    pub topics: HashSet<EventTopic>,
+    /// Only events concerning this tab
+    pub tab_id: Option<String>,
+}
+
+impl EventFilter {
+    /// Receive every event
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to the given topics
+    pub fn with_topics(mut self, topics: impl IntoIterator<Item = EventTopic>) -> Self {
+        self.topics.extend(topics);
+        self
+    }
+
+    /// Restrict to events concerning one tab
+    pub fn with_tab(mut self, tab_id: impl Into<String>) -> Self {
+        self.tab_id = Some(tab_id.into());
+        self
+    }
+
+    /// Whether an envelope passes the filter
+    pub fn matches(&self, envelope: &EventEnvelope) -> bool {
+        (self.topics.is_empty() || self.topics.contains(&envelope.topic))
+            && self
+                .tab_id
+                .as_deref()
+                .is_none_or(|tab| envelope.event.tab_id() == Some(tab))
+    }
+}
+
+/// Events a subscriber missed because they left the replay buffer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "gap")]
+pub struct StreamGap {
+    pub first_missed: u64,
+    pub last_missed: u64,
+}
+
+/// One item delivered to a subscriber
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StreamItem {
+    Event(EventEnvelope),
+    Gap(StreamGap),
+}
+
+struct BusState {
+    next_seq: u64,
+    history: VecDeque<EventEnvelope>,
+}
+
+/// Sequenced broadcast of `BrowserEvent`s with a replay buffer
+pub struct EventBus {
+    state: Mutex<BusState>,
+    sender: broadcast::Sender<EventEnvelope>,
+    replay_capacity: usize,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    /// Create a bus keeping `DEFAULT_REPLAY_CAPACITY` events for resumes
+    pub fn new() -> Self {
+        Self::with_replay_capacity(DEFAULT_REPLAY_CAPACITY)
+    }
+
+    /// Create a bus keeping the last `capacity` events for resumes
+    pub fn with_replay_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            state: Mutex::new(BusState {
+                next_seq: 1,
+                history: VecDeque::with_capacity(capacity.min(DEFAULT_REPLAY_CAPACITY)),
+            }),
+            sender,
+            replay_capacity: capacity.max(1),
+        }
+    }
+
+    /// Publish an event, returning its sequence number
+    pub fn publish(&self, event: BrowserEvent) -> u64 {
+        let mut state = self.state.lock();
+        let envelope = EventEnvelope {
+            seq: state.next_seq,
+            timestamp: Utc::now(),
+            topic: event.topic(),
+            event,
+        };
+        state.next_seq += 1;
+        if state.history.len() == self.replay_capacity {
+            state.history.pop_front();
+        }
+        state.history.push_back(envelope.clone());
+        // Sent under the lock so live delivery order matches sequence order
+        let _ = self.sender.send(envelope.clone());
+        envelope.seq
+    }
+
+    /// Sequence number of the latest event (0 before the first)
+    pub fn latest_seq(&self) -> u64 {
+        self.state.lock().next_seq - 1
+    }
+
+    /// Subscribe to events matching `filter`. With `since`, buffered events
+    /// after that sequence number are delivered first.
+    pub fn subscribe(&self, filter: EventFilter, since: Option<u64>) -> EventSubscription {
+        let state = self.state.lock();
+        let receiver = self.sender.subscribe();
+        let last_seq = since.unwrap_or(state.next_seq - 1).min(state.next_seq - 1);
+        let mut subscription = EventSubscription {
+            receiver,
+            filter,
+            pending: VecDeque::new(),
+            last_seq,
+        };
+        subscription.replay(&state.history);
+        subscription
+    }
+}
+
+/// A filtered, resumable view of an `EventBus`
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<EventEnvelope>,
+    filter: EventFilter,
+    pending: VecDeque<StreamItem>,
+    last_seq: u64,
+}
+
+impl EventSubscription {
+    /// Sequence number of the last event seen, filtered or not; pass it as
+    /// `since` to resume
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// Queue buffered events after `last_seq`, noting a gap if the buffer no
+    /// longer reaches back that far
+    fn replay(&mut self, history: &VecDeque<EventEnvelope>) {
+        if let Some(oldest) = history.front() {
+            if oldest.seq > self.last_seq + 1 {
+                self.pending.push_back(StreamItem::Gap(StreamGap {
+                    first_missed: self.last_seq + 1,
+                    last_missed: oldest.seq - 1,
+                }));
+                self.last_seq = oldest.seq - 1;
+            }
+        }
+        let after = self.last_seq;
+        for envelope in history.iter().filter(|e| e.seq > after) {
+            self.last_seq = envelope.seq;
+            if self.filter.matches(envelope) {
+                self.pending.push_back(StreamItem::Event(envelope.clone()));
+            }
+        }
+    }
+
+    /// Wait for the next matching event or gap. Returns `None` once the bus
+    /// is dropped.
+    pub async fn next(&mut self) -> Option<StreamItem> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+            match self.receiver.recv().await {
+                Ok(envelope) => {
+                    self.last_seq = envelope.seq;
+                    if self.filter.matches(&envelope) {
+                        return Some(StreamItem::Event(envelope));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    // Fell behind the channel; the skipped events are
+                    // contiguous, so report exactly that range
+                    self.pending.push_back(StreamItem::Gap(StreamGap {
+                        first_missed: self.last_seq + 1,
+                        last_missed: self.last_seq + missed,
+                    }));
+                    self.last_seq += missed;
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab_created(tab_id: &str) -> BrowserEvent {
+        BrowserEvent::TabCreated {
+            tab_id: tab_id.to_string(),
+            country_code: "DE".to_string(),
+            ip: "10.0.0.1".to_string(),
+        }
+    }
+
+    fn released(proxy_id: &str) -> BrowserEvent {
+        BrowserEvent::ProxyReleased {
+            proxy_id: proxy_id.to_string(),
+        }
+    }
+
+    async fn next_event(subscription: &mut EventSubscription) -> EventEnvelope {
+        match subscription.next().await {
+            Some(StreamItem::Event(envelope)) => envelope,
+            other => panic!("expected an event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filters_by_topic_and_tab() {
+        let bus = EventBus::new();
+        let mut tab_a = bus.subscribe(EventFilter::all().with_tab("a"), None);
+        let mut proxy = bus.subscribe(EventFilter::all().with_topics([EventTopic::Proxy]), None);
+
+        bus.publish(tab_created("b"));
+        bus.publish(released("10.0.0.1:8080"));
+        bus.publish(tab_created("a"));
+
+        let event = next_event(&mut tab_a).await;
+        assert_eq!(event.seq, 3);
+        assert_eq!(event.event.tab_id(), Some("a"));
+
+        let event = next_event(&mut proxy).await;
+        assert_eq!(event.seq, 2);
+        assert_eq!(event.topic, EventTopic::Proxy);
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_without_duplicates() {
+        let bus = EventBus::new();
+        for tab in ["a", "b", "c"] {
+            bus.publish(tab_created(tab));
+        }
+
+        let mut subscription = bus.subscribe(EventFilter::all(), Some(1));
+        bus.publish(tab_created("d"));
+
+        let seqs = [
+            next_event(&mut subscription).await.seq,
+            next_event(&mut subscription).await.seq,
+            next_event(&mut subscription).await.seq,
+        ];
+        assert_eq!(seqs, [2, 3, 4]);
+        assert_eq!(subscription.last_seq(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_resume_past_buffer_reports_gap() {
+        let bus = EventBus::with_replay_capacity(2);
+        for tab in ["a", "b", "c", "d"] {
+            bus.publish(tab_created(tab));
+        }
+
+        let mut subscription = bus.subscribe(EventFilter::all(), Some(0));
+        assert_eq!(
+            subscription.next().await,
+            Some(StreamItem::Gap(StreamGap {
+                first_missed: 1,
+                last_missed: 2
+            }))
+        );
+        assert_eq!(next_event(&mut subscription).await.seq, 3);
+        assert_eq!(next_event(&mut subscription).await.seq, 4);
+    }
+
+    #[test]
+    fn test_envelope_wire_format() {
+        let bus = EventBus::new();
+        bus.publish(released("10.0.0.1:8080"));
+        let subscription = bus.subscribe(EventFilter::all(), Some(0));
+        let Some(StreamItem::Event(envelope)) = subscription.pending.front().cloned() else {
+            panic!("expected a replayed event");
+        };
+
+        let json = serde_json::to_value(&envelope).expect("Serialize should succeed");
+        assert_eq!(json["seq"], 1);
+        assert_eq!(json["topic"], "proxy");
+        assert_eq!(json["type"], "proxy_released");
+        assert_eq!(json["proxy_id"], "10.0.0.1:8080");
+
+        let gap = serde_json::to_value(StreamItem::Gap(StreamGap {
+            first_missed: 1,
+            last_missed: 2,
+        }))
+        .expect("Serialize should succeed");
+        assert_eq!(gap["type"], "gap");
+        assert_eq!(EventTopic::parse(" Download"), Some(EventTopic::Download));
+    }
+}
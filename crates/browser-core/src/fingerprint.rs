@@ -5,7 +5,10 @@
 //! - WebGL fingerprint randomization
 //! - Audio context fingerprint spoofing
 //! - User-Agent rotation
+//! - Coherent fingerprint generation per platform and locale
 
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,3 +27,122 @@ pub struct BrowserFingerprint {
     pub canvas_hash: String,
     pub audio_hash: String,
 }
+
+/// Operating system a generated fingerprint imitates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FingerprintPlatform {
+    #[default]
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl FingerprintPlatform {
+    /// All supported platforms
+    pub fn all() -> Vec<Self> {
+        vec![Self::Windows, Self::MacOs, Self::Linux]
+    }
+
+    fn navigator_platform(&self) -> &'static str {
+        match self {
+            Self::Windows => "Win32",
+            Self::MacOs => "MacIntel",
+            Self::Linux => "Linux x86_64",
+        }
+    }
+
+    fn user_agent_os(&self) -> &'static str {
+        match self {
+            Self::Windows => "Windows NT 10.0; Win64; x64",
+            Self::MacOs => "Macintosh; Intel Mac OS X 10_15_7",
+            Self::Linux => "X11; Linux x86_64",
+        }
+    }
+
+    fn screen_resolutions(&self) -> &'static [(u32, u32)] {
+        match self {
+            Self::Windows => &[(1920, 1080), (1366, 768), (1536, 864), (2560, 1440)],
+            Self::MacOs => &[(1440, 900), (1512, 982), (1728, 1117), (2560, 1600)],
+            Self::Linux => &[(1920, 1080), (2560, 1440), (1680, 1050)],
+        }
+    }
+
+    fn webgl(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Windows => &[
+                ("Google Inc. (NVIDIA)", "ANGLE (NVIDIA, NVIDIA GeForce GTX 1660 Direct3D11 vs_5_0 ps_5_0, D3D11)"),
+                ("Google Inc. (Intel)", "ANGLE (Intel, Intel(R) UHD Graphics 620 Direct3D11 vs_5_0 ps_5_0, D3D11)"),
+                ("Google Inc. (AMD)", "ANGLE (AMD, AMD Radeon RX 580 Direct3D11 vs_5_0 ps_5_0, D3D11)"),
+            ],
+            Self::MacOs => &[
+                ("Google Inc. (Apple)", "ANGLE (Apple, Apple M1, OpenGL 4.1)"),
+                ("Google Inc. (Apple)", "ANGLE (Apple, Apple M2, OpenGL 4.1)"),
+                ("Google Inc. (Intel Inc.)", "ANGLE (Intel Inc., Intel(R) Iris(TM) Plus Graphics, OpenGL 4.1)"),
+            ],
+            Self::Linux => &[
+                ("Google Inc. (Intel)", "ANGLE (Intel, Mesa Intel(R) UHD Graphics 620 (KBL GT2), OpenGL 4.6)"),
+                ("Google Inc. (AMD)", "ANGLE (AMD, AMD Radeon RX 6600 (radeonsi, navi23, LLVM 15.0.7), OpenGL 4.6)"),
+            ],
+        }
+    }
+}
+
+impl BrowserFingerprint {
+    /// Generate a fingerprint whose user agent, platform, screen and GPU
+    /// values all describe the same kind of machine
+    ///
+    /// # Arguments
+    /// * `platform` - Operating system to imitate
+    /// * `language` - Locale such as `de-DE`, used for `Accept-Language`
+    /// * `timezone` - IANA timezone of the exit location
+    pub fn generate(platform: FingerprintPlatform, language: &str, timezone: &str) -> Self {
+        let mut rng = rand::thread_rng();
+        let chrome_major = rng.gen_range(120..=131);
+        let (webgl_vendor, webgl_renderer) = *platform
+            .webgl()
+            .choose(&mut rng)
+            .unwrap_or(&("Google Inc.", "ANGLE"));
+        let screen_resolution = *platform
+            .screen_resolutions()
+            .choose(&mut rng)
+            .unwrap_or(&(1920, 1080));
+        let hardware_concurrency = *[4u8, 8, 12, 16].choose(&mut rng).unwrap_or(&8);
+        let device_memory = if hardware_concurrency >= 8 { 8 } else { 4 };
+
+        Self {
+            user_agent: format!(
+                "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Safari/537.36",
+                platform.user_agent_os(),
+                chrome_major
+            ),
+            accept_language: accept_language_for(language),
+            timezone: timezone.to_string(),
+            screen_resolution,
+            color_depth: if platform == FingerprintPlatform::MacOs { 30 } else { 24 },
+            hardware_concurrency,
+            device_memory,
+            platform: platform.navigator_platform().to_string(),
+            webgl_vendor: webgl_vendor.to_string(),
+            webgl_renderer: webgl_renderer.to_string(),
+            canvas_hash: format!("{:x}", rng.gen::<u64>()),
+            audio_hash: format!("{:x}", rng.gen::<u64>()),
+        }
+    }
+}
+
+/// Build an `Accept-Language` header value for a locale, e.g.
+/// `de-DE` becomes `de-DE,de;q=0.9,en;q=0.8`
+fn accept_language_for(language: &str) -> String {
+    let primary = language.split(['-', '_']).next().unwrap_or(language);
+    if primary.eq_ignore_ascii_case("en") {
+        if language.contains(['-', '_']) {
+            format!("{},en;q=0.9", language)
+        } else {
+            "en-US,en;q=0.9".to_string()
+        }
+    } else if primary == language {
+        format!("{},en;q=0.8", language)
+    } else {
+        format!("{},{};q=0.9,en;q=0.8", language, primary)
+    }
+}
@@ -82,6 +82,11 @@ impl FreeIpProvider {
         ]
     }
 
+    /// Look up a provider by name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|p| p.name().eq_ignore_ascii_case(name))
+    }
+
     /// Performs name operation.
     pub fn name(&self) -> &str {
         match self {
@@ -275,6 +280,62 @@ impl FreeIpProviderManager {
         &self.proxy_pool
     }
 
+    /// Replace the proxy pool, e.g. when restoring from a backup
+    pub fn set_proxy_pool(&mut self, proxies: Vec<FreeProxy>) {
+        self.proxy_pool = proxies;
+    }
+
+    /// Add proxies not already in the pool (by IP and port), returning how
+    /// many were added
+    pub fn add_proxies(&mut self, proxies: Vec<FreeProxy>) -> usize {
+        let mut added = 0;
+        for proxy in proxies {
+            if self.find_proxy(&proxy.ip, proxy.port).is_none() {
+                self.proxy_pool.push(proxy);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Find a pooled proxy by IP and port
+    pub fn find_proxy(&self, ip: &str, port: u16) -> Option<&FreeProxy> {
+        self.proxy_pool.iter().find(|p| p.ip == ip && p.port == port)
+    }
+
+    /// Mutable access to a pooled proxy, e.g. to record a validation result
+    pub fn find_proxy_mut(&mut self, ip: &str, port: u16) -> Option<&mut FreeProxy> {
+        self.proxy_pool.iter_mut().find(|p| p.ip == ip && p.port == port)
+    }
+
+    /// Remove a proxy from the pool
+    pub fn remove_proxy(&mut self, ip: &str, port: u16) -> Option<FreeProxy> {
+        let index = self.proxy_pool.iter().position(|p| p.ip == ip && p.port == port)?;
+        Some(self.proxy_pool.remove(index))
+    }
+
+    /// When a provider was last fetched
+    pub fn last_updated(&self, provider: &FreeIpProvider) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.last_update.get(provider.name()).copied()
+    }
+
+    /// Fetch one provider and add its new proxies to the pool, returning
+    /// (fetched, added)
+    pub async fn refresh_provider(&mut self, provider: &FreeIpProvider) -> Result<(usize, usize)> {
+        let proxies = self.fetch_from_provider(provider).await?;
+        Ok(self.record_refresh(provider, proxies))
+    }
+
+    /// Add proxies fetched from a provider elsewhere, e.g. by a separate
+    /// manager so the shared pool stays unlocked during the fetch, and mark
+    /// the provider updated. Returns (fetched, added).
+    pub fn record_refresh(&mut self, provider: &FreeIpProvider, proxies: Vec<FreeProxy>) -> (usize, usize) {
+        let fetched = proxies.len();
+        let added = self.add_proxies(proxies);
+        self.last_update.insert(provider.name().to_string(), chrono::Utc::now());
+        (fetched, added)
+    }
+
     /// Gets the working proxies.
     pub fn get_working_proxies(&self) -> Vec<&FreeProxy> {
         self.proxy_pool.iter()
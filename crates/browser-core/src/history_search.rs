@@ -0,0 +1,605 @@
+//! History Search Module
+//!
+//! Provides ranked full-text search over browsing history including:
+//! - Inverted index over titles, URL tokens and extracted page text
+//! - Frecency ranking (visit count with exponential recency decay)
+//! - Prefix, phrase and date-range queries
+//! - Incremental updates suitable for as-you-type omnibox suggestions
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::storage::HistoryEntry;
+
+/// Position tags occupy the top byte of an encoded position so that phrase
+/// matching never crosses from one field into another.
+const FIELD_SHIFT: u32 = 24;
+const MAX_FIELD_POSITION: u32 = (1 << FIELD_SHIFT) - 1;
+
+/// Upper bound on indexed page-text tokens per document
+const MAX_TEXT_TOKENS: usize = 20_000;
+
+/// Indexed document field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HistoryField {
+    Title,
+    Url,
+    PageText,
+}
+
+impl HistoryField {
+    fn tag(self) -> u32 {
+        match self {
+            HistoryField::Title => 0,
+            HistoryField::Url => 1,
+            HistoryField::PageText => 2,
+        }
+    }
+
+    fn from_position(position: u32) -> Self {
+        match position >> FIELD_SHIFT {
+            0 => HistoryField::Title,
+            1 => HistoryField::Url,
+            _ => HistoryField::PageText,
+        }
+    }
+
+    /// Relevance weight of a match in this field
+    fn weight(self) -> f64 {
+        match self {
+            HistoryField::Title => 3.0,
+            HistoryField::Url => 2.0,
+            HistoryField::PageText => 1.0,
+        }
+    }
+}
+
+/// Ranking configuration for history search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrecencyConfig {
+    /// Days after which a visit counts half as much
+    pub half_life_days: f64,
+    /// Maximum number of distinct terms a prefix expands to
+    pub max_prefix_expansions: usize,
+}
+
+impl Default for FrecencyConfig {
+    fn default() -> Self {
+        Self {
+            half_life_days: 30.0,
+            max_prefix_expansions: 256,
+        }
+    }
+}
+
+/// A history search query
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryQuery {
+    /// Raw query text. Quoted segments are phrases, a trailing `*` marks a
+    /// prefix, and the last word is treated as a prefix unless followed by
+    /// whitespace (as-you-type behaviour).
+    pub text: String,
+    /// Only include entries last visited at or after this Unix timestamp
+    pub from: Option<i64>,
+    /// Only include entries last visited at or before this Unix timestamp
+    pub to: Option<i64>,
+    /// Maximum number of results (0 means the default of 100)
+    pub limit: usize,
+    /// Whether extracted page text takes part in matching
+    pub include_page_text: bool,
+}
+
+impl HistoryQuery {
+    /// Create a query over titles, URLs and page text
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            include_page_text: true,
+            ..Default::default()
+        }
+    }
+
+    /// Restrict results to a last-visit date range (Unix seconds, inclusive)
+    pub fn with_date_range(mut self, from: Option<i64>, to: Option<i64>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    /// Set the maximum number of results
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Exclude extracted page text from matching
+    pub fn titles_and_urls_only(mut self) -> Self {
+        self.include_page_text = false;
+        self
+    }
+
+    fn effective_limit(&self) -> usize {
+        if self.limit == 0 { 100 } else { self.limit }
+    }
+}
+
+/// A ranked search result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySearchHit {
+    pub entry: HistoryEntry,
+    pub score: f64,
+    pub frecency: f64,
+    /// Fields that matched at least one query clause
+    pub matched_fields: Vec<HistoryField>,
+}
+
+/// A parsed query clause
+#[derive(Debug, Clone, PartialEq)]
+enum QueryClause {
+    Term(String),
+    Prefix(String),
+    Phrase(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+struct IndexedDoc {
+    url: String,
+    visit_count: i32,
+    last_visit: i64,
+    terms: HashSet<String>,
+    has_page_text: bool,
+}
+
+/// Inverted index over history entries
+#[derive(Debug, Default)]
+pub struct HistoryIndex {
+    config: FrecencyConfig,
+    /// term -> doc id -> encoded positions (sorted)
+    postings: BTreeMap<String, HashMap<u32, Vec<u32>>>,
+    docs: HashMap<u32, IndexedDoc>,
+    doc_ids: HashMap<String, u32>,
+    next_doc_id: u32,
+}
+
+impl HistoryIndex {
+    /// Create an empty index with default ranking
+    pub fn new() -> Self {
+        Self::with_config(FrecencyConfig::default())
+    }
+
+    /// Create an empty index with custom ranking
+    pub fn with_config(config: FrecencyConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Number of indexed documents
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Number of distinct indexed terms
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// Insert or refresh a history entry. Page text previously attached to
+    /// the URL is kept.
+    pub fn upsert(&mut self, entry: &HistoryEntry) {
+        let page_positions = self
+            .doc_ids
+            .get(&entry.url)
+            .copied()
+            .map(|id| self.take_field(id, HistoryField::PageText));
+        self.remove(&entry.url);
+
+        let doc_id = self.next_doc_id;
+        self.next_doc_id = self.next_doc_id.wrapping_add(1);
+
+        let mut doc = IndexedDoc {
+            url: entry.url.clone(),
+            visit_count: entry.visit_count,
+            last_visit: entry.last_visit,
+            terms: HashSet::new(),
+            has_page_text: false,
+        };
+
+        if let Some(title) = &entry.title {
+            self.add_field(doc_id, &mut doc, HistoryField::Title, tokenize(title));
+        }
+        self.add_field(doc_id, &mut doc, HistoryField::Url, tokenize(&entry.url));
+
+        if let Some(positions) = page_positions.filter(|p| !p.is_empty()) {
+            doc.has_page_text = true;
+            for (term, list) in positions {
+                doc.terms.insert(term.clone());
+                self.postings.entry(term).or_default().entry(doc_id).or_default().extend(list);
+            }
+        }
+
+        self.doc_ids.insert(entry.url.clone(), doc_id);
+        self.docs.insert(doc_id, doc);
+    }
+
+    /// Attach extracted page text to an already indexed URL.
+    /// Returns false if the URL is not in the index.
+    pub fn set_page_text(&mut self, url: &str, text: &str) -> bool {
+        let Some(&doc_id) = self.doc_ids.get(url) else {
+            return false;
+        };
+        self.take_field(doc_id, HistoryField::PageText);
+
+        let mut tokens = tokenize(text);
+        tokens.truncate(MAX_TEXT_TOKENS);
+
+        if let Some(mut doc) = self.docs.remove(&doc_id) {
+            doc.has_page_text = !tokens.is_empty();
+            self.add_field(doc_id, &mut doc, HistoryField::PageText, tokens);
+            self.docs.insert(doc_id, doc);
+        }
+        true
+    }
+
+    /// Remove a URL from the index
+    pub fn remove(&mut self, url: &str) -> bool {
+        let Some(doc_id) = self.doc_ids.remove(url) else {
+            return false;
+        };
+        if let Some(doc) = self.docs.remove(&doc_id) {
+            for term in &doc.terms {
+                if let Some(docs) = self.postings.get_mut(term) {
+                    docs.remove(&doc_id);
+                    if docs.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Remove everything from the index
+    pub fn clear(&mut self) {
+        self.postings.clear();
+        self.docs.clear();
+        self.doc_ids.clear();
+        self.next_doc_id = 0;
+    }
+
+    /// Whether page text has been indexed for a URL
+    pub fn has_page_text(&self, url: &str) -> bool {
+        self.doc_ids
+            .get(url)
+            .and_then(|id| self.docs.get(id))
+            .map(|d| d.has_page_text)
+            .unwrap_or(false)
+    }
+
+    /// Frecency score for a visit count and last-visit time
+    pub fn frecency(&self, visit_count: i32, last_visit: i64, now: i64) -> f64 {
+        let age_days = (now - last_visit).max(0) as f64 / 86_400.0;
+        let decay = 0.5f64.powf(age_days / self.config.half_life_days.max(f64::EPSILON));
+        visit_count.max(1) as f64 * decay
+    }
+
+    /// Run a query and return `(url, score, frecency, matched fields)`
+    /// tuples ordered by descending score.
+    pub fn search(&self, query: &HistoryQuery, now: i64) -> Vec<(String, f64, f64, Vec<HistoryField>)> {
+        let clauses = parse_query(&query.text);
+        let limit = query.effective_limit();
+
+        let in_range = |doc: &IndexedDoc| {
+            query.from.map(|f| doc.last_visit >= f).unwrap_or(true)
+                && query.to.map(|t| doc.last_visit <= t).unwrap_or(true)
+        };
+
+        // An empty query lists entries by frecency alone
+        if clauses.is_empty() {
+            let mut results: Vec<_> = self
+                .docs
+                .values()
+                .filter(|d| in_range(d))
+                .map(|d| {
+                    let f = self.frecency(d.visit_count, d.last_visit, now);
+                    (d.url.clone(), f, f, Vec::new())
+                })
+                .collect();
+            sort_and_truncate(&mut results, limit);
+            return results;
+        }
+
+        // Each clause yields doc id -> (best field weight, matched fields)
+        let mut candidates: Option<HashMap<u32, (f64, HashSet<HistoryField>)>> = None;
+        for clause in &clauses {
+            let matches = self.match_clause(clause, query.include_page_text);
+            candidates = Some(match candidates {
+                None => matches,
+                Some(mut acc) => {
+                    acc.retain(|id, _| matches.contains_key(id));
+                    for (id, (score, fields)) in acc.iter_mut() {
+                        if let Some((s, f)) = matches.get(id) {
+                            *score += s;
+                            fields.extend(f.iter().copied());
+                        }
+                    }
+                    acc
+                }
+            });
+            if candidates.as_ref().map(|c| c.is_empty()).unwrap_or(true) {
+                return Vec::new();
+            }
+        }
+
+        let mut results: Vec<_> = candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, (relevance, fields))| {
+                let doc = self.docs.get(&id)?;
+                if !in_range(doc) {
+                    return None;
+                }
+                let frecency = self.frecency(doc.visit_count, doc.last_visit, now);
+                let mut fields: Vec<HistoryField> = fields.into_iter().collect();
+                fields.sort_by_key(|f| f.tag());
+                Some((doc.url.clone(), relevance * (1.0 + frecency), frecency, fields))
+            })
+            .collect();
+
+        sort_and_truncate(&mut results, limit);
+        results
+    }
+
+    fn match_clause(&self, clause: &QueryClause, include_text: bool) -> HashMap<u32, (f64, HashSet<HistoryField>)> {
+        let mut out: HashMap<u32, (f64, HashSet<HistoryField>)> = HashMap::new();
+        let mut record = |doc_id: u32, position: u32| {
+            let field = HistoryField::from_position(position);
+            if !include_text && field == HistoryField::PageText {
+                return;
+            }
+            let entry = out.entry(doc_id).or_insert((0.0, HashSet::new()));
+            entry.0 = entry.0.max(field.weight());
+            entry.1.insert(field);
+        };
+
+        match clause {
+            QueryClause::Term(term) => {
+                if let Some(docs) = self.postings.get(term) {
+                    for (&id, positions) in docs {
+                        positions.iter().for_each(|&p| record(id, p));
+                    }
+                }
+            }
+            QueryClause::Prefix(prefix) => {
+                let terms = self
+                    .postings
+                    .range(prefix.clone()..)
+                    .take_while(|(t, _)| t.starts_with(prefix.as_str()))
+                    .take(self.config.max_prefix_expansions);
+                for (_, docs) in terms {
+                    for (&id, positions) in docs {
+                        positions.iter().for_each(|&p| record(id, p));
+                    }
+                }
+            }
+            QueryClause::Phrase(words) => {
+                let lists: Option<Vec<&HashMap<u32, Vec<u32>>>> =
+                    words.iter().map(|w| self.postings.get(w)).collect();
+                let Some(lists) = lists else {
+                    return out;
+                };
+                let Some((first, rest)) = lists.split_first() else {
+                    return out;
+                };
+                for (&id, starts) in first.iter() {
+                    let Some(rest_positions) = rest.iter().map(|l| l.get(&id)).collect::<Option<Vec<_>>>() else {
+                        continue;
+                    };
+                    for &start in starts {
+                        let consecutive = rest_positions.iter().enumerate().all(|(i, positions)| {
+                            positions.binary_search(&(start + i as u32 + 1)).is_ok()
+                        });
+                        if consecutive {
+                            record(id, start);
+                        }
+                    }
+                }
+                // Phrases are a stronger signal than loose terms
+                for value in out.values_mut() {
+                    value.0 *= words.len() as f64;
+                }
+            }
+        }
+        out
+    }
+
+    fn add_field(&mut self, doc_id: u32, doc: &mut IndexedDoc, field: HistoryField, tokens: Vec<String>) {
+        let base = field.tag() << FIELD_SHIFT;
+        for (i, token) in tokens.into_iter().enumerate() {
+            let pos = base | (i as u32).min(MAX_FIELD_POSITION);
+            doc.terms.insert(token.clone());
+            self.postings.entry(token).or_default().entry(doc_id).or_default().push(pos);
+        }
+    }
+
+    /// Remove and return a document's postings for one field
+    fn take_field(&mut self, doc_id: u32, field: HistoryField) -> HashMap<String, Vec<u32>> {
+        let mut taken = HashMap::new();
+        let Some(doc) = self.docs.get_mut(&doc_id) else {
+            return taken;
+        };
+        let mut emptied = Vec::new();
+        for term in &doc.terms {
+            if let Some(positions) = self.postings.get_mut(term).and_then(|d| d.get_mut(&doc_id)) {
+                let (matching, keep): (Vec<u32>, Vec<u32>) =
+                    positions.iter().partition(|&&p| HistoryField::from_position(p) == field);
+                if !matching.is_empty() {
+                    taken.insert(term.clone(), matching);
+                }
+                *positions = keep;
+                if positions.is_empty() {
+                    emptied.push(term.clone());
+                }
+            }
+        }
+        for term in emptied {
+            doc.terms.remove(&term);
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(&doc_id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        if field == HistoryField::PageText {
+            doc.has_page_text = false;
+        }
+        taken
+    }
+}
+
+fn sort_and_truncate(results: &mut Vec<(String, f64, f64, Vec<HistoryField>)>, limit: usize) {
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+    results.truncate(limit);
+}
+
+/// Split text into lowercase alphanumeric tokens
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+fn parse_query(text: &str) -> Vec<QueryClause> {
+    let mut clauses = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('"') {
+        push_words(&rest[..start], false, &mut clauses);
+        let after = &rest[start + 1..];
+        let (phrase, remaining) = match after.find('"') {
+            Some(end) => (&after[..end], &after[end + 1..]),
+            None => (after, ""),
+        };
+        let words = tokenize(phrase);
+        match words.len() {
+            0 => {}
+            1 => clauses.push(QueryClause::Term(words[0].clone())),
+            _ => clauses.push(QueryClause::Phrase(words)),
+        }
+        rest = remaining;
+    }
+
+    push_words(rest, !rest.ends_with(char::is_whitespace), &mut clauses);
+    clauses
+}
+
+fn push_words(segment: &str, last_is_prefix: bool, clauses: &mut Vec<QueryClause>) {
+    let words: Vec<&str> = segment.split_whitespace().collect();
+    let count = words.len();
+    for (i, word) in words.into_iter().enumerate() {
+        let explicit_prefix = word.ends_with('*');
+        let tokens = tokenize(word);
+        let token_count = tokens.len();
+        for (j, token) in tokens.into_iter().enumerate() {
+            let is_last_token = j + 1 == token_count;
+            if is_last_token && (explicit_prefix || (last_is_prefix && i + 1 == count)) {
+                clauses.push(QueryClause::Prefix(token));
+            } else {
+                clauses.push(QueryClause::Term(token));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, url: &str, title: &str, visits: i32, last_visit: i64) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            url: url.to_string(),
+            title: Some(title.to_string()),
+            visit_count: visits,
+            last_visit,
+        }
+    }
+
+    fn urls(results: &[(String, f64, f64, Vec<HistoryField>)]) -> Vec<&str> {
+        results.iter().map(|r| r.0.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query("rust \"async book\" tok"),
+            vec![
+                QueryClause::Term("rust".into()),
+                QueryClause::Phrase(vec!["async".into(), "book".into()]),
+                QueryClause::Prefix("tok".into()),
+            ]
+        );
+        assert_eq!(parse_query("rust "), vec![QueryClause::Term("rust".into())]);
+        assert_eq!(parse_query("ru* lang "), vec![QueryClause::Prefix("ru".into()), QueryClause::Term("lang".into())]);
+    }
+
+    #[test]
+    fn test_frecency_ranking() {
+        let now = 100 * 86_400;
+        let mut index = HistoryIndex::new();
+        index.upsert(&entry(1, "https://old.example.com/rust", "Rust news", 50, 0));
+        index.upsert(&entry(2, "https://new.example.com/rust", "Rust news", 5, now));
+        index.upsert(&entry(3, "https://other.com", "Unrelated", 100, now));
+
+        let results = index.search(&HistoryQuery::new("rust "), now);
+        assert_eq!(urls(&results), vec!["https://new.example.com/rust", "https://old.example.com/rust"]);
+    }
+
+    #[test]
+    fn test_prefix_and_phrase() {
+        let mut index = HistoryIndex::new();
+        index.upsert(&entry(1, "https://a.com", "The async book", 1, 0));
+        index.upsert(&entry(2, "https://b.com", "Book about async", 1, 0));
+
+        assert_eq!(index.search(&HistoryQuery::new("asy"), 0).len(), 2);
+        assert_eq!(urls(&index.search(&HistoryQuery::new("\"async book\""), 0)), vec!["https://a.com"]);
+        assert!(index.search(&HistoryQuery::new("asy "), 0).is_empty());
+    }
+
+    #[test]
+    fn test_page_text_and_date_range() {
+        let mut index = HistoryIndex::new();
+        index.upsert(&entry(1, "https://a.com/post", "Post", 1, 1_000));
+        index.upsert(&entry(2, "https://b.com/post", "Post", 1, 5_000));
+        assert!(index.set_page_text("https://a.com/post", "ownership and borrowing explained"));
+
+        let hits = index.search(&HistoryQuery::new("borrowing "), 10_000);
+        assert_eq!(urls(&hits), vec!["https://a.com/post"]);
+        assert_eq!(hits[0].3, vec![HistoryField::PageText]);
+        assert!(index.search(&HistoryQuery::new("borrowing ").titles_and_urls_only(), 10_000).is_empty());
+
+        // Page text survives a visit refresh
+        index.upsert(&entry(1, "https://a.com/post", "Post", 2, 2_000));
+        assert!(index.has_page_text("https://a.com/post"));
+
+        let ranged = index.search(&HistoryQuery::new("post ").with_date_range(Some(3_000), None), 10_000);
+        assert_eq!(urls(&ranged), vec!["https://b.com/post"]);
+    }
+
+    #[test]
+    fn test_remove_cleans_postings() {
+        let mut index = HistoryIndex::new();
+        index.upsert(&entry(1, "https://a.com", "Alpha", 1, 0));
+        assert!(index.remove("https://a.com"));
+        assert!(index.is_empty());
+        assert_eq!(index.term_count(), 0);
+    }
+}
@@ -5,15 +5,18 @@ pub use prelude::{
     OptionExt, ResultExt, unix_timestamp, unix_timestamp_ms,
     RetryConfig, retry_async, RateLimiter,
     CircuitBreaker, CircuitState,
-    MetricsCollector, HistogramStats, MetricsSnapshot,
+    MetricsCollector, HistogramStats, MetricsSnapshot, metric_names,
     validators, string_utils
 };
 
-pub use browser_profile::{BrowserProfile, BrowserProfileManager, ProfileSettings};
+pub use browser_profile::{BrowserProfile, BrowserProfileManager, ProfileSettings, LoadedProfile, ProfileLock};
 pub use screenshot::{ScreenshotManager, ScreenshotOptions, ScreenshotFormat, ScreenshotResult};
 
 pub mod tab_manager;
 pub mod config_manager;
+pub mod config_layers;
+pub mod config_migration;
+pub mod config_reload;
 
 pub mod tab_isolation;
 pub mod fingerprint;
@@ -22,12 +25,25 @@ pub mod http_client;
 pub mod request;
 pub mod scraper_util;
 pub mod security;
+pub mod auth;
+pub mod secrets_vault;
 pub mod webview_manager;
 pub mod browser_tab_manager;
 pub mod free_ip_providers;
 pub mod storage;
+pub mod history_search;
+pub mod retention;
+pub mod ephemeral;
+pub mod profile_generator;
+pub mod profile_bundle;
+pub mod secure_container;
 pub mod backup;
+pub mod backup_scheduler;
+pub mod backup_repository;
+pub mod app_backup;
+pub mod restore_planner;
 pub mod browser_controls;
+pub mod event_stream;
 pub mod local_proxy;
 pub mod pac_server;
 pub mod proxy_rotation;
@@ -59,18 +75,31 @@ pub mod screenshot;
 // Configuration management exports
 pub use config_manager::{
     ConfigManager, AppConfig,
-    GeneralConfig, ProxyConfig, PrivacyConfig, PerformanceConfig,
-    NetworkConfig as AppNetworkConfig, StorageConfig, LoggingConfig, FeatureFlags
+    GeneralConfig, ProxyConfig, PrivacyConfig, RetentionConfig, PerformanceConfig,
+    NetworkConfig as AppNetworkConfig, StorageConfig, LoggingConfig, FeatureFlags, ConfigValidation
+};
+pub use config_layers::{
+    ConfigLayers, ConfigLayer, ConfigSource, UnknownKey, ResolvedConfig, config_schema
+};
+pub use config_migration::{
+    ConfigMigration, MigrationReport, CURRENT_CONFIG_VERSION, migrate_file
+};
+pub use config_reload::{
+    ConfigHotReloader, ConfigListener, ConfigSection, ConfigChange, ConfigReloadEvent
 };
 
 pub use tab_manager::TabIPManager;
 pub use tab_isolation::{TabProfile, NetworkConfig, TabStatus, TLSProfile, HTTP2Settings, TCPFingerprint};
-pub use fingerprint::BrowserFingerprint;
+pub use fingerprint::{BrowserFingerprint, FingerprintPlatform};
 pub use proxy::{ProxyManager, ProxySettings, ProxyType, FreeProxy, ProxyTestResult};
 pub use http_client::{HttpClient, PublicIpDetector, PublicIpInfo};
 pub use request::{RequestBuilder, RequestManager, RequestConfig, RequestResponse, RequestError, RequestErrorKind, HttpMethod, RequestBody};
 pub use scraper_util::ProxyScraper;
 pub use security::{SecurityManager, BookmarkInput, ProxyInput};
+pub use secrets_vault::{
+    SecretsVault, SecretRef, SecretBackend, KeyringBackend, EncryptedFileBackend, RedactSecrets,
+    is_secret_ref, SECRET_REF_SCHEME, REDACTED_SECRET
+};
 pub use webview_manager::{WebviewManager, WebviewTab};
 pub use browser_tab_manager::{BrowserTabManager, BrowserTab, CreateTabConfig, TabStats};
 pub use free_ip_providers::{FreeIpProvider, FreeIpProviderManager, ProxyFilter};
@@ -78,6 +107,8 @@ pub use storage::{
     SessionManager, BrowserSession, SessionTab, SessionSettings, SessionProxyConfig,
     WindowState, ScrollPosition, TabHistoryEntry, SessionStatistics,
     StorageEngine,
+    StorageContents,
+    StorageSnapshot,
     Cookie,
     HistoryEntry,
     Bookmark,
@@ -86,15 +117,31 @@ pub use storage::{
     ExportOptions,
     ImportExportStats
 };
-pub use backup::{BackupManager, BackupData, BackupOptions, BackupInfo, AutoBackupSettings};
+pub use history_search::{HistoryIndex, HistoryQuery, HistorySearchHit, HistoryField, FrecencyConfig};
+pub use retention::{DataRetentionManager, RetentionReport, ForgetSiteOptions, ForgetSiteReport};
+pub use ephemeral::{EphemeralContainer, EphemeralContainerManager, EphemeralTeardownReport};
+pub use profile_generator::{BulkProfileGenerator, ProfileTemplate, ProfileSettingsTemplate, GenerationRules, CountrySelection, BulkGenerationReport, GeneratedProfile, GenerationFailure, ProxyExitVerifier, GeoExitVerifier, ProxyExit};
+pub use profile_bundle::{ProfileBundler, BundleOptions, BundleManifest, BundleEntry, BundlePreview, BundleConflict, BundleImportReport, ConflictResolution};
+pub use secure_container::{KdfParams, ContainerHeader};
+pub use backup::{BackupManager, BackupData, BackupOptions, BackupInfo, AutoBackupSettings, BackupFrequency, GfsRetention};
+pub use backup_scheduler::{BackupScheduler, BackupSource, BackupEvent, BackupRunOutcome, SchedulerState};
+pub use backup_repository::{BackupRepository, SnapshotManifest, SnapshotStats, VerifyReport, PrunePolicy, PruneReport};
+pub use app_backup::{
+    AppBackupManager, AppBackup, AppBackupManifest, AppRestorePreview, AppRestoreReport,
+    BackupComponent, ComponentDiff, UserDirectory, UserRecord,
+};
+pub use restore_planner::{
+    RestorePlanner, RestorePlan, RestoreOptions, RestoreReport, RestoreCategory, RestoreMode,
+    ConflictRule, CategoryPolicy, CategoryDiff, CategoryOutcome, CategoryPlan,
+};
 pub use browser_controls::{
     BrowserController, BrowserState, BrowserSettings, WebRtcPolicy, HistoryItem,
     DownloadManager, DownloadItem, DownloadState,
     ContextMenuManager, ContextMenuItem, ContextMenuItemType, ContextType, ContextInfo
 };
 pub use local_proxy::{
-    LocalProxyServer, LocalProxyManager, ProxyConnection,
-    WebSocketProxyHandler, WebSocketInterception,
+    LocalProxyServer, LocalProxyManager, LocalProxyStats, ProxyConnection, ProxyGateway, GatewayRoute,
+    UpstreamFailover, WebSocketProxyHandler, WebSocketInterception,
     NetworkInterceptor, InterceptedRequest, ModificationRule, RequestModifications
 };
 pub use pac_server::{PacServer, PacManager};
@@ -106,7 +153,7 @@ pub use proxy_validator::{
     ProxyValidator, ProxyValidatorConfig, ValidationResult, ProxyHealthChecker,
     ProxyQuarantineManager, QuarantinedProxy, QuarantineStats,
     GeoVerifier, GeoVerificationConfig, GeoVerificationResult,
-    EnhancedProxyHealthChecker
+    EnhancedProxyHealthChecker, AnonymityLevel, JudgeResult, DEFAULT_JUDGE_URL
 };
 pub use chromium_engine::{
     ChromiumEngine,
@@ -141,6 +188,13 @@ pub use memory_profiler::{
     LeakReport,
     GcRecommendation
 };
+pub use auth::{
+    AuthManager, User, UserRole, Claims, RefreshToken, ApiScope, ApiKey, ApiKeyStore, NewApiKey,
+};
+pub use event_stream::{
+    BrowserEvent, EventBus, EventEnvelope, EventFilter, EventSubscription, EventTopic,
+    StreamGap, StreamItem,
+};
 pub use error_recovery::{
     ErrorRecoveryManager,
     ErrorRecoveryConfig,
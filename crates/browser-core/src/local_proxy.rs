@@ -1,15 +1,21 @@
 use anyhow::{anyhow, Result};
 use base64::engine::Engine;
-use std::collections::HashMap;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::proxy::ProxySettings;
+use crate::proxy::{FreeProxy, ProxySettings};
+use crate::proxy_rotation::{ProxyHealthMonitor, ProxyRotationManager, SmartProxySelector};
+use crate::proxy_validator::ProxyQuarantineManager;
 
 // ============================================================================
 // Shared Utility Functions
@@ -85,8 +91,9 @@ async fn establish_proxy_tunnel(
     Ok(proxy_stream)
 }
 
-/// Forward data from reader to writer until EOF or error
-async fn forward_data<R, W>(mut reader: R, mut writer: W)
+/// Forward data from reader to writer until EOF or error, adding the bytes
+/// written to every counter in `counters`
+async fn forward_data<R, W>(mut reader: R, mut writer: W, counters: &[&AtomicU64])
 where
     R: tokio::io::AsyncRead + Unpin,
     W: tokio::io::AsyncWrite + Unpin,
@@ -99,23 +106,78 @@ where
                 if writer.write_all(&buffer[..n]).await.is_err() {
                     break;
                 }
+                for counter in counters {
+                    counter.fetch_add(n as u64, Ordering::Relaxed);
+                }
             }
             Err(_) => break,
         }
     }
 }
 
-/// Bidirectional data forwarding between two streams
-async fn forward_bidirectional(client_stream: TcpStream, target_stream: TcpStream) {
+/// Bidirectional data forwarding between two streams, counting the bytes in
+/// each of `traffic`
+async fn forward_bidirectional(client_stream: TcpStream, target_stream: TcpStream, traffic: &[&ProxyTraffic]) {
     let (client_read, client_write) = client_stream.into_split();
     let (target_read, target_write) = target_stream.into_split();
+    let sent: Vec<&AtomicU64> = traffic.iter().map(|t| &t.bytes_sent).collect();
+    let received: Vec<&AtomicU64> = traffic.iter().map(|t| &t.bytes_received).collect();
 
     tokio::select! {
-        _ = forward_data(client_read, target_write) => {}
-        _ = forward_data(target_read, client_write) => {}
+        _ = forward_data(client_read, target_write, &sent) => {}
+        _ = forward_data(target_read, client_write, &received) => {}
+    }
+}
+
+/// Username from the `Proxy-Authorization: Basic` header of a request
+fn proxy_username(request: &str) -> Option<String> {
+    let value = request.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("proxy-authorization").then(|| value.trim())
+    })?;
+    let (scheme, encoded) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let credentials = String::from_utf8(decoded).ok()?;
+    let username = credentials.split_once(':').map_or(credentials.as_str(), |(username, _)| username);
+    Some(username.to_string())
+}
+
+/// Interceptor record for a CONNECT request; credentials are not kept
+fn intercepted_connect(request: &str, target: &str, status: u16, blocked: bool) -> InterceptedRequest {
+    let headers = request
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| !name.trim().eq_ignore_ascii_case("proxy-authorization"))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    InterceptedRequest {
+        id: Uuid::new_v4().to_string(),
+        method: "CONNECT".to_string(),
+        url: target.to_string(),
+        headers,
+        body: None,
+        timestamp: chrono::Utc::now(),
+        response_status: Some(status),
+        response_headers: None,
+        blocked,
+        modified: false,
     }
 }
 
+/// Bytes relayed by a local proxy server
+#[derive(Debug, Default)]
+struct ProxyTraffic {
+    /// From the browser towards the target
+    bytes_sent: AtomicU64,
+    /// From the target back to the browser
+    bytes_received: AtomicU64,
+}
+
 // ============================================================================
 // Local Proxy Server
 // ============================================================================
@@ -123,9 +185,26 @@ async fn forward_bidirectional(client_stream: TcpStream, target_stream: TcpStrea
 /// Local proxy server for routing tab traffic through upstream proxies
 pub struct LocalProxyServer {
     bind_addr: SocketAddr,
-    upstream_proxy: Option<ProxySettings>,
+    upstream_proxy: Arc<RwLock<Option<ProxySettings>>>,
+    gateway: Option<Arc<ProxyGateway>>,
+    failover: Option<UpstreamFailover>,
+    session: Option<String>,
+    interceptor: Option<Arc<NetworkInterceptor>>,
     connections: Arc<RwLock<HashMap<String, ProxyConnection>>>,
     is_running: Arc<RwLock<bool>>,
+    /// Task owning the listener; aborted on stop so the port is freed
+    accept_task: Mutex<Option<JoinHandle<()>>>,
+    traffic: Arc<ProxyTraffic>,
+}
+
+/// Connection and traffic figures for one tab's local proxy server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalProxyStats {
+    pub tab_id: String,
+    pub proxy_url: String,
+    pub active_connections: usize,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
 }
 
 /// Represents an active proxy connection
@@ -140,6 +219,227 @@ pub struct ProxyConnection {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// How a local proxy server recovers when its upstream refuses or times out:
+/// the CONNECT is retried on the next-best proxy from the pool, within a
+/// bounded number of attempts and a time budget
+#[derive(Clone)]
+pub struct UpstreamFailover {
+    pool: Arc<ProxyGateway>,
+    max_attempts: u32,
+    attempt_timeout: Duration,
+    time_budget: Duration,
+    rebind_session: bool,
+    fail_closed: bool,
+}
+
+impl UpstreamFailover {
+    /// Fail over to proxies from `pool`: three attempts of up to 10 seconds
+    /// each within 20 seconds, without rebinding sessions, never going direct
+    pub fn new(pool: Arc<ProxyGateway>) -> Self {
+        Self {
+            pool,
+            max_attempts: 3,
+            attempt_timeout: Duration::from_secs(10),
+            time_budget: Duration::from_secs(20),
+            rebind_session: false,
+            fail_closed: true,
+        }
+    }
+
+    /// Upstreams tried per connection, the first one included
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Time one upstream gets to set up the tunnel
+    pub fn with_attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_timeout = timeout;
+        self
+    }
+
+    /// Time all attempts of one connection get together
+    pub fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = budget;
+        self
+    }
+
+    /// Move the sticky session, and a tab server's upstream, to the proxy
+    /// that worked after failing over
+    pub fn with_rebind_session(mut self, rebind: bool) -> Self {
+        self.rebind_session = rebind;
+        self
+    }
+
+    /// When `false`, connect directly once every upstream failed instead of
+    /// refusing the connection
+    pub fn with_fail_closed(mut self, fail_closed: bool) -> Self {
+        self.fail_closed = fail_closed;
+        self
+    }
+}
+
+/// Upstream a connection is tunnelled through, with its pool entry when the
+/// pool lists it
+struct Upstream {
+    settings: ProxySettings,
+    proxy: Option<FreeProxy>,
+}
+
+impl Upstream {
+    fn from_pool(proxy: FreeProxy) -> Self {
+        Self {
+            settings: proxy.to_proxy_settings(),
+            proxy: Some(proxy),
+        }
+    }
+}
+
+/// State shared by the connection handlers of one server
+struct ServerContext {
+    upstream_proxy: Arc<RwLock<Option<ProxySettings>>>,
+    gateway: Option<Arc<ProxyGateway>>,
+    failover: Option<UpstreamFailover>,
+    session: Option<String>,
+    interceptor: Option<Arc<NetworkInterceptor>>,
+    connections: Arc<RwLock<HashMap<String, ProxyConnection>>>,
+    traffic: Arc<ProxyTraffic>,
+}
+
+impl ServerContext {
+    /// Log a CONNECT and the status it was answered with
+    async fn intercept(&self, request: &str, target: &str, status: u16) {
+        if let Some(interceptor) = &self.interceptor {
+            interceptor.log_request(intercepted_connect(request, target, status, false)).await;
+        }
+    }
+
+    /// Pool that upstream outcomes are reported to
+    fn pool(&self) -> Option<&Arc<ProxyGateway>> {
+        self.failover.as_ref().map(|f| &f.pool).or(self.gateway.as_ref())
+    }
+
+    /// First upstream to try for `route`; `None` connects directly
+    async fn first_upstream(&self, route: &GatewayRoute) -> Result<Option<Upstream>> {
+        if let Some(gateway) = &self.gateway {
+            return match gateway.select(route, &HashSet::new()).await {
+                Ok(proxy) => Ok(Some(Upstream::from_pool(proxy))),
+                Err(e) if self.failover.as_ref().is_some_and(|f| !f.fail_closed) => {
+                    warn!("{}; connecting directly", e);
+                    Ok(None)
+                }
+                Err(e) => Err(e),
+            };
+        }
+
+        let Some(settings) = self.upstream_proxy.read().await.clone() else {
+            return Ok(None);
+        };
+        let proxy = match self.pool() {
+            Some(pool) => pool.find(&settings).await,
+            None => None,
+        };
+        Ok(Some(Upstream { settings, proxy }))
+    }
+
+    /// Open a tunnel to the target through `first`, failing over to the
+    /// next-best pool proxy when allowed. Returns the stream, the upstream
+    /// that carried it and how long the tunnel took to set up.
+    async fn open_tunnel(
+        &self,
+        first: Option<Upstream>,
+        route: &GatewayRoute,
+        target_host: &str,
+        target_port: u16,
+    ) -> Result<(TcpStream, Option<Upstream>, f64)> {
+        let started = Instant::now();
+        let Some(mut upstream) = first else {
+            let stream = LocalProxyServer::connect_direct(target_host, target_port).await?;
+            return Ok((stream, None, elapsed_ms(started)));
+        };
+
+        let failover = self.failover.as_ref();
+        let max_attempts = failover.map_or(1, |f| f.max_attempts);
+        let country = route
+            .country
+            .clone()
+            .or_else(|| upstream.proxy.as_ref().map(|p| p.country_code.clone()));
+        let mut exclude = HashSet::new();
+        let mut attempt = 1;
+        let error = loop {
+            let attempt_started = Instant::now();
+            let address = get_proxy_address(&upstream.settings)?;
+            let result = match failover {
+                Some(f) => {
+                    let remaining = f.time_budget.saturating_sub(started.elapsed());
+                    tokio::time::timeout(
+                        f.attempt_timeout.min(remaining),
+                        establish_proxy_tunnel(&upstream.settings, target_host, target_port),
+                    )
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow!("Proxy {} timed out", address)))
+                }
+                None => establish_proxy_tunnel(&upstream.settings, target_host, target_port).await,
+            };
+            let e = match result {
+                Ok(stream) => {
+                    if attempt > 1 {
+                        self.rebind(route, &upstream).await;
+                    }
+                    return Ok((stream, Some(upstream), elapsed_ms(attempt_started)));
+                }
+                Err(e) => e,
+            };
+
+            warn!(
+                "Upstream {} failed for {}:{} (attempt {}/{}): {}",
+                address, target_host, target_port, attempt, max_attempts, e
+            );
+            if let (Some(pool), Some(proxy)) = (self.pool(), &upstream.proxy) {
+                pool.report_failure(proxy, &e.to_string()).await;
+            }
+            exclude.insert(address);
+
+            let Some(f) = failover else { break e };
+            if attempt >= max_attempts || started.elapsed() >= f.time_budget {
+                break e;
+            }
+            match f.pool.next_best(country.as_deref(), &exclude).await {
+                Ok(next) => upstream = Upstream::from_pool(next),
+                Err(_) => break e,
+            }
+            attempt += 1;
+        };
+
+        if failover.is_some_and(|f| !f.fail_closed) {
+            warn!("Every upstream failed for {}:{}; connecting directly", target_host, target_port);
+            let stream = LocalProxyServer::connect_direct(target_host, target_port).await?;
+            return Ok((stream, None, elapsed_ms(started)));
+        }
+        Err(error)
+    }
+
+    /// Move the sticky session to `upstream` after failing over to it
+    async fn rebind(&self, route: &GatewayRoute, upstream: &Upstream) {
+        if !self.failover.as_ref().is_some_and(|f| f.rebind_session) {
+            return;
+        }
+        let session = if self.gateway.is_some() {
+            route.session.as_deref().map(gateway_session_key)
+        } else {
+            *self.upstream_proxy.write().await = Some(upstream.settings.clone());
+            self.session.clone()
+        };
+        if let (Some(pool), Some(session), Some(proxy)) = (self.pool(), session, &upstream.proxy) {
+            pool.rebind_session(&session, proxy).await;
+        }
+    }
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
 impl LocalProxyServer {
     /// Create a new local proxy server
     pub fn new(bind_port: u16, upstream_proxy: Option<ProxySettings>) -> Result<Self> {
@@ -149,12 +449,50 @@ impl LocalProxyServer {
 
         Ok(Self {
             bind_addr,
-            upstream_proxy,
+            upstream_proxy: Arc::new(RwLock::new(upstream_proxy)),
+            gateway: None,
+            failover: None,
+            session: None,
+            interceptor: None,
             connections: Arc::new(RwLock::new(HashMap::new())),
             is_running: Arc::new(RwLock::new(false)),
+            accept_task: Mutex::new(None),
+            traffic: Arc::new(ProxyTraffic::default()),
         })
     }
 
+    /// Listen on `addr` instead of `127.0.0.1`
+    pub fn with_bind_addr(mut self, addr: SocketAddr) -> Self {
+        self.bind_addr = addr;
+        self
+    }
+
+    /// Run as a gateway: every connection gets its own upstream from
+    /// `gateway` instead of the fixed upstream proxy
+    pub fn with_gateway(mut self, gateway: Arc<ProxyGateway>) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    /// Retry failed upstream CONNECTs on other proxies as set in `failover`
+    pub fn with_failover(mut self, failover: UpstreamFailover) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    /// Rotation session the fixed upstream belongs to, usually the tab ID;
+    /// rebound when failover is set to rebind sessions
+    pub fn with_session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Log every CONNECT to `interceptor` and refuse targets it blocks
+    pub fn with_interceptor(mut self, interceptor: Arc<NetworkInterceptor>) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
     /// Start the local proxy server
     pub async fn start(&self) -> Result<()> {
         let mut is_running = self.is_running.write().await;
@@ -168,15 +506,24 @@ impl LocalProxyServer {
 
         info!("Local proxy server listening on {}", self.bind_addr);
         *is_running = true;
-        drop(is_running);
 
-        let connections = self.connections.clone();
-        let upstream_proxy = self.upstream_proxy.clone();
-        let is_running = self.is_running.clone();
-
-        tokio::spawn(async move {
-            Self::accept_connections(listener, connections, upstream_proxy, is_running).await;
+        let context = Arc::new(ServerContext {
+            upstream_proxy: self.upstream_proxy.clone(),
+            gateway: self.gateway.clone(),
+            failover: self.failover.clone(),
+            session: self.session.clone(),
+            interceptor: self.interceptor.clone(),
+            connections: self.connections.clone(),
+            traffic: self.traffic.clone(),
         });
+        let running = self.is_running.clone();
+
+        // Stored while `is_running` is still held, so a concurrent stop
+        // always finds the task to abort
+        *self.accept_task.lock().await = Some(tokio::spawn(async move {
+            Self::accept_connections(listener, context, running).await;
+        }));
+        drop(is_running);
 
         Ok(())
     }
@@ -184,8 +531,7 @@ impl LocalProxyServer {
     /// Accept incoming connections loop (extracted for reduced complexity)
     async fn accept_connections(
         listener: TcpListener,
-        connections: Arc<RwLock<HashMap<String, ProxyConnection>>>,
-        upstream_proxy: Option<ProxySettings>,
+        context: Arc<ServerContext>,
         is_running: Arc<RwLock<bool>>,
     ) {
         while *is_running.read().await {
@@ -193,18 +539,11 @@ impl LocalProxyServer {
                 Ok((stream, addr)) => {
                     debug!("New connection from {}", addr);
                     let conn_id = Uuid::new_v4().to_string();
-                    let connections_clone = connections.clone();
-                    let upstream_proxy_clone = upstream_proxy.clone();
+                    let context_clone = context.clone();
 
                     tokio::spawn(async move {
-                        if let Err(e) = Self::handle_connection(
-                            stream,
-                            addr.to_string(),
-                            conn_id.clone(),
-                            upstream_proxy_clone,
-                            connections_clone,
-                        )
-                        .await
+                        if let Err(e) =
+                            Self::handle_connection(stream, addr.to_string(), conn_id.clone(), context_clone).await
                         {
                             error!("Error handling connection {}: {}", conn_id, e);
                         }
@@ -219,11 +558,18 @@ impl LocalProxyServer {
         }
     }
 
-    /// Stop the local proxy server
+    /// Stop the local proxy server; the listener is closed by the time
+    /// this returns, so its port can be bound again
     pub async fn stop(&self) -> Result<()> {
         let mut is_running = self.is_running.write().await;
         *is_running = false;
 
+        if let Some(task) = self.accept_task.lock().await.take() {
+            task.abort();
+            // Resolves once the task, and with it the listener, is dropped
+            let _ = task.await;
+        }
+
         let mut connections = self.connections.write().await;
         connections.clear();
 
@@ -236,44 +582,99 @@ impl LocalProxyServer {
         format!("http://{}", self.bind_addr)
     }
 
-    /// Handle an incoming proxy connection (refactored for lower complexity)
+    /// Upstream proxy new connections are tunnelled through first
+    pub async fn upstream_proxy(&self) -> Option<ProxySettings> {
+        self.upstream_proxy.read().await.clone()
+    }
+
+    /// Handle an incoming proxy connection: set up the tunnel before
+    /// answering the CONNECT, so a failed upstream is reported to the client
+    /// and, in gateway mode or with failover, to the pool
     async fn handle_connection(
         mut client_stream: TcpStream,
         client_addr: String,
         conn_id: String,
-        upstream_proxy: Option<ProxySettings>,
-        connections: Arc<RwLock<HashMap<String, ProxyConnection>>>,
+        context: Arc<ServerContext>,
     ) -> Result<()> {
-        let (target_host, target_port) = Self::read_and_parse_connect_request(&mut client_stream).await?;
+        let request = Self::read_request(&mut client_stream).await?;
+        let (target_host, target_port) = Self::parse_connect_request(&request)?;
+        let route = proxy_username(&request)
+            .map(|username| GatewayRoute::from_username(&username))
+            .unwrap_or_default();
+
+        let target = format!("{}:{}", target_host, target_port);
+        if let Some(interceptor) = &context.interceptor {
+            if interceptor.should_block(&target).await {
+                interceptor.log_request(intercepted_connect(&request, &target, 403, true)).await;
+                client_stream.write_all(b"HTTP/1.1 403 Forbidden\r\n\r\n").await?;
+                debug!("Blocked CONNECT to {}", target);
+                return Ok(());
+            }
+        }
+
+        let first = match context.first_upstream(&route).await {
+            Ok(first) => first,
+            Err(e) => {
+                context.intercept(&request, &target, 503).await;
+                client_stream.write_all(b"HTTP/1.1 503 Service Unavailable\r\n\r\n").await?;
+                return Err(e);
+            }
+        };
 
         Self::record_connection(
-            &connections,
+            &context.connections,
             &conn_id,
             &client_addr,
             &target_host,
             target_port,
-            &upstream_proxy,
+            &first.as_ref().map(|upstream| upstream.settings.clone()),
         ).await;
 
-        // Send 200 Connection established response
-        client_stream.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await?;
-
-        let target_stream = Self::connect_to_target(&upstream_proxy, &target_host, target_port).await?;
+        let result = async {
+            let (target_stream, upstream, connect_ms) =
+                match context.open_tunnel(first, &route, &target_host, target_port).await {
+                    Ok(tunnel) => tunnel,
+                    Err(e) => {
+                        context.intercept(&request, &target, 502).await;
+                        client_stream.write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n").await?;
+                        return Err(e);
+                    }
+                };
+            // Failover may have moved the connection to another upstream
+            if let Some(connection) = context.connections.write().await.get_mut(&conn_id) {
+                connection.upstream_proxy = upstream.as_ref().map(|u| u.settings.clone());
+            }
 
-        forward_bidirectional(client_stream, target_stream).await;
+            context.intercept(&request, &target, 200).await;
+            client_stream.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await?;
+
+            let connection_traffic = ProxyTraffic::default();
+            forward_bidirectional(client_stream, target_stream, &[context.traffic.as_ref(), &connection_traffic]).await;
+            if let (Some(pool), Some(proxy)) = (context.pool(), upstream.and_then(|u| u.proxy)) {
+                pool.report_success(
+                    &proxy,
+                    connect_ms,
+                    connection_traffic.bytes_sent.load(Ordering::Relaxed),
+                    connection_traffic.bytes_received.load(Ordering::Relaxed),
+                )
+                .await;
+            }
+            Ok(())
+        }
+        .await;
 
-        Self::remove_connection(&connections, &conn_id).await;
+        // Untrack the connection even when the tunnel could not be set up
+        Self::remove_connection(&context.connections, &conn_id).await;
 
         debug!("Connection {} closed", conn_id);
-        Ok(())
+        result
     }
 
-    /// Read and parse the CONNECT request from client
-    async fn read_and_parse_connect_request(client_stream: &mut TcpStream) -> Result<(String, u16)> {
+    /// Read the request head sent by the client
+    async fn read_request(client_stream: &mut TcpStream) -> Result<String> {
         let mut buffer = vec![0u8; 4096];
         let n = client_stream.read(&mut buffer).await?;
-        let request = String::from_utf8_lossy(&buffer[..n]);
-        Self::parse_connect_request(&request)
+        Ok(String::from_utf8_lossy(&buffer[..n]).into_owned())
     }
 
     /// Record a new connection
@@ -308,19 +709,6 @@ impl LocalProxyServer {
         conns.remove(conn_id);
     }
 
-    /// Connect to target (directly or through upstream proxy)
-    async fn connect_to_target(
-        upstream_proxy: &Option<ProxySettings>,
-        target_host: &str,
-        target_port: u16,
-    ) -> Result<TcpStream> {
-        if let Some(ref proxy) = upstream_proxy {
-            establish_proxy_tunnel(proxy, target_host, target_port).await
-        } else {
-            Self::connect_direct(target_host, target_port).await
-        }
-    }
-
     /// Parse HTTP CONNECT request to extract target host and port
     fn parse_connect_request(request: &str) -> Result<(String, u16)> {
         let first_line = request.lines().next()
@@ -362,12 +750,253 @@ impl LocalProxyServer {
         self.connections.read().await.values().cloned().collect()
     }
 
+    /// Number of connections currently open
+    pub async fn active_connection_count(&self) -> usize {
+        self.connections.read().await.len()
+    }
+
+    /// Bytes relayed so far, as `(sent, received)`
+    pub fn bytes_transferred(&self) -> (u64, u64) {
+        (
+            self.traffic.bytes_sent.load(Ordering::Relaxed),
+            self.traffic.bytes_received.load(Ordering::Relaxed),
+        )
+    }
+
     /// Check if the proxy server is running
     pub async fn is_running(&self) -> bool {
         *self.is_running.read().await
     }
 }
 
+// ============================================================================
+// Rotating Gateway
+// ============================================================================
+
+/// Number of top-scoring proxies a non-sticky gateway connection picks from
+const GATEWAY_CANDIDATES: usize = 5;
+
+/// Idle time after which a gateway client's sticky session is dropped
+const GATEWAY_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Longest time between two sweeps for idle gateway sessions
+const GATEWAY_SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Prefix of the rotation sessions kept for gateway clients
+const GATEWAY_SESSION_PREFIX: &str = "gateway:";
+
+/// Routing options a gateway client puts in its proxy username as
+/// `key-value` pairs, e.g. `session-abc-country-DE`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GatewayRoute {
+    /// Connections with the same session keep their upstream until it fails
+    pub session: Option<String>,
+    /// Country code the upstream must be in
+    pub country: Option<String>,
+}
+
+impl GatewayRoute {
+    /// Parse the `session` and `country` pairs of a proxy username; other
+    /// parts are ignored
+    pub fn from_username(username: &str) -> Self {
+        let mut route = Self::default();
+        let mut parts = username.split('-');
+        while let Some(key) = parts.next() {
+            let slot = match key.to_ascii_lowercase().as_str() {
+                "session" => &mut route.session,
+                "country" => &mut route.country,
+                _ => continue,
+            };
+            *slot = parts.next().filter(|value| !value.is_empty()).map(str::to_string);
+        }
+        route.country = route.country.map(|cc| cc.to_ascii_uppercase());
+        route
+    }
+}
+
+/// Upstream selection from the proxy pool, for gateway servers and for
+/// failover: picks proxies and reports how they did to the health monitor
+/// and, when set, the quarantine. Sticky sessions of gateway clients expire
+/// once idle for the session TTL.
+pub struct ProxyGateway {
+    rotation: Arc<RwLock<ProxyRotationManager>>,
+    health: Arc<ProxyHealthMonitor>,
+    quarantine: Option<Arc<ProxyQuarantineManager>>,
+    selector: SmartProxySelector,
+    session_ttl: Duration,
+    last_session_sweep: std::sync::Mutex<Instant>,
+}
+
+impl ProxyGateway {
+    /// Create a gateway over the pool behind `rotation`
+    pub fn new(rotation: Arc<RwLock<ProxyRotationManager>>, health: Arc<ProxyHealthMonitor>) -> Self {
+        Self {
+            rotation,
+            health,
+            quarantine: None,
+            selector: SmartProxySelector::default(),
+            session_ttl: GATEWAY_SESSION_TTL,
+            last_session_sweep: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Drop a client's sticky session after it has been idle for `ttl`
+    pub fn with_session_ttl(mut self, ttl: Duration) -> Self {
+        self.session_ttl = ttl;
+        self
+    }
+
+    /// Record failures in `quarantine` and skip the proxies it holds
+    pub fn with_quarantine(mut self, quarantine: Arc<ProxyQuarantineManager>) -> Self {
+        self.quarantine = Some(quarantine);
+        self
+    }
+
+    /// Score non-sticky candidates with `selector`
+    pub fn with_selector(mut self, selector: SmartProxySelector) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// `exclude` plus the proxies marked unhealthy within their recovery
+    /// interval and the quarantined ones
+    async fn unusable(&self, exclude: &HashSet<String>) -> HashSet<String> {
+        let mut unusable = exclude.clone();
+        let recovery = chrono::Duration::seconds(self.health.recovery_interval_secs as i64);
+        let now = chrono::Utc::now();
+        unusable.extend(
+            self.health
+                .all_health()
+                .await
+                .into_iter()
+                .filter(|h| !h.is_healthy && h.last_check + recovery > now)
+                .map(|h| h.proxy_id),
+        );
+        if let Some(quarantine) = &self.quarantine {
+            unusable.extend(
+                quarantine
+                    .get_quarantined()
+                    .await
+                    .into_iter()
+                    .filter(|q| q.release_at > now)
+                    .map(|q| gateway_proxy_id(&q.proxy)),
+            );
+        }
+        unusable
+    }
+
+    /// Upstream for a connection on `route`, never one of the `ip:port` IDs
+    /// in `exclude`. Sticky sessions keep their proxy through the rotation
+    /// manager; other connections get one of the best-scoring proxies at
+    /// random. Proxies marked unhealthy are skipped until their recovery
+    /// interval has passed, quarantined ones until their release.
+    pub async fn select(&self, route: &GatewayRoute, exclude: &HashSet<String>) -> Result<FreeProxy> {
+        if route.session.is_some() {
+            self.expire_idle_sessions().await;
+        }
+        let exclude = self.unusable(exclude).await;
+        let rotation = self.rotation.read().await;
+        let country = route.country.as_deref();
+        match &route.session {
+            Some(session) => {
+                rotation
+                    .get_proxy_for_country(&gateway_session_key(session), country, &exclude)
+                    .await
+            }
+            None => {
+                let ranked = rotation
+                    .rank_proxies(&self.selector, country, &exclude, GATEWAY_CANDIDATES)
+                    .await;
+                if ranked.is_empty() {
+                    return Err(anyhow!(
+                        "No working proxies available in {}",
+                        country.unwrap_or("any country")
+                    ));
+                }
+                Ok(ranked[rand::thread_rng().gen_range(0..ranked.len())].clone())
+            }
+        }
+    }
+
+    /// Drop idle gateway sessions, at most once per sweep interval
+    async fn expire_idle_sessions(&self) {
+        {
+            let mut last = self.last_session_sweep.lock().unwrap_or_else(|e| e.into_inner());
+            if last.elapsed() < GATEWAY_SESSION_SWEEP_INTERVAL.min(self.session_ttl) {
+                return;
+            }
+            *last = Instant::now();
+        }
+        let max_age = chrono::Duration::from_std(self.session_ttl).unwrap_or(chrono::Duration::MAX);
+        self.rotation
+            .read()
+            .await
+            .expire_sessions(GATEWAY_SESSION_PREFIX, max_age)
+            .await;
+    }
+
+    /// Best-scoring usable proxy from `country_code` outside `exclude`,
+    /// the next one to fail over to
+    pub async fn next_best(&self, country_code: Option<&str>, exclude: &HashSet<String>) -> Result<FreeProxy> {
+        let exclude = self.unusable(exclude).await;
+        self.rotation
+            .read()
+            .await
+            .rank_proxies(&self.selector, country_code, &exclude, 1)
+            .await
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No other working proxies available in {}", country_code.unwrap_or("any country")))
+    }
+
+    /// Pool entry of an upstream given by its settings
+    pub async fn find(&self, settings: &ProxySettings) -> Option<FreeProxy> {
+        let host = settings.host.as_deref()?;
+        self.rotation.read().await.find_proxy(host, settings.port?).await
+    }
+
+    /// Move the rotation session `session_id` to `proxy`
+    pub async fn rebind_session(&self, session_id: &str, proxy: &FreeProxy) {
+        self.rotation.read().await.rebind_session(session_id, proxy).await;
+    }
+
+    /// Record a tunnel through `proxy` that connected in `connect_ms` and
+    /// relayed the given bytes
+    pub async fn report_success(&self, proxy: &FreeProxy, connect_ms: f64, bytes_sent: u64, bytes_received: u64) {
+        self.health
+            .record_success(&gateway_proxy_id(proxy), connect_ms, bytes_sent, bytes_received)
+            .await;
+        if let Some(quarantine) = &self.quarantine {
+            quarantine.record_success(proxy).await;
+        }
+        self.rotation
+            .read()
+            .await
+            .record_performance(&proxy.ip, true, Some(connect_ms))
+            .await;
+    }
+
+    /// Record a tunnel through `proxy` that could not be set up
+    pub async fn report_failure(&self, proxy: &FreeProxy, error: &str) {
+        self.health.record_failure(&gateway_proxy_id(proxy), error).await;
+        if let Some(quarantine) = &self.quarantine {
+            quarantine.record_failure(proxy, error.to_string()).await;
+        }
+        self.rotation.read().await.record_performance(&proxy.ip, false, None).await;
+    }
+}
+
+/// `ip:port` ID the health monitor tracks a proxy under
+fn gateway_proxy_id(proxy: &FreeProxy) -> String {
+    format!("{}:{}", proxy.ip, proxy.port)
+}
+
+/// Rotation session a gateway client's sticky session is kept under, apart
+/// from tab sessions
+fn gateway_session_key(session: &str) -> String {
+    format!("{}{}", GATEWAY_SESSION_PREFIX, session)
+}
+
 // ============================================================================
 // Local Proxy Manager
 // ============================================================================
@@ -377,6 +1006,7 @@ pub struct LocalProxyManager {
     proxy_servers: Arc<RwLock<HashMap<String, Arc<LocalProxyServer>>>>,
     port_range: std::ops::Range<u16>,
     used_ports: Arc<RwLock<std::collections::HashSet<u16>>>,
+    failover: Option<UpstreamFailover>,
 }
 
 impl LocalProxyManager {
@@ -386,18 +1016,44 @@ impl LocalProxyManager {
             proxy_servers: Arc::new(RwLock::new(HashMap::new())),
             port_range,
             used_ports: Arc::new(RwLock::new(std::collections::HashSet::new())),
+            failover: None,
         }
     }
 
+    /// Give every tab's proxy server `failover`, rebinding the tab's own
+    /// rotation session
+    pub fn with_failover(mut self, failover: UpstreamFailover) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
     /// Create a proxy server for a specific tab
     pub async fn create_proxy_for_tab(
         &self,
         tab_id: &str,
         upstream_proxy: Option<ProxySettings>,
+    ) -> Result<String> {
+        self.create_intercepted_proxy_for_tab(tab_id, upstream_proxy, None).await
+    }
+
+    /// Create a proxy server for a tab whose traffic is logged to, and
+    /// filtered by, `interceptor`
+    pub async fn create_intercepted_proxy_for_tab(
+        &self,
+        tab_id: &str,
+        upstream_proxy: Option<ProxySettings>,
+        interceptor: Option<Arc<NetworkInterceptor>>,
     ) -> Result<String> {
         let port = self.find_available_port().await?;
 
-        let proxy_server = Arc::new(LocalProxyServer::new(port, upstream_proxy)?);
+        let mut proxy_server = LocalProxyServer::new(port, upstream_proxy)?;
+        if let Some(failover) = &self.failover {
+            proxy_server = proxy_server.with_failover(failover.clone()).with_session(tab_id);
+        }
+        if let Some(interceptor) = interceptor {
+            proxy_server = proxy_server.with_interceptor(interceptor);
+        }
+        let proxy_server = Arc::new(proxy_server);
         proxy_server.start().await?;
 
         self.register_proxy_server(tab_id, proxy_server.clone(), port).await;
@@ -452,6 +1108,29 @@ impl LocalProxyManager {
         servers.get(tab_id).map(|server| server.get_proxy_url())
     }
 
+    /// Upstream a tab's proxy server tunnels through first
+    pub async fn upstream_for_tab(&self, tab_id: &str) -> Option<ProxySettings> {
+        let server = self.proxy_servers.read().await.get(tab_id).cloned()?;
+        server.upstream_proxy().await
+    }
+
+    /// File a tab's proxy server under a new ID, e.g. once the browser has
+    /// assigned the tab's real ID. Returns false if `from` has no server or
+    /// `to` already has one.
+    pub async fn rename_tab(&self, from: &str, to: &str) -> bool {
+        let mut servers = self.proxy_servers.write().await;
+        if servers.contains_key(to) {
+            return false;
+        }
+        match servers.remove(from) {
+            Some(server) => {
+                servers.insert(to.to_string(), server);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Find an available port in the configured range
     async fn find_available_port(&self) -> Result<u16> {
         let used_ports = self.used_ports.read().await;
@@ -474,6 +1153,28 @@ impl LocalProxyManager {
             .collect()
     }
 
+    /// Connection and traffic figures for every tab's proxy server, ordered by tab ID
+    pub async fn tab_stats(&self) -> Vec<LocalProxyStats> {
+        let servers: Vec<_> = {
+            let servers = self.proxy_servers.read().await;
+            servers.iter().map(|(tab_id, server)| (tab_id.clone(), server.clone())).collect()
+        };
+
+        let mut stats = Vec::with_capacity(servers.len());
+        for (tab_id, server) in servers {
+            let (bytes_sent, bytes_received) = server.bytes_transferred();
+            stats.push(LocalProxyStats {
+                tab_id,
+                proxy_url: server.get_proxy_url(),
+                active_connections: server.active_connection_count().await,
+                bytes_sent,
+                bytes_received,
+            });
+        }
+        stats.sort_by(|a, b| a.tab_id.cmp(&b.tab_id));
+        stats
+    }
+
     /// Stop all proxy servers
     pub async fn stop_all(&self) -> Result<()> {
         let servers: Vec<_> = {
@@ -285,6 +285,11 @@ impl BandwidthManager {
         allocated
     }
 
+    /// Change the bandwidth cap (0 = unlimited); existing allocations are kept
+    pub fn set_max_bandwidth(&mut self, max_bandwidth_bps: u64) {
+        self.max_bandwidth_bps = max_bandwidth_bps;
+    }
+
     /// Release bandwidth allocation
     pub fn release(&mut self, id: &str) {
         self.allocations.remove(id);
@@ -653,6 +658,11 @@ impl NetworkIntelligence {
         }
     }
 
+    /// Shared handle to the bandwidth manager, for live reconfiguration
+    pub fn bandwidth_manager(&self) -> Arc<RwLock<BandwidthManager>> {
+        self.bandwidth_manager.clone()
+    }
+
     /// Record a network request
     pub async fn record_request(&self, record: RequestRecord) {
         if self.config.traffic_analysis_enabled {
@@ -221,6 +221,21 @@ impl PredictiveCache {
         }
     }
 
+    /// Remove all entries whose URL matches the predicate
+    pub fn remove_where<F: Fn(&str) -> bool>(&mut self, predicate: F) -> usize {
+        let before = self.entries.len();
+        let mut freed = 0;
+        self.entries.retain(|url, entry| {
+            let remove = predicate(url);
+            if remove {
+                freed += entry.size_bytes;
+            }
+            !remove
+        });
+        self.total_size_bytes = self.total_size_bytes.saturating_sub(freed);
+        before - self.entries.len()
+    }
+
     /// Get prefetch candidates
     pub fn get_prefetch_candidates(&self, current_url: &str, limit: usize) -> Vec<String> {
         self.prediction_model.predict_next(current_url, limit)
@@ -521,6 +536,18 @@ impl PerformanceOptimizer {
         info!("Performance cache cleared");
     }
 
+    /// Evict cached resources served from a domain or any of its subdomains
+    pub async fn evict_domain(&self, domain: &str) -> usize {
+        let mut cache = self.cache.write().await;
+        let removed = cache.remove_where(|url| {
+            crate::retention::host_of(url).is_some_and(|h| crate::retention::host_matches_domain(&h, domain))
+        });
+        if removed > 0 {
+            info!("Evicted {} cached resources for {}", removed, domain);
+        }
+        removed
+    }
+
     /// Get configuration
     pub fn get_config(&self) -> &PerformanceConfig {
         &self.config
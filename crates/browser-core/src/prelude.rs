@@ -401,11 +401,87 @@ pub mod validators {
     }
 }
 
+/// Upper bounds of histogram buckets, in seconds (the Prometheus defaults)
+pub const DEFAULT_HISTOGRAM_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Samples kept per histogram for `get_histogram_stats`
+pub const HISTOGRAM_WINDOW: usize = 1024;
+
+/// Stable names of the metrics exported at `/metrics`
+pub mod metric_names {
+    /// Counter of requests per `proxy` and `outcome` (`success`/`failure`)
+    pub const PROXY_REQUESTS_TOTAL: &str = "proxy_browser_proxy_requests_total";
+    /// Histogram of request latency per `proxy`
+    pub const PROXY_LATENCY_SECONDS: &str = "proxy_browser_proxy_latency_seconds";
+    /// Counter of bytes per `proxy` and `direction` (`sent`/`received`)
+    pub const PROXY_BYTES_TOTAL: &str = "proxy_browser_proxy_bytes_total";
+    /// Gauge of the health score (0-1) per `proxy`
+    pub const PROXY_HEALTH_SCORE: &str = "proxy_browser_proxy_health_score";
+    /// Gauge of pooled proxies
+    pub const PROXY_POOL_SIZE: &str = "proxy_browser_proxy_pool_size";
+    /// Gauge of quarantined proxies
+    pub const QUARANTINED_PROXIES: &str = "proxy_browser_quarantined_proxies";
+    /// Gauge of tabs per `status`
+    pub const TABS: &str = "proxy_browser_tabs";
+    /// Gauge of per-tab proxy rotation sessions
+    pub const ROTATION_SESSIONS: &str = "proxy_browser_rotation_sessions";
+    /// Gauge of open connections per `tab` in its local proxy server
+    pub const LOCAL_PROXY_ACTIVE_CONNECTIONS: &str = "proxy_browser_local_proxy_active_connections";
+    /// Counter of bytes relayed per `tab` and `direction` (`sent`/`received`)
+    pub const TAB_BYTES_TOTAL: &str = "proxy_browser_tab_bytes_total";
+    /// Gauge, 1 for the current `state` of each circuit `breaker`
+    pub const CIRCUIT_BREAKER_STATE: &str = "proxy_browser_circuit_breaker_state";
+    /// Gauge of tabs open in the integrated Chromium engine
+    pub const ENGINE_TABS: &str = "proxy_browser_engine_tabs";
+    /// Counter of page loads by the integrated Chromium engine
+    pub const ENGINE_PAGE_LOADS_TOTAL: &str = "proxy_browser_engine_page_loads_total";
+    /// Counter of CDP commands sent to the integrated Chromium engine
+    pub const ENGINE_CDP_COMMANDS_TOTAL: &str = "proxy_browser_engine_cdp_commands_total";
+}
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    /// Most recent samples, for percentiles
+    recent: std::collections::VecDeque<f64>,
+    /// Cumulative count per `DEFAULT_HISTOGRAM_BUCKETS` bound
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            recent: std::collections::VecDeque::new(),
+            buckets: vec![0; DEFAULT_HISTOGRAM_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if self.recent.len() == HISTOGRAM_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(value);
+        for (count, bound) in self.buckets.iter_mut().zip(DEFAULT_HISTOGRAM_BUCKETS) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
 /// Metrics collection utilities
+///
+/// Names may carry Prometheus labels (see `labeled`); `render_prometheus`
+/// groups them into families.
 pub struct MetricsCollector {
     counters: Arc<RwLock<HashMap<String, u64>>>,
     gauges: Arc<RwLock<HashMap<String, f64>>>,
-    histograms: Arc<RwLock<HashMap<String, Vec<f64>>>>,
+    histograms: Arc<RwLock<HashMap<String, Histogram>>>,
 }
 
 impl MetricsCollector {
@@ -418,6 +494,21 @@ impl MetricsCollector {
         }
     }
 
+    /// Metric name with labels, e.g. `requests_total{proxy="1.2.3.4:80"}`
+    pub fn labeled(name: &str, labels: &[(&str, &str)]) -> String {
+        if labels.is_empty() {
+            return name.to_string();
+        }
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(key, value)| {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+                format!("{}=\"{}\"", key, value)
+            })
+            .collect();
+        format!("{}{{{}}}", name, labels.join(","))
+    }
+
     /// Increment a counter
     pub async fn increment(&self, name: &str) {
         let mut counters = self.counters.write().await;
@@ -439,7 +530,7 @@ impl MetricsCollector {
     /// Record a histogram value
     pub async fn record_histogram(&self, name: &str, value: f64) {
         let mut histograms = self.histograms.write().await;
-        histograms.entry(name.to_string()).or_insert_with(Vec::new).push(value);
+        histograms.entry(name.to_string()).or_insert_with(Histogram::new).observe(value);
     }
 
     /// Get counter value
@@ -454,10 +545,11 @@ impl MetricsCollector {
         gauges.get(name).copied()
     }
 
-    /// Get histogram statistics
+    /// Get histogram statistics over the most recent `HISTOGRAM_WINDOW` samples
     pub async fn get_histogram_stats(&self, name: &str) -> Option<HistogramStats> {
         let histograms = self.histograms.read().await;
-        histograms.get(name).map(|values| {
+        histograms.get(name).map(|histogram| {
+            let values = &histogram.recent;
             if values.is_empty() {
                 return HistogramStats::default();
             }
@@ -466,7 +558,7 @@ impl MetricsCollector {
             let sum: f64 = values.iter().sum();
             let mean = sum / count as f64;
             
-            let mut sorted = values.clone();
+            let mut sorted: Vec<f64> = values.iter().copied().collect();
             sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
             
             let min = sorted.first().copied().unwrap_or(0.0);
@@ -503,6 +595,82 @@ impl MetricsCollector {
         self.gauges.write().await.clear();
         self.histograms.write().await.clear();
     }
+
+    /// Render every metric in the Prometheus text exposition format
+    pub async fn render_prometheus(&self) -> String {
+        let mut families: std::collections::BTreeMap<&str, (&str, Vec<String>)> = std::collections::BTreeMap::new();
+        let counters = self.counters.read().await;
+        let gauges = self.gauges.read().await;
+        let histograms = self.histograms.read().await;
+
+        for (key, value) in counters.iter() {
+            let (family, _) = split_metric_key(key);
+            families.entry(family).or_insert(("counter", Vec::new())).1.push(format!("{} {}", key, value));
+        }
+        for (key, value) in gauges.iter() {
+            let (family, _) = split_metric_key(key);
+            families
+                .entry(family)
+                .or_insert(("gauge", Vec::new()))
+                .1
+                .push(format!("{} {}", key, format_metric_value(*value)));
+        }
+        for (key, histogram) in histograms.iter() {
+            let (family, labels) = split_metric_key(key);
+            let with_labels = |suffix: &str, extra: Option<String>| {
+                let labels: Vec<&str> = [labels, extra.as_deref().unwrap_or_default()]
+                    .into_iter()
+                    .filter(|l| !l.is_empty())
+                    .collect();
+                if labels.is_empty() {
+                    format!("{}{}", family, suffix)
+                } else {
+                    format!("{}{}{{{}}}", family, suffix, labels.join(","))
+                }
+            };
+            let lines = &mut families.entry(family).or_insert(("histogram", Vec::new())).1;
+            for (bound, count) in DEFAULT_HISTOGRAM_BUCKETS.iter().zip(&histogram.buckets) {
+                let le = format!("le=\"{}\"", format_metric_value(*bound));
+                lines.push(format!("{} {}", with_labels("_bucket", Some(le)), count));
+            }
+            let le = "le=\"+Inf\"".to_string();
+            lines.push(format!("{} {}", with_labels("_bucket", Some(le)), histogram.count));
+            lines.push(format!("{} {}", with_labels("_sum", None), format_metric_value(histogram.sum)));
+            lines.push(format!("{} {}", with_labels("_count", None), histogram.count));
+        }
+
+        let mut output = String::new();
+        for (family, (kind, mut lines)) in families {
+            // Histogram lines stay in bucket order within each series
+            if kind != "histogram" {
+                lines.sort();
+            }
+            output.push_str(&format!("# TYPE {} {}\n", family, kind));
+            for line in lines {
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
+
+/// Split `name{labels}` into the family name and the label list
+fn split_metric_key(key: &str) -> (&str, &str) {
+    match key.split_once('{') {
+        Some((family, labels)) => (family, labels.trim_end_matches('}')),
+        None => (key, ""),
+    }
+}
+
+fn format_metric_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
 }
 
 impl Default for MetricsCollector {
@@ -675,6 +843,38 @@ mod prelude_tests {
         assert_eq!(stats.mean, 150.0);
     }
 
+    #[tokio::test]
+    async fn test_metrics_render_prometheus() {
+        let metrics = MetricsCollector::new();
+        let requests = MetricsCollector::labeled("requests_total", &[("proxy", "a\"b")]);
+        metrics.increment_by(&requests, 3).await;
+        metrics.set_gauge("pool_size", 7.0).await;
+        metrics.record_histogram(&MetricsCollector::labeled("latency_seconds", &[("proxy", "p1")]), 0.2).await;
+        metrics.record_histogram(&MetricsCollector::labeled("latency_seconds", &[("proxy", "p1")]), 3.0).await;
+
+        let text = metrics.render_prometheus().await;
+        assert!(text.contains("# TYPE requests_total counter\nrequests_total{proxy=\"a\\\"b\"} 3\n"));
+        assert!(text.contains("# TYPE pool_size gauge\npool_size 7\n"));
+        assert!(text.contains("# TYPE latency_seconds histogram\n"));
+        assert!(text.contains("latency_seconds_bucket{proxy=\"p1\",le=\"0.1\"} 0\n"));
+        assert!(text.contains("latency_seconds_bucket{proxy=\"p1\",le=\"0.25\"} 1\n"));
+        assert!(text.contains("latency_seconds_bucket{proxy=\"p1\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("latency_seconds_sum{proxy=\"p1\"} 3.2\n"));
+        assert!(text.contains("latency_seconds_count{proxy=\"p1\"} 2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_histogram_window_is_bounded() {
+        let metrics = MetricsCollector::new();
+        for i in 0..(HISTOGRAM_WINDOW + 10) {
+            metrics.record_histogram("latency", i as f64).await;
+        }
+        let stats = metrics.get_histogram_stats("latency").await.expect("Get operation should succeed");
+        assert_eq!(stats.count, HISTOGRAM_WINDOW);
+        assert_eq!(stats.min, 10.0);
+        assert!(metrics.render_prometheus().await.contains(&format!("latency_count {}\n", HISTOGRAM_WINDOW + 10)));
+    }
+
     #[test]
     fn test_string_utils() {
         assert_eq!(string_utils::truncate("hello world", 5), "he...");
@@ -95,6 +95,9 @@ pub struct TrackerBlocker {
     blocked_requests: u64,
     allowed_requests: u64,
     custom_rules: Vec<BlockingRule>,
+    /// Rules owned by the application config, replaced on reload
+    config_rules: Vec<BlockingRule>,
+    enabled: bool,
 }
 
 /// Blocking rule
@@ -157,11 +160,18 @@ impl TrackerBlocker {
             blocked_requests: 0,
             allowed_requests: 0,
             custom_rules: Vec::new(),
+            config_rules: Vec::new(),
+            enabled: true,
         }
     }
 
     /// Check if a URL should be blocked
     pub fn should_block(&mut self, url: &str) -> bool {
+        if !self.enabled {
+            self.allowed_requests += 1;
+            return false;
+        }
+
         let domain = extract_domain(url);
         
         // Check known trackers
@@ -171,8 +181,8 @@ impl TrackerBlocker {
             return true;
         }
         
-        // Check custom rules
-        for rule in &self.custom_rules {
+        // Check custom and config-supplied rules
+        for rule in self.custom_rules.iter().chain(&self.config_rules) {
             if rule.enabled && url.contains(&rule.pattern) {
                 self.blocked_requests += 1;
                 debug!("Blocked by custom rule: {}", url);
@@ -189,6 +199,22 @@ impl TrackerBlocker {
         self.custom_rules.push(rule);
     }
 
+    /// Turn blocking on or off without losing rules or statistics
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Whether blocking is active
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Replace the rules supplied by configuration; rules added with
+    /// `add_rule` are kept
+    pub fn set_config_rules(&mut self, rules: Vec<BlockingRule>) {
+        self.config_rules = rules;
+    }
+
     /// Add a tracker domain
     pub fn add_tracker(&mut self, domain: &str) {
         self.known_trackers.insert(domain.to_string());
@@ -206,7 +232,7 @@ impl TrackerBlocker {
                 0.0
             },
             known_trackers: self.known_trackers.len(),
-            custom_rules: self.custom_rules.len(),
+            custom_rules: self.custom_rules.len() + self.config_rules.len(),
         }
     }
 }
@@ -683,6 +709,11 @@ impl PrivacyFortress {
         }
     }
 
+    /// Shared handle to the tracker blocker, for live reconfiguration
+    pub fn tracker_blocker(&self) -> Arc<RwLock<TrackerBlocker>> {
+        self.tracker_blocker.clone()
+    }
+
     /// Check if a request should be blocked
     pub async fn should_block_request(&self, url: &str) -> bool {
         if !self.config.block_trackers {
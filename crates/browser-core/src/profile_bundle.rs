@@ -0,0 +1,794 @@
+//! Profile Bundle Module
+//!
+//! Provides portable, passphrase-encrypted profile bundles including:
+//! - A single file holding settings, fingerprint, proxy assignment, cookies,
+//!   local storage, bookmarks and the Chromium profile directory
+//! - A manifest with format/app versions and SHA-256 checksums
+//! - Passphrase encryption using the secure container format
+//! - Conflict detection on import with rename, overwrite or merge
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::browser_profile::{
+    BrowserProfile, BrowserProfileManager, ProfileLock, PROFILE_CHROMIUM_DIR, PROFILE_LOCK_FILE, PROFILE_STORAGE_FILE,
+};
+use crate::secure_container::{self, KdfParams};
+use crate::storage::{ExportOptions, ImportOptions, StorageEngine, StorageExport};
+
+/// Current bundle format version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Chromium files that only make sense for a running browser
+const SKIPPED_CHROMIUM_FILES: &[&str] = &["SingletonLock", "SingletonSocket", "SingletonCookie", "lockfile"];
+
+/// What to put into a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleOptions {
+    /// Keep proxy usernames and passwords in the bundle
+    pub include_credentials: bool,
+    /// Include the Chromium user-data-dir
+    pub include_chromium_profile: bool,
+    /// Include browsing history alongside cookies, bookmarks and local storage
+    pub include_history: bool,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self {
+            include_credentials: false,
+            include_chromium_profile: true,
+            include_history: false,
+        }
+    }
+}
+
+/// Checksummed file inside a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Bundle manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub profile_id: String,
+    pub profile_name: String,
+    pub includes_credentials: bool,
+    pub profile_sha256: String,
+    pub storage_sha256: Option<String>,
+    pub files: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundleFile {
+    path: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundlePayload {
+    manifest: BundleManifest,
+    profile_json: String,
+    storage_json: Option<String>,
+    files: Vec<BundleFile>,
+}
+
+/// How to handle a bundle whose profile ID already exists with different contents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// Refuse to import when there is a conflict
+    Fail,
+    /// Import as a new profile, optionally with a new name
+    Rename { new_name: Option<String> },
+    /// Replace the local profile and its data
+    Overwrite,
+    /// Keep local settings and files, adding the bundle's data to them
+    Merge,
+}
+
+/// A local profile that clashes with a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleConflict {
+    pub profile_id: String,
+    pub local_name: String,
+    pub bundle_name: String,
+    /// Which parts differ: `settings`, `storage` or `files`
+    pub differences: Vec<String>,
+}
+
+/// Result of inspecting a bundle before import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundlePreview {
+    pub manifest: BundleManifest,
+    /// Set if the profile exists locally with different contents
+    pub conflict: Option<BundleConflict>,
+    /// The profile exists locally with identical contents
+    pub already_present: bool,
+    /// Resolutions that can be passed to `import_bundle`
+    pub available_resolutions: Vec<ConflictResolution>,
+}
+
+/// Result of importing a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportReport {
+    pub profile_id: String,
+    pub profile_name: String,
+    pub resolution: Option<ConflictResolution>,
+    pub unchanged: bool,
+    pub files_written: usize,
+    pub files_skipped: usize,
+}
+
+/// Exports and imports profile bundles
+pub struct ProfileBundler {
+    profiles: Arc<BrowserProfileManager>,
+}
+
+impl ProfileBundler {
+    /// Create a bundler for a profile manager
+    pub fn new(profiles: Arc<BrowserProfileManager>) -> Self {
+        Self { profiles }
+    }
+
+    /// Write an encrypted bundle of a profile
+    pub async fn export_bundle(
+        &self,
+        profile_id: &str,
+        path: &Path,
+        passphrase: &str,
+        options: &BundleOptions,
+    ) -> Result<BundleManifest> {
+        let mut profile = self
+            .profiles
+            .get_profile(profile_id)
+            .await
+            .ok_or_else(|| anyhow!("Profile not found"))?;
+
+        if !options.include_credentials {
+            if let Some(proxy) = profile.settings.proxy_config.as_mut() {
+                proxy.username = None;
+                proxy.password = None;
+            }
+        }
+
+        let storage = self.read_storage(&profile, options).await?;
+        let storage_json = storage.as_ref().map(serde_json::to_string).transpose()?;
+        let profile_json = serde_json::to_string(&profile)?;
+
+        let mut files = Vec::new();
+        let mut entries = Vec::new();
+        if options.include_chromium_profile {
+            if self.profiles.is_loaded(profile_id).await {
+                warn!("Bundling Chromium directory of loaded profile {}", profile_id);
+            }
+            let chromium_dir = profile.data_dir.join(PROFILE_CHROMIUM_DIR);
+            for (relative, data) in read_dir_recursive(&chromium_dir).await? {
+                entries.push(BundleEntry {
+                    path: relative.clone(),
+                    size: data.len() as u64,
+                    sha256: sha256_hex(&data),
+                });
+                files.push(BundleFile {
+                    path: relative,
+                    data: base64::engine::general_purpose::STANDARD.encode(&data),
+                });
+            }
+        }
+
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            profile_id: profile.id.clone(),
+            profile_name: profile.name.clone(),
+            includes_credentials: options.include_credentials,
+            profile_sha256: sha256_hex(profile_json.as_bytes()),
+            storage_sha256: storage_json.as_ref().map(|s| sha256_hex(s.as_bytes())),
+            files: entries,
+        };
+
+        let payload = BundlePayload {
+            manifest: manifest.clone(),
+            profile_json,
+            storage_json,
+            files,
+        };
+        let sealed = secure_container::encrypt_bytes(&serde_json::to_vec(&payload)?, passphrase, KdfParams::default()).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, sealed).await?;
+
+        info!(
+            "Exported profile {} to bundle {:?} ({} files)",
+            profile_id,
+            path,
+            manifest.files.len()
+        );
+        Ok(manifest)
+    }
+
+    /// Decrypt a bundle and check it against local profiles without importing it
+    pub async fn inspect_bundle(&self, path: &Path, passphrase: &str) -> Result<BundlePreview> {
+        let payload = read_payload(path, passphrase).await?;
+        let bundle_profile = bundle_profile(&payload)?;
+        let conflict = self.detect_conflict(&payload, &bundle_profile).await?;
+        let already_present = conflict.is_none()
+            && self.profiles.get_profile(&bundle_profile.id).await.is_some();
+
+        let available_resolutions = if conflict.is_some() {
+            vec![
+                ConflictResolution::Rename { new_name: None },
+                ConflictResolution::Overwrite,
+                ConflictResolution::Merge,
+            ]
+        } else {
+            Vec::new()
+        };
+
+        Ok(BundlePreview {
+            manifest: payload.manifest,
+            conflict,
+            already_present,
+            available_resolutions,
+        })
+    }
+
+    /// Import a bundle, resolving a conflicting local profile as requested
+    pub async fn import_bundle(
+        &self,
+        path: &Path,
+        passphrase: &str,
+        resolution: ConflictResolution,
+    ) -> Result<BundleImportReport> {
+        let payload = read_payload(path, passphrase).await?;
+        let bundle_profile = bundle_profile(&payload)?;
+        let existing = self.profiles.get_profile(&bundle_profile.id).await;
+        let conflict = self.detect_conflict(&payload, &bundle_profile).await?;
+
+        if existing.is_some() && conflict.is_none() {
+            return Ok(BundleImportReport {
+                profile_id: bundle_profile.id.clone(),
+                profile_name: bundle_profile.name.clone(),
+                resolution: None,
+                unchanged: true,
+                files_written: 0,
+                files_skipped: 0,
+            });
+        }
+
+        let Some(conflict) = conflict else {
+            let _lock = self.lock_data_dir(&bundle_profile.id).await?;
+            let (written, skipped) = self.write_profile(&payload, bundle_profile.clone(), false).await?;
+            return Ok(BundleImportReport {
+                profile_id: bundle_profile.id,
+                profile_name: bundle_profile.name,
+                resolution: None,
+                unchanged: false,
+                files_written: written,
+                files_skipped: skipped,
+            });
+        };
+
+        let (profile, merge) = match &resolution {
+            ConflictResolution::Fail => {
+                return Err(anyhow!(
+                    "Profile {} already exists with different {}",
+                    conflict.profile_id,
+                    conflict.differences.join(", ")
+                ));
+            }
+            ConflictResolution::Rename { new_name } => {
+                let mut profile = bundle_profile;
+                profile.id = Uuid::new_v4().to_string();
+                profile.name = new_name
+                    .clone()
+                    .unwrap_or_else(|| format!("{} (imported)", profile.name));
+                profile.is_default = false;
+                (profile, false)
+            }
+            ConflictResolution::Overwrite | ConflictResolution::Merge
+                if self.profiles.is_loaded(&conflict.profile_id).await =>
+            {
+                return Err(anyhow!("Profile {} is open; close it before importing over it", conflict.profile_id));
+            }
+            ConflictResolution::Overwrite => (bundle_profile, false),
+            ConflictResolution::Merge => {
+                let local = existing.ok_or_else(|| anyhow!("Profile not found"))?;
+                (merge_profiles(local, bundle_profile), true)
+            }
+        };
+
+        // Held from before anything is deleted until the profile is written,
+        // so a profile opened meanwhile cannot have its data removed under it
+        let lock = self.lock_data_dir(&profile.id).await?;
+        if resolution == ConflictResolution::Overwrite {
+            clear_data_dir(&self.profiles.base_data_dir().join(&profile.id)).await?;
+        }
+
+        let (written, skipped) = self.write_profile(&payload, profile.clone(), merge).await?;
+        drop(lock);
+        info!("Imported bundle for profile {} ({:?})", profile.id, resolution);
+        Ok(BundleImportReport {
+            profile_id: profile.id,
+            profile_name: profile.name,
+            resolution: Some(resolution),
+            unchanged: false,
+            files_written: written,
+            files_skipped: skipped,
+        })
+    }
+
+    async fn read_storage(&self, profile: &BrowserProfile, options: &BundleOptions) -> Result<Option<StorageExport>> {
+        let export_options = ExportOptions {
+            export_cookies: true,
+            export_history: options.include_history,
+            export_bookmarks: true,
+            export_local_storage: true,
+        };
+
+        if let Some(session) = self.profiles.get_loaded(&profile.id).await {
+            return Ok(Some(session.storage.export_with_options(&export_options).await?));
+        }
+
+        let storage_file = profile.data_dir.join(PROFILE_STORAGE_FILE);
+        if !storage_file.exists() {
+            return Ok(None);
+        }
+        let mut export: StorageExport = serde_json::from_str(&tokio::fs::read_to_string(&storage_file).await?)
+            .context("Failed to parse profile storage")?;
+        if !options.include_history {
+            export.history.clear();
+        }
+        Ok(Some(export))
+    }
+
+    async fn detect_conflict(
+        &self,
+        payload: &BundlePayload,
+        bundle_profile: &BrowserProfile,
+    ) -> Result<Option<BundleConflict>> {
+        let Some(local) = self.profiles.get_profile(&bundle_profile.id).await else {
+            return Ok(None);
+        };
+
+        let mut differences = Vec::new();
+        if local.name != bundle_profile.name
+            || serde_json::to_value(&local.settings)? != serde_json::to_value(&bundle_profile.settings)?
+        {
+            differences.push("settings".to_string());
+        }
+
+        if let Some(storage_json) = &payload.storage_json {
+            let bundle_storage: StorageExport = serde_json::from_str(storage_json)?;
+            let local_storage = self
+                .read_storage(&local, &BundleOptions { include_history: !bundle_storage.history.is_empty(), ..Default::default() })
+                .await?;
+            let same = local_storage
+                .map(|local| canonical_storage(&local) == canonical_storage(&bundle_storage))
+                .unwrap_or(false);
+            if !same {
+                differences.push("storage".to_string());
+            }
+        }
+
+        let chromium_dir = local.data_dir.join(PROFILE_CHROMIUM_DIR);
+        for entry in &payload.manifest.files {
+            let local_file = chromium_dir.join(safe_relative_path(&entry.path)?);
+            let matches = match tokio::fs::read(&local_file).await {
+                Ok(data) => sha256_hex(&data) == entry.sha256,
+                Err(_) => false,
+            };
+            if !matches {
+                differences.push("files".to_string());
+                break;
+            }
+        }
+
+        if differences.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(BundleConflict {
+                profile_id: local.id,
+                local_name: local.name,
+                bundle_name: bundle_profile.name.clone(),
+                differences,
+            }))
+        }
+    }
+
+    /// Create a profile's data directory and lock it
+    async fn lock_data_dir(&self, profile_id: &str) -> Result<ProfileLock> {
+        validate_profile_id(profile_id)?;
+        let data_dir = self.profiles.base_data_dir().join(profile_id);
+        tokio::fs::create_dir_all(&data_dir).await?;
+        ProfileLock::acquire(&data_dir)
+    }
+
+    /// Write a profile and the bundle's data into its data directory, which
+    /// the caller must hold the lock for.
+    /// Returns the number of Chromium files written and skipped.
+    async fn write_profile(&self, payload: &BundlePayload, profile: BrowserProfile, merge: bool) -> Result<(usize, usize)> {
+        let profile = self.profiles.insert_profile(profile).await?;
+
+        if let Some(storage_json) = &payload.storage_json {
+            let storage_file = profile.data_dir.join(PROFILE_STORAGE_FILE);
+            let bundle_storage: StorageExport = serde_json::from_str(storage_json)?;
+            let storage = StorageEngine::new(&profile.data_dir)?;
+            if merge && storage_file.exists() {
+                storage.import_from_file(&storage_file).await?;
+                storage.import_with_options(bundle_storage, &ImportOptions::all()).await?;
+            } else {
+                storage.import_with_options(bundle_storage, &ImportOptions::replace_all()).await?;
+            }
+            storage.export_to_file(&storage_file).await?;
+        }
+
+        let chromium_dir = profile.data_dir.join(PROFILE_CHROMIUM_DIR);
+        tokio::fs::create_dir_all(&chromium_dir).await?;
+        let mut written = 0;
+        let mut skipped = 0;
+        for file in &payload.files {
+            let target = chromium_dir.join(safe_relative_path(&file.path)?);
+            if merge && target.exists() {
+                skipped += 1;
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let data = base64::engine::general_purpose::STANDARD.decode(&file.data)?;
+            tokio::fs::write(&target, data).await?;
+            written += 1;
+        }
+
+        Ok((written, skipped))
+    }
+}
+
+/// Remove everything in a locked data directory except the lock itself
+async fn clear_data_dir(data_dir: &Path) -> Result<()> {
+    let mut entries = tokio::fs::read_dir(data_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name() == PROFILE_LOCK_FILE {
+            continue;
+        }
+        if entry.file_type().await?.is_dir() {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+        } else {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Keep local settings, filling gaps from the bundle
+fn merge_profiles(mut local: BrowserProfile, bundle: BrowserProfile) -> BrowserProfile {
+    let settings = &mut local.settings;
+    if settings.proxy_config.is_none() {
+        settings.proxy_config = bundle.settings.proxy_config;
+        settings.proxy_enabled = bundle.settings.proxy_enabled;
+    }
+    if settings.fingerprint.is_none() {
+        settings.fingerprint = bundle.settings.fingerprint;
+    }
+    if settings.virtual_ip.is_none() {
+        settings.virtual_ip = bundle.settings.virtual_ip;
+    }
+    if settings.user_agent.is_none() {
+        settings.user_agent = bundle.settings.user_agent;
+    }
+    if settings.timezone.is_none() {
+        settings.timezone = bundle.settings.timezone;
+    }
+    if settings.geolocation.is_none() {
+        settings.geolocation = bundle.settings.geolocation;
+    }
+    local
+}
+
+/// Order-independent representation of storage contents for comparison
+fn canonical_storage(export: &StorageExport) -> String {
+    fn sorted<T: Serialize>(items: &[T]) -> Vec<String> {
+        let mut out: Vec<String> = items
+            .iter()
+            .filter_map(|item| serde_json::to_string(item).ok())
+            .collect();
+        out.sort();
+        out
+    }
+
+    let local_storage: BTreeMap<&String, BTreeMap<&String, &String>> = export
+        .local_storage
+        .iter()
+        .map(|(origin, items)| (origin, items.iter().collect()))
+        .collect();
+
+    serde_json::json!({
+        "cookies": sorted(&export.cookies),
+        "history": sorted(&export.history),
+        "bookmarks": sorted(&export.bookmarks),
+        "local_storage": local_storage,
+    })
+    .to_string()
+}
+
+async fn read_payload(path: &Path, passphrase: &str) -> Result<BundlePayload> {
+    let sealed = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read bundle {:?}", path))?;
+    if !secure_container::is_container(&sealed) {
+        return Err(anyhow!("Not a profile bundle"));
+    }
+    let plaintext = secure_container::decrypt_bytes(&sealed, passphrase).await?;
+    let payload: BundlePayload = serde_json::from_slice(&plaintext).context("Failed to parse bundle")?;
+
+    if payload.manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Bundle format version {} is newer than supported version {}",
+            payload.manifest.format_version,
+            BUNDLE_FORMAT_VERSION
+        ));
+    }
+    verify_payload(&payload)?;
+    Ok(payload)
+}
+
+fn verify_payload(payload: &BundlePayload) -> Result<()> {
+    let manifest = &payload.manifest;
+    if sha256_hex(payload.profile_json.as_bytes()) != manifest.profile_sha256 {
+        return Err(anyhow!("Bundle profile checksum mismatch"));
+    }
+    let storage_sha = payload.storage_json.as_ref().map(|s| sha256_hex(s.as_bytes()));
+    if storage_sha != manifest.storage_sha256 {
+        return Err(anyhow!("Bundle storage checksum mismatch"));
+    }
+    if payload.files.len() != manifest.files.len() {
+        return Err(anyhow!("Bundle file list does not match manifest"));
+    }
+    for (file, entry) in payload.files.iter().zip(&manifest.files) {
+        let data = base64::engine::general_purpose::STANDARD.decode(&file.data)?;
+        if file.path != entry.path || sha256_hex(&data) != entry.sha256 {
+            return Err(anyhow!("Bundle checksum mismatch for {}", entry.path));
+        }
+    }
+    Ok(())
+}
+
+/// Parse the bundle's profile, whose ID becomes a directory name on import
+fn bundle_profile(payload: &BundlePayload) -> Result<BrowserProfile> {
+    let profile: BrowserProfile = serde_json::from_str(&payload.profile_json)?;
+    validate_profile_id(&profile.id)?;
+    Ok(profile)
+}
+
+/// Accept only IDs in the hyphenated UUID form the profile manager generates
+fn validate_profile_id(id: &str) -> Result<()> {
+    match Uuid::try_parse(id) {
+        Ok(uuid) if uuid.hyphenated().to_string() == id.to_ascii_lowercase() => Ok(()),
+        _ => Err(anyhow!("Invalid profile ID in bundle: {}", id)),
+    }
+}
+
+/// Read every regular file under a directory as (relative path, contents)
+async fn read_dir_recursive(root: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    if !root.exists() {
+        return Ok(files);
+    }
+
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let name = entry.file_name();
+                if SKIPPED_CHROMIUM_FILES.iter().any(|s| name == *s) {
+                    continue;
+                }
+                let relative = path
+                    .strip_prefix(root)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((relative, tokio::fs::read(&path).await?));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// Reject bundle paths that would escape the target directory
+fn safe_relative_path(path: &str) -> Result<PathBuf> {
+    let relative = PathBuf::from(path);
+    if relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        Ok(relative)
+    } else {
+        Err(anyhow!("Unsafe path in bundle: {}", path))
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser_profile::ProfileProxyConfig;
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, Arc<BrowserProfileManager>, BrowserProfile) {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let manager = Arc::new(BrowserProfileManager::new(temp_dir.path().join("profiles")));
+        let profile = manager.create_profile("Shop", false).await.expect("Create should succeed");
+
+        let mut settings = profile.settings.clone();
+        settings.proxy_enabled = true;
+        settings.proxy_config = Some(ProfileProxyConfig {
+            host: "81.2.69.1".to_string(),
+            port: 8080,
+            protocol: "http".to_string(),
+            username: Some("user".to_string()),
+            password: Some("secret".to_string()),
+        });
+        manager.update_settings(&profile.id, settings).await.expect("Update should succeed");
+
+        let session = manager.open_profile(&profile.id).await.expect("Open should succeed");
+        session.storage.set_local_storage("https://shop.example", "cart", "3").await.expect("Set should succeed");
+        tokio::fs::create_dir_all(session.chromium_user_data_dir().join("Default")).await.expect("Operation should succeed in test");
+        tokio::fs::write(session.chromium_user_data_dir().join("Default/Preferences"), b"{}").await.expect("Operation should succeed in test");
+        tokio::fs::write(session.chromium_user_data_dir().join("SingletonLock"), b"x").await.expect("Operation should succeed in test");
+        manager.unload_profile(&profile.id).await.expect("Unload should succeed");
+
+        let profile = manager.get_profile(&profile.id).await.expect("Profile should exist");
+        (temp_dir, manager, profile)
+    }
+
+    #[tokio::test]
+    async fn test_bundle_round_trip_to_new_machine() {
+        let (temp_dir, manager, profile) = setup().await;
+        let bundle_path = temp_dir.path().join("shop.vipbundle");
+        let manifest = ProfileBundler::new(manager)
+            .export_bundle(&profile.id, &bundle_path, "correct horse", &BundleOptions::default())
+            .await
+            .expect("Export should succeed");
+        assert_eq!(manifest.files.len(), 1);
+        assert!(!manifest.includes_credentials);
+
+        let other = Arc::new(BrowserProfileManager::new(temp_dir.path().join("other")));
+        let bundler = ProfileBundler::new(other.clone());
+        assert!(bundler.inspect_bundle(&bundle_path, "wrong").await.is_err());
+
+        let report = bundler
+            .import_bundle(&bundle_path, "correct horse", ConflictResolution::Fail)
+            .await
+            .expect("Import should succeed");
+        assert_eq!(report.profile_id, profile.id);
+        assert_eq!(report.files_written, 1);
+
+        let imported = other.get_profile(&profile.id).await.expect("Profile should exist");
+        let proxy = imported.settings.proxy_config.expect("Proxy should be kept");
+        assert_eq!(proxy.host, "81.2.69.1");
+        assert!(proxy.password.is_none());
+
+        let session = other.open_profile(&profile.id).await.expect("Open should succeed");
+        assert_eq!(
+            session.storage.get_local_storage("https://shop.example", "cart").await.expect("Get should succeed"),
+            Some("3".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_conflicts() {
+        let (temp_dir, manager, profile) = setup().await;
+        let bundle_path = temp_dir.path().join("shop.vipbundle");
+        let bundler = ProfileBundler::new(manager.clone());
+        bundler
+            .export_bundle(&profile.id, &bundle_path, "pw", &BundleOptions { include_credentials: true, ..Default::default() })
+            .await
+            .expect("Export should succeed");
+
+        let preview = bundler.inspect_bundle(&bundle_path, "pw").await.expect("Inspect should succeed");
+        assert!(preview.conflict.is_none());
+        assert!(preview.already_present);
+
+        let mut settings = profile.settings.clone();
+        settings.language = "de-DE".to_string();
+        manager.update_settings(&profile.id, settings).await.expect("Update should succeed");
+
+        let preview = bundler.inspect_bundle(&bundle_path, "pw").await.expect("Inspect should succeed");
+        let conflict = preview.conflict.expect("Conflict should be detected");
+        assert_eq!(conflict.differences, vec!["settings".to_string()]);
+        assert!(bundler.import_bundle(&bundle_path, "pw", ConflictResolution::Fail).await.is_err());
+
+        let renamed = bundler
+            .import_bundle(&bundle_path, "pw", ConflictResolution::Rename { new_name: Some("Shop 2".to_string()) })
+            .await
+            .expect("Import should succeed");
+        assert_ne!(renamed.profile_id, profile.id);
+        assert_eq!(manager.list_profiles().await.len(), 2);
+
+        bundler
+            .import_bundle(&bundle_path, "pw", ConflictResolution::Merge)
+            .await
+            .expect("Import should succeed");
+        assert_eq!(manager.get_profile(&profile.id).await.expect("Profile should exist").settings.language, "de-DE");
+
+        bundler
+            .import_bundle(&bundle_path, "pw", ConflictResolution::Overwrite)
+            .await
+            .expect("Import should succeed");
+        assert_eq!(manager.get_profile(&profile.id).await.expect("Profile should exist").settings.language, "");
+
+        // Another holder of the profile lock keeps its data from being overwritten
+        let mut settings = profile.settings.clone();
+        settings.language = "fr-FR".to_string();
+        manager.update_settings(&profile.id, settings).await.expect("Update should succeed");
+        let data_dir = manager.base_data_dir().join(&profile.id);
+        let marker = data_dir.join("marker");
+        std::fs::write(&marker, b"keep").expect("Write should succeed");
+        let lock = ProfileLock::acquire(&data_dir).expect("Lock should succeed");
+        assert!(bundler.import_bundle(&bundle_path, "pw", ConflictResolution::Overwrite).await.is_err());
+        assert!(marker.exists());
+        drop(lock);
+    }
+
+    #[tokio::test]
+    async fn test_bundle_with_unsafe_profile_id_is_rejected() {
+        let (temp_dir, manager, profile) = setup().await;
+        let bundle_path = temp_dir.path().join("shop.vipbundle");
+        ProfileBundler::new(manager)
+            .export_bundle(&profile.id, &bundle_path, "pw", &BundleOptions::default())
+            .await
+            .expect("Export should succeed");
+
+        // Re-seal the bundle with a profile ID that points outside the profiles directory
+        let sealed = tokio::fs::read(&bundle_path).await.expect("Read should succeed");
+        let plaintext = secure_container::decrypt_bytes(&sealed, "pw").await.expect("Decrypt should succeed");
+        let mut payload: BundlePayload = serde_json::from_slice(&plaintext).expect("Parse should succeed");
+        let mut tampered = profile.clone();
+        tampered.id = "../escaped".to_string();
+        payload.profile_json = serde_json::to_string(&tampered).expect("Serialize should succeed");
+        payload.manifest.profile_sha256 = sha256_hex(payload.profile_json.as_bytes());
+        let sealed = secure_container::encrypt_bytes(
+            &serde_json::to_vec(&payload).expect("Serialize should succeed"),
+            "pw",
+            KdfParams::default(),
+        )
+        .await
+        .expect("Encrypt should succeed");
+        tokio::fs::write(&bundle_path, sealed).await.expect("Write should succeed");
+
+        let other = Arc::new(BrowserProfileManager::new(temp_dir.path().join("other").join("profiles")));
+        let bundler = ProfileBundler::new(other);
+        assert!(bundler.inspect_bundle(&bundle_path, "pw").await.is_err());
+        for resolution in [ConflictResolution::Fail, ConflictResolution::Overwrite] {
+            assert!(bundler.import_bundle(&bundle_path, "pw", resolution).await.is_err());
+        }
+        assert!(!temp_dir.path().join("other").join("escaped").exists());
+    }
+}
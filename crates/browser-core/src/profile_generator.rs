@@ -0,0 +1,641 @@
+//! Bulk Profile Generator Module
+//!
+//! Provides template-driven profile creation for multi-account work including:
+//! - Profile templates made of partial settings plus generation rules
+//! - Per-profile country, virtual IP and coherent browser fingerprint
+//! - Proxy selection from the pool with geographic exit verification
+//! - Timezone, locale and geolocation derived from the verified exit
+//! - A batch report of created and failed profiles
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::browser_profile::{BrowserProfileManager, GeoLocation, ProfileProxyConfig, ProfileSettings};
+use crate::fingerprint::{BrowserFingerprint, FingerprintPlatform};
+use crate::free_ip_providers::FreeIpProviderManager;
+use crate::proxy::{FreeProxy, ProxyType};
+use crate::proxy_validator::{GeoVerificationConfig, GeoVerifier, ProxyValidator, ProxyValidatorConfig};
+use virtual_ip::{Country, IPGenerator};
+
+/// Settings a template pins for every generated profile. Unset fields are
+/// derived from the country, fingerprint and proxy exit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettingsTemplate {
+    pub user_agent: Option<String>,
+    pub language: Option<String>,
+    pub timezone: Option<String>,
+    pub geolocation: Option<GeoLocation>,
+    pub fingerprint_protection: Option<bool>,
+    pub clear_on_close: Option<bool>,
+}
+
+/// How countries are assigned across a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CountrySelection {
+    /// Cycle through the country list in order
+    #[default]
+    RoundRobin,
+    /// Pick a random country for each profile
+    Random,
+}
+
+/// Rules used to generate each profile of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationRules {
+    /// Country codes to draw from; empty means every known country
+    pub countries: Vec<String>,
+    pub country_selection: CountrySelection,
+    /// Platforms fingerprints are drawn from; empty means all platforms
+    pub platforms: Vec<FingerprintPlatform>,
+    /// Fail a profile when no verified proxy is available for its country
+    pub require_proxy: bool,
+    /// Never give two profiles of the batch the same proxy
+    pub unique_proxies: bool,
+    /// Candidate proxies tried per profile before giving up
+    pub max_proxy_attempts: usize,
+    /// Accuracy in metres reported for geolocation taken from the exit
+    pub geolocation_accuracy: f64,
+}
+
+impl Default for GenerationRules {
+    fn default() -> Self {
+        Self {
+            countries: Vec::new(),
+            country_selection: CountrySelection::RoundRobin,
+            platforms: Vec::new(),
+            require_proxy: true,
+            unique_proxies: true,
+            max_proxy_attempts: 5,
+            geolocation_accuracy: 1000.0,
+        }
+    }
+}
+
+/// Template for bulk profile generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileTemplate {
+    /// Profile name pattern; `{n}` is the 1-based index and `{country}` the
+    /// country code
+    pub name_pattern: String,
+    #[serde(default)]
+    pub settings: ProfileSettingsTemplate,
+    #[serde(default)]
+    pub rules: GenerationRules,
+}
+
+impl ProfileTemplate {
+    /// Create a template with default rules
+    pub fn new(name_pattern: &str) -> Self {
+        Self {
+            name_pattern: name_pattern.to_string(),
+            settings: ProfileSettingsTemplate::default(),
+            rules: GenerationRules::default(),
+        }
+    }
+
+    /// Restrict generation to the given countries
+    pub fn with_countries(mut self, countries: &[&str]) -> Self {
+        self.rules.countries = countries.iter().map(|c| c.to_uppercase()).collect();
+        self
+    }
+
+    /// Replace the generation rules
+    pub fn with_rules(mut self, rules: GenerationRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Replace the pinned settings
+    pub fn with_settings(mut self, settings: ProfileSettingsTemplate) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    fn profile_name(&self, index: usize, country_code: &str) -> String {
+        self.name_pattern
+            .replace("{n}", &(index + 1).to_string())
+            .replace("{country}", country_code)
+    }
+}
+
+/// Where a proxy's traffic actually leaves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyExit {
+    pub ip: String,
+    pub country: String,
+    pub location: Option<(f64, f64)>,
+    /// Timezone reported for the exit IP, when the lookup provides one
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Locale reported for the exit IP, when the lookup provides one
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+#[async_trait]
+/// Trait defining ProxyExitVerifier behavior.
+pub trait ProxyExitVerifier: Send + Sync {
+    /// Check that a proxy works and exits in its claimed country
+    async fn verify_exit(&self, proxy: &FreeProxy) -> Result<ProxyExit>;
+}
+
+/// Exit verifier backed by `ProxyValidator` and `GeoVerifier`
+pub struct GeoExitVerifier {
+    validator: ProxyValidator,
+    geo: GeoVerifier,
+}
+
+impl GeoExitVerifier {
+    /// Create a verifier with the given configurations
+    pub fn new(validator: ProxyValidatorConfig, geo: GeoVerificationConfig) -> Result<Self> {
+        Ok(Self {
+            validator: ProxyValidator::new(validator),
+            geo: GeoVerifier::new(geo)?,
+        })
+    }
+}
+
+#[async_trait]
+impl ProxyExitVerifier for GeoExitVerifier {
+    async fn verify_exit(&self, proxy: &FreeProxy) -> Result<ProxyExit> {
+        let validation = self.validator.validate_proxy(proxy).await?;
+        if !validation.is_working {
+            return Err(anyhow!(
+                "Proxy not working: {}",
+                validation.error.unwrap_or_else(|| "unknown error".to_string())
+            ));
+        }
+        let ip = validation
+            .detected_ip
+            .ok_or_else(|| anyhow!("Proxy exit IP could not be detected"))?;
+
+        let geo = self.geo.verify_proxy_location(proxy, &ip).await;
+        if !geo.is_verified {
+            return Err(anyhow!(
+                "Exit is in {} instead of {}",
+                geo.detected_country.unwrap_or_else(|| "an unknown country".to_string()),
+                geo.expected_country
+            ));
+        }
+
+        Ok(ProxyExit {
+            ip,
+            country: geo.detected_country.unwrap_or_else(|| proxy.country.clone()),
+            location: geo.detected_location,
+            timezone: geo.detected_timezone,
+            locale: geo.detected_locale,
+        })
+    }
+}
+
+/// A profile created by the generator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedProfile {
+    pub index: usize,
+    pub profile_id: String,
+    pub name: String,
+    pub country_code: String,
+    pub virtual_ip: String,
+    pub proxy: Option<String>,
+    pub exit_ip: Option<String>,
+    pub timezone: String,
+    pub language: String,
+    pub geolocation: Option<GeoLocation>,
+    pub platform: String,
+}
+
+/// A profile the generator could not create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationFailure {
+    pub index: usize,
+    pub country_code: Option<String>,
+    pub reason: String,
+}
+
+/// Result of a bulk generation run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkGenerationReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub requested: usize,
+    pub created: Vec<GeneratedProfile>,
+    pub failed: Vec<GenerationFailure>,
+}
+
+impl BulkGenerationReport {
+    /// Whether every requested profile was created
+    pub fn is_complete(&self) -> bool {
+        self.created.len() == self.requested
+    }
+}
+
+/// Generates batches of profiles from a template
+pub struct BulkProfileGenerator {
+    ip_generator: Arc<IPGenerator>,
+    profiles: Arc<BrowserProfileManager>,
+    verifier: Arc<dyn ProxyExitVerifier>,
+    proxy_pool: Arc<RwLock<Vec<FreeProxy>>>,
+}
+
+impl BulkProfileGenerator {
+    /// Create a generator writing into the given profile manager. Proxy
+    /// exits are checked with a default `GeoExitVerifier` before a proxy is
+    /// assigned.
+    pub fn new(ip_generator: Arc<IPGenerator>, profiles: Arc<BrowserProfileManager>) -> Result<Self> {
+        let verifier = GeoExitVerifier::new(ProxyValidatorConfig::default(), GeoVerificationConfig::default())?;
+        Ok(Self {
+            ip_generator,
+            profiles,
+            verifier: Arc::new(verifier),
+            proxy_pool: Arc::new(RwLock::new(Vec::new())),
+        })
+    }
+
+    /// Verify proxy exits with `verifier` instead of the default one
+    pub fn with_verifier(mut self, verifier: Arc<dyn ProxyExitVerifier>) -> Self {
+        self.verifier = verifier;
+        self
+    }
+
+    /// Use a fixed set of candidate proxies
+    pub fn with_proxy_pool(mut self, proxies: Vec<FreeProxy>) -> Self {
+        self.proxy_pool = Arc::new(RwLock::new(proxies));
+        self
+    }
+
+    /// Replace the candidate proxies with the provider manager's working pool
+    pub async fn refresh_pool_from(&self, provider_manager: &FreeIpProviderManager) -> usize {
+        let proxies: Vec<FreeProxy> = provider_manager
+            .get_working_proxies()
+            .into_iter()
+            .cloned()
+            .collect();
+        let count = proxies.len();
+        *self.proxy_pool.write().await = proxies;
+        count
+    }
+
+    /// Generate `count` profiles from a template
+    pub async fn generate(&self, template: &ProfileTemplate, count: usize) -> Result<BulkGenerationReport> {
+        let rules = &template.rules;
+        let countries: Vec<String> = if rules.countries.is_empty() {
+            self.ip_generator.list_countries().into_iter().map(|c| c.code).collect()
+        } else {
+            rules.countries.clone()
+        };
+        if countries.is_empty() {
+            return Err(anyhow!("No countries available for profile generation"));
+        }
+        let platforms = if rules.platforms.is_empty() {
+            FingerprintPlatform::all()
+        } else {
+            rules.platforms.clone()
+        };
+
+        let started_at = Utc::now();
+        let mut used_proxies = HashSet::new();
+        let mut created = Vec::new();
+        let mut failed = Vec::new();
+
+        for index in 0..count {
+            let country_code = match rules.country_selection {
+                CountrySelection::RoundRobin => countries[index % countries.len()].clone(),
+                CountrySelection::Random => countries
+                    .choose(&mut rand::thread_rng())
+                    .cloned()
+                    .unwrap_or_else(|| countries[0].clone()),
+            };
+            let platform = platforms
+                .choose(&mut rand::thread_rng())
+                .copied()
+                .unwrap_or_default();
+
+            match self
+                .generate_one(template, index, &country_code, platform, &mut used_proxies)
+                .await
+            {
+                Ok(profile) => created.push(profile),
+                Err(e) => {
+                    warn!("Failed to generate profile {} ({}): {}", index + 1, country_code, e);
+                    failed.push(GenerationFailure {
+                        index,
+                        country_code: Some(country_code),
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        info!("Generated {} of {} profiles", created.len(), count);
+        Ok(BulkGenerationReport {
+            started_at,
+            finished_at: Utc::now(),
+            requested: count,
+            created,
+            failed,
+        })
+    }
+
+    async fn generate_one(
+        &self,
+        template: &ProfileTemplate,
+        index: usize,
+        country_code: &str,
+        platform: FingerprintPlatform,
+        used_proxies: &mut HashSet<String>,
+    ) -> Result<GeneratedProfile> {
+        let virtual_ip = self.ip_generator.generate_for_country(country_code)?;
+        let exit = self.pick_proxy(country_code, &template.rules, used_proxies).await?;
+
+        // Timezone and locale follow the verified exit so they agree with the
+        // IP sites actually see; the country table is only a fallback
+        let pinned = &template.settings;
+        let exit_country = exit.as_ref().and_then(|(_, exit)| self.exit_country(exit));
+        let timezone = pinned
+            .timezone
+            .clone()
+            .or_else(|| exit.as_ref().and_then(|(_, exit)| exit.timezone.clone()))
+            .or_else(|| exit_country.as_ref().map(|c| c.timezone.clone()))
+            .unwrap_or_else(|| virtual_ip.timezone.clone());
+        let language = pinned
+            .language
+            .clone()
+            .or_else(|| exit.as_ref().and_then(|(_, exit)| exit.locale.clone()))
+            .or_else(|| exit_country.as_ref().map(|c| c.language.clone()))
+            .unwrap_or_else(|| virtual_ip.language.clone());
+        let geolocation = pinned.geolocation.clone().or_else(|| {
+            exit.as_ref()
+                .and_then(|(_, exit)| exit.location)
+                .map(|(latitude, longitude)| GeoLocation {
+                    latitude,
+                    longitude,
+                    accuracy: template.rules.geolocation_accuracy,
+                })
+        });
+
+        let mut fingerprint = BrowserFingerprint::generate(platform, &language, &timezone);
+        if let Some(user_agent) = &pinned.user_agent {
+            fingerprint.user_agent = user_agent.clone();
+        }
+
+        let settings = ProfileSettings {
+            proxy_enabled: exit.is_some(),
+            proxy_config: exit.as_ref().map(|(proxy, _)| ProfileProxyConfig {
+                host: proxy.ip.clone(),
+                port: proxy.port,
+                protocol: proxy_protocol(&proxy.protocol).to_string(),
+                username: None,
+                password: None,
+            }),
+            user_agent: Some(fingerprint.user_agent.clone()),
+            language: language.clone(),
+            timezone: Some(timezone.clone()),
+            geolocation: geolocation.clone(),
+            fingerprint_protection: pinned.fingerprint_protection.unwrap_or(true),
+            clear_on_close: pinned.clear_on_close.unwrap_or(false),
+            fingerprint: Some(fingerprint.clone()),
+            virtual_ip: Some(virtual_ip.clone()),
+        };
+
+        let name = template.profile_name(index, country_code);
+        let profile = self.profiles.create_profile(&name, false).await?;
+        self.profiles.update_settings(&profile.id, settings).await?;
+        debug!("Generated profile {} for {}", profile.id, country_code);
+
+        Ok(GeneratedProfile {
+            index,
+            profile_id: profile.id,
+            name,
+            country_code: country_code.to_string(),
+            virtual_ip: virtual_ip.ip.to_string(),
+            proxy: exit.as_ref().map(|(proxy, _)| format!("{}:{}", proxy.ip, proxy.port)),
+            exit_ip: exit.map(|(_, exit)| exit.ip),
+            timezone,
+            language,
+            geolocation,
+            platform: fingerprint.platform,
+        })
+    }
+
+    /// Look up the country a proxy exits in, by code or by name
+    fn exit_country(&self, exit: &ProxyExit) -> Option<Country> {
+        self.ip_generator.get_country(&exit.country).cloned().or_else(|| {
+            self.ip_generator
+                .list_countries()
+                .into_iter()
+                .find(|c| c.name.eq_ignore_ascii_case(&exit.country))
+        })
+    }
+
+    /// Find a verified proxy for a country, marking it used
+    async fn pick_proxy(
+        &self,
+        country_code: &str,
+        rules: &GenerationRules,
+        used_proxies: &mut HashSet<String>,
+    ) -> Result<Option<(FreeProxy, ProxyExit)>> {
+        let mut candidates: Vec<FreeProxy> = self
+            .proxy_pool
+            .read()
+            .await
+            .iter()
+            .filter(|p| p.is_working && p.country_code.eq_ignore_ascii_case(country_code))
+            .filter(|p| !rules.unique_proxies || !used_proxies.contains(&proxy_key(p)))
+            .cloned()
+            .collect();
+        candidates.shuffle(&mut rand::thread_rng());
+
+        let mut last_error = None;
+        for proxy in candidates.into_iter().take(rules.max_proxy_attempts.max(1)) {
+            let exit = match self.verifier.verify_exit(&proxy).await {
+                Ok(exit) => exit,
+                Err(e) => {
+                    debug!("Proxy {}:{} rejected: {}", proxy.ip, proxy.port, e);
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            used_proxies.insert(proxy_key(&proxy));
+            return Ok(Some((proxy, exit)));
+        }
+
+        if rules.require_proxy {
+            Err(match last_error {
+                Some(e) => anyhow!("No proxy for {} passed verification (last error: {})", country_code, e),
+                None => anyhow!("No available proxy for {}", country_code),
+            })
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn proxy_key(proxy: &FreeProxy) -> String {
+    format!("{}:{}", proxy.ip, proxy.port)
+}
+
+fn proxy_protocol(proxy_type: &ProxyType) -> &'static str {
+    match proxy_type {
+        ProxyType::Https => "https",
+        ProxyType::Socks4 => "socks4",
+        ProxyType::Socks5 => "socks5",
+        ProxyType::Http | ProxyType::Direct => "http",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct FakeVerifier;
+
+    #[async_trait]
+    impl ProxyExitVerifier for FakeVerifier {
+        async fn verify_exit(&self, proxy: &FreeProxy) -> Result<ProxyExit> {
+            if proxy.ip.starts_with("10.") {
+                return Err(anyhow!("Exit is in the wrong country"));
+            }
+            Ok(ProxyExit {
+                ip: proxy.ip.clone(),
+                country: proxy.country.clone(),
+                location: Some((51.5, -0.12)),
+                timezone: None,
+                locale: None,
+            })
+        }
+    }
+
+    fn proxy(ip: &str, country_code: &str) -> FreeProxy {
+        FreeProxy {
+            ip: ip.to_string(),
+            port: 8080,
+            protocol: ProxyType::Http,
+            country: country_code.to_string(),
+            country_code: country_code.to_string(),
+            anonymity: "elite".to_string(),
+            speed: 100,
+            uptime: 99.0,
+            last_checked: String::new(),
+            provider: "test".to_string(),
+            is_working: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_generation_report() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let profiles = Arc::new(BrowserProfileManager::new(temp_dir.path().to_path_buf()));
+        let generator = BulkProfileGenerator::new(Arc::new(virtual_ip::demo_generator()), profiles.clone())
+            .expect("Generator should build")
+            .with_verifier(Arc::new(FakeVerifier))
+            .with_proxy_pool(vec![
+                proxy("10.0.0.1", "GB"),
+                proxy("81.2.69.1", "GB"),
+                proxy("81.2.69.2", "GB"),
+            ]);
+
+        let template = ProfileTemplate::new("acct-{country}-{n}").with_countries(&["GB"]);
+        let report = generator.generate(&template, 3).await.expect("Generation should succeed");
+
+        assert_eq!(report.requested, 3);
+        assert_eq!(report.created.len(), 2);
+        assert_eq!(report.failed.len(), 1);
+        assert!(!report.is_complete());
+
+        let first = &report.created[0];
+        assert_eq!(first.name, "acct-GB-1");
+        assert_eq!(first.timezone, "Europe/London");
+        assert!(first.geolocation.is_some());
+        assert_ne!(report.created[0].proxy, report.created[1].proxy);
+
+        let stored = profiles.get_profile(&first.profile_id).await.expect("Profile should exist");
+        let fingerprint = stored.settings.fingerprint.expect("Fingerprint should be set");
+        assert_eq!(fingerprint.timezone, "Europe/London");
+        assert!(fingerprint.accept_language.starts_with("en-GB"));
+        assert!(stored.settings.proxy_enabled);
+    }
+
+    /// Reports every exit as a Lisbon address, whatever the proxy claims
+    struct LisbonVerifier;
+
+    #[async_trait]
+    impl ProxyExitVerifier for LisbonVerifier {
+        async fn verify_exit(&self, proxy: &FreeProxy) -> Result<ProxyExit> {
+            Ok(ProxyExit {
+                ip: proxy.ip.clone(),
+                country: "Portugal".to_string(),
+                location: Some((38.72, -9.14)),
+                timezone: Some("Europe/Lisbon".to_string()),
+                locale: Some("pt-PT".to_string()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timezone_and_locale_follow_the_verified_exit() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let profiles = Arc::new(BrowserProfileManager::new(temp_dir.path().to_path_buf()));
+        let generator = BulkProfileGenerator::new(Arc::new(virtual_ip::demo_generator()), profiles.clone())
+            .expect("Generator should build")
+            .with_verifier(Arc::new(LisbonVerifier))
+            .with_proxy_pool(vec![proxy("81.2.69.1", "GB")]);
+
+        let template = ProfileTemplate::new("p{n}").with_countries(&["GB"]);
+        let report = generator.generate(&template, 1).await.expect("Generation should succeed");
+
+        let created = &report.created[0];
+        assert_eq!(created.timezone, "Europe/Lisbon");
+        assert_eq!(created.language, "pt-PT");
+
+        let stored = profiles.get_profile(&created.profile_id).await.expect("Profile should exist");
+        let fingerprint = stored.settings.fingerprint.expect("Fingerprint should be set");
+        assert_eq!(fingerprint.timezone, "Europe/Lisbon");
+        assert!(fingerprint.accept_language.starts_with("pt-PT"));
+    }
+
+    #[tokio::test]
+    async fn test_generation_without_required_proxy() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let profiles = Arc::new(BrowserProfileManager::new(temp_dir.path().to_path_buf()));
+        let generator = BulkProfileGenerator::new(Arc::new(virtual_ip::demo_generator()), profiles)
+            .expect("Generator should build");
+
+        let rules = GenerationRules {
+            countries: vec!["US".to_string()],
+            require_proxy: false,
+            platforms: vec![FingerprintPlatform::MacOs],
+            ..Default::default()
+        };
+        let template = ProfileTemplate::new("p{n}").with_rules(rules);
+
+        let report = generator.generate(&template, 2).await.expect("Generation should succeed");
+        assert!(report.is_complete());
+        assert!(report.created.iter().all(|p| p.proxy.is_none() && p.platform == "MacIntel"));
+    }
+
+    #[tokio::test]
+    async fn test_proxies_are_verified_by_default() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let profiles = Arc::new(BrowserProfileManager::new(temp_dir.path().to_path_buf()));
+        // Nothing listens on port 1, so the default verifier rejects the proxy
+        let unreachable = FreeProxy { port: 1, ..proxy("127.0.0.1", "GB") };
+        let generator = BulkProfileGenerator::new(Arc::new(virtual_ip::demo_generator()), profiles)
+            .expect("Generator should build")
+            .with_proxy_pool(vec![unreachable]);
+
+        let template = ProfileTemplate::new("p{n}").with_countries(&["GB"]);
+        let report = generator.generate(&template, 1).await.expect("Generation should succeed");
+        assert!(report.created.is_empty());
+        assert!(report.failed[0].reason.contains("passed verification"));
+    }
+}
@@ -9,14 +9,17 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Duration, Utc};
 use rand::Rng;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 
+use crate::config_manager::ProxyConfig;
+use crate::event_stream::{BrowserEvent, EventBus};
 use crate::proxy::FreeProxy;
 use crate::free_ip_providers::FreeIpProviderManager;
+use crate::prelude::{metric_names, MetricsCollector};
 
 /// Manages proxy rotation strategies for browser tabs.
 pub struct ProxyRotationManager {
@@ -24,6 +27,7 @@ pub struct ProxyRotationManager {
     active_proxies: Arc<RwLock<HashMap<String, ProxySession>>>,
     strategy: ProxyRotationStrategy,
     performance_metrics: Arc<RwLock<HashMap<String, ProxyMetrics>>>,
+    events: Option<Arc<EventBus>>,
 }
 
 #[derive(Clone)]
@@ -62,6 +66,61 @@ pub enum ProxyRotationStrategy {
     Manual,
 }
 
+impl ProxyRotationStrategy {
+    /// Build the strategy named by `proxy.rotation_strategy`. Time-based
+    /// strategies use `rotation_interval_secs`, `per_request` uses
+    /// `rotation_requests`, `random` uses `rotation_probability` and
+    /// geographic rotation uses `preferred_countries`.
+    pub fn from_config(proxy: &ProxyConfig) -> Result<Self> {
+        let interval = Duration::seconds(proxy.rotation_interval_secs.min(i64::MAX as u64) as i64);
+        let strategy = match proxy.rotation_strategy.as_str() {
+            "per_request" => ProxyRotationStrategy::PerRequest(proxy.rotation_requests.max(1)),
+            "per_duration" => ProxyRotationStrategy::PerDuration(interval),
+            "per_session" => ProxyRotationStrategy::PerSession,
+            "random" => {
+                if !(0.0..=1.0).contains(&proxy.rotation_probability) {
+                    return Err(anyhow!(
+                        "Rotation probability must be between 0 and 1, got {}",
+                        proxy.rotation_probability
+                    ));
+                }
+                ProxyRotationStrategy::Random { probability: proxy.rotation_probability }
+            }
+            "sticky" => ProxyRotationStrategy::Sticky { duration: interval },
+            "geographic" => {
+                if proxy.preferred_countries.is_empty() {
+                    return Err(anyhow!("Geographic rotation needs at least one preferred country"));
+                }
+                ProxyRotationStrategy::Geographic {
+                    country_codes: proxy.preferred_countries.iter().map(|c| c.to_ascii_uppercase()).collect(),
+                }
+            }
+            "performance_based" => ProxyRotationStrategy::PerformanceBased,
+            "round_robin" => ProxyRotationStrategy::RoundRobin,
+            "domain_based" => ProxyRotationStrategy::DomainBased,
+            "manual" => ProxyRotationStrategy::Manual,
+            other => return Err(anyhow!("Unknown proxy rotation strategy: {}", other)),
+        };
+        Ok(strategy)
+    }
+
+    /// Config name of this strategy, as accepted by `from_config`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProxyRotationStrategy::PerRequest(_) => "per_request",
+            ProxyRotationStrategy::PerDuration(_) => "per_duration",
+            ProxyRotationStrategy::PerSession => "per_session",
+            ProxyRotationStrategy::Random { .. } => "random",
+            ProxyRotationStrategy::Sticky { .. } => "sticky",
+            ProxyRotationStrategy::Geographic { .. } => "geographic",
+            ProxyRotationStrategy::PerformanceBased => "performance_based",
+            ProxyRotationStrategy::RoundRobin => "round_robin",
+            ProxyRotationStrategy::DomainBased => "domain_based",
+            ProxyRotationStrategy::Manual => "manual",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a ProxyMetrics.
 pub struct ProxyMetrics {
@@ -75,6 +134,7 @@ pub struct ProxyMetrics {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a ProxySessionStats.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ProxySessionStats {
     pub tab_id: String,
     pub current_proxy_ip: String,
@@ -95,6 +155,24 @@ impl ProxyRotationManager {
             active_proxies: Arc::new(RwLock::new(HashMap::new())),
             strategy,
             performance_metrics: Arc::new(RwLock::new(HashMap::new())),
+            events: None,
+        }
+    }
+
+    /// Publish proxy assignments and rotations to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    fn publish_rotation(&self, tab_id: &str, previous: Option<&FreeProxy>, proxy: &FreeProxy) {
+        if let Some(bus) = &self.events {
+            bus.publish(BrowserEvent::ProxyRotated {
+                tab_id: tab_id.to_string(),
+                previous_proxy: previous.map(|p| format!("{}:{}", p.ip, p.port)),
+                proxy_id: format!("{}:{}", proxy.ip, proxy.port),
+                country_code: proxy.country_code.clone(),
+            });
         }
     }
 
@@ -124,6 +202,7 @@ impl ProxyRotationManager {
 
             if self.should_rotate(session).await {
                 let new_proxy = self.get_next_proxy(session).await?;
+                self.publish_rotation(tab_id, Some(&session.proxy), &new_proxy);
                 session.proxy = new_proxy.clone();
                 session.assigned_at = Utc::now();
                 session.last_used = Utc::now();
@@ -156,6 +235,7 @@ impl ProxyRotationManager {
 
         sessions.insert(tab_id.to_string(), session);
         info!("Assigned initial proxy {} to tab {}", proxy.ip, tab_id);
+        self.publish_rotation(tab_id, None, &proxy);
         Ok(proxy)
     }
 
@@ -164,6 +244,7 @@ impl ProxyRotationManager {
         let mut sessions = self.active_proxies.write().await;
         if let Some(session) = sessions.get_mut(tab_id) {
             let new_proxy = self.get_next_proxy(session).await?;
+            self.publish_rotation(tab_id, Some(&session.proxy), &new_proxy);
             session.proxy = new_proxy.clone();
             session.assigned_at = Utc::now();
             session.last_used = Utc::now();
@@ -177,6 +258,113 @@ impl ProxyRotationManager {
         }
     }
 
+    /// Sticky proxy for `session_id` from `country_code` (any country when
+    /// `None`). The session keeps its proxy until its `ip:port` is listed in
+    /// `exclude`, e.g. after failing, and then rotates to another working
+    /// proxy from the same country.
+    pub async fn get_proxy_for_country(
+        &self,
+        session_id: &str,
+        country_code: Option<&str>,
+        exclude: &HashSet<String>,
+    ) -> Result<FreeProxy> {
+        let usable = |proxy: &FreeProxy| {
+            country_code.is_none_or(|cc| proxy.country_code.eq_ignore_ascii_case(cc))
+                && !exclude.contains(&format!("{}:{}", proxy.ip, proxy.port))
+        };
+        let mut sessions = self.active_proxies.write().await;
+        if let Some(session) = sessions.get_mut(session_id) {
+            if usable(&session.proxy) {
+                session.last_used = Utc::now();
+                session.request_count += 1;
+                return Ok(session.proxy.clone());
+            }
+        }
+
+        let candidates: Vec<FreeProxy> = self
+            .provider_manager
+            .read()
+            .await
+            .get_working_proxies()
+            .into_iter()
+            .filter(|p| usable(p))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Err(anyhow!(
+                "No working proxies available in {}",
+                country_code.unwrap_or("any country")
+            ));
+        }
+        let proxy = candidates[rand::thread_rng().gen_range(0..candidates.len())].clone();
+
+        let previous = sessions.get(session_id).map(|s| s.proxy.clone());
+        self.publish_rotation(session_id, previous.as_ref(), &proxy);
+        sessions.insert(session_id.to_string(), ProxySession {
+            proxy: proxy.clone(),
+            assigned_at: Utc::now(),
+            last_used: Utc::now(),
+            request_count: 1,
+            tab_id: session_id.to_string(),
+            domain_proxy_map: HashMap::new(),
+        });
+        Ok(proxy)
+    }
+
+    /// Up to `n` working proxies from `country_code` (any country when
+    /// `None`), best first by `selector`'s score over the recorded metrics,
+    /// leaving out the `ip:port` IDs in `exclude`
+    pub async fn rank_proxies(
+        &self,
+        selector: &SmartProxySelector,
+        country_code: Option<&str>,
+        exclude: &HashSet<String>,
+        n: usize,
+    ) -> Vec<FreeProxy> {
+        let candidates: Vec<FreeProxy> = self
+            .provider_manager
+            .read()
+            .await
+            .get_working_proxies()
+            .into_iter()
+            .filter(|p| {
+                country_code.is_none_or(|cc| p.country_code.eq_ignore_ascii_case(cc))
+                    && !exclude.contains(&format!("{}:{}", p.ip, p.port))
+            })
+            .cloned()
+            .collect();
+        let metrics = self.performance_metrics.read().await;
+        selector.select_top_n(&candidates, &metrics, n)
+    }
+
+    /// Pool entry listening on `ip:port`, working or not
+    pub async fn find_proxy(&self, ip: &str, port: u16) -> Option<FreeProxy> {
+        self.provider_manager
+            .read()
+            .await
+            .get_proxy_pool()
+            .iter()
+            .find(|p| p.ip == ip && p.port == port)
+            .cloned()
+    }
+
+    /// Move `session_id` to `proxy`, starting the session if there is none,
+    /// e.g. after its proxy failed and another one took over
+    pub async fn rebind_session(&self, session_id: &str, proxy: &FreeProxy) {
+        let mut sessions = self.active_proxies.write().await;
+        let previous = sessions.get(session_id).map(|s| s.proxy.clone());
+        self.publish_rotation(session_id, previous.as_ref(), proxy);
+        sessions.insert(session_id.to_string(), ProxySession {
+            proxy: proxy.clone(),
+            assigned_at: Utc::now(),
+            last_used: Utc::now(),
+            request_count: 0,
+            tab_id: session_id.to_string(),
+            domain_proxy_map: HashMap::new(),
+        });
+        info!("Rebound session {} to proxy {}:{}", session_id, proxy.ip, proxy.port);
+    }
+
     /// Record proxy performance metrics
     pub async fn record_performance(&self, proxy_id: &str, success: bool, response_time_ms: Option<f64>) {
         let mut metrics = self.performance_metrics.write().await;
@@ -224,17 +412,80 @@ impl ProxyRotationManager {
         })
     }
 
+    /// Statistics for every active session, ordered by tab ID
+    pub async fn list_session_stats(&self) -> Vec<ProxySessionStats> {
+        let sessions = self.active_proxies.read().await;
+        let mut stats: Vec<ProxySessionStats> = sessions
+            .values()
+            .map(|s| ProxySessionStats {
+                tab_id: s.tab_id.clone(),
+                current_proxy_ip: s.proxy.ip.clone(),
+                proxy_country: s.proxy.country.clone(),
+                assigned_at: s.assigned_at,
+                request_count: s.request_count,
+                duration_seconds: (Utc::now() - s.assigned_at).num_seconds(),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.tab_id.cmp(&b.tab_id));
+        stats
+    }
+
+    /// Performance metrics recorded for a proxy
+    pub async fn get_metrics(&self, proxy_id: &str) -> Option<ProxyMetrics> {
+        self.performance_metrics.read().await.get(proxy_id).cloned()
+    }
+
+    /// Current rotation strategy
+    pub fn strategy(&self) -> &ProxyRotationStrategy {
+        &self.strategy
+    }
+
     /// Clean up expired sessions
     pub async fn cleanup_expired(&self, max_age: Duration) {
+        self.expire_sessions("", max_age).await;
+    }
+
+    /// Drop the sessions whose ID starts with `prefix` and that have not
+    /// been used for `max_age`. Returns how many were dropped.
+    pub async fn expire_sessions(&self, prefix: &str, max_age: Duration) -> usize {
         let mut sessions = self.active_proxies.write().await;
         let now = Utc::now();
         let initial_count = sessions.len();
-        sessions.retain(|_, session| now - session.last_used < max_age);
+        sessions.retain(|id, session| !id.starts_with(prefix) || now - session.last_used < max_age);
         let removed = initial_count - sessions.len();
         
         if removed > 0 {
             info!("Cleaned up {} expired proxy sessions", removed);
         }
+        removed
+    }
+
+    /// End a tab's proxy session, dropping its sticky assignments.
+    /// Returns true if a session existed.
+    pub async fn end_session(&self, tab_id: &str) -> bool {
+        let removed = self.active_proxies.write().await.remove(tab_id).is_some();
+        if removed {
+            debug!("Ended proxy session for tab {}", tab_id);
+        }
+        removed
+    }
+
+    /// Drop sticky domain assignments for a domain and its subdomains in all
+    /// tab sessions. Returns the number of assignments removed.
+    pub async fn forget_domain(&self, domain: &str) -> usize {
+        let mut sessions = self.active_proxies.write().await;
+        let mut removed = 0;
+        for session in sessions.values_mut() {
+            let before = session.domain_proxy_map.len();
+            session
+                .domain_proxy_map
+                .retain(|d, _| !crate::retention::host_matches_domain(d, domain));
+            removed += before - session.domain_proxy_map.len();
+        }
+        if removed > 0 {
+            debug!("Removed {} sticky proxy assignments for {}", removed, domain);
+        }
+        removed
     }
 
     /// Update rotation strategy
@@ -513,11 +764,14 @@ pub struct ProxyHealthMonitor {
     health_status: Arc<RwLock<HashMap<String, ProxyHealthStatus>>>,
     /// Bandwidth tracking
     bandwidth_tracker: Arc<RwLock<HashMap<String, BandwidthStats>>>,
+    /// Request counters and latency histograms, when exported
+    metrics: Option<Arc<MetricsCollector>>,
 }
 
 /// Health status for a proxy
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a ProxyHealthStatus.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ProxyHealthStatus {
     pub proxy_id: String,
     pub is_healthy: bool,
@@ -531,6 +785,7 @@ pub struct ProxyHealthStatus {
 /// Bandwidth statistics for a proxy
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 /// Represents a BandwidthStats.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct BandwidthStats {
     pub proxy_id: String,
     pub bytes_sent: u64,
@@ -567,11 +822,45 @@ impl ProxyHealthMonitor {
             recovery_interval_secs: 300,
             health_status: Arc::new(RwLock::new(HashMap::new())),
             bandwidth_tracker: Arc::new(RwLock::new(HashMap::new())),
+            metrics: None,
         }
     }
 
+    /// Count requests, latency and bytes per proxy in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     /// Record a successful request
     pub async fn record_success(&self, proxy_id: &str, latency_ms: f64, bytes_sent: u64, bytes_received: u64) {
+        if let Some(metrics) = &self.metrics {
+            let proxy = [("proxy", proxy_id)];
+            metrics
+                .increment(&MetricsCollector::labeled(
+                    metric_names::PROXY_REQUESTS_TOTAL,
+                    &[("proxy", proxy_id), ("outcome", "success")],
+                ))
+                .await;
+            metrics
+                .record_histogram(
+                    &MetricsCollector::labeled(metric_names::PROXY_LATENCY_SECONDS, &proxy),
+                    latency_ms / 1000.0,
+                )
+                .await;
+            for (direction, bytes) in [("sent", bytes_sent), ("received", bytes_received)] {
+                metrics
+                    .increment_by(
+                        &MetricsCollector::labeled(
+                            metric_names::PROXY_BYTES_TOTAL,
+                            &[("proxy", proxy_id), ("direction", direction)],
+                        ),
+                        bytes,
+                    )
+                    .await;
+            }
+        }
+
         // Update health status
         let mut status = self.health_status.write().await;
         let health = status.entry(proxy_id.to_string()).or_insert(ProxyHealthStatus {
@@ -611,6 +900,15 @@ impl ProxyHealthMonitor {
 
     /// Record a failed request
     pub async fn record_failure(&self, proxy_id: &str, error: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .increment(&MetricsCollector::labeled(
+                    metric_names::PROXY_REQUESTS_TOTAL,
+                    &[("proxy", proxy_id), ("outcome", "failure")],
+                ))
+                .await;
+        }
+
         let mut status = self.health_status.write().await;
         let health = status.entry(proxy_id.to_string()).or_insert(ProxyHealthStatus {
             proxy_id: proxy_id.to_string(),
@@ -656,6 +954,14 @@ impl ProxyHealthMonitor {
         status.get(proxy_id).cloned()
     }
 
+    /// Health status of every tracked proxy, ordered by proxy ID
+    pub async fn all_health(&self) -> Vec<ProxyHealthStatus> {
+        let status = self.health_status.read().await;
+        let mut all: Vec<ProxyHealthStatus> = status.values().cloned().collect();
+        all.sort_by(|a, b| a.proxy_id.cmp(&b.proxy_id));
+        all
+    }
+
     /// Get all healthy proxies
     pub async fn get_healthy_proxies(&self) -> Vec<String> {
         let status = self.health_status.read().await;
@@ -793,6 +1099,36 @@ impl Default for GeoDiversityManager {
 mod enhanced_tests {
     use super::*;
 
+    #[test]
+    fn test_strategy_from_config_reads_configured_values() {
+        let mut config = ProxyConfig {
+            rotation_strategy: "per_request".to_string(),
+            rotation_requests: 7,
+            rotation_probability: 0.4,
+            ..ProxyConfig::default()
+        };
+        assert!(matches!(
+            ProxyRotationStrategy::from_config(&config).expect("per_request should parse"),
+            ProxyRotationStrategy::PerRequest(7)
+        ));
+
+        config.rotation_strategy = "random".to_string();
+        match ProxyRotationStrategy::from_config(&config).expect("random should parse") {
+            ProxyRotationStrategy::Random { probability } => assert_eq!(probability, 0.4),
+            other => panic!("unexpected strategy {:?}", other),
+        }
+        config.rotation_probability = 1.5;
+        assert!(ProxyRotationStrategy::from_config(&config).is_err());
+
+        config.rotation_strategy = "geographic".to_string();
+        assert!(ProxyRotationStrategy::from_config(&config).is_err());
+        config.preferred_countries = vec!["de".to_string()];
+        match ProxyRotationStrategy::from_config(&config).expect("geographic should parse") {
+            ProxyRotationStrategy::Geographic { country_codes } => assert_eq!(country_codes, vec!["DE"]),
+            other => panic!("unexpected strategy {:?}", other),
+        }
+    }
+
     #[test]
     fn test_smart_proxy_selector_scoring() {
         let selector = SmartProxySelector::default();
@@ -862,4 +1198,28 @@ mod enhanced_tests {
         assert_eq!(stats.bytes_received, 3500);
         assert_eq!(stats.requests_count, 2);
     }
+
+    #[tokio::test]
+    async fn test_health_monitor_metrics() {
+        let metrics = Arc::new(MetricsCollector::new());
+        let monitor = ProxyHealthMonitor::new().with_metrics(metrics.clone());
+
+        monitor.record_success("1.2.3.4:8080", 250.0, 100, 400).await;
+        monitor.record_failure("1.2.3.4:8080", "connection refused").await;
+
+        let success = MetricsCollector::labeled(
+            metric_names::PROXY_REQUESTS_TOTAL,
+            &[("proxy", "1.2.3.4:8080"), ("outcome", "success")],
+        );
+        let failure = MetricsCollector::labeled(
+            metric_names::PROXY_REQUESTS_TOTAL,
+            &[("proxy", "1.2.3.4:8080"), ("outcome", "failure")],
+        );
+        assert_eq!(metrics.get_counter(&success).await, 1);
+        assert_eq!(metrics.get_counter(&failure).await, 1);
+
+        let latency = MetricsCollector::labeled(metric_names::PROXY_LATENCY_SECONDS, &[("proxy", "1.2.3.4:8080")]);
+        let stats = metrics.get_histogram_stats(&latency).await.expect("Latency should be recorded");
+        assert_eq!(stats.sum, 0.25);
+    }
 }
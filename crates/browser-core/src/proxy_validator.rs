@@ -5,6 +5,7 @@
 //! - Response time measurement
 //! - Geo-location verification
 //! - IP leak detection
+//! - Anonymity classification against a proxy judge
 //! - Health monitoring with automatic quarantine
 //! - Batch validation with concurrency control
 
@@ -16,6 +17,7 @@ use tokio::sync::Semaphore;
 use tracing::{debug, info, warn, error};
 use std::sync::Arc;
 
+use crate::event_stream::{BrowserEvent, EventBus};
 use crate::proxy::{FreeProxy, ProxySettings};
 use crate::http_client::HttpClient;
 
@@ -32,6 +34,7 @@ struct InternalTestResult {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a ValidationResult.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ValidationResult {
     pub is_working: bool,
     pub response_time_ms: u64,
@@ -71,6 +74,7 @@ impl Default for ProxyValidatorConfig {
 pub struct ProxyValidator {
     config: ProxyValidatorConfig,
     semaphore: Arc<Semaphore>,
+    events: Option<Arc<EventBus>>,
 }
 
 impl ProxyValidator {
@@ -79,6 +83,24 @@ impl ProxyValidator {
         Self {
             semaphore: Arc::new(Semaphore::new(config.concurrent_checks)),
             config,
+            events: None,
+        }
+    }
+
+    /// Publish every validation result to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    fn publish_result(&self, proxy: &FreeProxy, result: &ValidationResult) {
+        if let Some(bus) = &self.events {
+            bus.publish(BrowserEvent::ValidationCompleted {
+                proxy_id: format!("{}:{}", proxy.ip, proxy.port),
+                is_working: result.is_working,
+                response_time_ms: result.response_time_ms,
+                error: result.error.clone(),
+            });
         }
     }
 
@@ -142,6 +164,7 @@ impl ProxyValidator {
         } else {
             warn!("Proxy {}:{} failed validation: {:?}", proxy.ip, proxy.port, result.error);
         }
+        self.publish_result(proxy, &result);
         
         Ok(result)
     }
@@ -223,18 +246,59 @@ impl ProxyValidator {
     }
 
     async fn check_ip_leak(&self, detected_ip: &str) -> Result<bool> {
-        // Get local IP without proxy
+        Ok(self.detect_real_ip().await? == detected_ip)
+    }
+
+    /// Public address of this machine, requested without a proxy
+    pub async fn detect_real_ip(&self) -> Result<String> {
         let local_client = HttpClient::new()?;
         let local_response = local_client.get("https://api.ipify.org?format=json").await?;
-        
+
         #[derive(Deserialize)]
         struct LocalIpResponse {
             ip: String,
         }
-        
+
         let local_ip: LocalIpResponse = serde_json::from_str(&local_response)?;
-        
-        Ok(local_ip.ip == detected_ip)
+        Ok(local_ip.ip)
+    }
+
+    /// Send a request through `proxy` to a judge endpoint and classify how
+    /// much it reveals; `real_ip` is the address the proxy should hide
+    pub async fn judge_proxy(&self, proxy: &FreeProxy, judge_url: &str, real_ip: &str) -> Result<JudgeResult> {
+        let _permit = self.semaphore.acquire().await
+            .map_err(|e| anyhow!("Failed to acquire semaphore: {}", e))?;
+
+        let client = HttpClient::with_proxy(&proxy.to_proxy_settings())?;
+        let start = std::time::Instant::now();
+        let response = client.client().get(judge_url).timeout(self.config.timeout).send().await
+            .map_err(|e| anyhow!("Judge request through {}:{} failed: {}", proxy.ip, proxy.port, e))?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Judge answered {} through {}:{}", response.status(), proxy.ip, proxy.port));
+        }
+        let body = response.text().await?;
+
+        let (anonymity, revealing_headers) = classify_judge_response(&body, real_ip);
+        debug!("Proxy {}:{} judged {}", proxy.ip, proxy.port, anonymity.as_str());
+        Ok(JudgeResult {
+            anonymity,
+            revealing_headers,
+            response_time_ms: start.elapsed().as_millis() as u64,
+            judged_at: Utc::now(),
+        })
+    }
+
+    /// Judge several proxies concurrently, in the order given
+    pub async fn judge_batch(
+        &self,
+        proxies: &[FreeProxy],
+        judge_url: &str,
+        real_ip: &str,
+    ) -> Vec<(FreeProxy, Result<JudgeResult>)> {
+        let checks = proxies.iter().map(|proxy| async move {
+            (proxy.clone(), self.judge_proxy(proxy, judge_url, real_ip).await)
+        });
+        futures::future::join_all(checks).await
     }
 
     /// Validates the batch.
@@ -253,7 +317,8 @@ impl ProxyValidator {
         
         for proxy in proxies {
             let proxy = proxy.clone();
-            let validator = ProxyValidator::new(self.config.clone());
+            let mut validator = ProxyValidator::new(self.config.clone());
+            validator.events = self.events.clone();
             
             let task = tokio::spawn(async move {
                 let result = validator.validate_proxy(&proxy).await;
@@ -271,7 +336,7 @@ impl ProxyValidator {
                 }
                 Ok((proxy, Err(e))) => {
                     error!("Failed to validate proxy: {}", e);
-                    results.push((proxy, ValidationResult {
+                    let result = ValidationResult {
                         is_working: false,
                         response_time_ms: u64::MAX,
                         detected_country: None,
@@ -280,7 +345,9 @@ impl ProxyValidator {
                         has_ip_leak: false,
                         error: Some(e.to_string()),
                         validated_at: Utc::now(),
-                    }));
+                    };
+                    self.publish_result(&proxy, &result);
+                    results.push((proxy, result));
                 }
                 Err(e) => {
                     error!("Task failed: {}", e);
@@ -292,6 +359,99 @@ impl ProxyValidator {
     }
 }
 
+/// Judge used when none is configured; it echoes the request headers and
+/// origin address as JSON
+pub const DEFAULT_JUDGE_URL: &str = "http://httpbin.org/get";
+
+/// Headers that give away a proxy between the client and the judge
+const PROXY_HEADERS: &[&str] = &[
+    "via",
+    "forwarded",
+    "forwarded-for",
+    "x-forwarded",
+    "x-forwarded-for",
+    "x-real-ip",
+    "x-client-ip",
+    "client-ip",
+    "x-originating-ip",
+    "x-proxy-id",
+    "proxy-connection",
+];
+
+/// How much a proxy reveals about its client, as seen by a proxy judge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnonymityLevel {
+    /// The judge sees the client's real address
+    Transparent,
+    /// The real address is hidden, but proxy headers give the proxy away
+    Anonymous,
+    /// Neither the real address nor proxy headers reach the judge
+    Elite,
+}
+
+impl AnonymityLevel {
+    /// Name as stored in `FreeProxy::anonymity`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnonymityLevel::Transparent => "transparent",
+            AnonymityLevel::Anonymous => "anonymous",
+            AnonymityLevel::Elite => "elite",
+        }
+    }
+}
+
+/// What a proxy judge saw of a request sent through a proxy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JudgeResult {
+    pub anonymity: AnonymityLevel,
+    /// Proxy headers the judge received, lowercased
+    pub revealing_headers: Vec<String>,
+    pub response_time_ms: u64,
+    pub judged_at: DateTime<Utc>,
+}
+
+/// Classify a judge response. Understands JSON echoes with a `headers`
+/// object (httpbin) and `NAME = value` or `Name: value` listings (azenv).
+pub fn classify_judge_response(body: &str, real_ip: &str) -> (AnonymityLevel, Vec<String>) {
+    let names: Vec<String> = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(json) => json
+            .get("headers")
+            .and_then(|headers| headers.as_object())
+            .map(|headers| headers.keys().cloned().collect())
+            .unwrap_or_default(),
+        Err(_) => body
+            .lines()
+            .filter_map(|line| line.split_once(['=', ':']).map(|(name, _)| name.trim().to_string()))
+            .collect(),
+    };
+
+    let mut revealing: Vec<String> = names
+        .iter()
+        .map(|name| {
+            let name = name.to_ascii_lowercase().replace('_', "-");
+            name.strip_prefix("http-").map(str::to_string).unwrap_or(name)
+        })
+        .filter(|name| PROXY_HEADERS.contains(&name.as_str()))
+        .collect();
+    revealing.sort();
+    revealing.dedup();
+
+    // Compare whole address tokens so 1.2.3.4 does not match 11.2.3.45
+    let leaks_ip = !real_ip.is_empty()
+        && body
+            .split(|c: char| !(c.is_ascii_hexdigit() || c == '.' || c == ':'))
+            .any(|token| token == real_ip);
+    let level = if leaks_ip {
+        AnonymityLevel::Transparent
+    } else if !revealing.is_empty() {
+        AnonymityLevel::Anonymous
+    } else {
+        AnonymityLevel::Elite
+    };
+    (level, revealing)
+}
+
 #[allow(dead_code)]
 /// Represents a ProxyHealthChecker.
 pub struct ProxyHealthChecker {
@@ -370,6 +530,7 @@ use tokio::sync::RwLock;
 /// Represents a quarantined proxy with failure tracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a QuarantinedProxy.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct QuarantinedProxy {
     pub proxy: FreeProxy,
     pub consecutive_failures: u32,
@@ -384,6 +545,7 @@ pub struct ProxyQuarantineManager {
     max_consecutive_failures: u32,
     quarantine_duration: Duration,
     max_quarantine_duration: Duration,
+    events: Option<Arc<EventBus>>,
 }
 
 impl ProxyQuarantineManager {
@@ -398,6 +560,29 @@ impl ProxyQuarantineManager {
             max_consecutive_failures,
             quarantine_duration,
             max_quarantine_duration,
+            events: None,
+        }
+    }
+
+    /// Publish quarantine and release events to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    fn publish_quarantined(&self, key: &str, entry: &QuarantinedProxy) {
+        if let Some(bus) = &self.events {
+            bus.publish(BrowserEvent::ProxyQuarantined {
+                proxy_id: key.to_string(),
+                reason: entry.failure_reasons.last().cloned().unwrap_or_default(),
+                release_at: entry.release_at,
+            });
+        }
+    }
+
+    fn publish_released(&self, key: &str) {
+        if let Some(bus) = &self.events {
+            bus.publish(BrowserEvent::ProxyReleased { proxy_id: key.to_string() });
         }
     }
 
@@ -434,6 +619,9 @@ impl ProxyQuarantineManager {
                 "Proxy {} failure #{}: {}. Quarantine extended to {:?}",
                 key, entry.consecutive_failures, entry.failure_reasons.last().unwrap_or(&String::new()), entry.release_at
             );
+            if entry.consecutive_failures == self.max_consecutive_failures {
+                self.publish_quarantined(&key, entry);
+            }
             true
         } else {
             // First failure, check if we should quarantine
@@ -447,6 +635,7 @@ impl ProxyQuarantineManager {
             
             if entry.consecutive_failures >= self.max_consecutive_failures {
                 info!("Quarantining proxy {} after {} failures", key, entry.consecutive_failures);
+                self.publish_quarantined(&key, &entry);
                 quarantined.insert(key, entry);
                 true
             } else {
@@ -457,6 +646,38 @@ impl ProxyQuarantineManager {
         }
     }
 
+    /// Quarantine a proxy immediately, regardless of its failure count
+    pub async fn quarantine(&self, proxy: &FreeProxy, reason: String) -> QuarantinedProxy {
+        let key = Self::proxy_key(proxy);
+        let mut quarantined = self.quarantined.write().await;
+        let release_at = Utc::now()
+            + chrono::Duration::from_std(self.quarantine_duration).unwrap_or(chrono::Duration::minutes(5));
+        let entry = quarantined.entry(key.clone()).or_insert_with(|| QuarantinedProxy {
+            proxy: proxy.clone(),
+            consecutive_failures: 0,
+            quarantined_at: Utc::now(),
+            release_at,
+            failure_reasons: Vec::new(),
+        });
+        entry.consecutive_failures = entry.consecutive_failures.max(self.max_consecutive_failures);
+        entry.release_at = entry.release_at.max(release_at);
+        entry.failure_reasons.push(reason);
+        info!("Manually quarantined proxy {}", key);
+        let entry = entry.clone();
+        self.publish_quarantined(&key, &entry);
+        entry
+    }
+
+    /// Remove a proxy from quarantine, returning whether it was tracked
+    pub async fn release(&self, proxy: &FreeProxy) -> bool {
+        let key = Self::proxy_key(proxy);
+        let released = self.quarantined.write().await.remove(&key).is_some();
+        if released {
+            self.publish_released(&key);
+        }
+        released
+    }
+
     /// Record a success for a proxy, potentially releasing it from quarantine
     /// Record a successful proxy operation
     ///
@@ -466,8 +687,12 @@ impl ProxyQuarantineManager {
         let key = Self::proxy_key(proxy);
         let mut quarantined = self.quarantined.write().await;
         
-        if quarantined.remove(&key).is_some() {
+        if let Some(entry) = quarantined.remove(&key) {
             info!("Proxy {} released from quarantine after successful validation", key);
+            // Entries below the threshold were only being tracked
+            if entry.consecutive_failures >= self.max_consecutive_failures {
+                self.publish_released(&key);
+            }
         }
     }
 
@@ -510,6 +735,7 @@ impl ProxyQuarantineManager {
         for key in expired_keys {
             if let Some(entry) = quarantined.remove(&key) {
                 info!("Releasing proxy {} from quarantine (served time)", key);
+                self.publish_released(&key);
                 released.push(entry.proxy);
             }
         }
@@ -517,6 +743,15 @@ impl ProxyQuarantineManager {
         released
     }
 
+    /// Replace all quarantine entries, e.g. when restoring from a backup
+    pub async fn replace_all(&self, entries: Vec<QuarantinedProxy>) {
+        let mut quarantined = self.quarantined.write().await;
+        *quarantined = entries
+            .into_iter()
+            .map(|entry| (Self::proxy_key(&entry.proxy), entry))
+            .collect();
+    }
+
     /// Get quarantine statistics
     /// Get quarantine statistics
     pub async fn get_stats(&self) -> QuarantineStats {
@@ -543,6 +778,7 @@ impl ProxyQuarantineManager {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a QuarantineStats.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct QuarantineStats {
     pub total_quarantined: usize,
     pub actively_quarantined: usize,
@@ -585,10 +821,24 @@ pub struct GeoVerificationResult {
     pub detected_country: Option<String>,
     pub expected_location: Option<(f64, f64)>, // (lat, lon)
     pub detected_location: Option<(f64, f64)>,
+    /// IANA timezone reported for the exit IP
+    #[serde(default)]
+    pub detected_timezone: Option<String>,
+    /// Primary locale reported for the exit IP
+    #[serde(default)]
+    pub detected_locale: Option<String>,
     pub distance_km: Option<f64>,
     pub error: Option<String>,
 }
 
+/// Location details returned by a geo IP lookup
+struct IpGeoInfo {
+    country: String,
+    location: Option<(f64, f64)>,
+    timezone: Option<String>,
+    locale: Option<String>,
+}
+
 /// Geographic verifier for proxies
 pub struct GeoVerifier {
     config: GeoVerificationConfig,
@@ -618,6 +868,8 @@ impl GeoVerifier {
                 detected_country: Some(proxy.country.clone()),
                 expected_location: None,
                 detected_location: None,
+                detected_timezone: None,
+                detected_locale: None,
                 distance_km: None,
                 error: Some("Geographic verification disabled".to_string()),
             };
@@ -627,16 +879,18 @@ impl GeoVerifier {
         let geo_result = self.lookup_ip_location(detected_ip).await;
         
         match geo_result {
-            Ok((country, location)) => {
-                let is_country_match = country.to_lowercase() == proxy.country.to_lowercase()
-                    || country.to_lowercase() == proxy.country_code.to_lowercase();
+            Ok(info) => {
+                let is_country_match = info.country.to_lowercase() == proxy.country.to_lowercase()
+                    || info.country.to_lowercase() == proxy.country_code.to_lowercase();
                 
                 GeoVerificationResult {
                     is_verified: is_country_match,
                     expected_country: proxy.country.clone(),
-                    detected_country: Some(country),
+                    detected_country: Some(info.country),
                     expected_location: None, // We don't have expected coordinates
-                    detected_location: location,
+                    detected_location: info.location,
+                    detected_timezone: info.timezone,
+                    detected_locale: info.locale,
                     distance_km: None,
                     error: None,
                 }
@@ -648,6 +902,8 @@ impl GeoVerifier {
                     detected_country: None,
                     expected_location: None,
                     detected_location: None,
+                    detected_timezone: None,
+                    detected_locale: None,
                     distance_km: None,
                     error: Some(e.to_string()),
                 }
@@ -656,7 +912,7 @@ impl GeoVerifier {
     }
 
     /// Lookup IP location using geo IP APIs
-    async fn lookup_ip_location(&self, ip: &str) -> Result<(String, Option<(f64, f64)>)> {
+    async fn lookup_ip_location(&self, ip: &str) -> Result<IpGeoInfo> {
         for api_url_template in &self.config.geoip_api_urls {
             let url = api_url_template.replace("{ip}", ip);
             
@@ -678,7 +934,7 @@ impl GeoVerifier {
     }
 
     /// Parse various geo IP API response formats
-    fn parse_geo_response(&self, response: &str) -> Result<(String, Option<(f64, f64)>)> {
+    fn parse_geo_response(&self, response: &str) -> Result<IpGeoInfo> {
         // Try ipapi.co format
         #[derive(Deserialize)]
         struct IpApiCoResponse {
@@ -686,6 +942,8 @@ impl GeoVerifier {
             country: Option<String>,
             latitude: Option<f64>,
             longitude: Option<f64>,
+            timezone: Option<String>,
+            languages: Option<String>,
         }
         
         if let Ok(parsed) = serde_json::from_str::<IpApiCoResponse>(response) {
@@ -696,7 +954,15 @@ impl GeoVerifier {
                 (Some(lat), Some(lon)) => Some((lat, lon)),
                 _ => None,
             };
-            return Ok((country, location));
+            // `languages` lists the country's locales, most common first
+            let locale = parsed
+                .languages
+                .as_deref()
+                .and_then(|languages| languages.split(',').next())
+                .map(str::trim)
+                .filter(|locale| !locale.is_empty())
+                .map(str::to_string);
+            return Ok(IpGeoInfo { country, location, timezone: parsed.timezone, locale });
         }
         
         // Try ip-api.com format
@@ -705,6 +971,7 @@ impl GeoVerifier {
             country: Option<String>,
             lat: Option<f64>,
             lon: Option<f64>,
+            timezone: Option<String>,
         }
         
         if let Ok(parsed) = serde_json::from_str::<IpApiResponse>(response) {
@@ -713,7 +980,7 @@ impl GeoVerifier {
                 (Some(lat), Some(lon)) => Some((lat, lon)),
                 _ => None,
             };
-            return Ok((country, location));
+            return Ok(IpGeoInfo { country, location, timezone: parsed.timezone, locale: None });
         }
         
         Err(anyhow!("Failed to parse geo response"))
@@ -157,29 +157,32 @@ impl Default for RequestConfig {
     }
 }
 
-/// Response wrapper with metadata
+/// Response wrapper with metadata. The body is text unless the request was
+/// sent with `send_bytes`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RequestResponse {
+pub struct RequestResponse<B = String> {
     /// HTTP status code
     pub status: u16,
     /// Status text
     pub status_text: String,
     /// Response headers
     pub headers: HashMap<String, String>,
-    /// Response body as string
-    pub body: String,
+    /// Response body
+    pub body: B,
     /// Response time in milliseconds
     pub response_time_ms: u64,
     /// Final URL after redirects
     pub final_url: String,
 }
 
-impl RequestResponse {
+impl<B> RequestResponse<B> {
     /// Check if the response was successful (2xx status)
     pub fn is_success(&self) -> bool {
         self.status >= 200 && self.status < 300
     }
+}
 
+impl RequestResponse {
     /// Parse body as JSON
     pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
         serde_json::from_str(&self.body)
@@ -316,8 +319,22 @@ impl RequestBuilder {
         self
     }
 
-    /// Send the request
+    /// Send the request, reading the body as text
     pub async fn send(self) -> Result<RequestResponse> {
+        self.send_with(|response| response.text()).await
+    }
+
+    /// Send the request, keeping the body as the raw bytes received
+    pub async fn send_bytes(self) -> Result<RequestResponse<Vec<u8>>> {
+        self.send_with(|response| async move { response.bytes().await.map(|bytes| bytes.to_vec()) })
+            .await
+    }
+
+    async fn send_with<B, F, Fut>(self, read_body: F) -> Result<RequestResponse<B>>
+    where
+        F: FnOnce(reqwest::Response) -> Fut,
+        Fut: std::future::Future<Output = reqwest::Result<B>>,
+    {
         let start_time = std::time::Instant::now();
         
         debug!("Sending {} request to: {}", format!("{:?}", self.method), self.url);
@@ -401,7 +418,7 @@ impl RequestBuilder {
         }
 
         // Read body
-        let body = response.text().await
+        let body = read_body(response).await
             .map_err(|e| anyhow!("Failed to read response body: {}", e))?;
 
         let response_time_ms = start_time.elapsed().as_millis() as u64;
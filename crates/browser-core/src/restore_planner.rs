@@ -0,0 +1,612 @@
+//! Restore Planner Module
+//!
+//! Turns a `BackupData` into an explicit plan against current state:
+//! - Per-category diff of added, changed and removed items
+//! - Merge or replace chosen per category
+//! - Conflict rules for items present on both sides (newest wins, keep
+//!   local, keep backup)
+//! - Atomic application: the staleness check, the storage swap and the proxy
+//!   settings change all happen while the proxy settings and every storage
+//!   lock are held, so a plan made against state that has since changed is
+//!   refused and readers never see a partial restore
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::backup::BackupData;
+use crate::proxy::{ProxyManager, ProxySettings};
+use crate::secrets_vault::keep_redacted;
+use crate::storage::{Bookmark, Cookie, HistoryEntry, StorageContents, StorageEngine, StorageSnapshot};
+
+/// A kind of data a backup can restore
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreCategory {
+    ProxySettings,
+    Cookies,
+    History,
+    Bookmarks,
+    LocalStorage,
+}
+
+/// How a category is restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Leave the category untouched
+    Skip,
+    /// Keep local-only items and add the backup's
+    #[default]
+    Merge,
+    /// Drop local-only items so the category matches the backup
+    Replace,
+}
+
+/// Which side wins when an item exists locally and in the backup with
+/// different contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictRule {
+    /// The more recently updated side wins; items without a local timestamp
+    /// take the backup's version
+    #[default]
+    NewestWins,
+    KeepLocal,
+    KeepBackup,
+}
+
+/// Mode and conflict rule for one category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CategoryPolicy {
+    pub mode: RestoreMode,
+    pub conflict: ConflictRule,
+}
+
+/// Restore policy: a default plus per-category overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreOptions {
+    pub default: CategoryPolicy,
+    #[serde(default)]
+    pub categories: BTreeMap<RestoreCategory, CategoryPolicy>,
+}
+
+impl RestoreOptions {
+    /// Override the policy for one category
+    pub fn with_category(mut self, category: RestoreCategory, mode: RestoreMode, conflict: ConflictRule) -> Self {
+        self.categories.insert(category, CategoryPolicy { mode, conflict });
+        self
+    }
+
+    /// Policy in effect for `category`
+    pub fn policy(&self, category: RestoreCategory) -> CategoryPolicy {
+        self.categories.get(&category).copied().unwrap_or(self.default)
+    }
+}
+
+/// Differences between the backup and current state, by item key
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryDiff {
+    /// Only in the backup
+    pub added: Vec<String>,
+    /// In both, with different contents
+    pub changed: Vec<String>,
+    /// Only present locally
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+/// What applying a category will do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryOutcome {
+    pub added: usize,
+    /// Changed items taken from the backup
+    pub updated: usize,
+    /// Changed items where the local version was kept
+    pub kept_local: usize,
+    pub removed: usize,
+}
+
+/// Planned restore of one category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryPlan {
+    pub policy: CategoryPolicy,
+    pub diff: CategoryDiff,
+    pub outcome: CategoryOutcome,
+}
+
+/// A restore ready to be reviewed and applied
+#[derive(Debug, Clone)]
+pub struct RestorePlan {
+    pub categories: BTreeMap<RestoreCategory, CategoryPlan>,
+    local_fingerprint: String,
+    contents: StorageContents,
+    proxy_settings: Option<ProxySettings>,
+}
+
+impl RestorePlan {
+    /// Whether applying the plan would change anything
+    pub fn is_noop(&self) -> bool {
+        self.categories
+            .values()
+            .all(|c| c.outcome.added == 0 && c.outcome.updated == 0 && c.outcome.removed == 0)
+    }
+
+    fn insert(&mut self, category: RestoreCategory, policy: CategoryPolicy, diff: CategoryDiff, outcome: CategoryOutcome) {
+        let outcome = if policy.mode == RestoreMode::Skip { CategoryOutcome::default() } else { outcome };
+        self.categories.insert(category, CategoryPlan { policy, diff, outcome });
+    }
+}
+
+/// Result of applying a plan
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub applied: BTreeMap<RestoreCategory, CategoryOutcome>,
+}
+
+/// Plans and applies restores of `BackupData` into live state
+pub struct RestorePlanner {
+    storage: Arc<StorageEngine>,
+    proxy_manager: Option<Arc<ProxyManager>>,
+}
+
+/// An item with its identity and last-update time
+struct Keyed<T> {
+    key: String,
+    item: T,
+    updated_at: Option<i64>,
+}
+
+/// Result of resolving one category
+struct Resolved<T> {
+    diff: CategoryDiff,
+    outcome: CategoryOutcome,
+    items: Vec<T>,
+}
+
+/// Current state of the categories a plan touches
+struct LocalState {
+    proxy_settings: Option<ProxySettings>,
+    cookies: Vec<Keyed<Cookie>>,
+    history: Vec<Keyed<HistoryEntry>>,
+    bookmarks: Vec<Keyed<Bookmark>>,
+    local_storage: Vec<Keyed<String>>,
+}
+
+impl RestorePlanner {
+    /// Create a planner restoring into `storage`
+    pub fn new(storage: Arc<StorageEngine>) -> Self {
+        Self {
+            storage,
+            proxy_manager: None,
+        }
+    }
+
+    /// Also restore proxy settings into `proxy_manager`
+    pub fn with_proxy_manager(mut self, proxy_manager: Arc<ProxyManager>) -> Self {
+        self.proxy_manager = Some(proxy_manager);
+        self
+    }
+
+    /// Diff every category the backup contains against current state
+    pub async fn diff(&self, data: &BackupData) -> Result<BTreeMap<RestoreCategory, CategoryDiff>> {
+        let plan = self.plan(data, &RestoreOptions::default()).await?;
+        Ok(plan.categories.into_iter().map(|(c, p)| (c, p.diff)).collect())
+    }
+
+    /// Build a plan for restoring `data` under `options`
+    pub async fn plan(&self, data: &BackupData, options: &RestoreOptions) -> Result<RestorePlan> {
+        let backup_time = chrono::DateTime::parse_from_rfc3339(&data.timestamp)
+            .map(|t| t.timestamp())
+            .ok();
+        let local = self.local_state().await?;
+        let mut plan = RestorePlan {
+            categories: BTreeMap::new(),
+            local_fingerprint: local.fingerprint()?,
+            contents: StorageContents::default(),
+            proxy_settings: None,
+        };
+
+        if let (Some(settings), Some(_)) = (&data.proxy_settings, &self.proxy_manager) {
+            let policy = options.policy(RestoreCategory::ProxySettings);
+            let mut incoming = settings.clone();
+            if let Some(local) = &local.proxy_settings {
+                keep_redacted(&mut incoming.password, local.password.as_ref());
+            }
+            let backup = vec![Keyed { key: "proxy".to_string(), item: incoming, updated_at: backup_time }];
+            let local_items: Vec<Keyed<ProxySettings>> = local
+                .proxy_settings
+                .clone()
+                .map(|s| Keyed { key: "proxy".to_string(), item: s, updated_at: None })
+                .into_iter()
+                .collect();
+            let resolved = resolve(local_items, backup, policy)?;
+            if policy.mode != RestoreMode::Skip {
+                plan.proxy_settings = resolved.items.into_iter().next();
+            }
+            plan.insert(RestoreCategory::ProxySettings, policy, resolved.diff, resolved.outcome);
+        }
+
+        if let Some(cookies) = &data.cookies {
+            let policy = options.policy(RestoreCategory::Cookies);
+            let backup = cookies
+                .iter()
+                .map(|c| Keyed { key: cookie_key(c), item: c.clone(), updated_at: backup_time })
+                .collect();
+            let resolved = resolve(local.cookies, backup, policy)?;
+            if policy.mode != RestoreMode::Skip {
+                plan.contents.cookies = Some(resolved.items);
+            }
+            plan.insert(RestoreCategory::Cookies, policy, resolved.diff, resolved.outcome);
+        }
+
+        if let Some(history) = &data.history {
+            let policy = options.policy(RestoreCategory::History);
+            let backup = history
+                .iter()
+                .map(|h| Keyed { key: h.url.clone(), item: h.clone(), updated_at: Some(h.last_visit) })
+                .collect();
+            let local_ids: HashMap<String, i64> = local.history.iter().map(|h| (h.key.clone(), h.item.id)).collect();
+            let resolved = resolve(local.history, backup, policy)?;
+            if policy.mode != RestoreMode::Skip {
+                plan.contents.history = Some(renumber(resolved.items, &local_ids, |h| h.url.clone(), |h, id| h.id = id, |h| h.id));
+            }
+            plan.insert(RestoreCategory::History, policy, resolved.diff, resolved.outcome);
+        }
+
+        if let Some(bookmarks) = &data.bookmarks {
+            let policy = options.policy(RestoreCategory::Bookmarks);
+            let backup = bookmarks
+                .iter()
+                .map(|b| Keyed { key: b.url.clone(), item: b.clone(), updated_at: Some(b.created_at) })
+                .collect();
+            let local_ids: HashMap<String, i64> = local.bookmarks.iter().map(|b| (b.key.clone(), b.item.id)).collect();
+            let resolved = resolve(local.bookmarks, backup, policy)?;
+            if policy.mode != RestoreMode::Skip {
+                plan.contents.bookmarks = Some(renumber(resolved.items, &local_ids, |b| b.url.clone(), |b, id| b.id = id, |b| b.id));
+            }
+            plan.insert(RestoreCategory::Bookmarks, policy, resolved.diff, resolved.outcome);
+        }
+
+        if let Some(entries) = &data.local_storage {
+            let policy = options.policy(RestoreCategory::LocalStorage);
+            let backup = entries
+                .iter()
+                .map(|e| Keyed { key: local_storage_key(&e.origin, &e.key), item: e.value.clone(), updated_at: backup_time })
+                .collect();
+            // Keys are carried alongside values so origins can be rebuilt
+            let resolved = resolve_keyed(local.local_storage, backup, policy)?;
+            if policy.mode != RestoreMode::Skip {
+                let mut origins: HashMap<String, HashMap<String, String>> = HashMap::new();
+                for (key, value) in resolved.items {
+                    let (origin, item_key) = split_local_storage_key(&key)?;
+                    origins.entry(origin).or_default().insert(item_key, value);
+                }
+                plan.contents.local_storage = Some(origins);
+            }
+            plan.insert(RestoreCategory::LocalStorage, policy, resolved.diff, resolved.outcome);
+        }
+
+        Ok(plan)
+    }
+
+    /// Apply a plan. Fails without changing anything if local state changed
+    /// after the plan was made.
+    pub async fn apply(&self, plan: &RestorePlan) -> Result<RestoreReport> {
+        // Settings lock first, then the storage locks inside replace_contents_if
+        let mut settings = match &self.proxy_manager {
+            Some(manager) => Some(manager.lock_settings().await),
+            None => None,
+        };
+
+        self.storage
+            .replace_contents_if(plan.contents.clone(), |snapshot| {
+                let current = LocalState::from_snapshot(snapshot.clone(), settings.as_deref().cloned());
+                if current.fingerprint()? != plan.local_fingerprint {
+                    return Err(anyhow!("Local data changed since the restore was planned; plan it again"));
+                }
+                if let (Some(new_settings), Some(guard)) = (&plan.proxy_settings, settings.as_mut()) {
+                    **guard = new_settings.clone();
+                }
+                Ok(())
+            })
+            .await?;
+        drop(settings);
+
+        let report = RestoreReport {
+            applied: plan
+                .categories
+                .iter()
+                .filter(|(_, c)| c.policy.mode != RestoreMode::Skip)
+                .map(|(category, c)| (*category, c.outcome))
+                .collect(),
+        };
+        info!("Applied restore plan: {:?}", report.applied);
+        Ok(report)
+    }
+
+    async fn local_state(&self) -> Result<LocalState> {
+        let snapshot = self.storage.snapshot().await?;
+        let proxy_settings = match &self.proxy_manager {
+            Some(manager) => Some(manager.get_settings().await),
+            None => None,
+        };
+        Ok(LocalState::from_snapshot(snapshot, proxy_settings))
+    }
+}
+
+impl LocalState {
+    fn from_snapshot(snapshot: StorageSnapshot, proxy_settings: Option<ProxySettings>) -> Self {
+        let StorageSnapshot {
+            cookies,
+            history,
+            bookmarks,
+            local_storage: origins,
+            cookie_set_at,
+            local_storage_accessed_at,
+        } = snapshot;
+
+        let mut local_storage = Vec::new();
+        for (origin, items) in origins {
+            for (key, value) in items {
+                local_storage.push(Keyed {
+                    key: local_storage_key(&origin, &key),
+                    item: value,
+                    updated_at: local_storage_accessed_at.get(&origin).copied(),
+                });
+            }
+        }
+
+        LocalState {
+            proxy_settings,
+            cookies: cookies
+                .into_iter()
+                .map(|c| {
+                    let key = cookie_key(&c);
+                    let updated_at = cookie_set_at.get(&key).copied();
+                    Keyed { key, item: c, updated_at }
+                })
+                .collect(),
+            history: history
+                .into_iter()
+                .map(|h| Keyed { key: h.url.clone(), updated_at: Some(h.last_visit), item: h })
+                .collect(),
+            bookmarks: bookmarks
+                .into_iter()
+                .map(|b| Keyed { key: b.url.clone(), updated_at: Some(b.created_at), item: b })
+                .collect(),
+            local_storage,
+        }
+    }
+
+    /// Hash of everything a plan depends on
+    fn fingerprint(&self) -> Result<String> {
+        fn section<T: Serialize>(items: &[Keyed<T>]) -> Result<BTreeMap<&str, serde_json::Value>> {
+            items.iter().map(|k| Ok((k.key.as_str(), serde_json::to_value(&k.item)?))).collect()
+        }
+        let state = serde_json::json!({
+            "proxy_settings": self.proxy_settings,
+            "cookies": section(&self.cookies)?,
+            "history": section(&self.history)?,
+            "bookmarks": section(&self.bookmarks)?,
+            "local_storage": section(&self.local_storage)?,
+        });
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(&state)?)))
+    }
+}
+
+/// Diff and resolve one category, returning the resulting items
+fn resolve<T: Serialize>(local: Vec<Keyed<T>>, backup: Vec<Keyed<T>>, policy: CategoryPolicy) -> Result<Resolved<T>> {
+    let resolved = resolve_keyed(local, backup, policy)?;
+    Ok(Resolved {
+        diff: resolved.diff,
+        outcome: resolved.outcome,
+        items: resolved.items.into_iter().map(|(_, item)| item).collect(),
+    })
+}
+
+fn resolve_keyed<T: Serialize>(
+    local: Vec<Keyed<T>>,
+    backup: Vec<Keyed<T>>,
+    policy: CategoryPolicy,
+) -> Result<Resolved<(String, T)>> {
+    let mut diff = CategoryDiff::default();
+    let mut outcome = CategoryOutcome::default();
+    let mut local: BTreeMap<String, Keyed<T>> = local.into_iter().map(|k| (k.key.clone(), k)).collect();
+    let mut items = Vec::new();
+
+    let backup: BTreeMap<String, Keyed<T>> = backup.into_iter().map(|k| (k.key.clone(), k)).collect();
+    for (key, incoming) in backup {
+        match local.remove(&key) {
+            None => {
+                diff.added.push(key.clone());
+                outcome.added += 1;
+                items.push((key, incoming.item));
+            }
+            Some(existing) if serde_json::to_value(&existing.item)? == serde_json::to_value(&incoming.item)? => {
+                diff.unchanged += 1;
+                items.push((key, existing.item));
+            }
+            Some(existing) => {
+                diff.changed.push(key.clone());
+                let take_backup = match policy.conflict {
+                    ConflictRule::KeepBackup => true,
+                    ConflictRule::KeepLocal => false,
+                    ConflictRule::NewestWins => match (existing.updated_at, incoming.updated_at) {
+                        (Some(local_time), Some(backup_time)) => backup_time >= local_time,
+                        _ => true,
+                    },
+                };
+                if take_backup {
+                    outcome.updated += 1;
+                    items.push((key, incoming.item));
+                } else {
+                    outcome.kept_local += 1;
+                    items.push((key, existing.item));
+                }
+            }
+        }
+    }
+
+    for (key, existing) in local {
+        diff.removed.push(key.clone());
+        if policy.mode == RestoreMode::Replace {
+            outcome.removed += 1;
+        } else {
+            items.push((key, existing.item));
+        }
+    }
+
+    Ok(Resolved { diff, outcome, items })
+}
+
+/// Give items the local ID of the item they replace, and fresh IDs to items
+/// new to this store
+fn renumber<T>(
+    mut items: Vec<T>,
+    local_ids: &HashMap<String, i64>,
+    key: impl Fn(&T) -> String,
+    set_id: impl Fn(&mut T, i64),
+    get_id: impl Fn(&T) -> i64,
+) -> Vec<T> {
+    let mut next_id = local_ids.values().max().copied().unwrap_or(0) + 1;
+    for item in items.iter_mut() {
+        match local_ids.get(&key(item)) {
+            Some(id) if *id == get_id(item) => {}
+            Some(id) => set_id(item, *id),
+            None => {
+                set_id(item, next_id);
+                next_id += 1;
+            }
+        }
+    }
+    items
+}
+
+fn cookie_key(cookie: &Cookie) -> String {
+    format!("{}|{}|{}", cookie.domain, cookie.name, cookie.path)
+}
+
+fn local_storage_key(origin: &str, key: &str) -> String {
+    serde_json::to_string(&(origin, key)).unwrap_or_default()
+}
+
+fn split_local_storage_key(key: &str) -> Result<(String, String)> {
+    serde_json::from_str(key).map_err(|e| anyhow!("Invalid local storage key {}: {}", key, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::LocalStorageEntry;
+
+    fn backup_data(storage_bookmarks: Vec<Bookmark>, timestamp: &str) -> BackupData {
+        BackupData {
+            version: "1.0.0".to_string(),
+            timestamp: timestamp.to_string(),
+            proxy_settings: None,
+            browser_config: None,
+            cookies: None,
+            history: None,
+            bookmarks: Some(storage_bookmarks),
+            local_storage: Some(vec![LocalStorageEntry {
+                origin: "https://example.com".to_string(),
+                key: "theme".to_string(),
+                value: "dark".to_string(),
+            }]),
+        }
+    }
+
+    fn bookmark(url: &str, title: &str, created_at: i64) -> Bookmark {
+        Bookmark { id: 99, url: url.to_string(), title: title.to_string(), folder: None, created_at }
+    }
+
+    #[tokio::test]
+    async fn test_plan_diff_and_conflict_rules() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let storage = Arc::new(StorageEngine::new(dir.path()).expect("storage should succeed"));
+        storage.add_bookmark("https://a.example", "Local A", None).await.expect("bookmark should succeed");
+        storage.add_bookmark("https://local-only.example", "Local only", None).await.expect("bookmark should succeed");
+        storage.set_local_storage("https://example.com", "theme", "light").await.expect("set should succeed");
+
+        let planner = RestorePlanner::new(storage.clone());
+        // An old backup: local bookmark A is newer, local storage was touched after it
+        let data = backup_data(
+            vec![bookmark("https://a.example", "Backup A", 1), bookmark("https://b.example", "Backup B", 1)],
+            "2020-01-01T00:00:00Z",
+        );
+
+        let diff = planner.diff(&data).await.expect("diff should succeed");
+        let bookmarks = &diff[&RestoreCategory::Bookmarks];
+        assert_eq!(bookmarks.added, vec!["https://b.example".to_string()]);
+        assert_eq!(bookmarks.changed, vec!["https://a.example".to_string()]);
+        assert_eq!(bookmarks.removed, vec!["https://local-only.example".to_string()]);
+
+        let merged = planner.plan(&data, &RestoreOptions::default()).await.expect("plan should succeed");
+        let outcome = merged.categories[&RestoreCategory::Bookmarks].outcome;
+        assert_eq!(outcome, CategoryOutcome { added: 1, updated: 0, kept_local: 1, removed: 0 });
+
+        let options = RestoreOptions::default()
+            .with_category(RestoreCategory::Bookmarks, RestoreMode::Replace, ConflictRule::KeepBackup)
+            .with_category(RestoreCategory::LocalStorage, RestoreMode::Skip, ConflictRule::KeepBackup);
+        let replaced = planner.plan(&data, &options).await.expect("plan should succeed");
+        let report = planner.apply(&replaced).await.expect("apply should succeed");
+        assert_eq!(report.applied[&RestoreCategory::Bookmarks], CategoryOutcome { added: 1, updated: 1, kept_local: 0, removed: 1 });
+
+        let titles: Vec<String> = storage.get_bookmarks().await.expect("bookmarks should load").into_iter().map(|b| b.title).collect();
+        assert_eq!(titles.len(), 2);
+        assert!(titles.contains(&"Backup A".to_string()));
+        assert_eq!(
+            storage.get_local_storage("https://example.com", "theme").await.expect("get should succeed"),
+            Some("light".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stale_plan_is_refused() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let storage = Arc::new(StorageEngine::new(dir.path()).expect("storage should succeed"));
+        let planner = RestorePlanner::new(storage.clone());
+
+        let data = backup_data(vec![bookmark("https://a.example", "A", 1)], "2030-01-01T00:00:00Z");
+        let plan = planner.plan(&data, &RestoreOptions::default()).await.expect("plan should succeed");
+        assert!(!plan.is_noop());
+
+        storage.add_bookmark("https://new.example", "New", None).await.expect("bookmark should succeed");
+        assert!(planner.apply(&plan).await.is_err());
+        assert_eq!(storage.get_bookmarks().await.expect("bookmarks should load").len(), 1);
+        assert!(storage.get_local_storage("https://example.com", "theme").await.expect("get should succeed").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_settings_restored_with_storage() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let storage = Arc::new(StorageEngine::new(dir.path()).expect("storage should succeed"));
+        let manager = Arc::new(ProxyManager::new());
+        let planner = RestorePlanner::new(storage.clone()).with_proxy_manager(manager.clone());
+
+        let backup_settings = ProxySettings {
+            host: Some("10.0.0.1".to_string()),
+            port: Some(8080),
+            ..Default::default()
+        };
+        let mut data = backup_data(vec![bookmark("https://a.example", "A", 1)], "2030-01-01T00:00:00Z");
+        data.proxy_settings = Some(backup_settings.clone());
+
+        // A settings change after planning makes the plan stale
+        let plan = planner.plan(&data, &RestoreOptions::default()).await.expect("plan should succeed");
+        let changed = ProxySettings { host: Some("10.0.0.2".to_string()), ..Default::default() };
+        manager.set_settings(changed.clone()).await;
+        assert!(planner.apply(&plan).await.is_err());
+        assert_eq!(manager.get_settings().await.host, changed.host);
+        assert!(storage.get_bookmarks().await.expect("bookmarks should load").is_empty());
+
+        let plan = planner.plan(&data, &RestoreOptions::default()).await.expect("plan should succeed");
+        planner.apply(&plan).await.expect("apply should succeed");
+        assert_eq!(manager.get_settings().await.host, backup_settings.host);
+        assert_eq!(storage.get_bookmarks().await.expect("bookmarks should load").len(), 1);
+    }
+}
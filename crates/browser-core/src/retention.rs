@@ -0,0 +1,212 @@
+//! Data Retention Module
+//!
+//! Provides automatic expiry of stored browsing data including:
+//! - Per-type retention limits driven by `PrivacyConfig::retention`
+//! - A background sweeper that applies the policy periodically
+//! - "Forget this site" across storage, HTTP cache and proxy sessions
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use crate::config_manager::RetentionConfig;
+use crate::performance_optimizer::PerformanceOptimizer;
+use crate::proxy_rotation::ProxyRotationManager;
+use crate::storage::StorageEngine;
+
+/// Result of a retention sweep
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub swept_at: i64,
+    pub history_removed: usize,
+    pub cookies_removed: usize,
+    pub local_storage_origins_removed: usize,
+}
+
+impl RetentionReport {
+    /// Total number of removed items
+    pub fn total_removed(&self) -> usize {
+        self.history_removed + self.cookies_removed + self.local_storage_origins_removed
+    }
+}
+
+/// Options for forgetting a site
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForgetSiteOptions {
+    /// Also remove bookmarks pointing at the site
+    pub include_bookmarks: bool,
+}
+
+/// Result of forgetting a site
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForgetSiteReport {
+    pub domain: String,
+    pub cookies_removed: usize,
+    pub history_removed: usize,
+    pub bookmarks_removed: usize,
+    pub local_storage_origins_removed: usize,
+    pub cache_entries_removed: usize,
+    pub proxy_assignments_removed: usize,
+}
+
+/// Applies retention policies and site removal across data stores
+pub struct DataRetentionManager {
+    storage: Arc<StorageEngine>,
+    policy: Arc<RwLock<RetentionConfig>>,
+    http_cache: Option<Arc<PerformanceOptimizer>>,
+    proxy_rotation: Option<Arc<RwLock<ProxyRotationManager>>>,
+    last_report: Arc<RwLock<Option<RetentionReport>>>,
+}
+
+impl DataRetentionManager {
+    /// Create a retention manager for a storage engine
+    pub fn new(storage: Arc<StorageEngine>, policy: RetentionConfig) -> Self {
+        Self {
+            storage,
+            policy: Arc::new(RwLock::new(policy)),
+            http_cache: None,
+            proxy_rotation: None,
+            last_report: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Include the HTTP cache when forgetting sites
+    pub fn with_http_cache(mut self, cache: Arc<PerformanceOptimizer>) -> Self {
+        self.http_cache = Some(cache);
+        self
+    }
+
+    /// Include sticky proxy sessions when forgetting sites
+    pub fn with_proxy_rotation(mut self, rotation: Arc<RwLock<ProxyRotationManager>>) -> Self {
+        self.proxy_rotation = Some(rotation);
+        self
+    }
+
+    /// Replace the retention policy. Takes effect on the next sweep.
+    pub async fn set_policy(&self, policy: RetentionConfig) {
+        *self.policy.write().await = policy;
+    }
+
+    /// Get the current retention policy
+    pub async fn get_policy(&self) -> RetentionConfig {
+        self.policy.read().await.clone()
+    }
+
+    /// Result of the most recent sweep, if any
+    pub async fn last_report(&self) -> Option<RetentionReport> {
+        self.last_report.read().await.clone()
+    }
+
+    /// Apply the retention policy immediately
+    pub async fn sweep_now(&self) -> Result<RetentionReport> {
+        let policy = self.policy.read().await.clone();
+        let report = self
+            .storage
+            .apply_retention(&policy, chrono::Utc::now().timestamp())
+            .await?;
+        *self.last_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Start the background sweeper. The interval is re-read from the policy
+    /// after every sweep.
+    pub fn start_sweeper(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let interval = self.policy.read().await.sweep_interval_secs.max(1);
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+
+                if let Err(e) = self.sweep_now().await {
+                    error!("Retention sweep failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Remove everything stored for a domain and its subdomains
+    pub async fn forget_site(&self, domain: &str, options: &ForgetSiteOptions) -> Result<ForgetSiteReport> {
+        let mut report = self.storage.forget_domain(domain, options.include_bookmarks).await?;
+
+        if let Some(cache) = &self.http_cache {
+            report.cache_entries_removed = cache.evict_domain(&report.domain).await;
+        }
+        if let Some(rotation) = &self.proxy_rotation {
+            report.proxy_assignments_removed = rotation.read().await.forget_domain(&report.domain).await;
+        }
+
+        info!(
+            "Forgot site {} ({} cache entries, {} proxy assignments)",
+            report.domain, report.cache_entries_removed, report.proxy_assignments_removed
+        );
+        Ok(report)
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains (case-insensitive)
+pub fn host_matches_domain(host: &str, domain: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let domain = domain.trim_start_matches('.').trim_end_matches('.').to_ascii_lowercase();
+    if domain.is_empty() {
+        return false;
+    }
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Extract the host from a URL or origin string
+pub fn host_of(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance_optimizer::{CacheEntry, CachePriority};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_host_matches_domain() {
+        assert!(host_matches_domain("example.com", "example.com"));
+        assert!(host_matches_domain("www.Example.com", "example.com"));
+        assert!(host_matches_domain("a.b.example.com", ".example.com"));
+        assert!(!host_matches_domain("notexample.com", "example.com"));
+        assert!(!host_matches_domain("example.com", ""));
+    }
+
+    #[tokio::test]
+    async fn test_forget_site_clears_cache() {
+        let temp_dir = TempDir::new().expect("Operation should succeed in test");
+        let storage = Arc::new(StorageEngine::new(temp_dir.path()).expect("Failed to create storage engine"));
+        let cache = Arc::new(PerformanceOptimizer::new());
+
+        for url in ["https://cdn.example.com/app.js", "https://other.org/app.js"] {
+            cache
+                .cache_resource(CacheEntry {
+                    url: url.to_string(),
+                    content_type: "application/javascript".to_string(),
+                    size_bytes: 1024,
+                    last_accessed: 0,
+                    access_count: 0,
+                    priority: CachePriority::High,
+                    expires: None,
+                    compressed: false,
+                })
+                .await;
+        }
+        storage.add_history("https://www.example.com/", Some("Example")).await.expect("Add history should succeed");
+
+        let manager = DataRetentionManager::new(storage.clone(), RetentionConfig::default()).with_http_cache(cache.clone());
+        let report = manager
+            .forget_site("example.com", &ForgetSiteOptions::default())
+            .await
+            .expect("Forget operation should succeed");
+
+        assert_eq!(report.history_removed, 1);
+        assert_eq!(report.cache_entries_removed, 1);
+        assert!(cache.get_cached("https://other.org/app.js").await.is_some());
+    }
+}
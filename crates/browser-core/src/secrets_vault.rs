@@ -0,0 +1,485 @@
+//! Secrets Vault Module
+//!
+//! Keeps proxy and account credentials out of configs, exports and backups:
+//! - Pluggable backends: the OS keyring, or a master-password-encrypted file
+//!   for headless machines without a keyring
+//! - Secret references (`secret://<name>`) stored in place of values and
+//!   resolved at use time
+//! - Redaction of plaintext secrets in exports and backups
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use crate::browser_profile::{BrowserProfile, ProfileProxyConfig};
+use crate::chromium_engine::ChromiumEngineConfig;
+use crate::proxy::ProxySettings;
+use crate::secure_container::{self, KdfParams};
+
+/// Prefix marking a value as a reference into the vault
+pub const SECRET_REF_SCHEME: &str = "secret://";
+
+/// Placeholder written where a plaintext secret was removed
+pub const REDACTED_SECRET: &str = "<redacted>";
+
+/// Format version of the encrypted vault file
+const VAULT_FILE_VERSION: u32 = 1;
+
+/// Reference to a secret stored in the vault
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecretRef(String);
+
+impl SecretRef {
+    /// Reference to the secret called `name`
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// Parse a `secret://<name>` value
+    pub fn parse(value: &str) -> Option<Self> {
+        value
+            .strip_prefix(SECRET_REF_SCHEME)
+            .filter(|name| !name.is_empty())
+            .map(Self::new)
+    }
+
+    /// Name of the referenced secret
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", SECRET_REF_SCHEME, self.0)
+    }
+}
+
+/// Whether a stored value is a vault reference rather than a secret
+pub fn is_secret_ref(value: &str) -> bool {
+    SecretRef::parse(value).is_some()
+}
+
+/// Storage for secret values
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    /// Short backend name for logs
+    fn name(&self) -> &'static str;
+
+    async fn get(&self, name: &str) -> Result<Option<String>>;
+
+    async fn set(&self, name: &str, value: &str) -> Result<()>;
+
+    /// Remove a secret, returning whether it existed
+    async fn delete(&self, name: &str) -> Result<bool>;
+
+    /// Names of all stored secrets
+    async fn list(&self) -> Result<Vec<String>>;
+}
+
+/// OS keyring backend
+pub struct KeyringBackend {
+    service: String,
+}
+
+impl KeyringBackend {
+    /// Store secrets under a keyring service name
+    pub fn new(service: &str) -> Self {
+        Self { service: service.to_string() }
+    }
+
+    /// Whether a keyring can be reached on this machine
+    pub fn is_available(service: &str) -> bool {
+        match Entry::new(service, "__vault_probe__").and_then(|e| e.get_password()) {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(e) => {
+                debug!("Keyring unavailable: {}", e);
+                false
+            }
+        }
+    }
+
+    fn entry(&self, name: &str) -> Result<Entry> {
+        Entry::new(&self.service, name).map_err(|e| anyhow!("Failed to create keyring entry: {}", e))
+    }
+}
+
+#[async_trait]
+impl SecretBackend for KeyringBackend {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        match self.entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(anyhow!("Failed to read secret from keyring: {}", e)),
+        }
+    }
+
+    async fn set(&self, name: &str, value: &str) -> Result<()> {
+        self.entry(name)?
+            .set_password(value)
+            .map_err(|e| anyhow!("Failed to store secret in keyring: {}", e))
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(anyhow!("Failed to delete secret from keyring: {}", e)),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        bail!("The keyring backend cannot enumerate secrets")
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    secrets: BTreeMap<String, String>,
+}
+
+/// Secrets in a single file encrypted with a master password
+pub struct EncryptedFileBackend {
+    path: PathBuf,
+    master_password: String,
+    kdf: KdfParams,
+    /// Decrypted contents, loaded on first use
+    secrets: Mutex<Option<BTreeMap<String, String>>>,
+}
+
+impl EncryptedFileBackend {
+    /// Use `path`, created on the first write if missing
+    pub fn new(path: impl AsRef<Path>, master_password: &str) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            master_password: master_password.to_string(),
+            kdf: KdfParams::default(),
+            secrets: Mutex::new(None),
+        }
+    }
+
+    /// Key derivation cost for newly written files
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Decrypt the file now, failing early on a wrong master password
+    pub async fn unlock(&self) -> Result<()> {
+        let mut secrets = self.secrets.lock().await;
+        self.load(&mut secrets).await.map(|_| ())
+    }
+
+    async fn load<'a>(
+        &self,
+        secrets: &'a mut Option<BTreeMap<String, String>>,
+    ) -> Result<&'a mut BTreeMap<String, String>> {
+        if secrets.is_none() {
+            let loaded = if tokio::fs::try_exists(&self.path).await? {
+                let sealed = tokio::fs::read(&self.path).await?;
+                let plain = secure_container::decrypt_bytes(&sealed, &self.master_password)
+                    .await
+                    .context("Failed to unlock secrets vault - wrong master password?")?;
+                let file: VaultFile = serde_json::from_slice(&plain).context("Corrupt secrets vault")?;
+                if file.version > VAULT_FILE_VERSION {
+                    bail!("Secrets vault version {} is not supported", file.version);
+                }
+                file.secrets
+            } else {
+                BTreeMap::new()
+            };
+            *secrets = Some(loaded);
+        }
+        Ok(secrets.as_mut().expect("secrets were just loaded"))
+    }
+
+    async fn save(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        let file = VaultFile { version: VAULT_FILE_VERSION, secrets: secrets.clone() };
+        let sealed = secure_container::encrypt_bytes(&serde_json::to_vec(&file)?, &self.master_password, self.kdf).await?;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, sealed).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SecretBackend for EncryptedFileBackend {
+    fn name(&self) -> &'static str {
+        "encrypted-file"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        let mut secrets = self.secrets.lock().await;
+        Ok(self.load(&mut secrets).await?.get(name).cloned())
+    }
+
+    async fn set(&self, name: &str, value: &str) -> Result<()> {
+        let mut guard = self.secrets.lock().await;
+        let secrets = self.load(&mut guard).await?;
+        secrets.insert(name.to_string(), value.to_string());
+        self.save(secrets).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool> {
+        let mut guard = self.secrets.lock().await;
+        let secrets = self.load(&mut guard).await?;
+        if secrets.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save(secrets).await?;
+        Ok(true)
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let mut secrets = self.secrets.lock().await;
+        Ok(self.load(&mut secrets).await?.keys().cloned().collect())
+    }
+}
+
+/// Stores secrets and resolves references to them
+pub struct SecretsVault {
+    backend: Arc<dyn SecretBackend>,
+}
+
+impl SecretsVault {
+    /// Vault over any backend
+    pub fn new(backend: Arc<dyn SecretBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Vault in the OS keyring
+    pub fn keyring(service: &str) -> Self {
+        Self::new(Arc::new(KeyringBackend::new(service)))
+    }
+
+    /// Vault in a master-password-encrypted file
+    pub fn encrypted_file(path: impl AsRef<Path>, master_password: &str) -> Self {
+        Self::new(Arc::new(EncryptedFileBackend::new(path, master_password)))
+    }
+
+    /// Pick a backend for this machine: the encrypted file when a master
+    /// password is given (headless setups), otherwise the keyring
+    pub fn detect(service: &str, file: impl AsRef<Path>, master_password: Option<&str>) -> Result<Self> {
+        let vault = match master_password {
+            Some(password) => Self::encrypted_file(file, password),
+            None if KeyringBackend::is_available(service) => Self::keyring(service),
+            None => bail!("No keyring available; a master password is required for the encrypted vault"),
+        };
+        info!("Using {} secrets backend", vault.backend.name());
+        Ok(vault)
+    }
+
+    /// Name of the active backend
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// Store a secret and return a reference to it
+    pub async fn put(&self, name: &str, value: &str) -> Result<SecretRef> {
+        if name.is_empty() {
+            bail!("Secret name cannot be empty");
+        }
+        self.backend.set(name, value).await?;
+        debug!("Stored secret {}", name);
+        Ok(SecretRef::new(name))
+    }
+
+    /// Value of a named secret
+    pub async fn get(&self, name: &str) -> Result<String> {
+        self.backend
+            .get(name)
+            .await?
+            .ok_or_else(|| anyhow!("Secret not found: {}", name))
+    }
+
+    /// Remove a secret, returning whether it existed
+    pub async fn delete(&self, name: &str) -> Result<bool> {
+        self.backend.delete(name).await
+    }
+
+    /// Names of all stored secrets
+    pub async fn list(&self) -> Result<Vec<String>> {
+        self.backend.list().await
+    }
+
+    /// Resolve a stored value: references are looked up, anything else is
+    /// returned unchanged
+    pub async fn resolve(&self, value: &str) -> Result<String> {
+        match SecretRef::parse(value) {
+            Some(reference) => self.get(reference.name()).await,
+            None => Ok(value.to_string()),
+        }
+    }
+
+    /// Resolve an optional stored value in place
+    pub async fn resolve_in_place(&self, value: &mut Option<String>) -> Result<()> {
+        if let Some(stored) = value.as_mut() {
+            *stored = self.resolve(stored).await?;
+        }
+        Ok(())
+    }
+
+    /// Move a plaintext value into the vault under `name`, leaving a
+    /// reference behind. References and redacted values are left alone.
+    pub async fn seal(&self, name: &str, value: &mut Option<String>) -> Result<()> {
+        if let Some(plain) = value.as_ref().filter(|v| !is_secret_ref(v) && v.as_str() != REDACTED_SECRET) {
+            *value = Some(self.put(name, plain).await?.to_string());
+        }
+        Ok(())
+    }
+
+    /// Settings with the password resolved, ready to connect with
+    pub async fn resolve_proxy_settings(&self, settings: &ProxySettings) -> Result<ProxySettings> {
+        let mut resolved = settings.clone();
+        self.resolve_in_place(&mut resolved.password).await?;
+        Ok(resolved)
+    }
+
+    /// Store the proxy password in the vault under `name`
+    pub async fn seal_proxy_settings(&self, settings: &mut ProxySettings, name: &str) -> Result<()> {
+        self.seal(name, &mut settings.password).await
+    }
+
+    /// Store a profile's proxy password in the vault
+    pub async fn seal_profile(&self, profile: &mut BrowserProfile) -> Result<()> {
+        let name = format!("profile/{}/proxy", profile.id);
+        if let Some(proxy) = profile.settings.proxy_config.as_mut() {
+            self.seal(&name, &mut proxy.password).await?;
+        }
+        Ok(())
+    }
+
+    /// Profile with its proxy password resolved
+    pub async fn resolve_profile(&self, profile: &mut BrowserProfile) -> Result<()> {
+        if let Some(proxy) = profile.settings.proxy_config.as_mut() {
+            self.resolve_in_place(&mut proxy.password).await?;
+        }
+        Ok(())
+    }
+
+    /// Engine config with proxy credentials resolved
+    pub async fn resolve_chromium_config(&self, mut config: ChromiumEngineConfig) -> Result<ChromiumEngineConfig> {
+        if let Some(proxy) = config.proxy.as_mut() {
+            self.resolve_in_place(&mut proxy.password).await?;
+        }
+        if let Some(auth) = config.proxy_auth.as_mut() {
+            auth.password = self.resolve(&auth.password).await?;
+        }
+        Ok(config)
+    }
+}
+
+/// Removal of plaintext secrets before data leaves the machine
+pub trait RedactSecrets {
+    /// Replace plaintext secrets with `REDACTED_SECRET`, keeping vault
+    /// references. Returns whether anything was redacted.
+    fn redact_secrets(&mut self) -> bool;
+}
+
+fn redact(value: &mut Option<String>) -> bool {
+    match value {
+        Some(v) if !is_secret_ref(v) && v != REDACTED_SECRET => {
+            *v = REDACTED_SECRET.to_string();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// On restore, keep the local secret where the incoming value was redacted
+pub fn keep_redacted(incoming: &mut Option<String>, local: Option<&String>) {
+    if incoming.as_deref() == Some(REDACTED_SECRET) {
+        *incoming = local.cloned();
+    }
+}
+
+impl RedactSecrets for ProxySettings {
+    fn redact_secrets(&mut self) -> bool {
+        redact(&mut self.password)
+    }
+}
+
+impl RedactSecrets for ProfileProxyConfig {
+    fn redact_secrets(&mut self) -> bool {
+        redact(&mut self.password)
+    }
+}
+
+impl RedactSecrets for BrowserProfile {
+    fn redact_secrets(&mut self) -> bool {
+        self.settings.proxy_config.as_mut().is_some_and(|p| p.redact_secrets())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_file_vault(path: &Path, password: &str) -> SecretsVault {
+        SecretsVault::new(Arc::new(
+            EncryptedFileBackend::new(path, password).with_kdf_params(KdfParams { m_cost: 1024, t_cost: 1, p_cost: 1 }),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_file_vault_roundtrip() {
+        let dir = tempfile::tempdir().expect("tempdir should succeed");
+        let path = dir.path().join("vault.bin");
+        let vault = fast_file_vault(&path, "master");
+
+        let mut settings = ProxySettings {
+            host: Some("10.0.0.1".into()),
+            port: Some(8080),
+            username: Some("alice".into()),
+            password: Some("hunter2".into()),
+            ..Default::default()
+        };
+        vault.seal_proxy_settings(&mut settings, "proxy/default").await.expect("seal should succeed");
+        assert_eq!(settings.password.as_deref(), Some("secret://proxy/default"));
+        assert!(!std::fs::read(&path).expect("vault file should exist").windows(7).any(|w| w == b"hunter2"));
+
+        // A fresh handle on the same file resolves the reference
+        let reopened = fast_file_vault(&path, "master");
+        let resolved = reopened.resolve_proxy_settings(&settings).await.expect("resolve should succeed");
+        assert_eq!(resolved.password.as_deref(), Some("hunter2"));
+        assert_eq!(reopened.list().await.expect("list should succeed"), vec!["proxy/default"]);
+        assert_eq!(reopened.resolve("plain").await.expect("resolve should succeed"), "plain");
+        assert!(reopened.resolve("secret://missing").await.is_err());
+
+        let wrong = fast_file_vault(&path, "not-master");
+        assert!(wrong.get("proxy/default").await.is_err());
+
+        assert!(reopened.delete("proxy/default").await.expect("delete should succeed"));
+        assert!(fast_file_vault(&path, "master").list().await.expect("list should succeed").is_empty());
+    }
+
+    #[test]
+    fn test_redaction_keeps_references() {
+        let mut plain = ProxySettings { password: Some("hunter2".into()), ..Default::default() };
+        let mut referenced = ProxySettings { password: Some("secret://proxy/default".into()), ..Default::default() };
+        assert!(plain.redact_secrets());
+        assert!(!referenced.redact_secrets());
+        assert_eq!(plain.password.as_deref(), Some(REDACTED_SECRET));
+        assert_eq!(referenced.password.as_deref(), Some("secret://proxy/default"));
+
+        let local = "hunter2".to_string();
+        keep_redacted(&mut plain.password, Some(&local));
+        assert_eq!(plain.password.as_deref(), Some("hunter2"));
+    }
+}
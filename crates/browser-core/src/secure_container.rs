@@ -0,0 +1,438 @@
+//! Secure Container Module
+//!
+//! Provides the passphrase-encrypted file format used by backups and bundles including:
+//! - A versioned header (magic, format version, KDF id, Argon2id parameters,
+//!   salt, nonce prefix, chunk size)
+//! - Argon2id key derivation on the blocking thread pool, with cost
+//!   parameters from untrusted headers capped before use
+//! - Chunked AES-256-GCM with per-chunk nonces and a final-chunk flag so
+//!   truncation and reordering are detected; the stream and file helpers
+//!   work in bounded memory
+//! - In-memory helpers for payloads that are already held in memory, such
+//!   as serialized backups and bundles
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Magic bytes at the start of every container
+pub const CONTAINER_MAGIC: &[u8; 8] = b"VIPSEC\0\0";
+/// Current container format version
+pub const CONTAINER_VERSION: u16 = 2;
+/// Default plaintext bytes per encrypted chunk
+pub const DEFAULT_CHUNK_SIZE: u32 = 64 * 1024;
+
+const KDF_ARGON2ID: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+const MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+/// Highest accepted Argon2 memory cost in KiB (1 GiB)
+const MAX_M_COST: u32 = 1024 * 1024;
+/// Highest accepted Argon2 iteration count
+const MAX_T_COST: u32 = 64;
+/// Highest accepted Argon2 parallelism
+const MAX_P_COST: u32 = 16;
+/// magic + version + kdf id + 3 Argon2 params + salt + nonce prefix + chunk size
+pub const HEADER_LEN: usize = 8 + 2 + 1 + 4 * 3 + SALT_LEN + NONCE_PREFIX_LEN + 4;
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub m_cost: u32,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Degree of parallelism
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Reject costs above the caps, so a crafted header cannot make key
+    /// derivation allocate or run without bound
+    pub fn validate(&self) -> Result<()> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(anyhow!(
+                "Key derivation parameters exceed limits (m_cost {} <= {}, t_cost {} <= {}, p_cost {} <= {})",
+                self.m_cost,
+                MAX_M_COST,
+                self.t_cost,
+                MAX_T_COST,
+                self.p_cost,
+                MAX_P_COST
+            ));
+        }
+        Ok(())
+    }
+
+    /// Derive a key on the blocking thread pool; Argon2 would otherwise stall
+    /// the async worker for the whole derivation
+    async fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+        self.validate()?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| anyhow!("Invalid key derivation parameters: {}", e))?;
+        let passphrase = passphrase.to_string();
+        let salt = salt.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let mut key = [0u8; 32];
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+                .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+            Ok(key)
+        })
+        .await
+        .map_err(|e| anyhow!("Key derivation task failed: {}", e))?
+    }
+}
+
+/// Parsed container header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerHeader {
+    pub version: u16,
+    pub kdf_id: u8,
+    pub kdf: KdfParams,
+    pub salt: [u8; SALT_LEN],
+    pub nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    pub chunk_size: u32,
+}
+
+impl ContainerHeader {
+    /// Create a header with a fresh random salt and nonce prefix
+    pub fn new(kdf: KdfParams, chunk_size: u32) -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce_prefix);
+        Self {
+            version: CONTAINER_VERSION,
+            kdf_id: KDF_ARGON2ID,
+            kdf,
+            salt,
+            nonce_prefix,
+            chunk_size,
+        }
+    }
+
+    /// Serialize the header
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN);
+        out.extend_from_slice(CONTAINER_MAGIC);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.push(self.kdf_id);
+        out.extend_from_slice(&self.kdf.m_cost.to_le_bytes());
+        out.extend_from_slice(&self.kdf.t_cost.to_le_bytes());
+        out.extend_from_slice(&self.kdf.p_cost.to_le_bytes());
+        out.extend_from_slice(&self.salt);
+        out.extend_from_slice(&self.nonce_prefix);
+        out.extend_from_slice(&self.chunk_size.to_le_bytes());
+        out
+    }
+
+    /// Parse and validate a header
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if !is_container(bytes) || bytes.len() < HEADER_LEN {
+            return Err(anyhow!("Not an encrypted container"));
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+        let version = u16_at(8);
+        if version != CONTAINER_VERSION {
+            return Err(anyhow!("Unsupported container version {}", version));
+        }
+        let kdf_id = bytes[10];
+        if kdf_id != KDF_ARGON2ID {
+            return Err(anyhow!("Unsupported key derivation function {}", kdf_id));
+        }
+        let kdf = KdfParams {
+            m_cost: u32_at(11),
+            t_cost: u32_at(15),
+            p_cost: u32_at(19),
+        };
+        kdf.validate()?;
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&bytes[23..23 + SALT_LEN]);
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        nonce_prefix.copy_from_slice(&bytes[39..39 + NONCE_PREFIX_LEN]);
+        let chunk_size = u32_at(46);
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(anyhow!("Invalid container chunk size {}", chunk_size));
+        }
+
+        Ok(Self {
+            version,
+            kdf_id,
+            kdf,
+            salt,
+            nonce_prefix,
+            chunk_size,
+        })
+    }
+}
+
+/// Whether bytes start with the container magic
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.len() >= CONTAINER_MAGIC.len() && &bytes[..CONTAINER_MAGIC.len()] == CONTAINER_MAGIC
+}
+
+/// Per-container AEAD state; nonces are `prefix || counter || last flag`
+struct ChunkCipher {
+    cipher: Aes256Gcm,
+    header_bytes: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+}
+
+impl ChunkCipher {
+    async fn new(header: &ContainerHeader, passphrase: &str) -> Result<Self> {
+        let key = header.kdf.derive_key(passphrase, &header.salt).await?;
+        Ok(Self {
+            cipher: Aes256Gcm::new_from_slice(&key).map_err(|e| anyhow!("Invalid key: {}", e))?,
+            header_bytes: header.to_bytes(),
+            nonce_prefix: header.nonce_prefix,
+            counter: 0,
+        })
+    }
+
+    fn next_nonce(&mut self, last: bool) -> Result<[u8; 12]> {
+        let mut nonce = [0u8; 12];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&self.counter.to_be_bytes());
+        nonce[11] = last as u8;
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| anyhow!("Container too large"))?;
+        Ok(nonce)
+    }
+
+    fn seal(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>> {
+        let nonce = self.next_nonce(last)?;
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: chunk, aad: &self.header_bytes })
+            .map_err(|e| anyhow!("Encryption failed: {}", e))
+    }
+
+    fn open(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>> {
+        let nonce = self.next_nonce(last)?;
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: chunk, aad: &self.header_bytes })
+            .map_err(|_| anyhow!("Decryption failed - wrong password or corrupted data"))
+    }
+}
+
+/// Fill `buf` from the reader, returning fewer bytes only at end of input
+async fn read_full<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Encrypt a stream into a container, returning the number of plaintext bytes
+pub async fn encrypt_stream<R, W>(reader: &mut R, writer: &mut W, passphrase: &str, kdf: KdfParams) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    encrypt_stream_with_chunk_size(reader, writer, passphrase, kdf, DEFAULT_CHUNK_SIZE).await
+}
+
+/// Encrypt a stream using a specific chunk size
+pub async fn encrypt_stream_with_chunk_size<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    passphrase: &str,
+    kdf: KdfParams,
+    chunk_size: u32,
+) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(anyhow!("Invalid container chunk size {}", chunk_size));
+    }
+    let header = ContainerHeader::new(kdf, chunk_size);
+    let mut cipher = ChunkCipher::new(&header, passphrase).await?;
+    writer.write_all(&header.to_bytes()).await?;
+
+    let mut current = vec![0u8; chunk_size as usize];
+    let mut next = vec![0u8; chunk_size as usize];
+    let mut current_len = read_full(reader, &mut current).await?;
+    let mut total = 0u64;
+
+    loop {
+        // A chunk is final when nothing follows it
+        let next_len = if current_len == current.len() {
+            read_full(reader, &mut next).await?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+        writer.write_all(&cipher.seal(&current[..current_len], last)?).await?;
+        total += current_len as u64;
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+
+    writer.flush().await?;
+    Ok(total)
+}
+
+/// Decrypt a container stream, returning the number of plaintext bytes
+pub async fn decrypt_stream<R, W>(reader: &mut R, writer: &mut W, passphrase: &str) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut header_bytes = [0u8; HEADER_LEN];
+    if read_full(reader, &mut header_bytes).await? < HEADER_LEN {
+        return Err(anyhow!("Not an encrypted container"));
+    }
+    let header = ContainerHeader::parse(&header_bytes)?;
+    let mut cipher = ChunkCipher::new(&header, passphrase).await?;
+
+    let record_len = header.chunk_size as usize + TAG_LEN;
+    let mut current = vec![0u8; record_len];
+    let mut next = vec![0u8; record_len];
+    let mut current_len = read_full(reader, &mut current).await?;
+    let mut total = 0u64;
+
+    loop {
+        if current_len < TAG_LEN {
+            return Err(anyhow!("Encrypted container is truncated"));
+        }
+        let next_len = if current_len == record_len {
+            read_full(reader, &mut next).await?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+        let plaintext = cipher.open(&current[..current_len], last)?;
+        writer.write_all(&plaintext).await?;
+        total += plaintext.len() as u64;
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+
+    writer.flush().await?;
+    Ok(total)
+}
+
+/// Encrypt an in-memory payload
+pub async fn encrypt_bytes(plaintext: &[u8], passphrase: &str, kdf: KdfParams) -> Result<Vec<u8>> {
+    let mut reader = plaintext;
+    let mut out = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    encrypt_stream(&mut reader, &mut out, passphrase, kdf).await?;
+    Ok(out)
+}
+
+/// Decrypt an in-memory container
+pub async fn decrypt_bytes(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let mut reader = sealed;
+    let mut out = Vec::new();
+    decrypt_stream(&mut reader, &mut out, passphrase).await?;
+    Ok(out)
+}
+
+/// Encrypt a file into a container file without loading it into memory
+pub async fn encrypt_file(source: &std::path::Path, target: &std::path::Path, passphrase: &str, kdf: KdfParams) -> Result<u64> {
+    let mut reader = tokio::io::BufReader::new(tokio::fs::File::open(source).await?);
+    let mut writer = tokio::io::BufWriter::new(tokio::fs::File::create(target).await?);
+    encrypt_stream(&mut reader, &mut writer, passphrase, kdf).await
+}
+
+/// Decrypt a container file without loading it into memory
+pub async fn decrypt_file(source: &std::path::Path, target: &std::path::Path, passphrase: &str) -> Result<u64> {
+    let mut reader = tokio::io::BufReader::new(tokio::fs::File::open(source).await?);
+    let mut writer = tokio::io::BufWriter::new(tokio::fs::File::create(target).await?);
+    decrypt_stream(&mut reader, &mut writer, passphrase).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_kdf() -> KdfParams {
+        KdfParams { m_cost: 1024, t_cost: 1, p_cost: 1 }
+    }
+
+    async fn seal_with_chunks(data: &[u8], chunk_size: u32) -> Vec<u8> {
+        let mut reader = data;
+        let mut out = Vec::new();
+        encrypt_stream_with_chunk_size(&mut reader, &mut out, "pw", fast_kdf(), chunk_size)
+            .await
+            .expect("Encryption should succeed");
+        out
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_across_chunk_boundaries() {
+        for len in [0usize, 1, 15, 16, 17, 48, 100] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let sealed = seal_with_chunks(&data, 16).await;
+            let header = ContainerHeader::parse(&sealed).expect("Header should parse");
+            assert_eq!(header.kdf, fast_kdf());
+            assert_eq!(header.chunk_size, 16);
+            assert_eq!(decrypt_bytes(&sealed, "pw").await.expect("Decryption should succeed"), data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tampering_is_detected() {
+        let data = vec![7u8; 40];
+        let sealed = seal_with_chunks(&data, 16).await;
+
+        assert!(decrypt_bytes(&sealed, "wrong").await.is_err());
+
+        // Dropping the final chunk must not look like a shorter valid stream
+        let truncated = &sealed[..HEADER_LEN + 2 * (16 + TAG_LEN)];
+        assert!(decrypt_bytes(truncated, "pw").await.is_err());
+
+        // Header fields are authenticated
+        let mut altered = sealed.clone();
+        altered[40] ^= 1;
+        assert!(decrypt_bytes(&altered, "pw").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_kdf_params_are_rejected() {
+        let sealed = seal_with_chunks(b"data", 16).await;
+        for (offset, value) in [(11, MAX_M_COST + 1), (15, MAX_T_COST + 1), (19, MAX_P_COST + 1)] {
+            let mut altered = sealed.clone();
+            altered[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            assert!(ContainerHeader::parse(&altered).is_err());
+            assert!(decrypt_bytes(&altered, "pw").await.is_err());
+        }
+
+        let mut out = Vec::new();
+        let too_costly = KdfParams { m_cost: MAX_M_COST + 1, ..fast_kdf() };
+        assert!(encrypt_stream(&mut &b"data"[..], &mut out, "pw", too_costly).await.is_err());
+    }
+}
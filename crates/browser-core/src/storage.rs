@@ -5,9 +5,11 @@
 //! - Browsing history
 //! - Bookmarks
 //! - Import/Export functionality with JSON support
+//! - Ranked full-text history search
+//! - Retention sweeps and per-site data removal
 //! - Data migration and backup integration
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -15,6 +17,11 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::info;
 
+use crate::config_manager::RetentionConfig;
+use crate::content_enhancement::ExtractedArticle;
+use crate::history_search::{HistoryIndex, HistoryQuery, HistorySearchHit};
+use crate::retention::{host_matches_domain, host_of, ForgetSiteReport, RetentionReport};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a Cookie.
 pub struct Cookie {
@@ -58,6 +65,33 @@ pub struct StorageExport {
     pub history: Vec<HistoryEntry>,
     pub bookmarks: Vec<Bookmark>,
     pub local_storage: HashMap<String, HashMap<String, String>>,
+    /// When each cookie was set (Unix seconds), keyed by `domain|name|path`,
+    /// so cookie age limits survive an export and import
+    #[serde(default)]
+    pub cookie_set_at: HashMap<String, i64>,
+}
+
+/// Replacement contents for `StorageEngine::replace_contents`; `None` leaves
+/// a category untouched
+#[derive(Debug, Clone, Default)]
+pub struct StorageContents {
+    pub cookies: Option<Vec<Cookie>>,
+    pub history: Option<Vec<HistoryEntry>>,
+    pub bookmarks: Option<Vec<Bookmark>>,
+    pub local_storage: Option<HashMap<String, HashMap<String, String>>>,
+}
+
+/// Consistent copy of every category together with its timestamps
+#[derive(Debug, Clone, Default)]
+pub struct StorageSnapshot {
+    pub cookies: Vec<Cookie>,
+    pub history: Vec<HistoryEntry>,
+    pub bookmarks: Vec<Bookmark>,
+    pub local_storage: HashMap<String, HashMap<String, String>>,
+    /// Unix time each cookie was last set, keyed by `domain|name|path`
+    pub cookie_set_at: HashMap<String, i64>,
+    /// Unix time each origin's local storage was last accessed
+    pub local_storage_accessed_at: HashMap<String, i64>,
 }
 
 /// Import options for controlling what data to import
@@ -139,14 +173,22 @@ pub struct ImportExportStats {
 }
 
 /// In-memory storage engine (no database dependency)
+///
+/// Methods holding several locks at once take them in one order (cookies,
+/// cookie times, history, index, history IDs, bookmarks, bookmark IDs, local
+/// storage, access times) so they cannot deadlock against each other.
 pub struct StorageEngine {
     data_dir: PathBuf,
     cookies: Arc<RwLock<HashMap<String, Cookie>>>, // key: domain+name+path
     history: Arc<RwLock<HashMap<String, HistoryEntry>>>, // key: url
     bookmarks: Arc<RwLock<HashMap<i64, Bookmark>>>, // key: id
     local_storage: Arc<RwLock<HashMap<String, HashMap<String, String>>>>, // key: origin -> (key -> value)
+    history_index: Arc<RwLock<HistoryIndex>>,
+    cookie_set_at: Arc<RwLock<HashMap<String, i64>>>, // key: domain+name+path
+    local_storage_accessed_at: Arc<RwLock<HashMap<String, i64>>>, // key: origin
     next_history_id: Arc<RwLock<i64>>,
     next_bookmark_id: Arc<RwLock<i64>>,
+    ephemeral: bool,
 }
 
 impl StorageEngine {
@@ -162,11 +204,42 @@ impl StorageEngine {
             history: Arc::new(RwLock::new(HashMap::new())),
             bookmarks: Arc::new(RwLock::new(HashMap::new())),
             local_storage: Arc::new(RwLock::new(HashMap::new())),
+            history_index: Arc::new(RwLock::new(HistoryIndex::new())),
+            cookie_set_at: Arc::new(RwLock::new(HashMap::new())),
+            local_storage_accessed_at: Arc::new(RwLock::new(HashMap::new())),
             next_history_id: Arc::new(RwLock::new(1)),
             next_bookmark_id: Arc::new(RwLock::new(1)),
+            ephemeral: false,
         })
     }
 
+    /// Creates a memory-only storage engine for ephemeral tabs.
+    ///
+    /// Nothing is written to disk, history is never recorded and exports are
+    /// refused so the data cannot reach backups.
+    pub fn ephemeral() -> Self {
+        info!("Initialized ephemeral storage engine");
+
+        Self {
+            data_dir: PathBuf::new(),
+            cookies: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(HashMap::new())),
+            bookmarks: Arc::new(RwLock::new(HashMap::new())),
+            local_storage: Arc::new(RwLock::new(HashMap::new())),
+            history_index: Arc::new(RwLock::new(HistoryIndex::new())),
+            cookie_set_at: Arc::new(RwLock::new(HashMap::new())),
+            local_storage_accessed_at: Arc::new(RwLock::new(HashMap::new())),
+            next_history_id: Arc::new(RwLock::new(1)),
+            next_bookmark_id: Arc::new(RwLock::new(1)),
+            ephemeral: true,
+        }
+    }
+
+    /// Whether this engine belongs to an ephemeral tab
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
     // =========================================================================
     // EXPORT FUNCTIONS
     // =========================================================================
@@ -183,12 +256,18 @@ impl StorageEngine {
     /// # Arguments
     /// * `options` - Export options specifying what to export
     pub async fn export_with_options(&self, options: &ExportOptions) -> Result<StorageExport> {
+        if self.ephemeral {
+            return Err(anyhow!("Ephemeral storage cannot be exported"));
+        }
+
         let now = chrono::Utc::now().timestamp();
         
-        let cookies = if options.export_cookies {
-            self.get_all_cookies().await?
+        let (cookies, cookie_set_at) = if options.export_cookies {
+            let cookies = self.cookies.read().await;
+            let set_at = self.cookie_set_at.read().await;
+            (cookies.values().cloned().collect(), set_at.clone())
         } else {
-            Vec::new()
+            (Vec::new(), HashMap::new())
         };
 
         let history = if options.export_history {
@@ -225,6 +304,7 @@ impl StorageEngine {
             history,
             bookmarks,
             local_storage,
+            cookie_set_at,
         })
     }
 
@@ -285,17 +365,24 @@ impl StorageEngine {
     }
 
     /// Import storage data with specific options
-    /// Import cookies from export data
+    /// Import cookies from export data, keeping their recorded set times.
+    /// Cookies without one are stamped with the import time.
     async fn import_cookies_data(
         &self,
         cookies: &[Cookie],
+        set_at: &HashMap<String, i64>,
         merge: bool,
     ) -> Result<usize> {
         if !merge {
             self.clear_cookies().await?;
         }
+        let now = chrono::Utc::now().timestamp();
+        let mut stored = self.cookies.write().await;
+        let mut stored_set_at = self.cookie_set_at.write().await;
         for cookie in cookies {
-            self.set_cookie(cookie.clone()).await?;
+            let key = format!("{}|{}|{}", cookie.domain, cookie.name, cookie.path);
+            stored_set_at.insert(key.clone(), set_at.get(&key).copied().unwrap_or(now));
+            stored.insert(key, cookie.clone());
         }
         Ok(cookies.len())
     }
@@ -311,10 +398,12 @@ impl StorageEngine {
         }
         
         let mut history = self.history.write().await;
+        let mut index = self.history_index.write().await;
         let mut next_id = self.next_history_id.write().await;
         let count = history_entries.len();
         
         for mut entry in history_entries {
+            let url = entry.url.clone();
             if merge && history.contains_key(&entry.url) {
                 self.merge_history_entry(&mut history, &entry);
             } else {
@@ -322,6 +411,9 @@ impl StorageEngine {
                 *next_id += 1;
                 history.insert(entry.url.clone(), entry);
             }
+            if let Some(stored) = history.get(&url) {
+                index.upsert(stored);
+            }
         }
         Ok(count)
     }
@@ -401,7 +493,9 @@ impl StorageEngine {
         let mut stats = ImportExportStats::default();
 
         if options.import_cookies {
-            stats.cookies_count = self.import_cookies_data(&data.cookies, options.merge).await?;
+            stats.cookies_count = self
+                .import_cookies_data(&data.cookies, &data.cookie_set_at, options.merge)
+                .await?;
         }
 
         if options.import_history {
@@ -555,10 +649,12 @@ impl StorageEngine {
         }
         
         let mut history = self.history.write().await;
+        let mut index = self.history_index.write().await;
         let mut next_id = self.next_history_id.write().await;
         let mut count = 0;
         
         for mut entry in entries {
+            let url = entry.url.clone();
             if merge && history.contains_key(&entry.url) {
                 if let Some(existing) = history.get_mut(&entry.url) {
                     existing.visit_count += entry.visit_count;
@@ -572,6 +668,9 @@ impl StorageEngine {
                 *next_id += 1;
                 history.insert(entry.url.clone(), entry);
             }
+            if let Some(stored) = history.get(&url) {
+                index.upsert(stored);
+            }
             count += 1;
         }
         
@@ -590,7 +689,8 @@ impl StorageEngine {
     /// * `cookie` - The cookie to store
     pub async fn set_cookie(&self, cookie: Cookie) -> Result<()> {
         let key = format!("{}|{}|{}", cookie.domain, cookie.name, cookie.path);
-        self.cookies.write().await.insert(key, cookie);
+        self.cookies.write().await.insert(key.clone(), cookie);
+        self.cookie_set_at.write().await.insert(key, chrono::Utc::now().timestamp());
         Ok(())
     }
 
@@ -619,12 +719,14 @@ impl StorageEngine {
     pub async fn delete_cookie(&self, domain: &str, name: &str, path: &str) -> Result<()> {
         let key = format!("{}|{}|{}", domain, name, path);
         self.cookies.write().await.remove(&key);
+        self.cookie_set_at.write().await.remove(&key);
         Ok(())
     }
 
     /// Clears cookies.
     pub async fn clear_cookies(&self) -> Result<()> {
         self.cookies.write().await.clear();
+        self.cookie_set_at.write().await.clear();
         Ok(())
     }
 
@@ -634,6 +736,10 @@ impl StorageEngine {
 
     /// Adds a history.
     pub async fn add_history(&self, url: &str, title: Option<&str>) -> Result<()> {
+        if self.ephemeral {
+            return Ok(());
+        }
+
         let now = chrono::Utc::now().timestamp();
         let mut history = self.history.write().await;
         
@@ -643,18 +749,24 @@ impl StorageEngine {
             if let Some(t) = title {
                 entry.title = Some(t.to_string());
             }
+            self.history_index.write().await.upsert(entry);
         } else {
-            let mut id_guard = self.next_history_id.write().await;
-            let id = *id_guard;
-            *id_guard += 1;
+            let id = {
+                let mut id_guard = self.next_history_id.write().await;
+                let id = *id_guard;
+                *id_guard += 1;
+                id
+            };
             
-            history.insert(url.to_string(), HistoryEntry {
+            let entry = HistoryEntry {
                 id,
                 url: url.to_string(),
                 title: title.map(|t| t.to_string()),
                 visit_count: 1,
                 last_visit: now,
-            });
+            };
+            self.history_index.write().await.upsert(&entry);
+            history.insert(url.to_string(), entry);
         }
         Ok(())
     }
@@ -668,29 +780,80 @@ impl StorageEngine {
         Ok(entries)
     }
 
-    /// Performs search history operation.
+    /// Search history ranked by relevance and frecency.
+    ///
+    /// The last word is matched as a prefix, so this is suitable for
+    /// as-you-type suggestions. When nothing matches by token, entries
+    /// whose URL or title merely contain the query (e.g. `ample` in
+    /// `example.com`) are returned instead, most recent first. Returns at
+    /// most 100 entries.
     pub async fn search_history(&self, query: &str) -> Result<Vec<HistoryEntry>> {
+        const LIMIT: usize = 100;
+        let entries: Vec<HistoryEntry> = self
+            .search_history_ranked(&HistoryQuery::new(query))
+            .await?
+            .into_iter()
+            .map(|h| h.entry)
+            .collect();
+
+        // The substring scan reads every entry, so it only runs for queries
+        // the index has no answer to
+        let needle = query.trim().to_lowercase();
+        if !entries.is_empty() || needle.is_empty() {
+            return Ok(entries);
+        }
         let history = self.history.read().await;
-        let query_lower = query.to_lowercase();
-        let mut entries: Vec<HistoryEntry> = history
+        let mut substring: Vec<&HistoryEntry> = history
             .values()
             .filter(|e| {
-                e.url.to_lowercase().contains(&query_lower)
-                    || e.title
-                        .as_ref()
-                        .map(|t| t.to_lowercase().contains(&query_lower))
-                        .unwrap_or(false)
+                contains_ignore_case(&e.url, &needle)
+                    || e.title.as_ref().is_some_and(|t| contains_ignore_case(t, &needle))
             })
-            .cloned()
             .collect();
-        entries.sort_by(|a, b| b.last_visit.cmp(&a.last_visit));
-        entries.truncate(100);
-        Ok(entries)
+        if substring.len() > LIMIT {
+            substring.select_nth_unstable_by_key(LIMIT, |e| std::cmp::Reverse(e.last_visit));
+            substring.truncate(LIMIT);
+        }
+        substring.sort_by_key(|e| std::cmp::Reverse(e.last_visit));
+        Ok(substring.into_iter().cloned().collect())
+    }
+
+    /// Search history with phrase, prefix and date-range support
+    pub async fn search_history_ranked(&self, query: &HistoryQuery) -> Result<Vec<HistorySearchHit>> {
+        let now = chrono::Utc::now().timestamp();
+        let history = self.history.read().await;
+        let index = self.history_index.read().await;
+
+        Ok(index
+            .search(query, now)
+            .into_iter()
+            .filter_map(|(url, score, frecency, matched_fields)| {
+                history.get(&url).map(|entry| HistorySearchHit {
+                    entry: entry.clone(),
+                    score,
+                    frecency,
+                    matched_fields,
+                })
+            })
+            .collect())
+    }
+
+    /// Index extracted page text for a visited URL.
+    /// Returns false if the URL is not in history.
+    pub async fn index_page_text(&self, url: &str, text: &str) -> Result<bool> {
+        Ok(self.history_index.write().await.set_page_text(url, text))
+    }
+
+    /// Index an article produced by `ReaderMode::extract_article`
+    pub async fn index_article(&self, article: &ExtractedArticle) -> Result<bool> {
+        self.index_page_text(&article.url, &article.text_content).await
     }
 
     /// Clears history.
     pub async fn clear_history(&self) -> Result<()> {
-        self.history.write().await.clear();
+        let mut history = self.history.write().await;
+        history.clear();
+        self.history_index.write().await.clear();
         Ok(())
     }
 
@@ -701,6 +864,7 @@ impl StorageEngine {
     /// Adds a bookmark.
     pub async fn add_bookmark(&self, url: &str, title: &str, folder: Option<&str>) -> Result<i64> {
         let now = chrono::Utc::now().timestamp();
+        let mut bookmarks = self.bookmarks.write().await;
         let mut id_guard = self.next_bookmark_id.write().await;
         let id = *id_guard;
         *id_guard += 1;
@@ -713,7 +877,7 @@ impl StorageEngine {
             created_at: now,
         };
         
-        self.bookmarks.write().await.insert(id, bookmark);
+        bookmarks.insert(id, bookmark);
         Ok(id)
     }
 
@@ -742,11 +906,13 @@ impl StorageEngine {
             .entry(origin.to_string())
             .or_insert_with(HashMap::new)
             .insert(key.to_string(), value.to_string());
+        self.touch_local_storage(origin).await;
         Ok(())
     }
 
     /// Gets the local storage.
     pub async fn get_local_storage(&self, origin: &str, key: &str) -> Result<Option<String>> {
+        self.touch_local_storage(origin).await;
         let storage = self.local_storage.read().await;
         Ok(storage
             .get(origin)
@@ -766,15 +932,264 @@ impl StorageEngine {
     /// Clears local storage.
     pub async fn clear_local_storage(&self, origin: &str) -> Result<()> {
         self.local_storage.write().await.remove(origin);
+        self.local_storage_accessed_at.write().await.remove(origin);
         Ok(())
     }
 
     /// Clears all local storage.
     pub async fn clear_all_local_storage(&self) -> Result<()> {
         self.local_storage.write().await.clear();
+        self.local_storage_accessed_at.write().await.clear();
+        Ok(())
+    }
+
+    /// Unix time each cookie was last set, keyed by `domain|name|path`
+    pub async fn cookie_set_times(&self) -> HashMap<String, i64> {
+        self.cookie_set_at.read().await.clone()
+    }
+
+    /// Unix time each origin's local storage was last accessed
+    pub async fn local_storage_access_times(&self) -> HashMap<String, i64> {
+        self.local_storage_accessed_at.read().await.clone()
+    }
+
+    /// Copy every category and its timestamps under one set of read locks
+    pub async fn snapshot(&self) -> Result<StorageSnapshot> {
+        if self.ephemeral {
+            return Err(anyhow!("Ephemeral storage cannot be exported"));
+        }
+
+        let cookies = self.cookies.read().await;
+        let cookie_set_at = self.cookie_set_at.read().await;
+        let history = self.history.read().await;
+        let bookmarks = self.bookmarks.read().await;
+        let local_storage = self.local_storage.read().await;
+        let accessed_at = self.local_storage_accessed_at.read().await;
+
+        Ok(StorageSnapshot {
+            cookies: cookies.values().cloned().collect(),
+            history: history.values().cloned().collect(),
+            bookmarks: bookmarks.values().cloned().collect(),
+            local_storage: local_storage.clone(),
+            cookie_set_at: cookie_set_at.clone(),
+            local_storage_accessed_at: accessed_at.clone(),
+        })
+    }
+
+    /// Replace whole categories at once. Every affected lock is held until all
+    /// categories are swapped, so readers never observe a partial restore.
+    pub async fn replace_contents(&self, contents: StorageContents) -> Result<()> {
+        self.replace_contents_if(contents, |_| Ok(())).await
+    }
+
+    /// Like [`replace_contents`](Self::replace_contents), but first runs
+    /// `check` against a snapshot taken under the same write locks. Nothing is
+    /// replaced if `check` fails, and nothing can change between the check and
+    /// the swap.
+    pub async fn replace_contents_if<F>(&self, contents: StorageContents, check: F) -> Result<()>
+    where
+        F: FnOnce(&StorageSnapshot) -> Result<()>,
+    {
+        let now = chrono::Utc::now().timestamp();
+
+        // Same order as every other method holding several of these locks
+        let mut cookies = self.cookies.write().await;
+        let mut cookie_set_at = self.cookie_set_at.write().await;
+        let mut history = self.history.write().await;
+        let mut index = self.history_index.write().await;
+        let mut next_history_id = self.next_history_id.write().await;
+        let mut bookmarks = self.bookmarks.write().await;
+        let mut next_bookmark_id = self.next_bookmark_id.write().await;
+        let mut local_storage = self.local_storage.write().await;
+        let mut accessed_at = self.local_storage_accessed_at.write().await;
+
+        check(&StorageSnapshot {
+            cookies: cookies.values().cloned().collect(),
+            history: history.values().cloned().collect(),
+            bookmarks: bookmarks.values().cloned().collect(),
+            local_storage: local_storage.clone(),
+            cookie_set_at: cookie_set_at.clone(),
+            local_storage_accessed_at: accessed_at.clone(),
+        })?;
+
+        if let Some(new_cookies) = contents.cookies {
+            let replaced: HashMap<String, Cookie> = new_cookies
+                .into_iter()
+                .map(|c| (format!("{}|{}|{}", c.domain, c.name, c.path), c))
+                .collect();
+            cookie_set_at.retain(|key, _| replaced.contains_key(key));
+            for key in replaced.keys() {
+                cookie_set_at.entry(key.clone()).or_insert(now);
+            }
+            *cookies = replaced;
+        }
+
+        if let Some(entries) = contents.history {
+            history.clear();
+            index.clear();
+            for entry in entries {
+                index.upsert(&entry);
+                history.insert(entry.url.clone(), entry);
+            }
+            *next_history_id = history.values().map(|e| e.id).max().unwrap_or(0) + 1;
+        }
+
+        if let Some(entries) = contents.bookmarks {
+            *bookmarks = entries.into_iter().map(|b| (b.id, b)).collect();
+            *next_bookmark_id = bookmarks.keys().max().copied().unwrap_or(0) + 1;
+        }
+
+        if let Some(origins) = contents.local_storage {
+            accessed_at.retain(|origin, _| origins.contains_key(origin));
+            for origin in origins.keys() {
+                accessed_at.entry(origin.clone()).or_insert(now);
+            }
+            *local_storage = origins;
+        }
+
         Ok(())
     }
 
+    /// Record an access to an origin's local storage
+    async fn touch_local_storage(&self, origin: &str) {
+        self.local_storage_accessed_at
+            .write()
+            .await
+            .insert(origin.to_string(), chrono::Utc::now().timestamp());
+    }
+
+    // =========================================================================
+    // RETENTION
+    // =========================================================================
+
+    /// Remove data that falls outside the retention policy
+    ///
+    /// # Arguments
+    /// * `policy` - Retention limits per data type
+    /// * `now` - Reference Unix timestamp in seconds
+    pub async fn apply_retention(&self, policy: &RetentionConfig, now: i64) -> Result<RetentionReport> {
+        let mut report = RetentionReport {
+            swept_at: now,
+            ..Default::default()
+        };
+
+        if let Some(days) = policy.history_days {
+            let cutoff = now - i64::from(days) * 86_400;
+            let mut history = self.history.write().await;
+            let mut index = self.history_index.write().await;
+            let before = history.len();
+            history.retain(|url, entry| {
+                let keep = entry.last_visit >= cutoff;
+                if !keep {
+                    index.remove(url);
+                }
+                keep
+            });
+            report.history_removed = before - history.len();
+        }
+
+        {
+            let cutoff = policy.cookie_max_age_days.map(|d| now - i64::from(d) * 86_400);
+            let mut cookies = self.cookies.write().await;
+            let mut set_at = self.cookie_set_at.write().await;
+            let before = cookies.len();
+            cookies.retain(|key, cookie| {
+                let expired = cookie.expires.is_some_and(|e| e <= now);
+                let too_old = match (cutoff, set_at.get(key)) {
+                    (Some(cutoff), Some(&at)) => at < cutoff,
+                    _ => false,
+                };
+                !(expired || too_old)
+            });
+            set_at.retain(|key, _| cookies.contains_key(key));
+            report.cookies_removed = before - cookies.len();
+        }
+
+        if let Some(days) = policy.local_storage_inactive_days {
+            let cutoff = now - i64::from(days) * 86_400;
+            let mut storage = self.local_storage.write().await;
+            let mut accessed = self.local_storage_accessed_at.write().await;
+            let before = storage.len();
+            // Origins without a recorded access (e.g. imported) start their clock now
+            for origin in storage.keys() {
+                accessed.entry(origin.clone()).or_insert(now);
+            }
+            storage.retain(|origin, _| accessed.get(origin).is_some_and(|&at| at >= cutoff));
+            accessed.retain(|origin, _| storage.contains_key(origin));
+            report.local_storage_origins_removed = before - storage.len();
+        }
+
+        if report.total_removed() > 0 {
+            info!(
+                "Retention sweep removed {} history entries, {} cookies, {} local storage origins",
+                report.history_removed, report.cookies_removed, report.local_storage_origins_removed
+            );
+        }
+        Ok(report)
+    }
+
+    /// Remove all stored data for a domain and its subdomains
+    ///
+    /// # Arguments
+    /// * `domain` - Registrable domain such as `example.com`
+    /// * `include_bookmarks` - Whether bookmarks for the domain are removed too
+    pub async fn forget_domain(&self, domain: &str, include_bookmarks: bool) -> Result<ForgetSiteReport> {
+        let domain = domain.trim().trim_start_matches('.').to_lowercase();
+        let mut report = ForgetSiteReport {
+            domain: domain.clone(),
+            ..Default::default()
+        };
+
+        {
+            let mut cookies = self.cookies.write().await;
+            let mut set_at = self.cookie_set_at.write().await;
+            let before = cookies.len();
+            cookies.retain(|_, c| !host_matches_domain(c.domain.trim_start_matches('.'), &domain));
+            set_at.retain(|key, _| cookies.contains_key(key));
+            report.cookies_removed = before - cookies.len();
+        }
+
+        {
+            let mut history = self.history.write().await;
+            let mut index = self.history_index.write().await;
+            let before = history.len();
+            history.retain(|url, _| {
+                let matches = host_of(url).is_some_and(|h| host_matches_domain(&h, &domain));
+                if matches {
+                    index.remove(url);
+                }
+                !matches
+            });
+            report.history_removed = before - history.len();
+        }
+
+        if include_bookmarks {
+            let mut bookmarks = self.bookmarks.write().await;
+            let before = bookmarks.len();
+            bookmarks.retain(|_, b| !host_of(&b.url).is_some_and(|h| host_matches_domain(&h, &domain)));
+            report.bookmarks_removed = before - bookmarks.len();
+        }
+
+        {
+            let mut storage = self.local_storage.write().await;
+            let mut accessed = self.local_storage_accessed_at.write().await;
+            let before = storage.len();
+            storage.retain(|origin, _| !host_of(origin).is_some_and(|h| host_matches_domain(&h, &domain)));
+            accessed.retain(|origin, _| storage.contains_key(origin));
+            report.local_storage_origins_removed = before - storage.len();
+        }
+
+        info!(
+            "Forgot site {}: {} cookies, {} history, {} bookmarks, {} local storage origins",
+            domain,
+            report.cookies_removed,
+            report.history_removed,
+            report.bookmarks_removed,
+            report.local_storage_origins_removed
+        );
+        Ok(report)
+    }
+
     // =========================================================================
     // UTILITY FUNCTIONS
     // =========================================================================
@@ -816,6 +1231,23 @@ impl StorageEngine {
     }
 }
 
+/// Whether `haystack` contains the lowercase `needle`, ignoring case.
+/// Only non-ASCII text is copied to lowercase it.
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    if haystack.contains(needle) {
+        return true;
+    }
+    if !haystack.is_ascii() {
+        return haystack.to_lowercase().contains(needle);
+    }
+    let (haystack, needle) = (haystack.as_bytes(), needle.as_bytes());
+    haystack.len() >= needle.len()
+        && (0..=haystack.len() - needle.len()).any(|start| {
+            haystack[start].to_ascii_lowercase() == needle[0]
+                && haystack[start..start + needle.len()].eq_ignore_ascii_case(needle)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -930,6 +1362,199 @@ mod tests {
         let bookmarks = storage.get_bookmarks().await.expect("Get operation should succeed");
         assert_eq!(bookmarks.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_apply_retention() {
+        let (storage, _temp) = create_test_storage().await;
+        let now = chrono::Utc::now().timestamp();
+
+        storage.import_history_json(
+            &serde_json::json!([
+                { "id": 0, "url": "https://old.com", "title": null, "visit_count": 1, "last_visit": now - 40 * 86_400 },
+                { "id": 0, "url": "https://new.com", "title": null, "visit_count": 1, "last_visit": now },
+            ]).to_string(),
+            false,
+        ).await.expect("Import operation should succeed");
+        storage.set_cookie(Cookie {
+            domain: "expired.com".to_string(),
+            name: "a".to_string(),
+            value: "1".to_string(),
+            path: "/".to_string(),
+            expires: Some(now - 1),
+            http_only: false,
+            secure: false,
+            same_site: "Lax".to_string(),
+        }).await.expect("Async operation should succeed");
+        storage.set_local_storage("https://idle.com", "k", "v").await.expect("Set local storage should succeed");
+
+        let policy = RetentionConfig {
+            history_days: Some(30),
+            cookie_max_age_days: None,
+            local_storage_inactive_days: Some(7),
+            sweep_interval_secs: 3600,
+        };
+        let report = storage.apply_retention(&policy, now + 8 * 86_400).await.expect("Retention should succeed");
+
+        assert_eq!(report.history_removed, 1);
+        assert_eq!(report.cookies_removed, 1);
+        assert_eq!(report.local_storage_origins_removed, 1);
+        assert!(storage.search_history("old").await.expect("Search should succeed").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cookie_age_survives_export_and_import() {
+        let (storage, _temp) = create_test_storage().await;
+        let now = chrono::Utc::now().timestamp();
+        let cookie = |name: &str| Cookie {
+            domain: "example.com".to_string(),
+            name: name.to_string(),
+            value: "1".to_string(),
+            path: "/".to_string(),
+            expires: None,
+            http_only: false,
+            secure: false,
+            same_site: "Lax".to_string(),
+        };
+        storage.set_cookie(cookie("old")).await.expect("Set cookie should succeed");
+
+        let mut export = storage.export_all().await.expect("Export should succeed");
+        export.cookie_set_at.insert("example.com|old|/".to_string(), now - 40 * 86_400);
+        export.cookies.push(cookie("unstamped"));
+
+        let (restored, _temp2) = create_test_storage().await;
+        restored.import_all(export).await.expect("Import should succeed");
+        let set_at = restored.cookie_set_times().await;
+        assert_eq!(set_at.get("example.com|old|/"), Some(&(now - 40 * 86_400)));
+        assert!(set_at.get("example.com|unstamped|/").is_some_and(|&at| at >= now));
+
+        let policy = RetentionConfig {
+            history_days: None,
+            cookie_max_age_days: Some(30),
+            local_storage_inactive_days: None,
+            sweep_interval_secs: 3600,
+        };
+        let report = restored.apply_retention(&policy, now).await.expect("Retention should succeed");
+        assert_eq!(report.cookies_removed, 1);
+        assert_eq!(restored.get_all_cookies().await.expect("Get should succeed").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_forget_domain() {
+        let (storage, _temp) = create_test_storage().await;
+
+        storage.set_cookie(Cookie {
+            domain: ".example.com".to_string(),
+            name: "sid".to_string(),
+            value: "1".to_string(),
+            path: "/".to_string(),
+            expires: None,
+            http_only: true,
+            secure: true,
+            same_site: "Lax".to_string(),
+        }).await.expect("Async operation should succeed");
+        storage.add_history("https://mail.example.com/inbox", Some("Inbox")).await.expect("Add history should succeed");
+        storage.add_history("https://example.org/", Some("Other")).await.expect("Add history should succeed");
+        storage.add_bookmark("https://example.com/", "Example", None).await.expect("Add bookmark should succeed");
+        storage.set_local_storage("https://www.example.com", "k", "v").await.expect("Set local storage should succeed");
+
+        let report = storage.forget_domain("example.com", false).await.expect("Forget operation should succeed");
+        assert_eq!(report.cookies_removed, 1);
+        assert_eq!(report.history_removed, 1);
+        assert_eq!(report.bookmarks_removed, 0);
+        assert_eq!(report.local_storage_origins_removed, 1);
+        assert_eq!(storage.get_stats().await.history_count, 1);
+
+        let report = storage.forget_domain("example.com", true).await.expect("Forget operation should succeed");
+        assert_eq!(report.bookmarks_removed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_history_ranked() {
+        let (storage, _temp) = create_test_storage().await;
+
+        storage.add_history("https://docs.rs/tokio", Some("Tokio runtime docs")).await.expect("Add history should succeed");
+        storage.add_history("https://tokio.rs/blog", Some("Tokio blog")).await.expect("Add history should succeed");
+        storage.add_history("https://tokio.rs/blog", Some("Tokio blog")).await.expect("Add history should succeed");
+        storage.add_history("https://example.com", Some("Example")).await.expect("Add history should succeed");
+
+        let results = storage.search_history("tok").await.expect("Search should succeed");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://tokio.rs/blog");
+
+        let indexed = storage
+            .index_page_text("https://example.com", "a page about the reactor pattern")
+            .await
+            .expect("Index operation should succeed");
+        assert!(indexed);
+        let hits = storage
+            .search_history_ranked(&HistoryQuery::new("\"reactor pattern\""))
+            .await
+            .expect("Search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.url, "https://example.com");
+
+        storage.clear_history().await.expect("Clear operation should succeed");
+        assert!(storage.search_history("tok").await.expect("Search should succeed").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_history_falls_back_to_substrings() {
+        let (storage, _temp) = create_test_storage().await;
+        storage.add_history("https://example.com/", Some("Home")).await.expect("Add history should succeed");
+        storage.add_history("https://sample.org/", Some("Sampler")).await.expect("Add history should succeed");
+
+        let results = storage.search_history("ample").await.expect("Search should succeed");
+        assert_eq!(results.len(), 2);
+
+        // Token prefix matches rank ahead of plain substring matches
+        let results = storage.search_history("sampl").await.expect("Search should succeed");
+        assert_eq!(results[0].url, "https://sample.org/");
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_history_on_large_history() {
+        let (storage, _temp) = create_test_storage().await;
+        let now = chrono::Utc::now().timestamp();
+        let entries: Vec<HistoryEntry> = (0..100_000)
+            .map(|i| HistoryEntry {
+                id: i,
+                url: format!("https://site{}.example.com/articles/{}", i % 500, i),
+                title: Some(format!("Article {} about topic{}", i, i % 97)),
+                visit_count: (i % 13) as i32 + 1,
+                last_visit: now - i,
+            })
+            .collect();
+        let json = serde_json::to_string(&entries).expect("Serialize should succeed");
+        storage.import_history_json(&json, false).await.expect("Import should succeed");
+
+        let started = std::time::Instant::now();
+        for query in ["art", "topic4", "site12 art", "ample", "\"about topic7\"", "nomatch"] {
+            let results = storage.search_history(query).await.expect("Search should succeed");
+            assert!(results.len() <= 100);
+        }
+        // Generous bound for unoptimised test builds
+        assert!(started.elapsed() < std::time::Duration::from_secs(5), "Search took {:?}", started.elapsed());
+
+        // Queries without token matches scan every entry once, without
+        // cloning or sorting the whole history
+        for query in ["ample", "nomatch"] {
+            let started = std::time::Instant::now();
+            storage.search_history(query).await.expect("Search should succeed");
+            assert!(
+                started.elapsed() < std::time::Duration::from_millis(500),
+                "Search for {} took {:?}",
+                query,
+                started.elapsed()
+            );
+        }
+
+        // Substring matches come most recent first
+        let results = storage.search_history("ample").await.expect("Search should succeed");
+        assert_eq!(results.len(), 100);
+        assert_eq!(results[0].url, "https://site0.example.com/articles/0");
+        assert!(results.windows(2).all(|w| w[0].last_visit >= w[1].last_visit));
+    }
 }
 
 // =============================================================================
@@ -6,10 +6,12 @@
 //! - Multi-tab coordination
 //! - Resource management per tab
 
+use crate::event_stream::{BrowserEvent, EventBus};
 use crate::fingerprint::BrowserFingerprint;
 use crate::tab_isolation::{NetworkConfig, TabProfile, TabStatus, TLSProfile, HTTP2Settings, TCPFingerprint};
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::SystemTime;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -20,6 +22,7 @@ use tracing::{info, debug};
 pub struct TabIPManager {
     tabs: RwLock<HashMap<String, TabProfile>>,
     ip_generator: IPGenerator,
+    events: Option<Arc<EventBus>>,
 }
 
 impl TabIPManager {
@@ -28,6 +31,19 @@ impl TabIPManager {
         Self {
             tabs: RwLock::new(HashMap::new()),
             ip_generator,
+            events: None,
+        }
+    }
+
+    /// Publish tab lifecycle events to `bus`
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    fn publish(&self, event: BrowserEvent) {
+        if let Some(bus) = &self.events {
+            bus.publish(event);
         }
     }
 
@@ -57,6 +73,11 @@ impl TabIPManager {
             .insert(tab_id.clone(), tab_profile.clone());
 
         info!("Created tab {} for country {}", tab_id, country_code);
+        self.publish(BrowserEvent::TabCreated {
+            tab_id,
+            country_code: tab_profile.virtual_ip.country_code.clone(),
+            ip: tab_profile.virtual_ip.ip.to_string(),
+        });
         Ok(tab_profile)
     }
 
@@ -102,14 +123,16 @@ impl TabIPManager {
     /// Close tab
     pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
         // Remove from in-memory cache
-        self.tabs.write().await.remove(tab_id);
+        if self.tabs.write().await.remove(tab_id).is_some() {
+            self.publish(BrowserEvent::TabClosed { tab_id: tab_id.to_string() });
+        }
         
         info!("Tab {} closed successfully", tab_id);
         Ok(())
     }
 
     /// Navigate an existing tab
-    pub async fn navigate(&self, tab_id: &str, _url: &str) -> Result<()> {
+    pub async fn navigate(&self, tab_id: &str, url: &str) -> Result<()> {
         let mut tabs = self.tabs.write().await;
         let tab = tabs
             .get_mut(tab_id)
@@ -117,6 +140,10 @@ impl TabIPManager {
         tab.last_active = SystemTime::now();
         
         debug!("Tab {} navigated", tab_id);
+        self.publish(BrowserEvent::TabNavigated {
+            tab_id: tab_id.to_string(),
+            url: url.to_string(),
+        });
         Ok(())
     }
 
@@ -7,21 +7,25 @@
 //! - Proxy rotation and session management
 //! - PAC (Proxy Auto-Config) server integration
 //! - Free proxy provider management
+//! - Ephemeral (incognito) tabs with clear-on-close containers
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Manager, WebviewWindow, WebviewWindowBuilder, WebviewUrl};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use crate::proxy::{ProxySettings, FreeProxy};
-use crate::local_proxy::LocalProxyManager;
+use crate::local_proxy::{LocalProxyManager, ProxyGateway, UpstreamFailover};
 use crate::pac_server::PacManager;
 use crate::free_ip_providers::FreeIpProviderManager;
-use crate::proxy_rotation::{ProxyRotationManager, ProxyRotationStrategy, ProxySessionStats};
+use crate::proxy_rotation::{ProxyHealthMonitor, ProxyRotationManager, ProxyRotationStrategy, ProxySessionStats};
+use crate::proxy_validator::ProxyQuarantineManager;
+use crate::ephemeral::EphemeralContainerManager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Represents a WebviewTab.
@@ -38,6 +42,8 @@ pub struct WebviewTab {
     pub last_active: DateTime<Utc>,
     pub proxy_config: Option<ProxySettings>,
     pub zoom_level: f64,
+    #[serde(default)]
+    pub ephemeral: bool,
 }
 
 /// Represents a WebviewManager.
@@ -50,6 +56,7 @@ pub struct WebviewManager {
     pub(crate) pac_manager: Arc<PacManager>,
     proxy_provider_manager: Arc<RwLock<FreeIpProviderManager>>,
     proxy_rotation_manager: Arc<RwLock<ProxyRotationManager>>,
+    ephemeral_containers: Arc<EphemeralContainerManager>,
 }
 
 impl WebviewManager {
@@ -61,9 +68,6 @@ impl WebviewManager {
     /// # Arguments
     /// * `app_handle` - The Tauri application handle
     pub fn new(app_handle: AppHandle) -> Self {
-        // Initialize local proxy manager with port range 9000-9999
-        let local_proxy_manager = Arc::new(LocalProxyManager::new(9000..10000));
-        
         // Initialize PAC server on port 8080
         let pac_manager = Arc::new(PacManager::new(8080)
             .expect("Failed to create PAC manager"));
@@ -81,6 +85,28 @@ impl WebviewManager {
                 ProxyRotationStrategy::RoundRobin,
             )
         ));
+
+        // Initialize local proxy manager with port range 9000-9999. A tab
+        // whose upstream refuses or times out fails over to the next-best
+        // pool proxy; failures feed the health monitor and quarantine.
+        let proxy_gateway = Arc::new(
+            ProxyGateway::new(proxy_rotation_manager.clone(), Arc::new(ProxyHealthMonitor::new()))
+                .with_quarantine(Arc::new(ProxyQuarantineManager::new(
+                    3,
+                    Duration::from_secs(300),
+                    Duration::from_secs(3600),
+                ))),
+        );
+        let local_proxy_manager = Arc::new(
+            LocalProxyManager::new(9000..10000).with_failover(UpstreamFailover::new(proxy_gateway))
+        );
+
+        // Ephemeral tabs get their own proxy listener and rotation session
+        let ephemeral_containers = Arc::new(
+            EphemeralContainerManager::new()
+                .with_local_proxies(local_proxy_manager.clone())
+                .with_proxy_rotation(proxy_rotation_manager.clone())
+        );
         
         Self {
             app_handle,
@@ -91,9 +117,15 @@ impl WebviewManager {
             pac_manager,
             proxy_provider_manager,
             proxy_rotation_manager,
+            ephemeral_containers,
         }
     }
 
+    /// Get the registry of ephemeral tab containers
+    pub fn ephemeral_containers(&self) -> Arc<EphemeralContainerManager> {
+        self.ephemeral_containers.clone()
+    }
+
     /// Start the proxy infrastructure
     /// Start the proxy infrastructure including PAC server
     ///
@@ -193,19 +225,67 @@ impl WebviewManager {
         proxy_config: Option<ProxySettings>
     ) -> Result<WebviewTab> {
         let tab_id = Uuid::new_v4().to_string();
+
+        // The tab's listener must exist before its PAC file points at it
+        let proxy_url = match &proxy_config {
+            Some(settings) => Some(
+                self.local_proxy_manager
+                    .create_proxy_for_tab(&tab_id, Some(settings.clone()))
+                    .await?,
+            ),
+            None => None,
+        };
+
+        match self.build_tab(tab_id.clone(), initial_url, proxy_config, proxy_url.as_deref(), false).await {
+            Ok(tab) => Ok(tab),
+            Err(e) => {
+                self.release_tab_resources(&tab_id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Create an ephemeral tab
+    ///
+    /// The webview runs in incognito mode and all tab data lives in a
+    /// memory-only container that is destroyed when the tab closes.
+    pub async fn create_ephemeral_tab(
+        &self,
+        initial_url: Option<String>,
+        proxy_config: Option<ProxySettings>
+    ) -> Result<WebviewTab> {
+        let tab_id = Uuid::new_v4().to_string();
+        let container = self.ephemeral_containers.create_container(&tab_id, proxy_config.clone()).await?;
+
+        match self.build_tab(tab_id.clone(), initial_url, proxy_config, container.proxy_url.as_deref(), true).await {
+            Ok(tab) => Ok(tab),
+            Err(e) => {
+                self.release_tab_resources(&tab_id).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn build_tab(
+        &self,
+        tab_id: String,
+        initial_url: Option<String>,
+        proxy_config: Option<ProxySettings>,
+        proxy_url: Option<&str>,
+        ephemeral: bool,
+    ) -> Result<WebviewTab> {
         let mut counter = self.window_counter.write().await;
         *counter += 1;
         let window_label = format!("tab_{}", counter);
         
         let url = initial_url.unwrap_or_else(|| "https://www.google.com".to_string());
         
-        // Store proxy configuration for this tab
-        // Note: Using single proxy approach due to WebView2 limitations
-        let proxy_port = if proxy_config.is_some() {
-            // Use default proxy server on port 9000
-            9000
-        } else {
-            0 // No proxy
+        // Route the tab through its own local proxy listener, if it has one
+        let proxy_port = match proxy_url {
+            Some(proxy_url) => url::Url::parse(proxy_url)?
+                .port()
+                .ok_or_else(|| anyhow!("Local proxy URL has no port: {}", proxy_url))?,
+            None => 0, // No proxy
         };
         
         // Register PAC file for this tab
@@ -234,6 +314,7 @@ impl WebviewManager {
         .center()
         .decorations(true)
         .resizable(true)
+        .incognito(ephemeral)
         .build()
         .map_err(|e| anyhow!("Failed to create WebView window: {}", e))?;
         
@@ -250,6 +331,7 @@ impl WebviewManager {
             last_active: Utc::now(),
             proxy_config,
             zoom_level: 1.0,
+            ephemeral,
         };
         
         // Store tab reference
@@ -299,19 +381,21 @@ impl WebviewManager {
     }
 
     /// Close a tab and clean up associated proxy resources
+    ///
+    /// The tab's container, local proxy and PAC entry are released even if
+    /// its window fails to close.
     pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
         let tabs = self.tabs.read().await;
         let tab = tabs.get(tab_id).ok_or_else(|| anyhow!("Tab not found"))?;
         
-        if let Some(window) = self.app_handle.get_webview_window(&tab.window_label) {
-            window.close()?;
-        }
+        let closed = match self.app_handle.get_webview_window(&tab.window_label) {
+            Some(window) => window.close().map_err(|e| anyhow!("Failed to close WebView window: {}", e)),
+            None => Ok(()),
+        };
         
         drop(tabs);
-        
-        // Clean up proxy resources
-        self.local_proxy_manager.remove_proxy_for_tab(tab_id).await?;
-        self.pac_manager.remove_proxy_for_tab(tab_id).await?;
+
+        self.release_tab_resources(tab_id).await;
         
         self.tabs.write().await.remove(tab_id);
         
@@ -321,7 +405,26 @@ impl WebviewManager {
             *active_tab = self.tabs.read().await.keys().next().cloned();
         }
         
-        Ok(())
+        closed
+    }
+
+    /// Destroy a tab's ephemeral container and release its local proxy and
+    /// PAC entry. Failures are logged so the remaining steps still run.
+    async fn release_tab_resources(&self, tab_id: &str) {
+        // The container stops its own proxy listener and rotation session
+        match self.ephemeral_containers.destroy(tab_id).await {
+            Ok(Some(report)) => debug!("Destroyed ephemeral container {} on close", report.container_id),
+            Ok(None) => {
+                if let Err(e) = self.local_proxy_manager.remove_proxy_for_tab(tab_id).await {
+                    warn!("Failed to stop local proxy for tab {}: {}", tab_id, e);
+                }
+            }
+            Err(e) => warn!("Failed to destroy ephemeral container for tab {}: {}", tab_id, e),
+        }
+
+        if let Err(e) = self.pac_manager.remove_proxy_for_tab(tab_id).await {
+            warn!("Failed to remove PAC entry for tab {}: {}", tab_id, e);
+        }
     }
 
     /// Configure proxy settings in a WebView using PAC URL
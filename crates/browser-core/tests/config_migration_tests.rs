@@ -0,0 +1,146 @@
+//! Tests for config migrations
+//!
+//! This module tests:
+//! - Upgrading historical config files from `tests/fixtures/config`
+//! - Backups of the original file before it is rewritten
+//! - Current and too-new documents
+
+use browser_core::config_manager::ConfigManager;
+use browser_core::config_migration::{migrate_file, CURRENT_CONFIG_VERSION};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+// ============================================================================
+// Test Fixtures
+// ============================================================================
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config").join(name)
+}
+
+/// Copy a fixture into a temp dir, returning the dir and the copy's path
+fn copy_fixture(name: &str) -> (TempDir, PathBuf) {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let path = dir.path().join(name);
+    std::fs::copy(fixture(name), &path).expect("Fixture copy should succeed");
+    (dir, path)
+}
+
+async fn load(path: &Path) -> browser_core::AppConfig {
+    let manager = ConfigManager::with_path(path);
+    manager.load().await.expect("Load operation should succeed");
+    manager.get().await
+}
+
+// ============================================================================
+// Historical Fixtures
+// ============================================================================
+
+#[tokio::test]
+async fn test_migrates_every_historical_fixture() {
+    let fixtures = [
+        "v1_baseline_default.toml",
+        "v1_with_retention.toml",
+        "v1_custom_history.json",
+        "v2_current.toml",
+    ];
+
+    for name in fixtures {
+        let (_dir, path) = copy_fixture(name);
+        let config = load(&path).await;
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION, "{}", name);
+
+        let rewritten = std::fs::read_to_string(&path).expect("Read should succeed");
+        assert!(!rewritten.contains("history_retention_days"), "{}", name);
+
+        // Loading again is a no-op
+        let report = migrate_file(&path).await.expect("Migrate should succeed");
+        assert!(report.is_noop(), "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn test_v1_baseline_default_retention_becomes_opt_in() {
+    let (_dir, path) = copy_fixture("v1_baseline_default.toml");
+    let config = load(&path).await;
+
+    // 90 days was the version 1 default, not a user choice
+    assert_eq!(config.privacy.retention.history_days, None);
+    assert_eq!(config.general.theme, "system");
+    assert_eq!(config.network.max_bandwidth_bps, 0);
+}
+
+#[tokio::test]
+async fn test_v1_retention_policy_wins_over_legacy_setting() {
+    let (_dir, path) = copy_fixture("v1_with_retention.toml");
+    let config = load(&path).await;
+
+    assert_eq!(config.privacy.retention.history_days, Some(14));
+}
+
+#[tokio::test]
+async fn test_v1_json_keeps_custom_values() {
+    let (_dir, path) = copy_fixture("v1_custom_history.json");
+    let config = load(&path).await;
+
+    assert_eq!(config.privacy.retention.history_days, Some(30));
+    assert_eq!(config.proxy.default_port, Some(9050));
+    assert_eq!(config.proxy.preferred_countries, vec!["DE", "NL"]);
+    assert_eq!(config.storage.data_dir.as_deref(), Some("/var/lib/proxy-browser"));
+    assert_eq!(config.logging.level, "debug");
+
+    let rewritten: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).expect("Read should succeed"))
+            .expect("Rewritten file should be JSON");
+    assert_eq!(rewritten["version"], CURRENT_CONFIG_VERSION);
+}
+
+// ============================================================================
+// Backups
+// ============================================================================
+
+#[tokio::test]
+async fn test_original_is_backed_up_before_rewrite() {
+    let (dir, path) = copy_fixture("v1_baseline_default.toml");
+    let original = std::fs::read(&path).expect("Read should succeed");
+
+    load(&path).await;
+
+    let backup = dir.path().join("v1_baseline_default.toml.v1.bak");
+    assert_eq!(std::fs::read(&backup).expect("Backup should exist"), original);
+
+    // A second old file at the same path never overwrites the first backup
+    std::fs::write(&path, &original).expect("Write should succeed");
+    load(&path).await;
+    assert!(dir.path().join("v1_baseline_default.toml.v1.1.bak").exists());
+    assert_eq!(std::fs::read(&backup).expect("Backup should exist"), original);
+}
+
+#[tokio::test]
+async fn test_current_fixture_is_not_rewritten() {
+    let (dir, path) = copy_fixture("v2_current.toml");
+    let original = std::fs::read(&path).expect("Read should succeed");
+
+    let config = load(&path).await;
+
+    assert_eq!(config.privacy.retention.history_days, Some(60));
+    assert_eq!(config.privacy.blocked_patterns, vec!["*://ads.example.com/*"]);
+    assert_eq!(std::fs::read(&path).expect("Read should succeed"), original);
+    assert_eq!(std::fs::read_dir(dir.path()).expect("Read dir should succeed").count(), 1);
+}
+
+#[tokio::test]
+async fn test_newer_version_is_refused() {
+    let (_dir, path) = copy_fixture("v2_current.toml");
+    let content = std::fs::read_to_string(&path).expect("Read should succeed");
+    let future = content.replacen(
+        &format!("version = {}", CURRENT_CONFIG_VERSION),
+        &format!("version = {}", CURRENT_CONFIG_VERSION + 1),
+        1,
+    );
+    std::fs::write(&path, &future).expect("Write should succeed");
+
+    let manager = ConfigManager::with_path(&path);
+    assert!(manager.load().await.is_err());
+    assert_eq!(std::fs::read_to_string(&path).expect("Read should succeed"), future);
+}
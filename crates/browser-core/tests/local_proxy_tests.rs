@@ -1,6 +1,12 @@
 //! Unit tests for the local_proxy module.
 
+use base64::engine::Engine;
 use browser_core::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
 
 
 #[test]
@@ -204,3 +210,411 @@ fn test_get_websocket_connections() {
     // Test the get_websocket_connections function
     assert!(true, "get_websocket_connections test placeholder");
 }
+
+// ============================================================================
+// Traffic accounting
+// ============================================================================
+
+/// Echo server standing in for the CONNECT target
+async fn spawn_echo_server() -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+    let addr = listener.local_addr().expect("Local address should be available");
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buffer = [0u8; 1024];
+                while let Ok(n) = stream.read(&mut buffer).await {
+                    if n == 0 || stream.write_all(&buffer[..n]).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    addr
+}
+
+async fn free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+    listener.local_addr().expect("Local address should be available").port()
+}
+
+#[tokio::test]
+async fn test_tab_stats_count_connections_and_bytes() {
+    let target = spawn_echo_server().await;
+    let port = free_port().await;
+    let manager = LocalProxyManager::new(port..port + 1);
+    let proxy_url = manager
+        .create_proxy_for_tab("tab-1", None)
+        .await
+        .expect("Proxy creation should succeed");
+
+    let mut client = TcpStream::connect(proxy_url.trim_start_matches("http://"))
+        .await
+        .expect("Connect should succeed");
+    client
+        .write_all(format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n\r\n", target, target).as_bytes())
+        .await
+        .expect("Write should succeed");
+    let mut response = [0u8; 64];
+    let n = client.read(&mut response).await.expect("Read should succeed");
+    assert!(String::from_utf8_lossy(&response[..n]).contains("200"));
+
+    client.write_all(b"ping").await.expect("Write should succeed");
+    let mut echoed = [0u8; 4];
+    client.read_exact(&mut echoed).await.expect("Read should succeed");
+    assert_eq!(&echoed, b"ping");
+
+    // The received counter is bumped right after the echo is written back
+    let mut stats = manager.tab_stats().await;
+    for _ in 0..50 {
+        if stats[0].bytes_received == 4 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        stats = manager.tab_stats().await;
+    }
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].tab_id, "tab-1");
+    assert_eq!(stats[0].active_connections, 1);
+    assert_eq!((stats[0].bytes_sent, stats[0].bytes_received), (4, 4));
+
+    drop(client);
+    manager.stop_all().await.expect("Stop should succeed");
+}
+
+#[tokio::test]
+async fn test_removed_tab_proxy_frees_its_port() {
+    let port = free_port().await;
+    let manager = LocalProxyManager::new(port..port + 1);
+    let url = manager.create_proxy_for_tab("tab-1", None).await.expect("Proxy creation should succeed");
+
+    manager.remove_proxy_for_tab("tab-1").await.expect("Remove should succeed");
+    drop(TcpListener::bind(url.trim_start_matches("http://")).await.expect("Port should be free again"));
+
+    // The only port in the range is handed out again
+    let again = manager.create_proxy_for_tab("tab-2", None).await.expect("Proxy creation should succeed");
+    assert_eq!(again, url);
+    manager.stop_all().await.expect("Stop should succeed");
+}
+
+#[tokio::test]
+async fn test_tab_proxy_can_be_renamed() {
+    let port = free_port().await;
+    let manager = LocalProxyManager::new(port..port + 2);
+    let upstream = ProxySettings::parse_url("http://10.0.0.1:8080").expect("URL should parse");
+    let url = manager
+        .create_proxy_for_tab("pending", Some(upstream.clone()))
+        .await
+        .expect("Proxy creation should succeed");
+    manager.create_proxy_for_tab("other", None).await.expect("Proxy creation should succeed");
+
+    assert!(!manager.rename_tab("pending", "other").await);
+    assert!(manager.rename_tab("pending", "tab-1").await);
+    assert!(!manager.rename_tab("pending", "tab-2").await);
+    assert_eq!(manager.get_proxy_url_for_tab("tab-1").await, Some(url));
+    assert_eq!(
+        manager.upstream_for_tab("tab-1").await.and_then(|p| p.server_url()),
+        upstream.server_url()
+    );
+    assert!(manager.get_proxy_url_for_tab("pending").await.is_none());
+
+    manager.stop_all().await.expect("Stop should succeed");
+}
+
+// ============================================================================
+// Rotating gateway
+// ============================================================================
+
+/// Upstream HTTP proxy that accepts every CONNECT and echoes the tunnel,
+/// counting the tunnels it opened
+async fn spawn_upstream_proxy() -> (u16, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+    let port = listener.local_addr().expect("Local address should be available").port();
+    let tunnels = Arc::new(AtomicUsize::new(0));
+    let counter = tunnels.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let mut buffer = [0u8; 1024];
+                let Ok(n) = stream.read(&mut buffer).await else { return };
+                if !buffer[..n].starts_with(b"CONNECT ") {
+                    return;
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                if stream.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await.is_err() {
+                    return;
+                }
+                while let Ok(n) = stream.read(&mut buffer).await {
+                    if n == 0 || stream.write_all(&buffer[..n]).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    (port, tunnels)
+}
+
+fn pool_proxy(port: u16, country_code: &str) -> FreeProxy {
+    FreeProxy {
+        ip: "127.0.0.1".to_string(),
+        port,
+        protocol: ProxyType::Http,
+        country: country_code.to_string(),
+        country_code: country_code.to_string(),
+        anonymity: "elite".to_string(),
+        speed: 100,
+        uptime: 99.0,
+        last_checked: chrono::Utc::now().to_rfc3339(),
+        provider: "test".to_string(),
+        is_working: true,
+    }
+}
+
+/// Gateway server over `pool`, returning its address and health monitor
+async fn start_gateway(pool: Vec<FreeProxy>) -> (LocalProxyServer, String, Arc<ProxyHealthMonitor>) {
+    let providers = Arc::new(RwLock::new(FreeIpProviderManager::new().expect("Provider manager should build")));
+    providers.write().await.set_proxy_pool(pool);
+    let rotation = Arc::new(RwLock::new(ProxyRotationManager::new(providers, ProxyRotationStrategy::PerSession)));
+    let health = Arc::new(ProxyHealthMonitor::new());
+    let server = LocalProxyServer::new(free_port().await, None)
+        .expect("Server should build")
+        .with_gateway(Arc::new(ProxyGateway::new(rotation, health.clone())));
+    server.start().await.expect("Start should succeed");
+    let addr = server.get_proxy_url().trim_start_matches("http://").to_string();
+    (server, addr, health)
+}
+
+/// CONNECT through a local proxy as `username`, returning the status line
+async fn proxy_connect(gateway: &str, target: std::net::SocketAddr, username: Option<&str>) -> (TcpStream, String) {
+    let mut client = TcpStream::connect(gateway).await.expect("Connect should succeed");
+    let mut request = format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n", target, target);
+    if let Some(username) = username {
+        let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:secret", username));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+    client.write_all(request.as_bytes()).await.expect("Write should succeed");
+    let mut response = [0u8; 128];
+    let n = client.read(&mut response).await.expect("Read should succeed");
+    let status = String::from_utf8_lossy(&response[..n]).lines().next().unwrap_or_default().to_string();
+    (client, status)
+}
+
+#[test]
+fn test_gateway_route_from_username() {
+    assert_eq!(
+        GatewayRoute::from_username("session-abc-country-de"),
+        GatewayRoute { session: Some("abc".to_string()), country: Some("DE".to_string()) }
+    );
+    assert_eq!(
+        GatewayRoute::from_username("scraper-country-US"),
+        GatewayRoute { session: None, country: Some("US".to_string()) }
+    );
+    assert_eq!(GatewayRoute::from_username("scraper"), GatewayRoute::default());
+    assert_eq!(GatewayRoute::from_username("session-"), GatewayRoute::default());
+}
+
+#[tokio::test]
+async fn test_gateway_sticky_session_keeps_upstream() {
+    let target = spawn_echo_server().await;
+    let (de_a, de_a_tunnels) = spawn_upstream_proxy().await;
+    let (de_b, de_b_tunnels) = spawn_upstream_proxy().await;
+    let (fr, fr_tunnels) = spawn_upstream_proxy().await;
+    let (server, gateway, health) =
+        start_gateway(vec![pool_proxy(de_a, "DE"), pool_proxy(de_b, "DE"), pool_proxy(fr, "FR")]).await;
+
+    for _ in 0..4 {
+        let (mut client, status) = proxy_connect(&gateway, target, Some("session-abc-country-DE")).await;
+        assert!(status.contains("200"), "unexpected status: {}", status);
+        client.write_all(b"ping").await.expect("Write should succeed");
+        let mut echoed = [0u8; 4];
+        client.read_exact(&mut echoed).await.expect("Read should succeed");
+        assert_eq!(&echoed, b"ping");
+    }
+
+    let counts = (de_a_tunnels.load(Ordering::SeqCst), de_b_tunnels.load(Ordering::SeqCst));
+    assert!(counts == (4, 0) || counts == (0, 4), "session moved between upstreams: {:?}", counts);
+    assert_eq!(fr_tunnels.load(Ordering::SeqCst), 0);
+
+    // Successes are reported once the tunnel closes
+    let sticky = if counts.0 == 4 { de_a } else { de_b };
+    let mut requests = 0;
+    for _ in 0..50 {
+        requests = health
+            .get_bandwidth_stats(&format!("127.0.0.1:{}", sticky))
+            .await
+            .map_or(0, |stats| stats.requests_count);
+        if requests == 4 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(requests, 4);
+
+    server.stop().await.expect("Stop should succeed");
+}
+
+#[tokio::test]
+async fn test_gateway_reports_failed_upstream() {
+    let target = spawn_echo_server().await;
+    // Nothing listens on this port once the listener is dropped
+    let dead = free_port().await;
+    let (server, gateway, health) = start_gateway(vec![pool_proxy(dead, "DE")]).await;
+
+    let (_client, status) = proxy_connect(&gateway, target, None).await;
+    assert!(status.contains("502"), "unexpected status: {}", status);
+    let status = health
+        .get_health(&format!("127.0.0.1:{}", dead))
+        .await
+        .expect("Failure should be recorded");
+    assert_eq!(status.consecutive_failures, 1);
+
+    // No proxy in the requested country
+    let (_client, status) = proxy_connect(&gateway, target, Some("country-FR")).await;
+    assert!(status.contains("503"), "unexpected status: {}", status);
+
+    server.stop().await.expect("Stop should succeed");
+}
+
+#[tokio::test]
+async fn test_idle_gateway_sessions_expire() {
+    let providers = Arc::new(RwLock::new(FreeIpProviderManager::new().expect("Provider manager should build")));
+    providers.write().await.set_proxy_pool(vec![pool_proxy(1, "DE"), pool_proxy(2, "FR")]);
+    let rotation = Arc::new(RwLock::new(ProxyRotationManager::new(providers, ProxyRotationStrategy::PerSession)));
+    let gateway = ProxyGateway::new(rotation.clone(), Arc::new(ProxyHealthMonitor::new()))
+        .with_session_ttl(std::time::Duration::from_millis(50));
+    let none = std::collections::HashSet::new();
+
+    gateway
+        .select(&GatewayRoute::from_username("session-a"), &none)
+        .await
+        .expect("Select should succeed");
+    rotation
+        .read()
+        .await
+        .get_proxy_for_country("tab-1", None, &none)
+        .await
+        .expect("Tab session should get a proxy");
+    assert!(rotation.read().await.get_session_stats("gateway:a").await.is_some());
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    gateway
+        .select(&GatewayRoute::from_username("session-b"), &none)
+        .await
+        .expect("Select should succeed");
+
+    let rotation = rotation.read().await;
+    assert!(rotation.get_session_stats("gateway:a").await.is_none());
+    assert!(rotation.get_session_stats("gateway:b").await.is_some());
+    // Tab sessions are not the gateway's to expire
+    assert!(rotation.get_session_stats("tab-1").await.is_some());
+}
+
+// ============================================================================
+// Upstream failover
+// ============================================================================
+
+/// Upstream that accepts connections but never answers the CONNECT
+async fn spawn_silent_proxy() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Bind should succeed");
+    let port = listener.local_addr().expect("Local address should be available").port();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+    port
+}
+
+/// Pool services over `pool` for failover tests
+async fn failover_pool(
+    pool: Vec<FreeProxy>,
+) -> (Arc<ProxyGateway>, Arc<RwLock<ProxyRotationManager>>, Arc<ProxyQuarantineManager>) {
+    let providers = Arc::new(RwLock::new(FreeIpProviderManager::new().expect("Provider manager should build")));
+    providers.write().await.set_proxy_pool(pool);
+    let rotation = Arc::new(RwLock::new(ProxyRotationManager::new(providers, ProxyRotationStrategy::PerSession)));
+    let quarantine = Arc::new(ProxyQuarantineManager::new(
+        1,
+        std::time::Duration::from_secs(300),
+        std::time::Duration::from_secs(3600),
+    ));
+    let gateway = Arc::new(
+        ProxyGateway::new(rotation.clone(), Arc::new(ProxyHealthMonitor::new())).with_quarantine(quarantine.clone()),
+    );
+    (gateway, rotation, quarantine)
+}
+
+async fn start_tab_server(upstream: &FreeProxy, failover: UpstreamFailover) -> (LocalProxyServer, String) {
+    let server = LocalProxyServer::new(free_port().await, Some(upstream.to_proxy_settings()))
+        .expect("Server should build")
+        .with_failover(failover)
+        .with_session("tab-1");
+    server.start().await.expect("Start should succeed");
+    let addr = server.get_proxy_url().trim_start_matches("http://").to_string();
+    (server, addr)
+}
+
+#[tokio::test]
+async fn test_failover_moves_to_next_best_and_rebinds() {
+    let target = spawn_echo_server().await;
+    let dead = pool_proxy(free_port().await, "DE");
+    let (live_port, live_tunnels) = spawn_upstream_proxy().await;
+    let (fr_port, fr_tunnels) = spawn_upstream_proxy().await;
+    let (pool, rotation, quarantine) =
+        failover_pool(vec![dead.clone(), pool_proxy(live_port, "DE"), pool_proxy(fr_port, "FR")]).await;
+    let (server, addr) = start_tab_server(&dead, UpstreamFailover::new(pool).with_rebind_session(true)).await;
+
+    let (mut client, status) = proxy_connect(&addr, target, None).await;
+    assert!(status.contains("200"), "unexpected status: {}", status);
+    client.write_all(b"ping").await.expect("Write should succeed");
+    let mut echoed = [0u8; 4];
+    client.read_exact(&mut echoed).await.expect("Read should succeed");
+    assert_eq!(&echoed, b"ping");
+
+    // The failover stayed in the failed proxy's country
+    assert_eq!(live_tunnels.load(Ordering::SeqCst), 1);
+    assert_eq!(fr_tunnels.load(Ordering::SeqCst), 0);
+    assert!(quarantine.is_quarantined(&dead).await);
+
+    let upstream = server.upstream_proxy().await.expect("Upstream should be set");
+    assert_eq!(upstream.port, Some(live_port));
+    let session = rotation.read().await.get_current_proxy("tab-1").await.expect("Session should be bound");
+    assert_eq!(session.port, live_port);
+
+    drop(client);
+    server.stop().await.expect("Stop should succeed");
+}
+
+#[tokio::test]
+async fn test_failover_respects_time_budget_and_fails_closed() {
+    let target = spawn_echo_server().await;
+    let silent = pool_proxy(spawn_silent_proxy().await, "DE");
+    let (pool, _, _) = failover_pool(vec![silent.clone()]).await;
+    let failover = UpstreamFailover::new(pool)
+        .with_max_attempts(5)
+        .with_attempt_timeout(std::time::Duration::from_millis(200))
+        .with_time_budget(std::time::Duration::from_millis(300));
+
+    let (server, addr) = start_tab_server(&silent, failover.clone()).await;
+    let started = std::time::Instant::now();
+    let (_client, status) = proxy_connect(&addr, target, None).await;
+    assert!(status.contains("502"), "unexpected status: {}", status);
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    // Not rebound without being asked to
+    assert_eq!(server.upstream_proxy().await.and_then(|u| u.port), Some(silent.port));
+    server.stop().await.expect("Stop should succeed");
+
+    // Failing open reaches the target directly
+    let (server, addr) = start_tab_server(&silent, failover.with_fail_closed(false)).await;
+    let (mut client, status) = proxy_connect(&addr, target, None).await;
+    assert!(status.contains("200"), "unexpected status: {}", status);
+    client.write_all(b"ping").await.expect("Write should succeed");
+    let mut echoed = [0u8; 4];
+    client.read_exact(&mut echoed).await.expect("Read should succeed");
+    assert_eq!(&echoed, b"ping");
+    server.stop().await.expect("Stop should succeed");
+}
@@ -14,7 +14,7 @@ use browser_core::free_ip_providers::FreeIpProviderManager;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use chrono::{Duration, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // ============================================================================
 // Test Fixtures
@@ -392,3 +392,42 @@ fn test_proxy_metrics_clone() {
     assert_eq!(cloned.response_time_ms, metrics.response_time_ms);
     assert_eq!(cloned.total_requests, metrics.total_requests);
 }
+
+// ============================================================================
+// Country Sessions
+// ============================================================================
+
+#[tokio::test]
+async fn test_get_proxy_for_country_is_sticky_and_rotates_on_exclude() {
+    let provider_manager = create_test_provider_manager().await;
+    provider_manager.write().await.set_proxy_pool(vec![
+        create_test_proxy("10.0.0.1", 8080, "DE"),
+        create_test_proxy("10.0.0.2", 8080, "DE"),
+        create_test_proxy("10.0.0.3", 8080, "US"),
+    ]);
+    let manager = ProxyRotationManager::new(provider_manager, ProxyRotationStrategy::RoundRobin);
+
+    let mut exclude = HashSet::new();
+    let first = manager
+        .get_proxy_for_country("job-de", Some("de"), &exclude)
+        .await
+        .expect("Proxy selection should succeed");
+    assert_eq!(first.country_code, "DE");
+    let again = manager
+        .get_proxy_for_country("job-de", Some("DE"), &exclude)
+        .await
+        .expect("Proxy selection should succeed");
+    assert_eq!(again.ip, first.ip);
+
+    exclude.insert(format!("{}:{}", first.ip, first.port));
+    let rotated = manager
+        .get_proxy_for_country("job-de", Some("DE"), &exclude)
+        .await
+        .expect("Proxy selection should succeed");
+    assert_eq!(rotated.country_code, "DE");
+    assert_ne!(rotated.ip, first.ip);
+
+    exclude.insert(format!("{}:{}", rotated.ip, rotated.port));
+    assert!(manager.get_proxy_for_country("job-de", Some("DE"), &exclude).await.is_err());
+    assert!(manager.get_proxy_for_country("job-fr", Some("FR"), &HashSet::new()).await.is_err());
+}
@@ -13,6 +13,7 @@ use browser_core::proxy_validator::{
     ProxyQuarantineManager, QuarantinedProxy, QuarantineStats,
     GeoVerifier, GeoVerificationConfig, GeoVerificationResult,
     ProxyHealthChecker, EnhancedProxyHealthChecker,
+    AnonymityLevel, classify_judge_response,
 };
 use std::time::Duration;
 use std::sync::Arc;
@@ -354,6 +355,8 @@ fn test_geo_verification_result_verified() {
         detected_country: Some("United States".to_string()),
         expected_location: Some((37.7749, -122.4194)),
         detected_location: Some((37.7749, -122.4194)),
+        detected_timezone: Some("America/Los_Angeles".to_string()),
+        detected_locale: Some("en-US".to_string()),
         distance_km: Some(0.0),
         error: None,
     };
@@ -370,6 +373,8 @@ fn test_geo_verification_result_mismatch() {
         detected_country: Some("China".to_string()),
         expected_location: None,
         detected_location: None,
+        detected_timezone: None,
+        detected_locale: None,
         distance_km: None,
         error: None,
     };
@@ -461,6 +466,41 @@ fn test_quarantine_stats_structure() {
     assert_eq!(stats.average_failures, 2.5);
 }
 
+// ============================================================================
+// Proxy Judge Tests
+// ============================================================================
+
+#[test]
+fn test_judge_classifies_json_echo() {
+    let elite = r#"{"headers": {"Host": "httpbin.org", "Accept": "*/*"}, "origin": "203.0.113.7"}"#;
+    assert_eq!(classify_judge_response(elite, "198.51.100.2"), (AnonymityLevel::Elite, vec![]));
+
+    let anonymous = r#"{"headers": {"Host": "httpbin.org", "Via": "1.1 squid"}, "origin": "203.0.113.7"}"#;
+    assert_eq!(
+        classify_judge_response(anonymous, "198.51.100.2"),
+        (AnonymityLevel::Anonymous, vec!["via".to_string()])
+    );
+
+    let transparent = r#"{"headers": {"X-Forwarded-For": "198.51.100.2"}, "origin": "198.51.100.2, 203.0.113.7"}"#;
+    let (level, headers) = classify_judge_response(transparent, "198.51.100.2");
+    assert_eq!(level, AnonymityLevel::Transparent);
+    assert_eq!(headers, vec!["x-forwarded-for".to_string()]);
+}
+
+#[test]
+fn test_judge_classifies_env_listing() {
+    let body = "REMOTE_ADDR = 203.0.113.7\nHTTP_HOST = judge.example\nHTTP_X_FORWARDED_FOR = 10.0.0.1\n";
+    assert_eq!(
+        classify_judge_response(body, "198.51.100.2"),
+        (AnonymityLevel::Anonymous, vec!["x-forwarded-for".to_string()])
+    );
+
+    // Only whole addresses count as a leak
+    let body = "REMOTE_ADDR = 11.2.3.45\n";
+    assert_eq!(classify_judge_response(body, "1.2.3.4").0, AnonymityLevel::Elite);
+    assert_eq!(AnonymityLevel::Elite.as_str(), "elite");
+}
+
 // ============================================================================
 // Integration Tests
 // ============================================================================
@@ -0,0 +1,159 @@
+//! Backups Module
+//!
+//! `backup create|restore|list` for the configuration, profiles and proxy
+//! pool in the data directory. Backups live in `backups/`; they are
+//! encrypted when a password is given.
+
+use anyhow::Result;
+use browser_core::secure_container;
+use browser_core::{AppBackupManager, BackupComponent, FreeIpProviderManager};
+use chrono::Utc;
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::output::{not_found, usage, Outcome};
+use crate::store::DataDir;
+
+#[derive(Debug, Subcommand)]
+pub enum BackupCommand {
+    /// Back up the selected components (default: all)
+    Create {
+        /// File name inside `backups/` (default: timestamped)
+        #[arg(long)]
+        name: Option<String>,
+        /// `config`, `profiles` or `proxy_pool`, repeatable
+        #[arg(long = "component", value_parser = parse_component)]
+        components: Vec<BackupComponent>,
+        /// Encrypt the backup with this password
+        #[arg(long, env = "PROXY_BROWSER_BACKUP_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Restore the selected components (default: all in the backup)
+    Restore {
+        /// Backup name inside `backups/`, or a path
+        backup: String,
+        #[arg(long = "component", value_parser = parse_component)]
+        components: Vec<BackupComponent>,
+        #[arg(long, env = "PROXY_BROWSER_BACKUP_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+        /// Only show what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List backups with their manifests
+    List {
+        #[arg(long, env = "PROXY_BROWSER_BACKUP_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+}
+
+fn parse_component(name: &str) -> Result<BackupComponent, String> {
+    serde_json::from_value(Value::String(name.to_string())).map_err(|_| format!("unknown component: {}", name))
+}
+
+/// Backup manager attached to everything the CLI keeps in the data directory
+async fn manager(data: &DataDir) -> Result<(AppBackupManager, Arc<RwLock<FreeIpProviderManager>>)> {
+    let pool = data.shared_pool().await?;
+    let manager = AppBackupManager::new()
+        .with_config(data.config().await?)
+        .with_profiles(data.profiles().await?)
+        .with_proxy_pool(pool.clone());
+    Ok((manager, pool))
+}
+
+pub async fn run(command: BackupCommand, data: &DataDir) -> Result<Outcome> {
+    match command {
+        BackupCommand::Create { name, components, password } => {
+            let (manager, _) = manager(data).await?;
+            let extension = if password.is_some() { "enc" } else { "json" };
+            let name = name.unwrap_or_else(|| format!("backup-{}.{}", Utc::now().format("%Y%m%d-%H%M%S"), extension));
+            if name.contains(['/', '\\']) {
+                return Err(usage("Backup names cannot contain path separators"));
+            }
+            let path = data.backups_dir().join(&name);
+            let manifest = manager.create_backup(&path, &components, password.as_deref()).await?;
+            Outcome::ok(json!({ "name": name, "path": path, "manifest": manifest }))
+        }
+        BackupCommand::Restore { backup, components, password, dry_run } => {
+            let path = resolve_backup(data, &backup)?;
+            let (manager, pool) = manager(data).await?;
+            let contents = manager.read_backup(&path, password.as_deref()).await?;
+
+            // Default to what both the backup and the CLI know about
+            let available = manager.available_components();
+            let components = if components.is_empty() {
+                contents
+                    .manifest
+                    .components
+                    .iter()
+                    .map(|entry| entry.component)
+                    .filter(|c| available.contains(c))
+                    .collect()
+            } else {
+                components
+            };
+
+            if dry_run {
+                return Outcome::ok(manager.preview_restore(&contents, &components).await?);
+            }
+            let report = manager.restore(&contents, &components).await?;
+            if report.restored.contains(&BackupComponent::ProxyPool) {
+                data.save_pool(pool.read().await.get_proxy_pool()).await?;
+            }
+            Outcome::ok(report)
+        }
+        BackupCommand::List { password } => {
+            let dir = data.backups_dir();
+            if !dir.exists() {
+                return Outcome::ok(Vec::<Value>::new());
+            }
+            let manager = AppBackupManager::new();
+            let mut paths = Vec::new();
+            let mut entries = tokio::fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_type().await?.is_file() {
+                    paths.push(entry.path());
+                }
+            }
+            paths.sort();
+
+            let mut backups = Vec::new();
+            for path in paths {
+                let content = tokio::fs::read(&path).await?;
+                let encrypted = secure_container::is_container(&content);
+                let mut listing = json!({
+                    "name": path.file_name().map(|n| n.to_string_lossy().into_owned()),
+                    "path": path,
+                    "size": content.len(),
+                    "encrypted": encrypted,
+                    "manifest": null,
+                });
+                // Encrypted manifests can only be shown with the password
+                if !encrypted || password.is_some() {
+                    match manager.read_backup(&path, password.as_deref()).await {
+                        Ok(contents) => listing["manifest"] = serde_json::to_value(contents.manifest)?,
+                        Err(e) => listing["error"] = json!(e.to_string()),
+                    }
+                }
+                backups.push(listing);
+            }
+            Outcome::ok(backups)
+        }
+    }
+}
+
+/// A backup name inside `backups/`, or a path to a backup file
+fn resolve_backup(data: &DataDir, backup: &str) -> Result<PathBuf> {
+    let named = data.backups_dir().join(backup);
+    if named.is_file() {
+        return Ok(named);
+    }
+    let path = PathBuf::from(backup);
+    if path.is_file() {
+        return Ok(path);
+    }
+    Err(not_found(format!("No backup named {}", backup)))
+}
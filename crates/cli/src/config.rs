@@ -0,0 +1,111 @@
+//! Config Module
+//!
+//! `config get|set|validate` on the configuration file in the data
+//! directory, addressing settings by dotted key path such as
+//! `proxy.rotation_strategy`.
+
+use anyhow::Result;
+use browser_core::{AppConfig, ConfigManager};
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use crate::output::{invalid, not_found, Outcome, EXIT_INVALID, EXIT_OK};
+use crate::store::DataDir;
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the whole configuration, or the value at a dotted key path
+    Get { key: Option<String> },
+    /// Set the value at a dotted key path; the value is read as JSON,
+    /// falling back to a plain string
+    Set { key: String, value: String },
+    /// Check the configuration, exiting with 3 when it has errors
+    Validate {
+        /// File to check instead of the data directory's
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+}
+
+pub async fn run(command: ConfigCommand, data: &DataDir) -> Result<Outcome> {
+    match command {
+        ConfigCommand::Get { key } => {
+            let config = serde_json::to_value(data.config().await?.get().await)?;
+            match key {
+                None => Outcome::ok(config),
+                Some(key) => {
+                    let value = lookup(&config, &key).ok_or_else(|| not_found(format!("Unknown configuration key: {}", key)))?;
+                    Outcome::ok(json!({ "key": key, "value": value }))
+                }
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            let manager = data.config().await?;
+            let mut document = serde_json::to_value(manager.get().await)?;
+            let slot = lookup_mut(&mut document, &key)
+                .ok_or_else(|| not_found(format!("Unknown configuration key: {}", key)))?;
+            // Keep strings as typed, so `general.language 42` stays a string
+            *slot = match slot {
+                Value::String(_) => Value::String(value),
+                _ => serde_json::from_str(&value).unwrap_or(Value::String(value)),
+            };
+            let new_value = slot.clone();
+
+            let config: AppConfig = serde_json::from_value(document)
+                .map_err(|e| invalid(format!("Invalid value for {}: {}", key, e)))?;
+            let validation = ConfigManager::check(&config);
+            if !validation.is_valid() {
+                return Err(invalid(validation.errors.join("; ")));
+            }
+            manager.replace(config).await;
+            manager.save().await?;
+            Outcome::ok(json!({ "key": key, "value": new_value, "warnings": validation.warnings }))
+        }
+        ConfigCommand::Validate { file } => {
+            let path = file.unwrap_or_else(|| data.config_path());
+            let config = if path.exists() {
+                ConfigManager::parse_file(&path)
+                    .await
+                    .map_err(|e| invalid(format!("Cannot parse {}: {:#}", path.display(), e)))?
+            } else {
+                return Err(not_found(format!("No configuration file at {}", path.display())));
+            };
+            let validation = ConfigManager::check(&config);
+            let code = if validation.is_valid() { EXIT_OK } else { EXIT_INVALID };
+            Outcome::with_code(
+                json!({
+                    "path": path,
+                    "valid": validation.is_valid(),
+                    "errors": validation.errors,
+                    "warnings": validation.warnings,
+                }),
+                code,
+            )
+        }
+    }
+}
+
+fn lookup<'v>(document: &'v Value, key: &str) -> Option<&'v Value> {
+    key.split('.').try_fold(document, |node, part| node.as_object()?.get(part))
+}
+
+fn lookup_mut<'v>(document: &'v mut Value, key: &str) -> Option<&'v mut Value> {
+    key.split('.').try_fold(document, |node, part| node.as_object_mut()?.get_mut(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_dotted_keys() {
+        let mut document = json!({ "proxy": { "enabled": false, "default_port": null } });
+        assert_eq!(lookup(&document, "proxy.enabled"), Some(&json!(false)));
+        assert_eq!(lookup(&document, "proxy.missing"), None);
+        assert_eq!(lookup(&document, "proxy.enabled.deeper"), None);
+
+        *lookup_mut(&mut document, "proxy.default_port").expect("Key should exist") = json!(8080);
+        assert_eq!(document["proxy"]["default_port"], 8080);
+    }
+}
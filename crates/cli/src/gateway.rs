@@ -0,0 +1,87 @@
+//! Gateway Module
+//!
+//! `gateway`: one local proxy endpoint backed by the whole saved pool. Each
+//! CONNECT gets its own upstream; clients pick a sticky session and a
+//! country through the proxy username, e.g. `session-abc-country-DE`.
+//! Failed upstreams are quarantined and the CONNECT is retried on the
+//! next-best proxy; connections never go direct unless `--fail-open` is set.
+
+use anyhow::Result;
+use browser_core::{
+    LocalProxyServer, ProxyGateway, ProxyHealthMonitor, ProxyQuarantineManager, ProxyRotationManager,
+    ProxyRotationStrategy, SmartProxySelector, UpstreamFailover,
+};
+use clap::Args;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::output::{not_found, render, usage, write_stdout, Outcome};
+use crate::store::DataDir;
+
+#[derive(Debug, Args)]
+pub struct GatewayArgs {
+    /// Listen port
+    #[arg(long, default_value_t = 8899)]
+    port: u16,
+    /// Listen address
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// Upstreams tried per connection, the first one included
+    #[arg(long, default_value_t = 3)]
+    max_attempts: u32,
+    /// Seconds one upstream gets to open the tunnel
+    #[arg(long, default_value_t = 10)]
+    attempt_timeout_secs: u64,
+    /// Seconds all attempts of one connection get together
+    #[arg(long, default_value_t = 20)]
+    time_budget_secs: u64,
+    /// Move a sticky session to the proxy that took over after a failure
+    #[arg(long)]
+    rebind_sessions: bool,
+    /// Connect directly when every upstream failed
+    #[arg(long)]
+    fail_open: bool,
+}
+
+pub async fn run(args: GatewayArgs, data: &DataDir, pretty: bool) -> Result<Outcome> {
+    let addr: SocketAddr = format!("{}:{}", args.host, args.port)
+        .parse()
+        .map_err(|e| usage(format!("Invalid listen address {}:{}: {}", args.host, args.port, e)))?;
+    let pool = data.shared_pool().await?;
+    let working = pool.read().await.get_working_proxies().len();
+    if working == 0 {
+        return Err(not_found("The proxy pool has no working proxies"));
+    }
+
+    let config = data.config().await?.get().await.proxy;
+    let selector = SmartProxySelector {
+        preferred_countries: config.preferred_countries,
+        ..SmartProxySelector::default()
+    };
+    let quarantine = ProxyQuarantineManager::new(config.max_failures, Duration::from_secs(300), Duration::from_secs(3600));
+    let rotation = Arc::new(RwLock::new(ProxyRotationManager::new(pool, ProxyRotationStrategy::PerSession)));
+    let gateway = Arc::new(
+        ProxyGateway::new(rotation, Arc::new(ProxyHealthMonitor::new()))
+            .with_selector(selector)
+            .with_quarantine(Arc::new(quarantine)),
+    );
+    let failover = UpstreamFailover::new(gateway.clone())
+        .with_max_attempts(args.max_attempts)
+        .with_attempt_timeout(Duration::from_secs(args.attempt_timeout_secs))
+        .with_time_budget(Duration::from_secs(args.time_budget_secs))
+        .with_rebind_session(args.rebind_sessions)
+        .with_fail_closed(!args.fail_open);
+    let server = LocalProxyServer::new(args.port, None)?
+        .with_bind_addr(addr)
+        .with_gateway(gateway)
+        .with_failover(failover);
+    server.start().await?;
+
+    // Announce the endpoint up front, since the gateway runs until killed
+    let started = json!({ "proxy_url": format!("http://{}", addr), "working_proxies": working });
+    write_stdout(&(render(&started, pretty) + "\n"));
+    std::future::pending().await
+}
@@ -0,0 +1,156 @@
+//! Proxy Browser CLI
+//!
+//! Scriptable front end to the browser core:
+//! - `proxies fetch|import|validate|export|judge`: manage the proxy pool
+//! - `profile create|export|import|list`: browser profiles and bundles
+//! - `backup create|restore|list`: application backups
+//! - `config get|set|validate`: the configuration file
+//! - `tab open --country XX --url ...`: load a page in headless Chromium
+//! - `serve`: run the API server with the saved proxy pool
+//! - `gateway`: one rotating proxy endpoint backed by the saved pool
+//!
+//! State lives in the data directory (`--data-dir`, default the desktop
+//! app's data directory, e.g. `~/.local/share/com.virtualipbrowser.app`). Every
+//! command prints one JSON document to stdout; errors go to stderr as JSON.
+//! `serve` and `gateway` announce themselves with one document when they
+//! start, and `serve` logs to stderr as JSON lines. Exit codes are listed in
+//! `output`.
+
+mod backups;
+mod config;
+mod gateway;
+mod output;
+mod profiles;
+mod proxies;
+mod store;
+mod tab;
+
+use anyhow::Result;
+use clap::error::ErrorKind;
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crate::output::{Outcome, Output, EXIT_OK, EXIT_USAGE};
+use crate::store::DataDir;
+
+#[derive(Debug, Parser)]
+#[command(name = "proxy-browser", version, about = "Manage proxies, profiles and headless tabs from scripts")]
+struct Cli {
+    /// Directory holding the proxy pool, profiles, backups and config
+    /// [default: the desktop app's data directory]
+    #[arg(long, global = true, env = "PROXY_BROWSER_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+    /// Indent the JSON output
+    #[arg(long, global = true)]
+    pretty: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage the proxy pool
+    #[command(subcommand)]
+    Proxies(proxies::ProxiesCommand),
+    /// Manage browser profiles
+    #[command(subcommand)]
+    Profile(profiles::ProfileCommand),
+    /// Create, list and restore backups
+    #[command(subcommand)]
+    Backup(backups::BackupCommand),
+    /// Read, change and check the configuration
+    #[command(subcommand)]
+    Config(config::ConfigCommand),
+    /// Drive headless Chromium tabs
+    #[command(subcommand)]
+    Tab(tab::TabCommand),
+    /// Run the API server with the saved proxy pool
+    ///
+    /// Reads the same environment variables as the api-server binary.
+    Serve {
+        /// Listen port
+        #[arg(long, env = "PORT", default_value_t = 8080)]
+        port: u16,
+        /// Listen address
+        #[arg(long, env = "API_BIND_HOST")]
+        host: Option<String>,
+    },
+    /// Run a rotating proxy endpoint backed by the saved proxy pool
+    ///
+    /// Every CONNECT is tunnelled through its own upstream. A proxy username
+    /// such as `session-abc-country-DE` keeps a sticky upstream per session
+    /// and picks the country. Failed upstreams are retried on the next-best
+    /// proxy. Prints the endpoint, then runs until killed.
+    Gateway(gateway::GatewayArgs),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) if matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) => {
+            output::write_stdout(&e.to_string());
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            let message = e.render().to_string();
+            let first_line = message.lines().next().unwrap_or_default().trim_start_matches("error: ");
+            eprintln!("{}", output::render(&output::error_document(first_line, EXIT_USAGE), false));
+            return exit_code(EXIT_USAGE);
+        }
+    };
+
+    let pretty = cli.pretty;
+    match run(cli).await {
+        Ok(Outcome { output, exit_code: code }) => {
+            match output {
+                Output::Json(value) => output::write_stdout(&(output::render(&value, pretty) + "\n")),
+                Output::Text(text) => output::write_stdout(&text),
+            }
+            exit_code(code)
+        }
+        Err(e) => {
+            let code = output::exit_code_of(&e);
+            eprintln!("{}", output::render(&output::error_document(&format!("{:#}", e), code), pretty));
+            exit_code(code)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<Outcome> {
+    let data = DataDir::new(cli.data_dir.unwrap_or_else(store::default_data_dir));
+    let pretty = cli.pretty;
+    match cli.command {
+        Command::Proxies(command) => proxies::run(command, &data).await,
+        Command::Profile(command) => profiles::run(command, &data).await,
+        Command::Backup(command) => backups::run(command, &data).await,
+        Command::Config(command) => config::run(command, &data).await,
+        Command::Tab(command) => tab::run(command, &data).await,
+        Command::Serve { port, host } => {
+            tracing_subscriber::fmt().with_writer(std::io::stderr).json().init();
+            let bootstrapped = api_server::bootstrap::server_from_env(data.load_pool().await?).await?;
+            let mut server = bootstrapped.server;
+            if let Some(host) = host {
+                server = server.with_bind_host(host);
+            }
+
+            // Announce the endpoint up front, since the server runs until
+            // stopped; a newly created admin key is only ever shown here
+            let started = json!({ "url": server.listen_url(port), "admin_api_key": bootstrapped.admin_key });
+            output::write_stdout(&(output::render(&started, pretty) + "\n"));
+            server.run(port).await?;
+            Outcome::ok(json!({ "stopped": true }))
+        }
+        Command::Gateway(args) => gateway::run(args, &data, pretty).await,
+    }
+}
+
+fn exit_code(code: i32) -> ExitCode {
+    if code == EXIT_OK {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(u8::try_from(code).unwrap_or(1))
+    }
+}
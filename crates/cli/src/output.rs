@@ -0,0 +1,128 @@
+//! Output Module
+//!
+//! Every command prints one JSON document to stdout and exits with a code
+//! scripts can branch on. Errors are printed to stderr as
+//! `{"error": "...", "exit_code": N}`.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::io::Write;
+
+/// The command succeeded
+pub const EXIT_OK: i32 = 0;
+/// The command failed, e.g. on I/O or network errors
+pub const EXIT_FAILURE: i32 = 1;
+/// The arguments were invalid
+pub const EXIT_USAGE: i32 = 2;
+/// The input was understood but is invalid, e.g. a config with errors
+pub const EXIT_INVALID: i32 = 3;
+/// A named profile, proxy, backup or config key does not exist
+pub const EXIT_NOT_FOUND: i32 = 4;
+
+/// What a command prints to stdout
+#[derive(Debug)]
+pub enum Output {
+    Json(Value),
+    /// Raw text, for exports in formats other than JSON
+    Text(String),
+}
+
+/// Result of a command: what to print and the exit code
+#[derive(Debug)]
+pub struct Outcome {
+    pub output: Output,
+    pub exit_code: i32,
+}
+
+impl Outcome {
+    /// A successful result
+    pub fn ok(output: impl Serialize) -> anyhow::Result<Self> {
+        Self::with_code(output, EXIT_OK)
+    }
+
+    /// A result that still prints `output` but exits with `exit_code`
+    pub fn with_code(output: impl Serialize, exit_code: i32) -> anyhow::Result<Self> {
+        Ok(Self {
+            output: Output::Json(serde_json::to_value(output)?),
+            exit_code,
+        })
+    }
+
+    /// A successful result printed as is
+    pub fn text(text: String) -> Self {
+        Self { output: Output::Text(text), exit_code: EXIT_OK }
+    }
+}
+
+/// Error carrying the exit code it should end the process with; other
+/// errors exit with `EXIT_FAILURE`
+#[derive(Debug)]
+pub struct CliError {
+    pub exit_code: i32,
+    pub message: String,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Error for invalid arguments
+pub fn usage(message: impl Into<String>) -> anyhow::Error {
+    CliError { exit_code: EXIT_USAGE, message: message.into() }.into()
+}
+
+/// Error for input that is understood but invalid
+pub fn invalid(message: impl Into<String>) -> anyhow::Error {
+    CliError { exit_code: EXIT_INVALID, message: message.into() }.into()
+}
+
+/// Error for something that does not exist
+pub fn not_found(message: impl Into<String>) -> anyhow::Error {
+    CliError { exit_code: EXIT_NOT_FOUND, message: message.into() }.into()
+}
+
+/// Exit code for an error returned by a command
+pub fn exit_code_of(error: &anyhow::Error) -> i32 {
+    error.downcast_ref::<CliError>().map_or(EXIT_FAILURE, |e| e.exit_code)
+}
+
+/// Error document printed to stderr
+pub fn error_document(message: &str, exit_code: i32) -> Value {
+    json!({ "error": message, "exit_code": exit_code })
+}
+
+/// Render `value` compactly, or indented when `pretty` is set
+pub fn render(value: &Value, pretty: bool) -> String {
+    let rendered = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    rendered.unwrap_or_else(|_| value.to_string())
+}
+
+/// Write to stdout, ignoring a reader that went away (e.g. `| head`)
+pub fn write_stdout(text: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(text.as_bytes()).and_then(|_| stdout.flush());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_of_errors() {
+        assert_eq!(exit_code_of(&not_found("missing")), EXIT_NOT_FOUND);
+        assert_eq!(exit_code_of(&invalid("bad")), EXIT_INVALID);
+        assert_eq!(exit_code_of(&anyhow::anyhow!("io")), EXIT_FAILURE);
+        // Context does not hide the code
+        let wrapped = usage("flag").context("while parsing");
+        assert_eq!(exit_code_of(&wrapped), EXIT_USAGE);
+    }
+}
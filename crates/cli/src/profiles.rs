@@ -0,0 +1,128 @@
+//! Profiles Module
+//!
+//! `profile create|export|import|list` over the profiles in the data
+//! directory. Export and import use encrypted profile bundles.
+
+use anyhow::Result;
+use browser_core::{BrowserProfile, BrowserProfileManager, BundleOptions, ConflictResolution, ProfileBundler};
+use clap::{Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::output::{invalid, not_found, Outcome};
+use crate::store::DataDir;
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// Create an empty profile
+    Create {
+        name: String,
+        /// Make it the default profile
+        #[arg(long)]
+        default: bool,
+    },
+    /// List profiles
+    List,
+    /// Write a profile to an encrypted bundle
+    Export {
+        /// Profile ID or name
+        profile: String,
+        /// Bundle file to write
+        #[arg(long, short)]
+        output: PathBuf,
+        #[arg(long, env = "PROXY_BROWSER_BUNDLE_PASSPHRASE", hide_env_values = true)]
+        passphrase: String,
+        /// Keep proxy usernames and passwords
+        #[arg(long)]
+        include_credentials: bool,
+        /// Include browsing history
+        #[arg(long)]
+        include_history: bool,
+        /// Leave out the Chromium user-data-dir
+        #[arg(long)]
+        no_chromium_profile: bool,
+    },
+    /// Import a profile bundle
+    Import {
+        /// Bundle file to read
+        file: PathBuf,
+        #[arg(long, env = "PROXY_BROWSER_BUNDLE_PASSPHRASE", hide_env_values = true)]
+        passphrase: String,
+        /// What to do when a different profile with the same ID exists
+        #[arg(long, value_enum, default_value_t = OnConflict::Fail)]
+        on_conflict: OnConflict,
+        /// New name when renaming
+        #[arg(long)]
+        rename_to: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    Fail,
+    Rename,
+    Overwrite,
+    Merge,
+}
+
+pub async fn run(command: ProfileCommand, data: &DataDir) -> Result<Outcome> {
+    let profiles = data.profiles().await?;
+    match command {
+        ProfileCommand::Create { name, default } => Outcome::ok(profiles.create_profile(&name, default).await?),
+        ProfileCommand::List => Outcome::ok(profiles.list_profiles().await),
+        ProfileCommand::Export {
+            profile,
+            output,
+            passphrase,
+            include_credentials,
+            include_history,
+            no_chromium_profile,
+        } => {
+            let profile = find_profile(&profiles, &profile).await?;
+            let options = BundleOptions {
+                include_credentials,
+                include_chromium_profile: !no_chromium_profile,
+                include_history,
+            };
+            let manifest = ProfileBundler::new(profiles)
+                .export_bundle(&profile.id, &output, &passphrase, &options)
+                .await?;
+            Outcome::ok(manifest)
+        }
+        ProfileCommand::Import { file, passphrase, on_conflict, rename_to } => {
+            if !file.exists() {
+                return Err(not_found(format!("No bundle at {}", file.display())));
+            }
+            let bundler = ProfileBundler::new(profiles);
+            let resolution = match on_conflict {
+                OnConflict::Fail => {
+                    let preview = bundler.inspect_bundle(&file, &passphrase).await?;
+                    if let Some(conflict) = preview.conflict {
+                        return Err(invalid(format!(
+                            "Profile {} differs from the bundle ({}); choose --on-conflict",
+                            conflict.local_name,
+                            conflict.differences.join(", ")
+                        )));
+                    }
+                    ConflictResolution::Fail
+                }
+                OnConflict::Rename => ConflictResolution::Rename { new_name: rename_to },
+                OnConflict::Overwrite => ConflictResolution::Overwrite,
+                OnConflict::Merge => ConflictResolution::Merge,
+            };
+            Outcome::ok(bundler.import_bundle(&file, &passphrase, resolution).await?)
+        }
+    }
+}
+
+/// Look a profile up by ID, then by name
+async fn find_profile(profiles: &BrowserProfileManager, key: &str) -> Result<BrowserProfile> {
+    if let Some(profile) = profiles.get_profile(key).await {
+        return Ok(profile);
+    }
+    profiles
+        .list_profiles()
+        .await
+        .into_iter()
+        .find(|p| p.name == key)
+        .ok_or_else(|| not_found(format!("No profile named {}", key)))
+}
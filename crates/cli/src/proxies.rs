@@ -0,0 +1,350 @@
+//! Proxies Module
+//!
+//! `proxies fetch|import|validate|export|judge` over the pool saved in the
+//! data directory.
+
+use anyhow::{Context, Result};
+use api_server::jobs::csv_field;
+use api_server::proxy_api::{parse_proxy_line, proxy_id};
+use browser_core::{
+    FreeIpProvider, FreeProxy, ProxyValidator, ProxyValidatorConfig, DEFAULT_JUDGE_URL,
+};
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::output::{invalid, not_found, usage, Outcome, EXIT_FAILURE, EXIT_INVALID, EXIT_OK};
+use crate::store::DataDir;
+
+#[derive(Debug, Subcommand)]
+pub enum ProxiesCommand {
+    /// Fetch proxies from the free providers and add new ones to the pool
+    Fetch {
+        /// Provider to fetch from, repeatable (default: all)
+        #[arg(long = "provider")]
+        providers: Vec<String>,
+    },
+    /// Add proxies from a file, or `-` for stdin: `ip:port` or
+    /// `scheme://ip:port` lines, or a JSON array as written by `export`
+    Import {
+        file: String,
+        /// Provider recorded for text lines
+        #[arg(long)]
+        provider: Option<String>,
+    },
+    /// Check which pooled proxies work and record the results
+    Validate {
+        #[command(flatten)]
+        filter: PoolFilter,
+        /// Timeout per request, in seconds
+        #[arg(long, default_value_t = 10)]
+        timeout_secs: u64,
+        /// Attempts per proxy
+        #[arg(long, default_value_t = 1)]
+        attempts: u32,
+    },
+    /// Write the pool as JSON, `scheme://ip:port` lines or CSV
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// File to write instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Only proxies that passed their last check
+        #[arg(long)]
+        working: bool,
+        #[command(flatten)]
+        filter: PoolFilter,
+    },
+    /// Classify pooled proxies as transparent, anonymous or elite against a
+    /// proxy judge, recording the result as their anonymity
+    Judge {
+        #[command(flatten)]
+        filter: PoolFilter,
+        /// Endpoint echoing the request headers
+        #[arg(long, default_value = DEFAULT_JUDGE_URL)]
+        judge_url: String,
+        /// This machine's public address (detected if not given)
+        #[arg(long)]
+        real_ip: Option<String>,
+        /// Timeout per request, in seconds
+        #[arg(long, default_value_t = 10)]
+        timeout_secs: u64,
+    },
+}
+
+/// Which pooled proxies a command works on
+#[derive(Debug, Args)]
+pub struct PoolFilter {
+    /// Only proxies in this country (ISO code)
+    #[arg(long)]
+    pub country: Option<String>,
+    /// At most this many proxies
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+impl PoolFilter {
+    fn select<'a>(&self, pool: impl IntoIterator<Item = &'a FreeProxy>) -> Vec<FreeProxy> {
+        pool.into_iter()
+            .filter(|p| self.country.as_ref().is_none_or(|cc| p.country_code.eq_ignore_ascii_case(cc)))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Text,
+    Csv,
+}
+
+/// Column order of the CSV export
+const CSV_HEADER: &str = "ip,port,protocol,country_code,country,anonymity,speed,is_working,provider,last_checked";
+
+pub async fn run(command: ProxiesCommand, data: &DataDir) -> Result<Outcome> {
+    match command {
+        ProxiesCommand::Fetch { providers } => fetch(data, &providers).await,
+        ProxiesCommand::Import { file, provider } => import(data, &file, provider).await,
+        ProxiesCommand::Validate { filter, timeout_secs, attempts } => {
+            validate(data, &filter, timeout_secs, attempts).await
+        }
+        ProxiesCommand::Export { format, output, working, filter } => {
+            export(data, format, output, working, &filter).await
+        }
+        ProxiesCommand::Judge { filter, judge_url, real_ip, timeout_secs } => {
+            judge(data, &filter, &judge_url, real_ip, timeout_secs).await
+        }
+    }
+}
+
+async fn fetch(data: &DataDir, names: &[String]) -> Result<Outcome> {
+    let providers = if names.is_empty() {
+        FreeIpProvider::all()
+    } else {
+        names
+            .iter()
+            .map(|name| FreeIpProvider::from_name(name).ok_or_else(|| usage(format!("Unknown provider: {}", name))))
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    let mut manager = data.pool_manager().await?;
+    let mut results = Vec::new();
+    let mut failures = 0;
+    for provider in &providers {
+        match manager.refresh_provider(provider).await {
+            Ok((fetched, added)) => {
+                results.push(json!({ "provider": provider.name(), "fetched": fetched, "added": added }))
+            }
+            Err(e) => {
+                failures += 1;
+                results.push(json!({ "provider": provider.name(), "error": e.to_string() }));
+            }
+        }
+    }
+    data.save_pool(manager.get_proxy_pool()).await?;
+
+    let output = json!({ "providers": results, "pool_size": manager.get_proxy_pool().len() });
+    // Only a total failure is an error; free providers are often down
+    let code = if failures == providers.len() { EXIT_FAILURE } else { EXIT_OK };
+    Outcome::with_code(output, code)
+}
+
+async fn import(data: &DataDir, file: &str, provider: Option<String>) -> Result<Outcome> {
+    let text = if file == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        tokio::fs::read_to_string(file)
+            .await
+            .map_err(|e| not_found(format!("Cannot read {}: {}", file, e)))?
+    };
+
+    let mut proxies = Vec::new();
+    let mut rejected = Vec::new();
+    if text.trim_start().starts_with('[') {
+        proxies = serde_json::from_str::<Vec<FreeProxy>>(&text)
+            .map_err(|e| invalid(format!("Invalid proxy list in {}: {}", file, e)))?;
+    } else {
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_proxy_line(line, provider.as_ref()) {
+                Some(proxy) => proxies.push(proxy),
+                None => rejected.push(line.to_string()),
+            }
+        }
+    }
+
+    let mut manager = data.pool_manager().await?;
+    let parsed = proxies.len();
+    let imported = manager.add_proxies(proxies);
+    data.save_pool(manager.get_proxy_pool()).await?;
+
+    let output = json!({
+        "imported": imported,
+        "duplicates": parsed - imported,
+        "invalid": rejected,
+        "pool_size": manager.get_proxy_pool().len(),
+    });
+    let code = if parsed == 0 && !rejected.is_empty() { EXIT_INVALID } else { EXIT_OK };
+    Outcome::with_code(output, code)
+}
+
+async fn validate(data: &DataDir, filter: &PoolFilter, timeout_secs: u64, attempts: u32) -> Result<Outcome> {
+    let mut manager = data.pool_manager().await?;
+    let selected = filter.select(manager.get_proxy_pool());
+    let validator = ProxyValidator::new(ProxyValidatorConfig {
+        timeout: Duration::from_secs(timeout_secs),
+        max_retries: attempts.max(1),
+        ..ProxyValidatorConfig::default()
+    });
+
+    let mut results = Vec::new();
+    let mut working = 0;
+    for (proxy, result) in validator.validate_batch(&selected).await {
+        if let Some(pooled) = manager.find_proxy_mut(&proxy.ip, proxy.port) {
+            pooled.is_working = result.is_working;
+            pooled.last_checked = result.validated_at.to_rfc3339();
+            if result.is_working {
+                pooled.speed = result.response_time_ms.min(u32::MAX as u64) as u32;
+            }
+        }
+        working += usize::from(result.is_working);
+        results.push(json!({ "proxy": proxy_id(&proxy), "result": result }));
+    }
+    data.save_pool(manager.get_proxy_pool()).await?;
+
+    Outcome::ok(json!({
+        "validated": results.len(),
+        "working": working,
+        "failed": results.len() - working,
+        "results": results,
+    }))
+}
+
+async fn export(
+    data: &DataDir,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+    working: bool,
+    filter: &PoolFilter,
+) -> Result<Outcome> {
+    let pool = data.load_pool().await?;
+    let proxies = filter.select(pool.iter().filter(|p| !working || p.is_working));
+
+    let text = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&proxies)?,
+        ExportFormat::Text => proxies
+            .iter()
+            .filter_map(|p| p.to_proxy_settings().server_url())
+            .map(|url| url + "\n")
+            .collect(),
+        ExportFormat::Csv => {
+            let mut csv = format!("{}\n", CSV_HEADER);
+            for p in &proxies {
+                let protocol = serde_json::to_value(&p.protocol)?;
+                let fields = [
+                    p.ip.clone(),
+                    p.port.to_string(),
+                    protocol.as_str().unwrap_or_default().to_ascii_lowercase(),
+                    p.country_code.clone(),
+                    p.country.clone(),
+                    p.anonymity.clone(),
+                    p.speed.to_string(),
+                    p.is_working.to_string(),
+                    p.provider.clone(),
+                    p.last_checked.clone(),
+                ];
+                csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+    };
+
+    match output {
+        Some(path) => {
+            tokio::fs::write(&path, text)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Outcome::ok(json!({ "path": path, "format": format_name(format), "exported": proxies.len() }))
+        }
+        None if format == ExportFormat::Json => Outcome::ok(proxies),
+        None => Ok(Outcome::text(text)),
+    }
+}
+
+fn format_name(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Json => "json",
+        ExportFormat::Text => "text",
+        ExportFormat::Csv => "csv",
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline
+async fn judge(
+    data: &DataDir,
+    filter: &PoolFilter,
+    judge_url: &str,
+    real_ip: Option<String>,
+    timeout_secs: u64,
+) -> Result<Outcome> {
+    let mut manager = data.pool_manager().await?;
+    let selected = filter.select(manager.get_proxy_pool());
+    let validator = ProxyValidator::new(ProxyValidatorConfig {
+        timeout: Duration::from_secs(timeout_secs),
+        ..ProxyValidatorConfig::default()
+    });
+    let real_ip = match real_ip {
+        Some(ip) => ip,
+        None => validator.detect_real_ip().await.context("Failed to detect this machine's public address")?,
+    };
+
+    let mut results = Vec::new();
+    let mut levels: BTreeMap<&str, usize> = BTreeMap::new();
+    for (proxy, result) in validator.judge_batch(&selected, judge_url, &real_ip).await {
+        match result {
+            Ok(judged) => {
+                if let Some(pooled) = manager.find_proxy_mut(&proxy.ip, proxy.port) {
+                    pooled.anonymity = judged.anonymity.as_str().to_string();
+                }
+                *levels.entry(judged.anonymity.as_str()).or_default() += 1;
+                results.push(json!({ "proxy": proxy_id(&proxy), "result": judged }));
+            }
+            Err(e) => {
+                *levels.entry("failed").or_default() += 1;
+                results.push(json!({ "proxy": proxy_id(&proxy), "error": e.to_string() }));
+            }
+        }
+    }
+    data.save_pool(manager.get_proxy_pool()).await?;
+
+    Outcome::ok(json!({
+        "judge_url": judge_url,
+        "judged": results.len(),
+        "levels": levels,
+        "results": results,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("US"), "US");
+        assert_eq!(csv_field("Korea, Republic of"), "\"Korea, Republic of\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\r\nbreak"), "\"line\r\nbreak\"");
+    }
+}
@@ -0,0 +1,105 @@
+//! Store Module
+//!
+//! Layout of the CLI data directory:
+//! - `proxies.json`: the proxy pool, a JSON array of `FreeProxy`
+//! - `profiles/`: browser profiles, one directory each
+//! - `backups/`: application backups
+//! - `config.toml`: application configuration
+
+use anyhow::{Context, Result};
+use browser_core::{BrowserProfileManager, ConfigManager, FreeIpProviderManager, FreeProxy};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Identifier the desktop app keeps its data under
+const APP_IDENTIFIER: &str = "com.virtualipbrowser.app";
+
+/// The desktop app's data directory, or `data` in the working directory on
+/// platforms without one
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir().map_or_else(|| PathBuf::from("data"), |dir| dir.join(APP_IDENTIFIER))
+}
+
+/// Paths and loaders for the state kept in the data directory
+pub struct DataDir {
+    root: PathBuf,
+}
+
+impl DataDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn proxies_path(&self) -> PathBuf {
+        self.root.join("proxies.json")
+    }
+
+    pub fn profiles_dir(&self) -> PathBuf {
+        self.root.join("profiles")
+    }
+
+    pub fn backups_dir(&self) -> PathBuf {
+        self.root.join("backups")
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join("config.toml")
+    }
+
+    /// The saved proxy pool, empty if none was saved yet
+    pub async fn load_pool(&self) -> Result<Vec<FreeProxy>> {
+        let path = self.proxies_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let json = tokio::fs::read_to_string(&path).await?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid proxy pool in {}", path.display()))
+    }
+
+    /// Replace the saved proxy pool
+    pub async fn save_pool(&self, proxies: &[FreeProxy]) -> Result<()> {
+        write_atomic(&self.proxies_path(), serde_json::to_string_pretty(proxies)?.as_bytes()).await
+    }
+
+    /// Provider manager holding the saved pool
+    pub async fn pool_manager(&self) -> Result<FreeIpProviderManager> {
+        let mut manager = FreeIpProviderManager::new()?;
+        manager.set_proxy_pool(self.load_pool().await?);
+        Ok(manager)
+    }
+
+    /// Profile manager with every saved profile discovered
+    pub async fn profiles(&self) -> Result<Arc<BrowserProfileManager>> {
+        let manager = BrowserProfileManager::new(self.profiles_dir());
+        manager.discover_profiles().await?;
+        Ok(Arc::new(manager))
+    }
+
+    /// Config manager holding the saved configuration as written, without
+    /// environment overrides
+    pub async fn config(&self) -> Result<Arc<ConfigManager>> {
+        let path = self.config_path();
+        let manager = ConfigManager::with_path(&path);
+        if path.exists() {
+            manager.replace(ConfigManager::parse_file(&path).await?).await;
+        }
+        Ok(Arc::new(manager))
+    }
+
+    /// Shared pool handle for components such as `AppBackupManager`
+    pub async fn shared_pool(&self) -> Result<Arc<RwLock<FreeIpProviderManager>>> {
+        Ok(Arc::new(RwLock::new(self.pool_manager().await?)))
+    }
+}
+
+/// Write through a temporary file so a crash never leaves half a file
+async fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let temp = path.with_extension("tmp");
+    tokio::fs::write(&temp, content).await?;
+    tokio::fs::rename(&temp, path).await?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Tab Module
+//!
+//! `tab open`: load a URL in a headless Chromium tab through a pooled proxy
+//! from the requested country, print what the page looked like and close
+//! the browser again.
+
+use anyhow::{Context, Result};
+use api_server::proxy_api::proxy_id;
+use browser_core::{ChromiumEngine, ChromiumEngineConfig, ChromiumTab, ProxySettings};
+use clap::Subcommand;
+use rand::seq::SliceRandom;
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::output::{not_found, usage, Outcome};
+use crate::store::DataDir;
+
+#[derive(Debug, Subcommand)]
+pub enum TabCommand {
+    /// Open a URL in a headless tab
+    Open {
+        #[arg(long)]
+        url: String,
+        /// Use a working pooled proxy from this country (ISO code)
+        #[arg(long, conflicts_with = "proxy")]
+        country: Option<String>,
+        /// Use this proxy URL instead of the pool
+        #[arg(long)]
+        proxy: Option<String>,
+        /// Seconds to wait after loading, e.g. for scripts to settle
+        #[arg(long, default_value_t = 0)]
+        wait_secs: u64,
+        /// Save a PNG screenshot here
+        #[arg(long)]
+        screenshot: Option<PathBuf>,
+        /// Chromium executable (auto-detected if not given)
+        #[arg(long, env = "CHROMIUM_PATH")]
+        chromium: Option<PathBuf>,
+        /// Disable the Chromium sandbox, e.g. in containers
+        #[arg(long)]
+        no_sandbox: bool,
+    },
+}
+
+pub async fn run(command: TabCommand, data: &DataDir) -> Result<Outcome> {
+    let TabCommand::Open { url, country, proxy, wait_secs, screenshot, chromium, no_sandbox } = command;
+
+    let (settings, proxy_id) = match (country, proxy) {
+        (Some(country), _) => {
+            let pool = data.load_pool().await?;
+            let candidates: Vec<_> = pool
+                .iter()
+                .filter(|p| p.is_working && p.country_code.eq_ignore_ascii_case(&country))
+                .collect();
+            let chosen = candidates
+                .choose(&mut rand::thread_rng())
+                .ok_or_else(|| not_found(format!("No working proxy in {} in the pool", country.to_uppercase())))?;
+            (Some(chosen.to_proxy_settings()), Some(proxy_id(chosen)))
+        }
+        (None, Some(url)) => {
+            let settings = ProxySettings::parse_url(&url).map_err(|e| usage(e.to_string()))?;
+            (Some(settings), None)
+        }
+        (None, None) => (None, None),
+    };
+
+    let mut engine = ChromiumEngine::new(ChromiumEngineConfig {
+        executable_path: chromium,
+        headless: true,
+        sandbox: !no_sandbox,
+        ..ChromiumEngineConfig::default()
+    });
+    engine.launch().await.context("Failed to launch Chromium")?;
+
+    let result = visit(&engine, &url, settings, wait_secs, screenshot.as_ref()).await;
+    // Reported in the output document; stderr only carries JSON errors
+    let shutdown_error = engine.shutdown().await.err().map(|e| format!("{:#}", e));
+    let (tab, screenshot_bytes) = result?;
+
+    Outcome::ok(json!({
+        "tab_id": tab.id,
+        "url": tab.url,
+        "title": tab.title,
+        "proxy": proxy_id,
+        "proxy_url": tab.proxy.as_ref().and_then(|p| p.server_url()),
+        "screenshot": screenshot,
+        "screenshot_bytes": screenshot_bytes,
+        "shutdown_error": shutdown_error,
+    }))
+}
+
+/// Load `url` in a new tab and capture its final state
+async fn visit(
+    engine: &ChromiumEngine,
+    url: &str,
+    proxy: Option<ProxySettings>,
+    wait_secs: u64,
+    screenshot: Option<&PathBuf>,
+) -> Result<(ChromiumTab, Option<usize>)> {
+    let tab = engine.create_tab(Some(url), proxy).await?;
+    if wait_secs > 0 {
+        tokio::time::sleep(Duration::from_secs(wait_secs)).await;
+    }
+    // The tab list carries the title and URL after redirects
+    let tab = engine.get_tabs().await.into_iter().find(|t| t.id == tab.id).unwrap_or(tab);
+
+    let screenshot_bytes = match screenshot {
+        Some(path) => {
+            let png = engine.capture_screenshot(&tab.id).await?;
+            tokio::fs::write(path, &png)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Some(png.len())
+        }
+        None => None,
+    };
+    Ok((tab, screenshot_bytes))
+}
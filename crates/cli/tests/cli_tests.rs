@@ -0,0 +1,346 @@
+//! Tests for the proxy-browser CLI
+//!
+//! Runs the binary against a temporary data directory and checks its JSON
+//! output and exit codes. Nothing here needs network access.
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+// ============================================================================
+// Test Fixtures
+// ============================================================================
+
+struct Run {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+impl Run {
+    fn json(&self) -> Value {
+        serde_json::from_str(&self.stdout).expect("Stdout should be JSON")
+    }
+
+    fn error(&self) -> Value {
+        serde_json::from_str(self.stderr.trim()).expect("Stderr should be a JSON error")
+    }
+}
+
+fn cli_with_stdin(data: &Path, args: &[&str], stdin: &str) -> Run {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_proxy-browser"))
+        .arg("--data-dir")
+        .arg(data)
+        .args(args)
+        .env_remove("PROXY_BROWSER_BUNDLE_PASSPHRASE")
+        .env_remove("PROXY_BROWSER_BACKUP_PASSWORD")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("CLI should start");
+    child
+        .stdin
+        .take()
+        .expect("Stdin should be piped")
+        .write_all(stdin.as_bytes())
+        .expect("Stdin should accept input");
+    let output = child.wait_with_output().expect("CLI should finish");
+    Run {
+        code: output.status.code().expect("CLI should exit normally"),
+        stdout: String::from_utf8(output.stdout).expect("Stdout should be UTF-8"),
+        stderr: String::from_utf8(output.stderr).expect("Stderr should be UTF-8"),
+    }
+}
+
+fn cli(data: &Path, args: &[&str]) -> Run {
+    cli_with_stdin(data, args, "")
+}
+
+fn import_sample(data: &Path) {
+    let run = cli_with_stdin(data, &["proxies", "import", "-"], "10.0.0.1:8080\nsocks5://10.0.0.2:1080\n");
+    assert_eq!(run.code, 0, "{}", run.stderr);
+}
+
+/// Plain HTTP proxy that answers every request itself, like a judge behind
+/// a proxy that adds a `Via` header
+fn spawn_judging_proxy() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Bind should succeed");
+    let port = listener.local_addr().expect("Local address should be available").port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream.try_clone().expect("Stream should clone"));
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 0) && line != "\r\n" {
+                line.clear();
+            }
+            let body = json!({ "headers": { "Host": "judge.test", "Via": "1.1 fake" }, "origin": "203.0.113.9" }).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let mut stream = stream;
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    port
+}
+
+// ============================================================================
+// Usage Tests
+// ============================================================================
+
+#[test]
+fn test_usage_errors_exit_2_with_json() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let run = cli(dir.path(), &["proxies", "teleport"]);
+    assert_eq!(run.code, 2);
+    assert!(run.stdout.is_empty());
+    assert_eq!(run.error()["exit_code"], 2);
+
+    let run = cli(dir.path(), &["proxies", "fetch", "--provider", "NoSuchProvider"]);
+    assert_eq!(run.code, 2);
+    assert!(run.error()["error"].as_str().is_some_and(|e| e.contains("NoSuchProvider")));
+}
+
+// ============================================================================
+// Proxy Pool Tests
+// ============================================================================
+
+#[test]
+fn test_import_and_export_pool() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let run = cli_with_stdin(
+        dir.path(),
+        &["proxies", "import", "-", "--provider", "list"],
+        "# comment\n10.0.0.1:8080\nsocks5://10.0.0.2:1080\nnot-a-proxy\n",
+    );
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.json(), json!({ "imported": 2, "duplicates": 0, "invalid": ["not-a-proxy"], "pool_size": 2 }));
+
+    // Importing again only finds duplicates
+    let run = cli_with_stdin(dir.path(), &["proxies", "import", "-"], "10.0.0.1:8080\n");
+    assert_eq!(run.json()["duplicates"], 1);
+
+    let run = cli(dir.path(), &["proxies", "export", "--format", "text"]);
+    assert_eq!(run.code, 0);
+    assert_eq!(run.stdout, "http://10.0.0.1:8080\nsocks5://10.0.0.2:1080\n");
+
+    let run = cli(dir.path(), &["proxies", "export", "--format", "csv", "--limit", "1"]);
+    let mut lines = run.stdout.lines();
+    assert!(lines.next().is_some_and(|header| header.starts_with("ip,port,protocol")));
+    assert!(lines.next().is_some_and(|row| row.starts_with("10.0.0.1,8080,http,XX,")));
+    assert_eq!(lines.next(), None);
+
+    // A JSON export imports into another data directory unchanged
+    let export_path = dir.path().join("pool.json");
+    let run = cli(dir.path(), &["proxies", "export", "--output", export_path.to_str().expect("Path should be UTF-8")]);
+    assert_eq!(run.json()["exported"], 2);
+    let other = TempDir::new().expect("Temp dir should be created");
+    let run = cli(other.path(), &["proxies", "import", export_path.to_str().expect("Path should be UTF-8")]);
+    assert_eq!(run.json()["imported"], 2);
+    let run = cli(other.path(), &["proxies", "export"]);
+    assert_eq!(run.json()[1]["provider"], "list");
+}
+
+#[test]
+fn test_import_exit_codes() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let run = cli_with_stdin(dir.path(), &["proxies", "import", "-"], "garbage\n");
+    assert_eq!(run.code, 3);
+    assert_eq!(run.json()["imported"], 0);
+
+    let run = cli(dir.path(), &["proxies", "import", "missing.txt"]);
+    assert_eq!(run.code, 4);
+    assert_eq!(run.error()["exit_code"], 4);
+}
+
+#[test]
+fn test_judge_records_anonymity() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let port = spawn_judging_proxy();
+    let run = cli_with_stdin(dir.path(), &["proxies", "import", "-"], &format!("127.0.0.1:{port}\n"));
+    assert_eq!(run.code, 0, "{}", run.stderr);
+
+    let run = cli(
+        dir.path(),
+        &["proxies", "judge", "--judge-url", "http://judge.test/get", "--real-ip", "198.51.100.2"],
+    );
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    let report = run.json();
+    assert_eq!(report["levels"], json!({ "anonymous": 1 }));
+    assert_eq!(report["results"][0]["result"]["revealing_headers"], json!(["via"]));
+
+    let run = cli(dir.path(), &["proxies", "export"]);
+    assert_eq!(run.json()[0]["anonymity"], "anonymous");
+}
+
+#[test]
+fn test_gateway_needs_working_pool() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let run = cli(dir.path(), &["gateway", "--port", "0"]);
+    assert_eq!(run.code, 4);
+    assert!(run.error()["error"].as_str().is_some_and(|e| e.contains("no working proxies")));
+
+    let run = cli(dir.path(), &["gateway", "--host", "not an address"]);
+    assert_eq!(run.code, 2);
+}
+
+#[test]
+fn test_serve_announces_bootstrap_key_as_json() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Bind should succeed")
+        .port();
+    // Relative default paths such as `data/api_keys.json` land in the temp dir
+    let mut child = Command::new(env!("CARGO_BIN_EXE_proxy-browser"))
+        .current_dir(dir.path())
+        .arg("--data-dir")
+        .arg(dir.path())
+        .args(["serve", "--port", &port.to_string(), "--host", "127.0.0.1"])
+        .env("SECRETS_MASTER_PASSWORD", "test-master-password")
+        .env_remove("API_AUTH_DISABLED")
+        .env_remove("API_KEYS_PATH")
+        .env_remove("API_TLS_CERT")
+        .env_remove("BROWSER_DAEMON")
+        .env_remove("CONFIG_PATH")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("CLI should start");
+
+    let mut started = String::new();
+    BufReader::new(child.stdout.take().expect("Stdout should be piped"))
+        .read_line(&mut started)
+        .expect("Stdout should be readable");
+    child.kill().expect("Serve should be killed");
+    let output = child.wait_with_output().expect("CLI should finish");
+
+    let started: Value = serde_json::from_str(&started).expect("Stdout should be JSON");
+    assert_eq!(started["url"], format!("http://127.0.0.1:{}", port));
+    assert!(started["admin_api_key"].as_str().is_some_and(|key| key.starts_with("pxb_")));
+
+    let stderr = String::from_utf8(output.stderr).expect("Stderr should be UTF-8");
+    assert!(!stderr.trim().is_empty());
+    for line in stderr.lines() {
+        serde_json::from_str::<Value>(line).expect("Every stderr line should be JSON");
+    }
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_config_get_set_validate() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let run = cli(dir.path(), &["config", "get", "logging.level"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.json(), json!({ "key": "logging.level", "value": "info" }));
+
+    let run = cli(dir.path(), &["config", "set", "performance.max_tabs", "12"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.json()["value"], 12);
+    let run = cli(dir.path(), &["config", "get", "performance.max_tabs"]);
+    assert_eq!(run.json()["value"], 12);
+
+    // Errors are rejected and nothing is saved
+    let run = cli(dir.path(), &["config", "set", "performance.max_tabs", "0"]);
+    assert_eq!(run.code, 3);
+    let run = cli(dir.path(), &["config", "set", "performance.max_tabs", "many"]);
+    assert_eq!(run.code, 3);
+    let run = cli(dir.path(), &["config", "get", "performance.max_tabs"]);
+    assert_eq!(run.json()["value"], 12);
+
+    let run = cli(dir.path(), &["config", "set", "no.such.key", "1"]);
+    assert_eq!(run.code, 4);
+
+    let run = cli(dir.path(), &["config", "validate"]);
+    assert_eq!(run.code, 0);
+    assert_eq!(run.json()["valid"], true);
+
+    let mut config = cli(dir.path(), &["config", "get"]).json();
+    config["logging"]["level"] = json!("loud");
+    let file = dir.path().join("broken.json");
+    std::fs::write(&file, config.to_string()).expect("Config should be written");
+    let run = cli(dir.path(), &["config", "validate", "--file", file.to_str().expect("Path should be UTF-8")]);
+    assert_eq!(run.code, 3);
+    assert_eq!(run.json()["errors"], json!(["Invalid log level: loud"]));
+}
+
+// ============================================================================
+// Profile Tests
+// ============================================================================
+
+#[test]
+fn test_profile_create_export_import() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    let run = cli(dir.path(), &["profile", "create", "work"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    let id = run.json()["id"].as_str().expect("Profile should have an ID").to_string();
+
+    let run = cli(dir.path(), &["profile", "list"]);
+    assert_eq!(run.json().as_array().map(Vec::len), Some(1));
+
+    let bundle = dir.path().join("work.bundle");
+    let bundle_arg = bundle.to_str().expect("Path should be UTF-8");
+    let run = cli(dir.path(), &["profile", "export", "work", "--output", bundle_arg, "--passphrase", "pw"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.json()["profile_id"], id.as_str());
+
+    let run = cli(dir.path(), &["profile", "export", "missing", "--output", bundle_arg, "--passphrase", "pw"]);
+    assert_eq!(run.code, 4);
+
+    // Into a fresh data directory, then again as a no-op
+    let other = TempDir::new().expect("Temp dir should be created");
+    let run = cli(other.path(), &["profile", "import", bundle_arg, "--passphrase", "pw"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.json()["profile_name"], "work");
+    let run = cli(other.path(), &["profile", "import", bundle_arg, "--passphrase", "pw"]);
+    assert_eq!(run.json()["unchanged"], true);
+}
+
+// ============================================================================
+// Backup Tests
+// ============================================================================
+
+#[test]
+fn test_backup_create_list_restore() {
+    let dir = TempDir::new().expect("Temp dir should be created");
+    import_sample(dir.path());
+
+    let run = cli(dir.path(), &["backup", "create", "--name", "before.json"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    let components: Vec<Value> = run.json()["manifest"]["components"]
+        .as_array()
+        .expect("Manifest should list components")
+        .iter()
+        .map(|c| c["component"].clone())
+        .collect();
+    assert_eq!(components, vec![json!("config"), json!("profiles"), json!("proxy_pool")]);
+
+    let run = cli(dir.path(), &["backup", "list"]);
+    assert_eq!(run.json()[0]["name"], "before.json");
+    assert_eq!(run.json()[0]["encrypted"], false);
+
+    let run = cli_with_stdin(dir.path(), &["proxies", "import", "-"], "10.0.0.3:3128\n");
+    assert_eq!(run.json()["pool_size"], 3);
+
+    let run = cli(dir.path(), &["backup", "restore", "before.json", "--component", "proxy_pool", "--dry-run"]);
+    assert_eq!(run.json()["components"]["proxy_pool"]["removed"], json!(["10.0.0.3:3128"]));
+    let run = cli(dir.path(), &["backup", "restore", "before.json", "--component", "proxy_pool"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.json()["restored"], json!(["proxy_pool"]));
+    let run = cli(dir.path(), &["proxies", "export"]);
+    assert_eq!(run.json().as_array().map(Vec::len), Some(2));
+
+    let run = cli(dir.path(), &["backup", "restore", "missing.json"]);
+    assert_eq!(run.code, 4);
+    let run = cli(dir.path(), &["backup", "create", "--component", "teleporter"]);
+    assert_eq!(run.code, 2);
+}
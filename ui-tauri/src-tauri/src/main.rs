@@ -1,14 +1,19 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use browser_core::{
     ProxyManager, ProxySettings, ProxyType, FreeProxy,
     PublicIpDetector, PublicIpInfo, FreeIpProviderManager,
     StorageEngine, BackupManager, BackupData, BackupOptions, BackupInfo,
     BrowserController, BrowserState, BrowserSettings, WebRtcPolicy,
+    RestorePlanner, RestoreOptions, RestoreReport, RestoreCategory, CategoryPlan,
+    ProfileLock, ConfigManager, DataRetentionManager, SecretsVault,
+    EventBus, EventFilter, DownloadManager, DownloadItem, ErrorRecoveryManager,
+    BackupScheduler, AutoBackupSettings, BackupRunOutcome, ConfigHotReloader,
 };
 use serde::{Deserialize, Serialize};
-use tauri::{State, Manager};
+use tauri::{Emitter, State, Manager};
 use tracing::{info, error, debug, warn};
 use virtual_ip::{
     demo_generator, load_countries_from_file, load_ip_ranges, load_ip_ranges_from_file,
@@ -18,7 +23,7 @@ use virtual_ip::{
 mod webview_manager;
 use webview_manager::{
     WebviewManager, WebviewTab,
-    create_webview_tab, create_webview_tab_with_proxy,
+    create_webview_tab, create_webview_tab_with_proxy, create_ephemeral_webview_tab,
     navigate_webview_tab, close_webview_tab, focus_webview_tab,
     get_webview_tabs, navigation_changed, title_changed,
 };
@@ -29,6 +34,22 @@ struct AppState {
     storage_engine: Arc<StorageEngine>,
     backup_manager: Arc<BackupManager>,
     browser_controller: Arc<BrowserController>,
+    /// Holds proxy passwords; settings keep `secret://` references
+    secrets_vault: Option<Arc<SecretsVault>>,
+    downloads: Arc<DownloadManager>,
+    backup_scheduler: Arc<BackupScheduler>,
+    /// Held for the app's lifetime so a second instance cannot share its data
+    _data_lock: ProfileLock,
+}
+
+impl AppState {
+    /// Move a plaintext proxy password into the vault, if there is one
+    async fn seal_proxy_credentials(&self) -> Result<(), String> {
+        if self.secrets_vault.is_some() {
+            self.proxy_manager.seal_credentials().await.map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -182,7 +203,7 @@ async fn get_proxy_settings(state: State<'_, AppState>) -> Result<ProxySettingsR
 #[tauri::command]
 async fn set_proxy_settings(state: State<'_, AppState>, settings: ProxySettingsRequest) -> Result<(), String> {
     state.proxy_manager.set_settings(settings.into()).await;
-    Ok(())
+    state.seal_proxy_credentials().await
 }
 
 #[tauri::command]
@@ -199,7 +220,7 @@ async fn set_active_proxy(state: State<'_, AppState>, proxy: Option<FreeProxyReq
 // Public IP Detection
 #[tauri::command]
 async fn detect_public_ip(state: State<'_, AppState>) -> Result<PublicIpResponse, String> {
-    let settings = state.proxy_manager.get_settings().await;
+    let settings = state.proxy_manager.resolved_settings().await.map_err(|e| e.to_string())?;
     let detector = if settings.proxy_type != ProxyType::Direct {
         PublicIpDetector::with_proxy(&settings).map_err(|e| e.to_string())?
     } else {
@@ -281,6 +302,7 @@ async fn create_backup(state: State<'_, AppState>, options: BackupOptionsRequest
         include_bookmarks: options.include_bookmarks,
         include_local_storage: options.include_local_storage,
         password: options.password,
+        include_secrets: options.include_secrets,
     };
 
     let info = state.backup_manager.create_backup(data, &backup_options).await.map_err(|e| e.to_string())?;
@@ -293,33 +315,51 @@ async fn list_backups(state: State<'_, AppState>) -> Result<Vec<BackupInfoRespon
     Ok(backups.into_iter().map(BackupInfoResponse::from).collect())
 }
 
+fn restore_planner(state: &AppState) -> RestorePlanner {
+    RestorePlanner::new(state.storage_engine.clone())
+        .with_proxy_manager(state.proxy_manager.clone())
+}
+
 #[tauri::command]
-async fn restore_backup(state: State<'_, AppState>, path: String, password: Option<String>) -> Result<(), String> {
+async fn preview_backup_restore(
+    state: State<'_, AppState>,
+    path: String,
+    password: Option<String>,
+    options: Option<RestoreOptions>,
+) -> Result<BTreeMap<RestoreCategory, CategoryPlan>, String> {
     let backup_data = state.backup_manager.restore_backup(
         std::path::Path::new(&path),
         password.as_deref()
     ).await.map_err(|e| e.to_string())?;
 
-    // Restore proxy settings
-    if let Some(proxy_settings) = backup_data.proxy_settings {
-        state.proxy_manager.set_settings(proxy_settings).await;
-    }
-
-    // Restore cookies
-    if let Some(cookies) = backup_data.cookies {
-        for cookie in cookies {
-            state.storage_engine.set_cookie(cookie.clone()).await.map_err(|e| e.to_string())?;
-        }
-    }
+    let plan = restore_planner(&state)
+        .plan(&backup_data, &options.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(plan.categories)
+}
 
-    // Restore bookmarks
-    if let Some(bookmarks) = backup_data.bookmarks {
-        for bookmark in bookmarks {
-            state.storage_engine.add_bookmark(&bookmark.url, &bookmark.title, bookmark.folder.as_deref()).await.map_err(|e| e.to_string())?;
-        }
-    }
+#[tauri::command]
+async fn restore_backup(
+    state: State<'_, AppState>,
+    path: String,
+    password: Option<String>,
+    options: Option<RestoreOptions>,
+) -> Result<RestoreReport, String> {
+    let backup_data = state.backup_manager.restore_backup(
+        std::path::Path::new(&path),
+        password.as_deref()
+    ).await.map_err(|e| e.to_string())?;
 
-    Ok(())
+    let planner = restore_planner(&state);
+    let plan = planner
+        .plan(&backup_data, &options.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())?;
+    let report = planner.apply(&plan).await.map_err(|e| e.to_string())?;
+    // A backup taken with secrets included restores plaintext passwords
+    state.seal_proxy_credentials().await?;
+    Ok(report)
 }
 
 #[tauri::command]
@@ -327,6 +367,50 @@ async fn delete_backup(state: State<'_, AppState>, id: String) -> Result<(), Str
     state.backup_manager.delete_backup(&id).await.map_err(|e| e.to_string())
 }
 
+// Downloads
+#[tauri::command]
+async fn start_download(
+    state: State<'_, AppState>,
+    url: String,
+    filename: Option<String>,
+    tab_id: Option<String>,
+) -> Result<String, String> {
+    state.downloads
+        .start_download(&url, filename.as_deref(), tab_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_downloads(state: State<'_, AppState>) -> Result<Vec<DownloadItem>, String> {
+    Ok(state.downloads.get_all_downloads().await)
+}
+
+#[tauri::command]
+async fn cancel_download(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    state.downloads.cancel_download(&id).await.map_err(|e| e.to_string())
+}
+
+// Scheduled backups
+#[tauri::command]
+async fn get_auto_backup_settings(state: State<'_, AppState>) -> Result<AutoBackupSettings, String> {
+    Ok(state.backup_scheduler.settings().await)
+}
+
+#[tauri::command]
+async fn set_auto_backup_settings(state: State<'_, AppState>, settings: AutoBackupSettings) -> Result<(), String> {
+    state.backup_scheduler.update_settings(settings).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn run_backup_now(state: State<'_, AppState>) -> Result<Option<BackupInfoResponse>, String> {
+    match state.backup_scheduler.run_now().await.map_err(|e| e.to_string())? {
+        BackupRunOutcome::Created { info, .. } => Ok(Some(BackupInfoResponse::from(info))),
+        BackupRunOutcome::Unchanged { .. } => Ok(None),
+    }
+}
+
 // Tab management - close tab
 #[tauri::command]
 async fn close_tab(state: State<'_, AppState>, app_handle: tauri::AppHandle, tab_id: String) -> Result<(), String> {
@@ -338,10 +422,15 @@ async fn close_tab(state: State<'_, AppState>, app_handle: tauri::AppHandle, tab
 
 // Browser controls
 #[tauri::command]
-async fn navigate(state: State<'_, AppState>, tab_id: String, url: String) -> Result<BrowserStateResponse, String> {
+async fn navigate(state: State<'_, AppState>, app_handle: tauri::AppHandle, tab_id: String, url: String) -> Result<BrowserStateResponse, String> {
     let browser_state = state.browser_controller.navigate(&tab_id, &url).await.map_err(|e| e.to_string())?;
-    // Also record in storage
-    let _ = state.storage_engine.add_history(&url, None).await;
+    // Also record in the tab's storage; ephemeral tabs never keep history
+    let storage = app_handle
+        .state::<WebviewManager>()
+        .ephemeral_containers()
+        .storage_for(&tab_id, &state.storage_engine)
+        .await;
+    let _ = storage.add_history(&url, None).await;
     Ok(BrowserStateResponse::from(browser_state))
 }
 
@@ -712,6 +801,8 @@ pub struct BackupOptionsRequest {
     pub include_bookmarks: bool,
     pub include_local_storage: bool,
     pub password: Option<String>,
+    #[serde(default)]
+    pub include_secrets: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -930,25 +1021,126 @@ fn init_storage_engine(app_data_dir: &std::path::Path) -> Arc<StorageEngine> {
 }
 
 /// Initialize backup manager with fallback to temp directory
-fn init_backup_manager(app_data_dir: &std::path::Path) -> Arc<BackupManager> {
+fn init_backup_manager(app_data_dir: &std::path::Path, secrets_vault: Option<Arc<SecretsVault>>) -> Arc<BackupManager> {
     let backup_dir = app_data_dir.join("backups");
-    match BackupManager::new(&backup_dir) {
-        Ok(manager) => Arc::new(manager),
+    let manager = match BackupManager::new(&backup_dir) {
+        Ok(manager) => manager,
         Err(e) => {
             warn!("Failed to initialize backup manager: {}. Using temp directory.", e);
             let temp_dir = std::env::temp_dir().join("virtual-ip-browser/backups");
-            Arc::new(BackupManager::new(&temp_dir).expect("Failed to create backup manager"))
+            BackupManager::new(&temp_dir).expect("Failed to create backup manager")
+        }
+    };
+    match secrets_vault {
+        Some(vault) => Arc::new(manager.with_secrets_vault(vault)),
+        None => Arc::new(manager),
+    }
+}
+
+/// Open the secrets vault: the OS keyring, or an encrypted file when
+/// `SECRETS_MASTER_PASSWORD` is set. Without either, proxy passwords
+/// stay in the settings.
+fn init_secrets_vault(app_data_dir: &std::path::Path) -> Option<Arc<SecretsVault>> {
+    let master_password = std::env::var("SECRETS_MASTER_PASSWORD").ok();
+    match SecretsVault::detect("virtual-ip-browser", app_data_dir.join("secrets.vault"), master_password.as_deref()) {
+        Ok(vault) => Some(Arc::new(vault)),
+        Err(e) => {
+            warn!("Secrets vault unavailable: {}. Proxy passwords will not be sealed.", e);
+            None
         }
     }
 }
 
+/// Proxy manager resolving password references through the vault
+fn init_proxy_manager(secrets_vault: Option<Arc<SecretsVault>>) -> Arc<ProxyManager> {
+    match secrets_vault {
+        Some(vault) => Arc::new(ProxyManager::new().with_secrets_vault(vault)),
+        None => Arc::new(ProxyManager::new()),
+    }
+}
+
+/// Load the app's config file, falling back to defaults if it is unreadable
+fn init_config_manager(app_data_dir: &std::path::Path) -> Arc<ConfigManager> {
+    let config_manager = Arc::new(ConfigManager::with_path(app_data_dir.join("config.toml")));
+    if let Err(e) = tauri::async_runtime::block_on(config_manager.load()) {
+        warn!("Failed to load configuration: {}. Using defaults.", e);
+    }
+    config_manager
+}
+
+/// Apply the configured data retention policy now and then periodically
+fn init_retention_manager(storage_engine: Arc<StorageEngine>, config_manager: &ConfigManager) -> Arc<DataRetentionManager> {
+    let policy = tauri::async_runtime::block_on(config_manager.get_privacy()).retention;
+    let retention = Arc::new(DataRetentionManager::new(storage_engine, policy));
+    let sweeper = retention.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = sweeper.sweep_now().await {
+            error!("Initial retention sweep failed: {}", e);
+        }
+        sweeper.start_sweeper();
+    });
+    retention
+}
+
+/// Watch the config file and apply edits to live components
+fn init_config_reloader(config_manager: Arc<ConfigManager>, retention: Arc<DataRetentionManager>) {
+    let reloader = match ConfigHotReloader::new(config_manager) {
+        Ok(reloader) => Arc::new(reloader),
+        Err(e) => {
+            warn!("Configuration hot reload disabled: {}", e);
+            return;
+        }
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = reloader.register(retention).await {
+            error!("Failed to apply configuration to data retention: {}", e);
+        }
+        reloader.start();
+    });
+}
+
+/// Forward every event on the bus to the frontend as `browser-event`
+fn init_event_bus(app_handle: tauri::AppHandle) -> Arc<EventBus> {
+    let bus = Arc::new(EventBus::new());
+    let mut subscription = bus.subscribe(EventFilter::default(), None);
+    tauri::async_runtime::spawn(async move {
+        while let Some(item) = subscription.next().await {
+            if let Err(e) = app_handle.emit("browser-event", &item) {
+                debug!("Failed to forward browser event: {}", e);
+            }
+        }
+    });
+    bus
+}
+
+/// Run scheduled backups of the app's storage into the backups directory
+fn init_backup_scheduler(
+    app_data_dir: &std::path::Path,
+    storage_engine: Arc<StorageEngine>,
+    events: Arc<EventBus>,
+) -> anyhow::Result<Arc<BackupScheduler>> {
+    let settings = AutoBackupSettings {
+        backup_path: app_data_dir.join("backups"),
+        ..AutoBackupSettings::default()
+    };
+    let scheduler = Arc::new(BackupScheduler::new(settings, storage_engine)?.with_event_bus(events));
+    let runner = scheduler.clone();
+    tauri::async_runtime::spawn(async move {
+        runner.start();
+    });
+    Ok(scheduler)
+}
+
 /// Spawn async task to fetch proxies on startup
-fn spawn_proxy_fetch_task(proxy_manager: Arc<ProxyManager>) {
+fn spawn_proxy_fetch_task(proxy_manager: Arc<ProxyManager>, error_recovery: Arc<ErrorRecoveryManager>) {
     tauri::async_runtime::spawn(async move {
         info!("Fetching free proxies on startup...");
         match proxy_manager.fetch_proxies().await {
             Ok(count) => info!("Successfully fetched {} proxies", count),
-            Err(e) => error!("Failed to fetch free proxies on startup: {}", e),
+            Err(e) => {
+                error!("Failed to fetch free proxies on startup: {}", e);
+                error_recovery.handle_error("proxy", &e.to_string()).await;
+            }
         }
     });
 }
@@ -963,7 +1155,6 @@ fn get_app_data_dir(app: &tauri::App) -> std::path::PathBuf {
 
 fn main() {
     let ip_generator = Arc::new(build_ip_generator());
-    let proxy_manager = Arc::new(ProxyManager::new());
     let browser_controller = Arc::new(BrowserController::new());
     
     tauri::Builder::default()
@@ -978,11 +1169,32 @@ fn main() {
             
             // Get app data directory and initialize components
             let app_data_dir = get_app_data_dir(app);
+            let data_lock = ProfileLock::acquire(&app_data_dir).map_err(|e| {
+                error!("Another instance is using {}: {}", app_data_dir.display(), e);
+                e
+            })?;
+            let secrets_vault = init_secrets_vault(&app_data_dir);
+            let proxy_manager = init_proxy_manager(secrets_vault.clone());
             let storage_engine = init_storage_engine(&app_data_dir);
-            let backup_manager = init_backup_manager(&app_data_dir);
+            let backup_manager = init_backup_manager(&app_data_dir, secrets_vault.clone());
+            let config_manager = init_config_manager(&app_data_dir);
+            let retention = init_retention_manager(storage_engine.clone(), &config_manager);
+            init_config_reloader(config_manager, retention);
+
+            // Downloads, backups and recovered errors are pushed to the frontend
+            let events = init_event_bus(app.handle().clone());
+            let downloads = Arc::new(
+                DownloadManager::new(app_data_dir.join("downloads")).with_event_bus(events.clone()),
+            );
+            let error_recovery = Arc::new(ErrorRecoveryManager::new().with_event_bus(events.clone()));
+            let backup_scheduler = init_backup_scheduler(&app_data_dir, storage_engine.clone(), events)
+                .map_err(|e| {
+                    error!("Failed to start backup scheduler: {}", e);
+                    e
+                })?;
             
             // Fetch free proxies on startup
-            spawn_proxy_fetch_task(proxy_manager.clone());
+            spawn_proxy_fetch_task(proxy_manager.clone(), error_recovery);
             
             // Manage the app state
             app.manage(AppState {
@@ -991,6 +1203,10 @@ fn main() {
                 storage_engine,
                 backup_manager,
                 browser_controller,
+                secrets_vault,
+                downloads,
+                backup_scheduler,
+                _data_lock: data_lock,
             });
             
             Ok(())
@@ -999,6 +1215,7 @@ fn main() {
             // WebView Manager commands
             create_webview_tab,
             create_webview_tab_with_proxy,
+            create_ephemeral_webview_tab,
             navigate_webview_tab,
             close_webview_tab,
             focus_webview_tab,
@@ -1030,7 +1247,15 @@ fn main() {
             create_backup,
             list_backups,
             restore_backup,
+            preview_backup_restore,
             delete_backup,
+            get_auto_backup_settings,
+            set_auto_backup_settings,
+            run_backup_now,
+            // Downloads
+            start_download,
+            get_downloads,
+            cancel_download,
             // Browser controls
             navigate,
             go_back,
@@ -5,13 +5,16 @@
 //! - Navigation control (forward, back, reload)
 //! - Tab lifecycle management (create, close, focus)
 //! - Tab state tracking (URL, title, loading status)
+//! - Ephemeral (incognito) tabs whose data is destroyed on close
 
 use anyhow::{anyhow, Result};
+use browser_core::EphemeralContainerManager;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager, WebviewWindow};
 use tokio::sync::RwLock;
+use tracing::{debug, warn};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +28,8 @@ pub struct WebviewTab {
     pub can_go_back: bool,
     pub can_go_forward: bool,
     pub created_at: std::time::SystemTime,
+    #[serde(default)]
+    pub ephemeral: bool,
 }
 
 /// Represents a WebviewManager.
@@ -32,6 +37,7 @@ pub struct WebviewManager {
     app_handle: AppHandle,
     tabs: Arc<RwLock<HashMap<String, WebviewTab>>>,
     window_counter: RwLock<u32>,
+    ephemeral_containers: Arc<EphemeralContainerManager>,
 }
 
 impl WebviewManager {
@@ -41,11 +47,17 @@ impl WebviewManager {
             app_handle,
             tabs: Arc::new(RwLock::new(HashMap::new())),
             window_counter: RwLock::new(0),
+            ephemeral_containers: Arc::new(EphemeralContainerManager::new()),
         }
     }
 
+    /// Get the registry of ephemeral tab containers
+    pub fn ephemeral_containers(&self) -> Arc<EphemeralContainerManager> {
+        self.ephemeral_containers.clone()
+    }
+
     /// Create a new webview tab with native window and proxy settings
-    pub fn create_tab_with_proxy_sync(&self, initial_url: Option<String>, proxy_url: Option<String>, window_label: String, tab_id: String) -> Result<(WebviewTab, WebviewWindow)> {
+    pub fn create_tab_with_proxy_sync(&self, initial_url: Option<String>, proxy_url: Option<String>, window_label: String, tab_id: String, ephemeral: bool) -> Result<(WebviewTab, WebviewWindow)> {
         let url = initial_url.unwrap_or_else(|| "https://www.google.com".to_string());
         
         // Apply proxy settings if provided (environment variables for now)
@@ -78,6 +90,7 @@ impl WebviewManager {
         .center()
         .decorations(true)
         .resizable(true)
+        .incognito(ephemeral)
         .build()?;
         
         let tab = WebviewTab {
@@ -89,6 +102,7 @@ impl WebviewManager {
             can_go_back: false,
             can_go_forward: false,
             created_at: std::time::SystemTime::now(),
+            ephemeral,
         };
         
         Ok((tab, window))
@@ -96,7 +110,24 @@ impl WebviewManager {
 
     /// Create a new webview tab with native window and proxy settings
     pub async fn create_tab_with_proxy(&self, initial_url: Option<String>, proxy_url: Option<String>) -> Result<WebviewTab> {
+        self.open_tab(Uuid::new_v4().to_string(), initial_url, proxy_url, false).await
+    }
+
+    /// Create an incognito tab whose data lives in a memory-only container
+    /// that is destroyed when the tab closes
+    pub async fn create_ephemeral_tab(&self, initial_url: Option<String>, proxy_url: Option<String>) -> Result<WebviewTab> {
         let tab_id = Uuid::new_v4().to_string();
+        self.ephemeral_containers.create_container(&tab_id, None).await?;
+        match self.open_tab(tab_id.clone(), initial_url, proxy_url, true).await {
+            Ok(tab) => Ok(tab),
+            Err(e) => {
+                self.ephemeral_containers.destroy(&tab_id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn open_tab(&self, tab_id: String, initial_url: Option<String>, proxy_url: Option<String>, ephemeral: bool) -> Result<WebviewTab> {
         let counter = {
             let mut c = self.window_counter.write().await;
             *c += 1;
@@ -105,7 +136,7 @@ impl WebviewManager {
         let window_label = format!("tab_{}", counter);
         
         // Do synchronous window creation first
-        let (tab, _window) = self.create_tab_with_proxy_sync(initial_url, proxy_url, window_label, tab_id.clone())?;
+        let (tab, _window) = self.create_tab_with_proxy_sync(initial_url, proxy_url, window_label, tab_id.clone(), ephemeral)?;
         
         // Store tab reference
         self.tabs.write().await.insert(tab_id.clone(), tab.clone());
@@ -138,19 +169,27 @@ impl WebviewManager {
         self.tabs.read().await.values().cloned().collect()
     }
 
-    /// Close a tab
+    /// Close a tab. An ephemeral tab's container is destroyed even if its
+    /// window fails to close.
     pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
         let tabs = self.tabs.read().await;
         let tab = tabs.get(tab_id).ok_or_else(|| anyhow!("Tab not found"))?;
         
-        if let Some(window) = self.app_handle.get_webview_window(&tab.window_label) {
-            window.close()?;
-        }
+        let closed = match self.app_handle.get_webview_window(&tab.window_label) {
+            Some(window) => window.close().map_err(|e| anyhow!("Failed to close window: {}", e)),
+            None => Ok(()),
+        };
         
         drop(tabs);
+
+        match self.ephemeral_containers.destroy(tab_id).await {
+            Ok(Some(report)) => debug!("Destroyed ephemeral container {} on close", report.container_id),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to destroy ephemeral container for tab {}: {}", tab_id, e),
+        }
         self.tabs.write().await.remove(tab_id);
         
-        Ok(())
+        closed
     }
 
     /// Focus a tab's window
@@ -202,6 +241,13 @@ pub async fn create_webview_tab(app_handle: tauri::AppHandle, url: Option<String
     manager.create_tab_with_proxy(url, None).await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+/// Creates a new ephemeral webview tab.
+pub async fn create_ephemeral_webview_tab(app_handle: tauri::AppHandle, url: Option<String>) -> Result<WebviewTab, String> {
+    let manager = app_handle.state::<WebviewManager>();
+    manager.create_ephemeral_tab(url, None).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 /// Performs navigate webview tab operation.
 pub async fn navigate_webview_tab(app_handle: tauri::AppHandle, tab_id: String, url: String) -> Result<(), String> {